@@ -0,0 +1,48 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::{address_add, address_diff, address_of, UserDatatype, View};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let values: Vec<f64> = (0..8).map(|i| f64::from(rank * 10 + i)).collect();
+    let selected = [1, 4, 6];
+
+    // Displacements of the selected elements from the start of the vector
+    let base = address_of(&values[0]);
+    let displacements: Vec<mpi::Address> = selected
+        .iter()
+        .map(|&i| address_diff(address_of(&values[i]), base))
+        .collect();
+    for (&i, &displacement) in selected.iter().zip(&displacements) {
+        assert_eq!(
+            displacement,
+            (i * std::mem::size_of::<f64>()) as mpi::Address
+        );
+        assert_eq!(address_add(base, displacement), address_of(&values[i]));
+    }
+
+    let datatype =
+        UserDatatype::heterogeneous_indexed(&[1; 3], &displacements, &f64::equivalent_datatype());
+    let view = unsafe { View::with_count_and_datatype(&values[..], 1, &datatype) };
+    let mut received = [0.0f64; 3];
+    mpi::request::scope(|scope| {
+        let send = next.immediate_send(scope, &view);
+        previous.receive_into(&mut received[..]);
+        send.wait();
+    });
+
+    let previous_rank = previous.rank();
+    let expected: Vec<f64> = selected
+        .iter()
+        .map(|&i| f64::from(previous_rank * 10 + i as i32))
+        .collect();
+    assert_eq!(&received[..], &expected[..]);
+}
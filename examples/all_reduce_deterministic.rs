@@ -0,0 +1,48 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+fn contribution(rank: i32) -> f64 {
+    1.0 / f64::from(3 * rank + 7) + 1e-9 * f64::from(rank)
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    let mut sum = [0.0f64];
+    world.all_reduce_deterministic_into(&[contribution(rank)], &mut sum, SystemOperation::sum());
+
+    // Replay the reduction tree locally.
+    let mut partial: Vec<f64> = (0..size).map(contribution).collect();
+    let mut mask = 1;
+    while mask < size {
+        for r in (0..size).step_by(2 * mask as usize) {
+            if r + mask < size {
+                partial[r as usize] += partial[(r + mask) as usize];
+            }
+        }
+        mask <<= 1;
+    }
+    assert_eq!(partial[0].to_bits(), sum[0].to_bits());
+
+    // Repeated reductions give bitwise identical results.
+    for _ in 0..10 {
+        let mut again = [0.0f64];
+        world.all_reduce_deterministic_into(
+            &[contribution(rank)],
+            &mut again,
+            SystemOperation::sum(),
+        );
+        assert_eq!(sum[0].to_bits(), again[0].to_bits());
+    }
+
+    let mut counts = [0i64; 2];
+    world.all_reduce_deterministic_into(&[1, i64::from(rank)], &mut counts, SystemOperation::sum());
+    let n = i64::from(size);
+    assert_eq!([n, n * (n - 1) / 2], counts);
+}
@@ -0,0 +1,27 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::{Hierarchy, SystemOperation};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let hierarchy = Hierarchy::new(&world);
+    assert_eq!(hierarchy.leaders().is_some(), hierarchy.node().rank() == 0);
+
+    let local: Vec<i64> = (0..1000).map(|i| i64::from(rank) * 1000 + i).collect();
+
+    let mut flat = vec![0i64; local.len()];
+    world.all_reduce_into(&local[..], &mut flat[..], SystemOperation::sum());
+
+    let mut hierarchical = vec![0i64; local.len()];
+    hierarchy.all_reduce_hierarchical(&local[..], &mut hierarchical[..], SystemOperation::sum());
+    assert_eq!(hierarchical, flat);
+
+    let mut max = 0;
+    hierarchy.all_reduce_hierarchical(&rank, &mut max, SystemOperation::max());
+    assert_eq!(max, world.size() - 1);
+}
@@ -0,0 +1,81 @@
+#![deny(warnings)]
+#![allow(clippy::float_cmp)]
+extern crate mpi;
+
+#[cfg(feature = "user-operations")]
+#[macro_use]
+extern crate memoffset;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+#[cfg(feature = "user-operations")]
+mod stats {
+    use mpi::datatype::{StructLayoutBuilder, UserDatatype};
+    use mpi::traits::*;
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    #[repr(C)]
+    pub struct Stats {
+        pub min: f64,
+        pub max: f64,
+        pub count: u64,
+    }
+
+    unsafe impl Equivalence for Stats {
+        type Out = UserDatatype;
+        fn equivalent_datatype() -> Self::Out {
+            StructLayoutBuilder::<Stats>::new()
+                .field(offset_of!(Stats, min), 1, &f64::equivalent_datatype())
+                .field(offset_of!(Stats, max), 1, &f64::equivalent_datatype())
+                .field(offset_of!(Stats, count), 1, &u64::equivalent_datatype())
+                .build()
+        }
+    }
+
+    pub fn merge(input: &[Stats], inout: &mut [Stats]) {
+        for (x, y) in input.iter().zip(inout) {
+            y.min = y.min.min(x.min);
+            y.max = y.max.max(x.max);
+            y.count += x.count;
+        }
+    }
+}
+
+#[cfg(feature = "user-operations")]
+fn test_derived<C: Communicator>(comm: &C) {
+    use stats::Stats;
+
+    let rank = f64::from(comm.rank());
+    let size = comm.size();
+    let mut local: Vec<Stats> = (0..3)
+        .map(|i| Stats {
+            min: rank + f64::from(i),
+            max: rank + f64::from(i),
+            count: 1,
+        })
+        .collect();
+    comm.all_reduce_in_place_with(&mut local[..], true, stats::merge);
+    for (i, s) in local.iter().enumerate() {
+        assert_eq!(s.min, i as f64);
+        assert_eq!(s.max, f64::from(size - 1) + i as f64);
+        assert_eq!(s.count, size as u64);
+    }
+}
+
+#[cfg(not(feature = "user-operations"))]
+fn test_derived<C: Communicator>(_: &C) {}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank() as u64;
+    let size = world.size() as u64;
+
+    let mut buf: Vec<u64> = (0..8).map(|i| rank * i).collect();
+    world.all_reduce_in_place(&mut buf[..], SystemOperation::sum());
+    let ranks: u64 = (0..size).sum();
+    assert!(buf.iter().enumerate().all(|(i, &x)| x == ranks * i as u64));
+
+    test_derived(&world);
+}
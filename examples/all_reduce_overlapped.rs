@@ -0,0 +1,29 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Reduce the residuals of the last iteration while computing the next one.
+    let residuals: Vec<f64> = (0..4).map(|i| f64::from(rank * 4 + i)).collect();
+    let mut total = vec![0.0; residuals.len()];
+    let next: Vec<f64> = world.all_reduce_overlapped(
+        &residuals[..],
+        &mut total[..],
+        SystemOperation::sum(),
+        || residuals.iter().map(|r| r / 2.0).collect(),
+    );
+
+    let halved: Vec<f64> = (0..4).map(|i| f64::from(rank * 4 + i) / 2.0).collect();
+    assert_eq!(next, halved);
+    for (i, &t) in total.iter().enumerate() {
+        let expected: i32 = (0..size).map(|r| r * 4 + i as i32).sum();
+        assert_eq!(t, f64::from(expected));
+    }
+}
@@ -0,0 +1,25 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let sum = world.sum(f64::from(rank));
+    assert_eq!(sum, f64::from(size * (size - 1) / 2));
+    assert_eq!(world.sum(1), size);
+
+    assert_eq!(world.max(rank), size - 1);
+    assert_eq!(world.min(rank), 0);
+    assert_eq!(world.min(-f64::from(rank)), -f64::from(size - 1));
+
+    let product = world.all_reduce(2u64, SystemOperation::product());
+    assert_eq!(product, 1 << size);
+    let bits = world.all_reduce(1u32 << rank, SystemOperation::bitwise_or());
+    assert_eq!(bits, (1 << size) - 1);
+}
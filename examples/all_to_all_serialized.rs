@@ -0,0 +1,40 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // The vertices of a ring graph of 3 vertices per process are distributed round-robin. Every
+    // process sends the edges leaving its vertices to the owners of their target vertices.
+    let vertices = 3 * size;
+    let owner = |vertex: i32| (vertex % size) as usize;
+    let mut outgoing: Vec<Vec<(i32, i32)>> = vec![Vec::new(); size as usize];
+    for source in (rank..vertices).step_by(size as usize) {
+        for &target in &[(source + 1) % vertices, (source + vertices - 1) % vertices] {
+            outgoing[owner(target)].push((source, target));
+        }
+    }
+
+    let incoming = world.all_to_all_serialized(&outgoing);
+    assert_eq!(incoming.len(), size as usize);
+    for (sender, edges) in incoming.iter().enumerate() {
+        for &(source, target) in edges {
+            assert_eq!(owner(source), sender);
+            assert_eq!(owner(target), rank as usize);
+        }
+    }
+
+    // Every vertex of this process is the target of the edges from both of its neighbours.
+    let mut targets: Vec<i32> = incoming.into_iter().flatten().map(|(_, t)| t).collect();
+    targets.sort_unstable();
+    let expected: Vec<i32> = (rank..vertices)
+        .step_by(size as usize)
+        .flat_map(|vertex| vec![vertex, vertex])
+        .collect();
+    assert_eq!(targets, expected);
+}
@@ -0,0 +1,40 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let upper_bound = mpi::environment::tag_upper_bound();
+    assert!(upper_bound >= 32767);
+
+    // Two independent "libraries" allocate their tags on the same communicator.
+    let first = world.allocate_tag();
+    let range = world.allocate_tags(4);
+    assert_eq!(first, upper_bound);
+    assert_eq!(range.len(), 4);
+    assert!(!range.contains(first));
+    assert_eq!(range.tags().count(), 4);
+    assert_eq!(range.tag(3), range.start() + 3);
+
+    // Allocations on a duplicate start over.
+    let comm = world.duplicate();
+    assert_eq!(comm.allocate_tag(), upper_bound);
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let mut received = -1;
+    mpi::point_to_point::send_receive_into_with_tags(
+        &rank,
+        &next,
+        range.tag(1),
+        &mut received,
+        &previous,
+        range.tag(1),
+    );
+    assert_eq!(received, previous.rank());
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::topology::{AnyCommunicator, Color};
+use mpi::traits::*;
+
+/// A library type that stores its communicator without being generic over its type
+struct Counter {
+    comm: AnyCommunicator,
+}
+
+impl Counter {
+    fn new<C: Into<AnyCommunicator>>(comm: C) -> Counter {
+        Counter { comm: comm.into() }
+    }
+
+    fn total(&self, local: u64) -> u64 {
+        let mut total = 0;
+        self.comm
+            .all_reduce_into(&local, &mut total, SystemOperation::sum());
+        total
+    }
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size() as u64;
+    let rank = world.rank();
+
+    let counters = vec![
+        Counter::new(world),
+        Counter::new(world.duplicate()),
+        Counter::new(
+            world
+                .split_by_color(Color::with_value(rank % 2))
+                .expect("Every process has a color."),
+        ),
+    ];
+
+    assert_eq!(counters[0].total(1), size);
+    assert_eq!(counters[1].total(1), size);
+    assert_eq!(counters[2].total(1), counters[2].comm.size() as u64);
+    assert_eq!(
+        counters[2].comm.size() as u64,
+        (size + 1 - (rank % 2) as u64) / 2
+    );
+    assert!(counters.iter().all(|c| c.comm.as_cartesian().is_none()));
+}
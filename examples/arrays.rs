@@ -0,0 +1,39 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    // A slice of 3D coordinates is a buffer of arrays.
+    let coordinates: Vec<[f64; 3]> = (0..4)
+        .map(|i| [f64::from(rank), f64::from(i), -f64::from(i)])
+        .collect();
+    let mut received = vec![[0.0; 3]; 4];
+    mpi::point_to_point::send_receive_into(&coordinates[..], &next, &mut received[..], &previous);
+    for (i, point) in received.iter().enumerate() {
+        let i = i as f64;
+        assert_eq!(*point, [f64::from(previous.rank()), i, -i]);
+    }
+
+    // The datatype of an array type is built once.
+    assert_eq!(
+        <[f64; 3]>::equivalent_datatype().as_raw(),
+        <[f64; 3]>::equivalent_datatype().as_raw()
+    );
+
+    // Arrays of arrays
+    let matrix = [[rank; 2], [-rank; 2]];
+    let mut matrices = vec![[[0; 2]; 2]; size as usize];
+    world.all_gather_into(&matrix, &mut matrices[..]);
+    for (r, m) in matrices.iter().enumerate() {
+        let r = r as i32;
+        assert_eq!(*m, [[r; 2], [-r; 2]]);
+    }
+}
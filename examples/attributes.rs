@@ -0,0 +1,51 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::sync::Arc;
+
+use mpi::datatype::UserDatatype;
+use mpi::traits::*;
+
+struct Pair(UserDatatype);
+
+struct Counter(usize);
+
+fn pair_datatype(universe: &mpi::environment::Universe) -> Arc<Pair> {
+    universe.get_or_insert_with(|| Pair(UserDatatype::contiguous(2, &i32::equivalent_datatype())))
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    assert!(universe.get::<Counter>().is_none());
+    assert!(universe.store(Counter(1)).is_none());
+    assert_eq!(universe.get::<Counter>().unwrap().0, 1);
+    assert_eq!(universe.store(Counter(2)).unwrap().0, 1);
+    assert_eq!(universe.remove::<Counter>().unwrap().0, 2);
+    assert!(universe.get::<Counter>().is_none());
+
+    // Both calls share the same committed datatype.
+    let first = pair_datatype(&universe);
+    let second = pair_datatype(&universe);
+    assert!(Arc::ptr_eq(&first, &second));
+    drop(second);
+
+    let next_rank = (world.rank() + 1) % world.size();
+    let previous_rank = (world.rank() + world.size() - 1) % world.size();
+    let send = [world.rank(), 2 * world.rank()];
+    let mut recv = [0; 2];
+    {
+        let send = unsafe { mpi::datatype::View::with_count_and_datatype(&send[..], 1, &first.0) };
+        let mut recv =
+            unsafe { mpi::datatype::MutView::with_count_and_datatype(&mut recv[..], 1, &first.0) };
+        mpi::point_to_point::send_receive_into(
+            &send,
+            &world.process_at_rank(next_rank),
+            &mut recv,
+            &world.process_at_rank(previous_rank),
+        );
+    }
+    assert_eq!(recv, [previous_rank, 2 * previous_rank]);
+    drop(first);
+}
@@ -0,0 +1,42 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::bench;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+
+    let pairs = bench::ping_pong(&world, 1024, 10);
+    assert_eq!(pairs.len() as i32, size * (size - 1) / 2);
+    for pair in &pairs {
+        assert!(pair.source < pair.destination);
+        assert_eq!(pair.bytes, 1024);
+        assert!(0.0 <= pair.min_latency);
+        assert!(pair.min_latency <= pair.mean_latency);
+        assert!(pair.mean_latency <= pair.max_latency);
+        assert!(pair.bandwidth() > 0.0);
+    }
+
+    let timings = bench::all_reduce(&world, &[8, 1024, 65536], 5);
+    assert_eq!(timings.len(), 3);
+    for (timing, &bytes) in timings.iter().zip(&[8, 1024, 65536]) {
+        assert_eq!(timing.bytes, bytes);
+        assert!(timing.min_time <= timing.mean_time);
+        assert!(timing.mean_time <= timing.max_time);
+    }
+
+    if world.rank() == 0 {
+        for pair in &pairs {
+            println!(
+                "{} <-> {}: {:.2} us, {:.2} MB/s",
+                pair.source,
+                pair.destination,
+                pair.min_latency * 1e6,
+                pair.bandwidth() / 1e6
+            );
+        }
+    }
+}
@@ -0,0 +1,41 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::bitset::BitSetBuffer;
+use mpi::traits::*;
+
+const ELEMENTS: usize = 100;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank() as usize;
+    let size = world.size() as usize;
+
+    // Every process marks the elements it owns, dealt out round-robin.
+    let owned: Vec<bool> = (0..ELEMENTS).map(|i| i % size == rank).collect();
+    let mut marked = BitSetBuffer::from_bools(&owned);
+    assert_eq!(marked.len(), ELEMENTS);
+    assert_eq!(marked.words().len(), 2);
+    assert_eq!(marked.to_bools(), owned);
+
+    // Every element is owned by some process.
+    marked.all_reduce_or(&world);
+    assert_eq!(marked.count_ones(), ELEMENTS);
+
+    // Only the multiples of all of 1..=size are marked everywhere.
+    let mut common: BitSetBuffer = (0..ELEMENTS).map(|i| i % (rank + 1) == 0).collect();
+    common.all_reduce_and(&world);
+    for i in 0..ELEMENTS {
+        assert_eq!(common.get(i), (1..=size).all(|d| i % d == 0));
+    }
+
+    // Bit sets are buffers of their words.
+    let mut broadcast = BitSetBuffer::new(ELEMENTS);
+    if rank == 0 {
+        broadcast.set(3, true);
+        broadcast.set(70, true);
+    }
+    world.process_at_rank(0).broadcast_into(&mut broadcast);
+    assert_eq!(broadcast.ones().collect::<Vec<_>>(), vec![3, 70]);
+}
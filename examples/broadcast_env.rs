@@ -0,0 +1,36 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::env;
+
+use mpi::environment;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    // Every process sees a different value, only the one of rank 0 is broadcast.
+    env::set_var("RSMPI_EXAMPLE_CONFIG", format!("value of rank {}", rank));
+    if rank == 0 {
+        env::set_var("RSMPI_EXAMPLE_EMPTY", "");
+        env::remove_var("RSMPI_EXAMPLE_UNSET");
+    } else {
+        env::set_var("RSMPI_EXAMPLE_UNSET", "set on a non-root process");
+    }
+
+    let values = environment::broadcast_env(
+        &world,
+        &[
+            "RSMPI_EXAMPLE_CONFIG",
+            "RSMPI_EXAMPLE_UNSET",
+            "RSMPI_EXAMPLE_EMPTY",
+        ],
+    );
+
+    assert_eq!(values.len(), 2);
+    assert_eq!(values["RSMPI_EXAMPLE_CONFIG"], "value of rank 0");
+    assert_eq!(values["RSMPI_EXAMPLE_EMPTY"], "");
+    assert!(!values.contains_key("RSMPI_EXAMPLE_UNSET"));
+}
@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::fs;
+
+use mpi::staging::{broadcast_file, stage_file};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let root = world.process_at_rank(0);
+
+    // Name the files after the process id of the root process, which is unique on its node.
+    let mut id = std::process::id();
+    root.broadcast_into(&mut id);
+    let input = std::env::temp_dir().join(format!("rsmpi-input-{}.bin", id));
+    let staged = std::env::temp_dir().join(format!("rsmpi-staged-{}.bin", id));
+
+    let contents: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    if world.rank() == 0 {
+        fs::write(&input, &contents).unwrap();
+    }
+
+    let received = broadcast_file(&root, &input).unwrap();
+    assert_eq!(received, contents);
+
+    // The file is written once per node, after which all processes of the node can read it.
+    stage_file(&world, &input, &staged).unwrap();
+    assert_eq!(fs::read(&staged).unwrap(), contents);
+
+    // A missing file is an error on all processes.
+    let missing = std::env::temp_dir().join(format!("rsmpi-missing-{}.bin", id));
+    assert!(broadcast_file(&root, &missing).is_err());
+
+    world.barrier();
+    let node = world.split_shared(world.rank());
+    if node.rank() == 0 {
+        fs::remove_file(&staged).unwrap();
+    }
+    if world.rank() == 0 {
+        fs::remove_file(&input).unwrap();
+    }
+}
@@ -0,0 +1,37 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::memory::{Allocation, BufferPool, PAGE_SIZE};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let next_process = world.process_at_rank((rank + 1) % size);
+    let previous_process = world.process_at_rank((rank + size - 1) % size);
+
+    for &allocation in &[Allocation::PageAligned, Allocation::Registered] {
+        let pool = BufferPool::new(allocation);
+        for round in 0..10u8 {
+            let len = 1000 * (usize::from(round) + 1);
+            let mut send = pool.acquire(len);
+            let mut recv = pool.acquire(len);
+            assert_eq!(0, send.as_ptr() as usize % PAGE_SIZE);
+            assert_eq!(len, send.len());
+
+            for x in send.iter_mut() {
+                *x = round;
+            }
+            mpi::request::scope(|scope| {
+                let request = next_process.immediate_send(scope, &send[..]);
+                previous_process.receive_into(&mut recv[..]);
+                request.wait();
+            });
+            assert!(recv.iter().all(|&x| x == round));
+        }
+        assert!(pool.cached_bytes() > 0);
+    }
+}
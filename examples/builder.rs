@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::environment::{ErrorMode, Threading};
+use mpi::request::WaitGuard;
+use mpi::statistics;
+use mpi::traits::*;
+
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+fn main() {
+    // No operation fails in this example.
+    let builder = unsafe { mpi::environment::builder().error_mode(ErrorMode::Return) };
+    let universe = builder
+        .threading(Threading::Funneled)
+        .buffer_size(BUFFER_SIZE)
+        .abort_on_panic(true)
+        .statistics(true)
+        .initialize()
+        .unwrap();
+    assert!(mpi::environment::builder().initialize().is_none());
+    assert_eq!(universe.buffer_size(), BUFFER_SIZE);
+    assert!(statistics::is_enabled());
+
+    let world = universe.world();
+    let x = vec![world.rank(); 1024];
+    let mut y = vec![-1; 1024];
+    mpi::request::scope(|scope| {
+        let _rreq = WaitGuard::from(
+            world
+                .this_process()
+                .immediate_receive_into(scope, &mut y[..]),
+        );
+        world.this_process().buffered_send(&x[..]);
+    });
+    assert_eq!(x, y);
+
+    let sent = statistics::of(&world)
+        .sent()
+        .values()
+        .map(|traffic| traffic.messages)
+        .sum::<u64>();
+    assert_eq!(sent, 1);
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::io::{BufRead, Read, Write};
+
+use mpi::stream::{ByteReceiver, ByteSender};
+use mpi::traits::*;
+
+const LINES: usize = 100;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    if size < 2 {
+        return;
+    }
+    let last = size - 1;
+
+    // Stream lines of text from the first to the last process in small chunks.
+    if rank == 0 {
+        let mut sender = ByteSender::with_chunk_size(world.process_at_rank(last), 7, 13);
+        for i in 0..LINES {
+            writeln!(sender, "Line {} of the stream", i).unwrap();
+        }
+        sender.finish();
+    } else if rank == last {
+        let receiver = ByteReceiver::new(world.process_at_rank(0), 7);
+        let lines: Vec<String> = receiver.lines().map(|line| line.unwrap()).collect();
+        assert_eq!(lines.len(), LINES);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(*line, format!("Line {} of the stream", i));
+        }
+    }
+
+    // Stream a large buffer back with the default chunk size.
+    let data: Vec<u8> = (0..200_000).map(|i: u32| (i % 251) as u8).collect();
+    if rank == last {
+        let mut sender = ByteSender::new(world.process_at_rank(0), 8);
+        sender.write_all(&data).unwrap();
+    } else if rank == 0 {
+        let mut receiver = ByteReceiver::new(world.process_at_rank(last), 8);
+        let mut received = Vec::new();
+        receiver.read_to_end(&mut received).unwrap();
+        assert_eq!(received, data);
+        assert!(receiver.is_finished());
+    }
+}
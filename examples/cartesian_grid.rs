@@ -0,0 +1,46 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    if world.size() < 4 {
+        return;
+    }
+
+    let grid = if let Some(grid) =
+        world.create_cartesian_communicator(&[2, world.size() / 2], &[false, false], true)
+    {
+        grid
+    } else {
+        assert!(world.size() % 2 == 1 && world.rank() == world.size() - 1);
+        return;
+    };
+
+    let (rows, cols) = grid.grid_shape();
+    assert_eq!((rows, cols), (2, world.size() / 2));
+    let (row, col) = grid.grid_position(grid.rank());
+    assert_eq!(grid.grid_rank(row, col), grid.rank());
+
+    let row_comm = grid.row_comm();
+    let col_comm = grid.col_comm();
+    assert_eq!(row_comm.size(), cols);
+    assert_eq!(col_comm.size(), rows);
+    assert_eq!(row_comm.rank(), col);
+    assert_eq!(col_comm.rank(), row);
+
+    // Every process of a row sees the same row index, every process of a column the same column.
+    let mut row_from_first = row;
+    row_comm
+        .process_at_rank(0)
+        .broadcast_into(&mut row_from_first);
+    assert_eq!(row_from_first, row);
+    let mut col_from_first = col;
+    col_comm
+        .process_at_rank(0)
+        .broadcast_into(&mut col_from_first);
+    assert_eq!(col_from_first, col);
+}
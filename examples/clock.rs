@@ -0,0 +1,38 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::time::{Duration, SystemTime};
+
+use mpi::clock::{self, WireDuration};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let root_process = world.process_at_rank(0);
+
+    let mut timeout = WireDuration::from(Duration::from_millis(1500));
+    if rank != 0 {
+        timeout = WireDuration::default();
+    }
+    root_process.broadcast_into(&mut timeout);
+    assert_eq!(Duration::from(timeout), Duration::from_millis(1500));
+    assert_eq!(timeout.secs, 1);
+    assert_eq!(timeout.nanos, 500_000_000);
+
+    let before = SystemTime::now();
+    let times = clock::all_gather_wall_clock(&world);
+    assert_eq!(times.len(), world.size() as usize);
+    assert!(times[rank as usize] >= before);
+
+    match clock::measure_clock_skew(&world, 0, 10) {
+        Some(offsets) => {
+            assert_eq!(rank, 0);
+            assert_eq!(offsets.len(), world.size() as usize);
+            assert!(offsets[0].abs() < f64::EPSILON);
+            assert!(offsets.iter().all(|offset| offset.is_finite()));
+        }
+        None => assert_ne!(rank, 0),
+    }
+}
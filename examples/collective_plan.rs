@@ -0,0 +1,53 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::CollectivePlan;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    let rank = world.rank();
+    let size = world.size();
+    let root_process = world.process_at_rank(0);
+
+    let plan = CollectivePlan::from_all_gather(&world, rank);
+    assert_eq!((0..size).collect::<Vec<_>>(), plan.counts());
+    assert_eq!(size * (size - 1) / 2, plan.extent());
+
+    // Gaps between partitions are fine, partitions that end beyond the largest `Count` are not.
+    // Overlapping partitions can only be sent from, not received into.
+    let gapped = CollectivePlan::with_displacements(vec![2, 3], vec![4, 0]);
+    assert_eq!(6, gapped.extent());
+    let overlapping = CollectivePlan::with_displacements(vec![2, 3], vec![2, 0]);
+    assert_eq!(4, overlapping.extent());
+    assert!(std::panic::catch_unwind(|| {
+        let mut buf = [0; 4];
+        overlapping.partition_mut(&mut buf[..]);
+    })
+    .is_err());
+    assert!(std::panic::catch_unwind(|| {
+        CollectivePlan::with_displacements(vec![2], vec![mpi::Count::max_value()])
+    })
+    .is_err());
+
+    let mut gathered = vec![0; plan.extent() as usize];
+    for iteration in 0..10 {
+        let msg: Vec<_> = (0..rank).map(|i| i + iteration).collect();
+
+        plan.all_gather_varcount_into(&world, &msg[..], &mut gathered[..]);
+        assert!(gathered
+            .iter()
+            .zip((0..size).flat_map(|r| (0..r)))
+            .all(|(&i, j)| i == j + iteration));
+
+        if rank == 0 {
+            let mut buf = vec![0; plan.extent() as usize];
+            plan.gather_varcount_into_root(&root_process, &msg[..], &mut buf[..]);
+            assert_eq!(gathered, buf);
+        } else {
+            root_process.gather_varcount_into(&msg[..]);
+        }
+    }
+}
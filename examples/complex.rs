@@ -0,0 +1,41 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+use num_complex::{Complex32, Complex64};
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Pass a complex number around the ring.
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let z = Complex64::new(f64::from(rank), -f64::from(rank));
+    let (received, _) = mpi::request::scope(|scope| {
+        let _sreq = next.immediate_send(scope, &z);
+        previous.receive::<Complex64>()
+    });
+    let previous_rank = f64::from(previous.rank());
+    assert_eq!(received, Complex64::new(previous_rank, -previous_rank));
+
+    // The sum of `rank + rank i` over all processes
+    let sum = world.sum(Complex64::new(f64::from(rank), f64::from(rank)));
+    let expected = f64::from(size * (size - 1) / 2);
+    assert_eq!(sum, Complex64::new(expected, expected));
+
+    // The products of `i` and `1 + i` over all processes are their `size`-th powers.
+    let factors = [Complex32::new(0.0, 1.0), Complex32::new(1.0, 1.0)];
+    let mut products = [Complex32::new(0.0, 0.0); 2];
+    world.all_reduce_into(&factors[..], &mut products[..], SystemOperation::product());
+    let mut expected = [Complex32::new(1.0, 0.0); 2];
+    for _ in 0..size {
+        for (e, f) in expected.iter_mut().zip(&factors) {
+            *e *= *f;
+        }
+    }
+    assert_eq!(products, expected);
+}
@@ -0,0 +1,37 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::serialized;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    serialized::set_compression_threshold(1024);
+    assert_eq!(serialized::compression_threshold(), 1024);
+
+    // A large and well compressible value and a small one that is sent as is.
+    let large = vec![rank; 100_000];
+    let gathered = world.all_gather_serialized(&large);
+    for (r, values) in gathered.iter().enumerate() {
+        assert_eq!(values.len(), 100_000);
+        assert!(values.iter().all(|&value| value as usize == r));
+    }
+    let small = format!("process {}", rank);
+    let gathered = world.all_gather_serialized(&small);
+    for (r, value) in gathered.iter().enumerate() {
+        assert_eq!(value, &format!("process {}", r));
+    }
+
+    let mut message = if rank == 0 {
+        "compressed ".repeat(1000)
+    } else {
+        String::new()
+    };
+    world
+        .process_at_rank(0)
+        .broadcast_serialized_into(&mut message);
+    assert_eq!(message, "compressed ".repeat(1000));
+}
@@ -0,0 +1,82 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::env;
+use std::fs;
+
+use mpi::container::{ArrayLayout, ContainerReader, ContainerWriter};
+use mpi::datatype::{Distribution, Order};
+use mpi::traits::*;
+
+const ROWS: i32 = 8;
+const COLUMNS: i32 = 6;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let path = env::temp_dir().join("rsmpi_container_example.rsmpict");
+
+    // Blocks of rows, one block per process
+    let by_rows = ArrayLayout::block(&[ROWS, COLUMNS], &[size, 1]);
+    let rows = block(ROWS, size, rank);
+    let field: Vec<f64> = rows
+        .clone()
+        .flat_map(|row| (0..COLUMNS).map(move |column| value(row, column)))
+        .collect();
+    assert_eq!(field.len(), by_rows.local_len(&world));
+
+    // Every process holds every `size`-th step, starting at its rank
+    let steps_layout = ArrayLayout::new(
+        &[2 * size],
+        &[Distribution::Cyclic(1)],
+        &[size],
+        Order::RowMajor,
+    );
+    let steps = vec![rank, rank + size];
+
+    let mut writer = ContainerWriter::create(&world, &path).unwrap();
+    writer.write("field", &by_rows, &field[..]).unwrap();
+    writer.write("steps", &steps_layout, &steps[..]).unwrap();
+    writer.finish().unwrap();
+
+    let reader = ContainerReader::open(&world, &path).unwrap();
+    let names: Vec<&str> = reader.datasets().iter().map(|d| d.name()).collect();
+    assert_eq!(names, ["field", "steps"]);
+    let dataset = reader.dataset("field").unwrap();
+    assert_eq!(dataset.global_sizes(), &[ROWS, COLUMNS]);
+    assert_eq!(dataset.element_size(), 8);
+
+    // Read the field back distributed in blocks of columns instead.
+    let by_columns = ArrayLayout::block(&[ROWS, COLUMNS], &[1, size]);
+    let columns = block(COLUMNS, size, rank);
+    let expected: Vec<f64> = (0..ROWS)
+        .flat_map(|row| columns.clone().map(move |column| value(row, column)))
+        .collect();
+    assert_eq!(reader.read::<f64>("field", &by_columns).unwrap(), expected);
+
+    let all_steps = reader
+        .read::<i32>("steps", &ArrayLayout::block(&[2 * size], &[size]))
+        .unwrap();
+    assert_eq!(all_steps, vec![2 * rank, 2 * rank + 1]);
+
+    assert!(reader.read::<f64>("missing", &by_rows).is_err());
+    assert!(reader.read::<i32>("field", &by_rows).is_err());
+    reader.close().unwrap();
+
+    world.barrier();
+    if rank == 0 {
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+/// The indices of the block of `len` elements held by process `rank` of `size` processes
+fn block(len: i32, size: i32, rank: i32) -> std::ops::Range<i32> {
+    let block = (len + size - 1) / size;
+    (rank * block).min(len)..((rank + 1) * block).min(len)
+}
+
+fn value(row: i32, column: i32) -> f64 {
+    f64::from(row * COLUMNS + column)
+}
@@ -0,0 +1,63 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::counts::{self, CountError};
+use mpi::datatype::PartitionMut;
+use mpi::traits::*;
+use mpi::Count;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    assert_eq!(counts::displacements(&[2, 0, 3]), vec![0, 2, 2]);
+    assert_eq!(
+        counts::try_displacements(&[1, -1]),
+        Err(CountError::Negative { index: 1 })
+    );
+    assert_eq!(
+        counts::try_displacements(&[Count::max_value(), 1]).map(|d| d.len()),
+        Ok(2)
+    );
+    assert_eq!(
+        counts::try_displacements(&[Count::max_value(), 1, 1]),
+        Err(CountError::Overflow)
+    );
+    assert_eq!(
+        counts::check_bounds(&[2, 2], &[0, 2], 3),
+        Err(CountError::OutOfBounds {
+            index: 1,
+            end: 4,
+            len: 3
+        })
+    );
+    assert_eq!(
+        counts::check_disjoint(&[2, 2], &[0, 1]),
+        Err(CountError::Overlap {
+            first: 0,
+            second: 1
+        })
+    );
+    assert_eq!(counts::check_disjoint(&[2, 0, 2], &[2, 3, 0]), Ok(()));
+    assert_eq!(counts::check_partitions(&[2, 2], &[0, 1]), Ok(()));
+    assert_eq!(
+        counts::check_partitions(&[2, -1], &[0, 1]),
+        Err(CountError::Negative { index: 1 })
+    );
+
+    // Gather with a checked receive partition.
+    let counts: Vec<Count> = (0..size).map(|r| r + 1).collect();
+    let displs = counts::displacements(&counts);
+    let msg = vec![rank; rank as usize + 1];
+    let mut buf = vec![-1; counts.iter().sum::<Count>() as usize];
+    {
+        let mut partition = PartitionMut::new(&mut buf[..], &counts[..], &displs[..]);
+        world.all_gather_varcount_into(&msg[..], &mut partition);
+    }
+    for r in 0..size {
+        let start = displs[r as usize] as usize;
+        assert!(buf[start..start + r as usize + 1].iter().all(|&x| x == r));
+    }
+}
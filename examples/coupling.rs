@@ -0,0 +1,77 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::coupling::{self, Endpoint};
+use mpi::topology::{Color, Rank};
+use mpi::traits::*;
+
+const TAG: mpi::Tag = 10;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // The first half of the processes play the simulation, the second half an analysis tool
+    // attaching to it.
+    let num_servers = size / 2;
+    let is_server = rank < num_servers;
+    let local = world
+        .split_by_color(Color::with_value(if is_server { 0 } else { 1 }))
+        .unwrap();
+
+    // The port name is handed to the clients out of band, here via the world communicator.
+    let endpoint = if is_server {
+        Some(Endpoint::open(&local, 0))
+    } else {
+        None
+    };
+    let mut port_name: Vec<u8> = endpoint
+        .as_ref()
+        .and_then(Endpoint::port_name)
+        .map(|name| name.as_bytes().to_vec())
+        .unwrap_or_default();
+    let mut len = port_name.len() as u64;
+    world.process_at_rank(0).broadcast_into(&mut len);
+    port_name.resize(len as usize, 0);
+    world.process_at_rank(0).broadcast_into(&mut port_name[..]);
+    let port_name = String::from_utf8(port_name).unwrap();
+
+    let connection = match endpoint {
+        Some(ref endpoint) => endpoint.accept(),
+        None => coupling::connect_to_port(&local, 0, &port_name),
+    };
+    assert_eq!(size - local.size(), connection.remote_size());
+
+    let channel = connection.channel::<Rank, i64>(TAG);
+    if is_server {
+        // Every client sends a single request to server `client % num_servers`.
+        let num_clients = size - num_servers;
+        let num_requests = (0..num_clients)
+            .filter(|client| client % num_servers == local.rank())
+            .count();
+        for _ in 0..num_requests {
+            let client = channel.serve(|x| i64::from(x) * i64::from(x));
+            assert_eq!(local.rank(), client % num_servers);
+        }
+    } else {
+        let x = local.rank() + 100;
+        let server = local.rank() % connection.remote_size();
+        assert_eq!(i64::from(x) * i64::from(x), channel.request(server, &x));
+    }
+
+    // The simulation tells the tool how many requests it served, collectively across the groups.
+    let intercomm = connection.intercommunicator();
+    let num_clients = size - num_servers;
+    if is_server {
+        intercomm.broadcast_to_remote(local.rank() == 0, &num_clients);
+    } else {
+        let mut served: Rank = 0;
+        intercomm.broadcast_from_remote(0, &mut served);
+        assert_eq!(num_clients, served);
+    }
+    intercomm.barrier();
+
+    connection.disconnect();
+}
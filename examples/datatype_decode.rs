@@ -0,0 +1,63 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::{Combiner, DatatypeArgument, UncommittedDatatypeRef, UserDatatype};
+use mpi::traits::*;
+
+/// Describe `datatype` and its arguments as a nested expression, e.g. `vector[2, 1, 3](named)`.
+fn describe<D: UncommittedDatatype>(datatype: &D) -> String {
+    let decoded = datatype.decode();
+    if decoded.combiner == Combiner::Named {
+        return "named".to_owned();
+    }
+    let arguments: Vec<String> = decoded.datatypes.iter().map(describe).collect();
+    format!(
+        "{}{:?}{:?}({})",
+        decoded.combiner.name(),
+        decoded.integers,
+        decoded.addresses,
+        arguments.join(", ")
+    )
+}
+
+fn main() {
+    let _universe = mpi::initialize().unwrap();
+
+    let int = i32::equivalent_datatype();
+    let named = int.decode();
+    assert_eq!(named.combiner, Combiner::Named);
+    assert!(named.integers.is_empty() && named.datatypes.is_empty());
+
+    let vector = UserDatatype::vector(2, 1, 3, &int);
+    let decoded = vector.decode();
+    assert_eq!(decoded.combiner, Combiner::Vector);
+    assert_eq!(decoded.integers, [2, 1, 3]);
+    assert!(decoded.addresses.is_empty());
+    assert_eq!(decoded.datatypes.len(), 1);
+    assert!(matches!(decoded.datatypes[0], DatatypeArgument::Named(_)));
+
+    // A struct of a double and the vector decodes recursively down to the predefined types.
+    let structured = UserDatatype::structured(
+        &[1, 1],
+        &[0, 8],
+        &[
+            UncommittedDatatypeRef::from(f64::equivalent_datatype()),
+            UncommittedDatatypeRef::from(&vector),
+        ],
+    );
+    let decoded = structured.decode();
+    assert_eq!(decoded.combiner, Combiner::Struct);
+    assert_eq!(decoded.integers, [2, 1, 1]);
+    assert_eq!(decoded.addresses, [0, 8]);
+    let vector_argument = &decoded.datatypes[1];
+    assert!(matches!(vector_argument, DatatypeArgument::Derived(_)));
+    assert_eq!(vector_argument.decode().integers, [2, 1, 3]);
+    assert_eq!(
+        describe(&structured),
+        "struct[2, 1, 1][0, 8](named, vector[2, 1, 3][](named))"
+    );
+
+    // Arguments can be used to build new datatypes.
+    let copy = UserDatatype::contiguous(2, vector_argument);
+    assert_eq!(copy.size(), 2 * vector.size());
+}
@@ -0,0 +1,73 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::mem::size_of;
+
+use mpi::datatype::{FootprintError, MutView, UserDatatype, View};
+use mpi::point_to_point as p2p;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let int = i32::equivalent_datatype();
+    assert_eq!(int.size(), size_of::<i32>() as mpi::Count);
+    assert_eq!(int.extent(), (0, size_of::<i32>() as mpi::Address));
+
+    // Three blocks of two elements with a stride of five elements touch 12 elements of data.
+    let t = UserDatatype::vector(3, 2, 5, &int);
+    assert_eq!(t.size(), 6 * size_of::<i32>() as mpi::Count);
+    assert_eq!(t.extent(), (0, 12 * size_of::<i32>() as mpi::Address));
+    assert_eq!(t.true_extent(), t.extent());
+
+    // A single element placed after a gap starts at a lower bound beyond zero.
+    let shifted = UserDatatype::structured(&[1], &[8], &[int]);
+    assert_eq!(shifted.extent(), (8, size_of::<i32>() as mpi::Address));
+    assert_eq!(shifted.true_extent(), (8, size_of::<i32>() as mpi::Address));
+
+    // Allocate the receive buffer for two instances of the vector from its extent.
+    let (lower_bound, extent) = t.extent();
+    let len = (lower_bound + 2 * extent) as usize / size_of::<i32>();
+    let message: Vec<i32> = (0..len as i32).map(|i| 100 * rank + i).collect();
+    let mut received = vec![-1; len];
+    {
+        let sent = View::try_new(&message[..], 2, &t).unwrap();
+        let mut view = unsafe { MutView::with_count_and_datatype(&mut received[..], 2, &t) };
+        p2p::send_receive_into(&sent, &next, &mut view, &previous);
+    }
+    let expected: Vec<i32> = (0..len as i32)
+        .map(|i| {
+            if i % 12 % 5 < 2 {
+                100 * previous.rank() + i
+            } else {
+                -1
+            }
+        })
+        .collect();
+    assert_eq!(received, expected);
+
+    // A third instance of the vector would reach beyond the end of the buffer.
+    let error = View::try_new(&message[..], 3, &t).err().unwrap();
+    assert_eq!(
+        error,
+        FootprintError::OutOfBounds {
+            lower: 0,
+            upper: 2 * extent + 12 * size_of::<i32>() as mpi::Address,
+            buffer_len: len * size_of::<i32>(),
+        }
+    );
+    assert_eq!(
+        View::try_new(&message[..], -1, &t).err().unwrap(),
+        FootprintError::NegativeCount(-1)
+    );
+
+    // The shifted element lies within a buffer of three elements, but not within one of two.
+    assert!(View::try_new(&message[..3], 1, &shifted).is_ok());
+    assert!(View::try_new(&message[..2], 1, &shifted).is_err());
+    assert!(View::try_new(&message[..0], 0, &shifted).is_ok());
+}
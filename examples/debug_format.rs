@@ -0,0 +1,68 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::UserDatatype;
+use mpi::topology::Rank;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let world_debug = format!("{:?}", world);
+    assert!(world_debug.starts_with("SystemCommunicator"));
+    assert!(world_debug.contains(&format!("rank: {}", rank)));
+    assert!(world_debug.contains(&format!("size: {}", size)));
+
+    let dup = world.duplicate();
+    dup.set_name("duplicate");
+    let dup_debug = format!("{:?}", dup);
+    assert!(dup_debug.starts_with("UserCommunicator"));
+    assert!(dup_debug.contains("name: \"duplicate\""));
+
+    let cart = world
+        .create_cartesian_communicator(&[size], &[true], false)
+        .unwrap();
+    assert!(format!("{:?}", cart).contains(&format!("coords: [{}]", cart.rank())));
+
+    let named = format!("{:?}", u32::equivalent_datatype());
+    assert!(named.contains("combiner: \"named\""));
+    assert!(named.contains("size: 4"));
+
+    let vector = UserDatatype::vector(2, 1, 3, &u32::equivalent_datatype());
+    let vector_debug = format!("{:?}", vector);
+    assert!(vector_debug.starts_with("UserDatatype"));
+    assert!(vector_debug.contains("combiner: \"vector\""));
+    assert!(vector_debug.contains("size: 8"));
+    assert!(vector_debug.contains("extent: 16"));
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let x = [rank; 3];
+    let mut y = [0; 3];
+    mpi::request::scope(|scope| {
+        let rreq = previous.immediate_receive_into_with_tag(scope, &mut y[..], 7);
+        let sreq = next.immediate_send_with_tag(scope, &x[..], 7);
+        assert!(format!("{:?}", rreq).starts_with("Request { state: "));
+        let status = rreq.wait();
+        sreq.wait();
+
+        assert_eq!(
+            format!("{}", status),
+            format!(
+                "message of 12 bytes from process {} with tag 7",
+                previous.rank()
+            )
+        );
+        let status_debug = format!("{:?}", status);
+        assert!(status_debug.contains("bytes: 12"));
+        assert!(status_debug.contains("cancelled: false"));
+    });
+
+    let (received, status): (Rank, _) =
+        mpi::point_to_point::send_receive_with_tags(&rank, &next, 3, &previous, 3);
+    assert_eq!(received, previous.rank());
+    println!("Rank {}: received {}", rank, status);
+}
@@ -0,0 +1,63 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::mem::size_of;
+
+use mpi::traits::*;
+
+/// A header without padding between its fields
+#[derive(Equivalence, Copy, Clone, Default, PartialEq, Debug)]
+#[repr(C, packed)]
+struct Header {
+    kind: u8,
+    length: u32,
+    checksum: u16,
+}
+
+/// A value that occupies a cache line of its own
+#[derive(Equivalence, Copy, Clone, Default, PartialEq, Debug)]
+#[repr(C, align(64))]
+struct Padded {
+    value: f64,
+    flag: u8,
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    // The extents match the Rust layout, so slices of the structs are described correctly.
+    assert_eq!(size_of::<Header>(), 7);
+    let header = Header::equivalent_datatype();
+    assert_eq!(header.extent(), (0, 7));
+    assert_eq!(header.size(), 7);
+    let padded = Padded::equivalent_datatype();
+    assert_eq!(padded.extent(), (0, 64));
+    assert_eq!(padded.size(), 9);
+
+    let expected_headers: Vec<Header> = (0..5)
+        .map(|i| Header {
+            kind: i,
+            length: 1000 * u32::from(i),
+            checksum: 0xbeef ^ u16::from(i),
+        })
+        .collect();
+    let expected_padded: Vec<Padded> = (0..3)
+        .map(|i| Padded {
+            value: f64::from(i) / 4.0,
+            flag: i as u8,
+        })
+        .collect();
+
+    let (mut headers, mut values) = if rank == 0 {
+        (expected_headers.clone(), expected_padded.clone())
+    } else {
+        (vec![Header::default(); 5], vec![Padded::default(); 3])
+    };
+    let root_process = world.process_at_rank(0);
+    root_process.broadcast_into(&mut headers[..]);
+    root_process.broadcast_into(&mut values[..]);
+    assert_eq!(headers, expected_headers);
+    assert_eq!(values, expected_padded);
+}
@@ -0,0 +1,43 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::marker::PhantomData;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+#[derive(Equivalence, Copy, Clone, Default, PartialEq, Debug)]
+#[repr(transparent)]
+struct Meters(f64);
+
+/// An index into a collection of `T`s
+#[derive(Equivalence, Copy, Clone, PartialEq, Debug)]
+#[repr(transparent)]
+struct Index<T> {
+    value: u32,
+    phantom: PhantomData<T>,
+}
+
+struct Cell;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let mut max = Meters::default();
+    world.all_reduce_into(
+        &Meters(f64::from(rank) * 0.5),
+        &mut max,
+        SystemOperation::max(),
+    );
+    assert!((max.0 - f64::from(size - 1) * 0.5).abs() < f64::EPSILON);
+
+    let mut index = Index::<Cell> {
+        value: if rank == 0 { 42 } else { 0 },
+        phantom: PhantomData,
+    };
+    world.process_at_rank(0).broadcast_into(&mut index);
+    assert_eq!(index.value, 42);
+}
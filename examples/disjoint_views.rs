@@ -0,0 +1,101 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::mem::size_of;
+
+use mpi::datatype::{DisjointViews, UserDatatype};
+use mpi::point_to_point::send_receive_disjoint;
+use mpi::traits::*;
+use mpi::{Address, Count};
+
+/// Number of rows of the matrix on every process
+const B: usize = 2;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank() as usize;
+    let size = world.size() as usize;
+    let n = size * B;
+    let element = size_of::<u32>() as Address;
+    let value = |row: usize, column: usize| (row * n + column) as u32;
+
+    // Every process holds `B` rows of an `n` x `n` matrix, followed by room for `B` rows of its
+    // transpose.
+    let mut buffer = vec![0u32; 2 * B * n];
+    for row in 0..B {
+        for column in 0..n {
+            buffer[row * n + column] = value(rank * B + row, column);
+        }
+    }
+
+    // The block of `B` x `B` elements that goes to a process, one block after the other.
+    let block = UserDatatype::vector(
+        B as Count,
+        B as Count,
+        n as Count,
+        &u32::equivalent_datatype(),
+    );
+    let send_type = UserDatatype::resized(&block, 0, B as Address * element);
+
+    // A received block is stored transposed, i.e. its rows become columns.
+    let column = UserDatatype::vector(B as Count, 1, n as Count, &u32::equivalent_datatype());
+    let column = UserDatatype::resized(&column, 0, element);
+    let columns = UserDatatype::contiguous(B as Count, &column);
+    let receive_type = UserDatatype::resized(&columns, 0, B as Address * element);
+
+    let mut views = unsafe {
+        DisjointViews::new(
+            &mut buffer[..],
+            0,
+            size as Count,
+            &send_type,
+            B * n,
+            size as Count,
+            &receive_type,
+        )
+    };
+    world.all_to_all_disjoint(&mut views);
+
+    let buffer = views.into_buffer();
+    for row in 0..B {
+        for column in 0..n {
+            assert_eq!(
+                buffer[B * n + row * n + column],
+                value(column, rank * B + row)
+            );
+        }
+    }
+
+    // Send the even elements of the buffer to the next process and receive the even elements of
+    // the previous process into the odd elements.
+    let mut buffer = vec![0u32; 2 * n];
+    for (i, x) in buffer.iter_mut().enumerate() {
+        *x = if i % 2 == 0 {
+            (rank * 2 * n + i) as u32
+        } else {
+            0
+        };
+    }
+    let strided = UserDatatype::vector(n as Count, 1, 2, &u32::equivalent_datatype());
+    let next = world.process_at_rank(((rank + 1) % size) as mpi::Rank);
+    let previous = world.process_at_rank(((rank + size - 1) % size) as mpi::Rank);
+
+    let mut views = unsafe { DisjointViews::new(&mut buffer[..], 0, 1, &strided, 1, 1, &strided) };
+    let status = send_receive_disjoint(&mut views, &next, &previous);
+    assert_eq!(status.source_rank(), previous.rank());
+
+    let buffer = views.into_buffer();
+    let previous = previous.rank() as usize;
+    for i in 0..n {
+        assert_eq!(buffer[2 * i], (rank * 2 * n + 2 * i) as u32);
+        assert_eq!(buffer[2 * i + 1], (previous * 2 * n + 2 * i) as u32);
+    }
+
+    // Offsets beyond the end of the buffer are rejected even for empty views.
+    let len = buffer.len();
+    let empty_beyond_end = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+        DisjointViews::new(&mut buffer[..], len + 1, 0, &strided, 0, 0, &strided);
+    }));
+    assert!(empty_beyond_end.is_err());
+}
@@ -0,0 +1,45 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::hashmap::DistributedHashMap;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let map: DistributedHashMap<u64, i64> = DistributedHashMap::new(&world, 64);
+    assert_eq!(map.capacity(), 64);
+
+    // Every process inserts its own keys, which end up spread over all processes.
+    let key = |r: i32, i: u64| 1000 * r as u64 + i;
+    for i in 0..10 {
+        assert_eq!(map.insert(key(rank, i), i as i64 * 2), None);
+    }
+    assert_eq!(map.insert(key(rank, 0), -1), Some(0));
+    map.synchronize();
+
+    // The keys of the next process are visible without its participation.
+    let next = (rank + 1) % size;
+    assert_eq!(map.get(&key(next, 0)), Some(-1));
+    for i in 1..10 {
+        assert_eq!(map.get(&key(next, i)), Some(i as i64 * 2));
+    }
+    assert!(!map.contains_key(&key(size, 0)));
+
+    // Concurrent updates of a shared counter are not lost.
+    let counter = u64::max_value();
+    for _ in 0..5 {
+        map.update(counter, |count| count.unwrap_or(0) + 1);
+    }
+    map.synchronize();
+    assert_eq!(map.get(&counter), Some(5 * i64::from(size)));
+
+    // Every entry is held by exactly one process, its owner.
+    let entries = map.local_entries();
+    assert!(entries.iter().all(|(k, _)| map.owner(k) == rank));
+    assert_eq!(world.sum(entries.len() as i32), 10 * size + 1);
+    map.synchronize();
+}
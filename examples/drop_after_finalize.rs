@@ -0,0 +1,26 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::UserDatatype;
+use mpi::environment;
+use mpi::traits::*;
+
+fn main() {
+    let (datatype, comm) = {
+        let universe = mpi::initialize().unwrap();
+        let world = universe.world();
+        let datatype = UserDatatype::contiguous(2, &i32::equivalent_datatype());
+        let comm = world.duplicate();
+        assert!(environment::leaked_handles().is_empty());
+        (datatype, comm)
+    };
+
+    // MPI has been finalized when the universe was dropped, so the handles are leaked instead of
+    // freed.
+    drop(datatype);
+    drop(comm);
+    assert_eq!(
+        environment::leaked_handles(),
+        vec![("UserCommunicator", 1), ("UserDatatype", 1)]
+    );
+}
@@ -0,0 +1,66 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::dump::dump_typed;
+use mpi::datatype::{UncommittedDatatypeRef, UserDatatype, View};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    // Predefined datatypes print one value per element.
+    let values = [1.5f64, -2.0];
+    let dump = dump_typed(&values[..]);
+    assert!(dump.contains("2 x "), "{}", dump);
+    assert!(dump.contains("+0 MPI_DOUBLE: 1.5"), "{}", dump);
+    assert!(dump.contains("+8 MPI_DOUBLE: -2.0"), "{}", dump);
+
+    // Derived datatypes print their constructors around the elements they describe.
+    let record = UserDatatype::structured::<UncommittedDatatypeRef>(
+        &[1, 2],
+        &[0, 4],
+        &[
+            i32::equivalent_datatype().into(),
+            u16::equivalent_datatype().into(),
+        ],
+    );
+    let data = [rank, 0x0002_0001];
+    let view = unsafe { View::with_count_and_datatype(&data[..], 1, &record) };
+    let dump = dump_typed(&view);
+    assert!(dump.contains("struct"), "{}", dump);
+    assert!(
+        dump.contains(&format!("+0 MPI_INT32_T: {}", rank)),
+        "{}",
+        dump
+    );
+    let bytes = data[1].to_ne_bytes();
+    let (low, high) = (
+        u16::from_ne_bytes([bytes[0], bytes[1]]),
+        u16::from_ne_bytes([bytes[2], bytes[3]]),
+    );
+    assert!(
+        dump.contains(&format!("+4 MPI_UINT16_T: {}", low)),
+        "{}",
+        dump
+    );
+    assert!(
+        dump.contains(&format!("+6 MPI_UINT16_T: {}", high)),
+        "{}",
+        dump
+    );
+
+    // A vector skips the elements between its blocks.
+    let every_other = UserDatatype::vector(2, 1, 2, &i32::equivalent_datatype());
+    let numbers = [10, 11, 12];
+    let view = unsafe { View::with_count_and_datatype(&numbers[..], 1, &every_other) };
+    let dump = dump_typed(&view);
+    assert!(dump.contains("+0 MPI_INT32_T: 10"), "{}", dump);
+    assert!(dump.contains("+8 MPI_INT32_T: 12"), "{}", dump);
+    assert!(!dump.contains("11"), "{}", dump);
+
+    if rank == 0 {
+        print!("{}", dump);
+    }
+}
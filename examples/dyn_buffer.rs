@@ -0,0 +1,80 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::datatype::{DynBuffer, DynBufferMut};
+use mpi::traits::*;
+
+/// Values of an element type that is only known at run time, e.g. from a configuration file
+enum Values {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    I32(Vec<i32>),
+}
+
+impl Values {
+    fn new(element_type: &str, values: &[i32]) -> Values {
+        match element_type {
+            "f32" => Values::F32(values.iter().map(|&x| x as f32).collect()),
+            "f64" => Values::F64(values.iter().map(|&x| f64::from(x)).collect()),
+            "i32" => Values::I32(values.to_vec()),
+            _ => panic!("Unknown element type {}", element_type),
+        }
+    }
+
+    fn as_dyn(&self) -> DynBuffer {
+        match *self {
+            Values::F32(ref values) => DynBuffer::new(&values[..]),
+            Values::F64(ref values) => DynBuffer::new(&values[..]),
+            Values::I32(ref values) => DynBuffer::new(&values[..]),
+        }
+    }
+
+    fn as_dyn_mut(&mut self) -> DynBufferMut {
+        match *self {
+            Values::F32(ref mut values) => DynBufferMut::new(&mut values[..]),
+            Values::F64(ref mut values) => DynBufferMut::new(&mut values[..]),
+            Values::I32(ref mut values) => DynBufferMut::new(&mut values[..]),
+        }
+    }
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root = world.process_at_rank(0);
+
+    for &element_type in &["f32", "f64", "i32"] {
+        // Only the allocation depends on the element type, the communication does not.
+        let mut values = Values::new(element_type, &[rank + 1, 2 * rank, 3 * rank]);
+        root.broadcast_into(&mut values.as_dyn_mut());
+        assert!(values.as_dyn().downcast::<u8>().is_none());
+
+        let mut sums = Values::new(element_type, &[0, 0, 0]);
+        world.all_reduce_into(
+            &values.as_dyn(),
+            &mut sums.as_dyn_mut(),
+            SystemOperation::sum(),
+        );
+        match sums {
+            Values::F32(ref sums) => assert_eq!(sums[..], [size as f32, 0.0, 0.0]),
+            Values::F64(ref sums) => assert_eq!(sums[..], [f64::from(size), 0.0, 0.0]),
+            Values::I32(ref sums) => assert_eq!(sums[..], [size, 0, 0]),
+        }
+
+        let mut gathered = Values::new(element_type, &vec![0; 3 * size as usize]);
+        world.all_gather_into(
+            &Values::new(element_type, &[rank; 3]).as_dyn(),
+            &mut gathered.as_dyn_mut(),
+        );
+        let gathered = gathered.as_dyn();
+        assert_eq!(gathered.len(), 3 * size as usize);
+        if let Some(gathered) = gathered.downcast::<i32>() {
+            for (i, &x) in gathered.iter().enumerate() {
+                assert_eq!(x, i as i32 / 3);
+            }
+        }
+    }
+}
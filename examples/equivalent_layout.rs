@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::assert_equivalent_layout;
+use mpi::traits::*;
+
+#[derive(Equivalence)]
+struct Particle {
+    position: [f64; 3],
+    velocity: [f64; 3],
+    charge: i8,
+    id: u32,
+}
+
+#[derive(Equivalence)]
+#[repr(C)]
+struct Cell {
+    flag: bool,
+    density: f64,
+    neighbors: (u16, u16),
+}
+
+#[derive(Equivalence)]
+struct Pair(u8, f32);
+
+fn main() {
+    let _universe = mpi::initialize().unwrap();
+
+    assert_equivalent_layout!(f64);
+    assert_equivalent_layout!(bool);
+    assert_equivalent_layout!(Particle);
+    assert_equivalent_layout!(Particle {
+        position,
+        velocity,
+        charge,
+        id,
+    });
+    assert_equivalent_layout!(Cell {
+        flag,
+        density,
+        neighbors
+    });
+    assert_equivalent_layout!(Pair { 0, 1 });
+}
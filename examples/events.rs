@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::events::{self, Timestamped};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root = world.process_at_rank(0);
+
+    // Every process records events at interleaved times, including one that all processes
+    // record at the same time.
+    let mut log: Vec<Timestamped<i32>> = (0..5)
+        .map(|i| Timestamped {
+            nanos: i64::from(i * size + rank) * 1_000,
+            payload: rank * 100 + i,
+        })
+        .collect();
+    log.push(Timestamped {
+        nanos: i64::from(5 * size) * 1_000,
+        payload: rank * 100 + 5,
+    });
+
+    let now = Timestamped::now(0u8);
+    assert_eq!(Timestamped::new(now.time(), 0u8), now);
+
+    if let Some(trace) = events::merge_sorted_by_time(&root, &log[..]) {
+        assert_eq!(trace.len(), log.len() * size as usize);
+        assert!(trace.windows(2).all(|pair| pair[0].nanos <= pair[1].nanos));
+        for (i, event) in trace.iter().take(5 * size as usize).enumerate() {
+            let i = i as i32;
+            assert_eq!(event.payload, (i % size) * 100 + i / size);
+        }
+        // Events with the same time stamp are ordered by rank.
+        for (r, event) in trace.iter().skip(5 * size as usize).enumerate() {
+            assert_eq!(event.payload, r as i32 * 100 + 5);
+        }
+    } else {
+        assert_ne!(rank, 0);
+    }
+}
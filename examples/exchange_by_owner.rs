@@ -0,0 +1,35 @@
+#![deny(warnings)]
+#![allow(clippy::float_cmp)]
+extern crate mpi;
+
+use mpi::random::Stream;
+use mpi::topology::Rank;
+use mpi::traits::*;
+
+/// The particles of process `rank`, positions on the unit interval
+fn particles(rank: Rank) -> Vec<f64> {
+    let mut stream = Stream::new(7, rank, 0);
+    (0..100 + rank).map(|_| stream.next_f64()).collect()
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Every process owns the particles in one slab of the unit interval.
+    let owner = |x: &f64| ((x * f64::from(size)) as Rank).min(size - 1);
+    let migrated = world.exchange_by_owner(&particles(rank), owner);
+
+    // The particles arrive ordered by the rank of their previous owner and keep their order.
+    let expected: Vec<f64> = (0..size)
+        .flat_map(particles)
+        .filter(|x| owner(x) == rank)
+        .collect();
+    assert_eq!(migrated, expected);
+
+    // Migrating again leaves every particle where it is.
+    let stay = world.exchange_by_owner(&migrated, owner);
+    assert_eq!(stay, migrated);
+}
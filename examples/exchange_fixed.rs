@@ -0,0 +1,33 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::point_to_point as p2p;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    let next_rank = (rank + 1) % size;
+    let previous_rank = (rank + size - 1) % size;
+    let next_process = world.process_at_rank(next_rank);
+    let previous_process = world.process_at_rank(previous_rank);
+
+    let msg = [rank, 2 * rank, 3 * rank];
+    let received = p2p::exchange_fixed(&msg, &next_process, &previous_process);
+    assert_eq!(
+        [previous_rank, 2 * previous_rank, 3 * previous_rank],
+        received
+    );
+
+    let mut halo = [0.0f64; 4];
+    for step in 0..10 {
+        let boundary = [f64::from(rank), f64::from(step), 0.5, -0.5];
+        halo = p2p::exchange_fixed(&boundary, &previous_process, &next_process);
+        assert_eq!(f64::from(next_rank), halo[0]);
+        assert_eq!(f64::from(step), halo[1]);
+    }
+    assert_eq!([f64::from(next_rank), 9.0, 0.5, -0.5], halo);
+}
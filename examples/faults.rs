@@ -0,0 +1,85 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::time::{Duration, Instant};
+
+use mpi::faults::{self, Fault, Rule};
+use mpi::request::{self, join_or_cancel};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+    // Only injected faults make operations fail in this example and `join_or_cancel()` reports
+    // them.
+    unsafe {
+        world.set_errors_return();
+    }
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    // Delay the second barrier.
+    faults::inject(Rule::new("barrier", Fault::Delay(Duration::from_millis(50))).skip(1));
+    world.barrier();
+    let start = Instant::now();
+    world.barrier();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(faults::injected(), 1);
+    faults::clear();
+
+    // Fail the first completed request of a join, the remaining ones are cancelled or complete.
+    faults::inject(Rule::new("join_or_cancel", Fault::Error(1)).times(1));
+    let mut received = 0;
+    request::scope(|scope| {
+        let requests = vec![
+            previous.immediate_receive_into_with_tag(scope, &mut received, 3),
+            next.immediate_send_with_tag(scope, &rank, 3),
+        ];
+        match join_or_cancel(requests) {
+            Ok(_) => panic!("The injected fault was not reported."),
+            Err(report) => {
+                assert_eq!(report.code(), 1);
+                let failed = report.failed().expect("The failed request is known.");
+                assert!(failed < 2);
+                assert_eq!(
+                    report.completed().len() + report.cancelled().len(),
+                    1,
+                    "Every other request is either completed or cancelled."
+                );
+            }
+        }
+    });
+    assert_eq!(faults::injected(), 2);
+
+    // The rule is used up, so joins succeed again.
+    world.barrier();
+    request::scope(|scope| {
+        let requests = vec![
+            previous.immediate_receive_into_with_tag(scope, &mut received, 4),
+            next.immediate_send_with_tag(scope, &rank, 4),
+        ];
+        assert!(join_or_cancel(requests).is_ok());
+    });
+    assert_eq!(received, previous.rank());
+    faults::clear();
+
+    // Drop the first message to the next process, only the second one arrives.
+    faults::inject(
+        Rule::new("immediate_send_with_tag", Fault::Drop)
+            .peer(next.rank())
+            .times(1),
+    );
+    request::scope(|scope| {
+        let dropped = next.immediate_send_with_tag(scope, &rank, 5);
+        let sent = next.immediate_send_with_tag(scope, &size, 5);
+        let (msg, _) = previous.receive_with_tag::<mpi::topology::Rank>(5);
+        assert_eq!(msg, size);
+        dropped.wait();
+        sent.wait();
+    });
+    assert_eq!(faults::injected(), 3);
+    faults::clear();
+}
@@ -0,0 +1,29 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::FixedStr;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size() as usize;
+
+    let c_field = FixedStr::<8>::nul_padded("abc");
+    assert_eq!(c_field.as_bytes(), b"abc\0\0\0\0\0");
+    let fortran_field = FixedStr::<8>::space_padded("abc");
+    assert_eq!(fortran_field.as_bytes(), b"abc     ");
+    assert_eq!(c_field.to_str().unwrap(), "abc");
+    assert_eq!(fortran_field.to_str().unwrap(), "abc");
+    assert_eq!(format!("{}", fortran_field), "abc");
+
+    // Exchange a column of fixed-width text fields, e.g. station names of a Fortran record.
+    let name = FixedStr::<16>::space_padded(&format!("station {}", rank));
+    let mut names = vec![FixedStr::<16>::default(); size];
+    world.all_gather_into(&name, &mut names[..]);
+    for (r, name) in names.iter().enumerate() {
+        assert_eq!(name.to_string_lossy(), format!("station {}", r));
+        assert_eq!(name.as_bytes().len(), 16);
+    }
+}
@@ -0,0 +1,35 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::funnel::{FunnelReceiver, FunnelSender};
+use mpi::traits::*;
+
+const WINDOW: i32 = 4;
+const MESSAGES: i32 = 20;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    if rank == 0 {
+        let mut funnel = FunnelReceiver::new(&world, WINDOW);
+        let mut next = vec![0; size as usize];
+        while let Some((source, msg)) = funnel.receive::<i32>() {
+            // Messages of every sender arrive in order.
+            assert_eq!(msg, vec![source, next[source as usize]]);
+            next[source as usize] += 1;
+        }
+        assert!(funnel.is_finished());
+        assert!(next[1..].iter().all(|&n| n == MESSAGES));
+    } else {
+        let mut funnel = FunnelSender::new(&world, 0, WINDOW);
+        assert_eq!(funnel.credits(), WINDOW);
+        for i in 0..MESSAGES {
+            funnel.send(&[rank, i][..]);
+            assert!(funnel.credits() <= WINDOW);
+        }
+        funnel.finish();
+    }
+}
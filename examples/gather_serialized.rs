@@ -0,0 +1,33 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let root_rank = 0;
+    let root_process = world.process_at_rank(root_rank);
+
+    // Every process contributes a result of a different size.
+    let result = (
+        rank,
+        format!("parameter set {}", rank),
+        (0..rank).map(f64::from).collect::<Vec<_>>(),
+    );
+
+    if rank == root_rank {
+        let results = root_process.gather_serialized_into_root(&result);
+        assert_eq!(size as usize, results.len());
+        for (r, (rank, name, values)) in (0..size).zip(results) {
+            assert_eq!(r, rank);
+            assert_eq!(format!("parameter set {}", r), name);
+            assert_eq!((0..r).map(f64::from).collect::<Vec<_>>(), values);
+        }
+    } else {
+        root_process.gather_serialized_into(&result);
+    }
+}
@@ -0,0 +1,43 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // Contributions of different lengths
+    let contribution: Vec<u64> = (0..=rank as u64).map(|i| 1000 * rank as u64 + i).collect();
+
+    let root_rank = size - 1;
+    let root_process = world.process_at_rank(root_rank);
+    if rank == root_rank {
+        let mut next_rank = 0;
+        let mut total = 0;
+        root_process.gather_streaming_into_root(&contribution[..], |r, chunk| {
+            assert_eq!(next_rank, r);
+            next_rank += 1;
+            let expected: Vec<u64> = (0..=r as u64).map(|i| 1000 * r as u64 + i).collect();
+            assert_eq!(&expected[..], chunk);
+            total += chunk.len();
+        });
+        assert_eq!(size, next_rank);
+        assert_eq!((size * (size + 1) / 2) as usize, total);
+
+        // Windows of three processes, the last window may be smaller
+        let mut next_rank = 0;
+        root_process.gather_streaming_into_root_windowed(&contribution[..], 3, |r, chunk| {
+            assert_eq!(next_rank, r);
+            next_rank += 1;
+            assert_eq!(r as usize + 1, chunk.len());
+            assert_eq!(1000 * r as u64, chunk[0]);
+        });
+        assert_eq!(size, next_rank);
+    } else {
+        root_process.gather_streaming_into(&contribution[..]);
+        root_process.gather_streaming_into(&contribution[..]);
+    }
+}
@@ -0,0 +1,53 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::topology::HaloStencil;
+use mpi::traits::*;
+use mpi::Count;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+
+    let dims = [size, 1];
+    let cart = world
+        .create_cartesian_communicator(&dims, &[true, false], false)
+        .unwrap();
+    let rank = cart.rank();
+
+    // Every process owns a 3x4 block surrounded by one layer of ghost cells.
+    let local_shape: [Count; 2] = [3, 4];
+    let halo = cart.halo::<i32>(&local_shape, 1, HaloStencil::Full);
+    assert_eq!([5, 6], halo.padded_shape());
+    assert_eq!(8, halo.neighbors().len());
+
+    let (rows, columns) = (5, 6);
+    let mut array = vec![-1; halo.padded_len()];
+    for row in 1..rows - 1 {
+        for column in 1..columns - 1 {
+            array[row * columns + column] = 1000 * rank + 10 * row as i32 + column as i32;
+        }
+    }
+    halo.exchange(&mut array);
+
+    let previous_rank = (rank + size - 1) % size;
+    let next_rank = (rank + 1) % size;
+    for column in 1..columns - 1 {
+        // The top ghost row holds the bottom interior row of the previous process.
+        assert_eq!(
+            1000 * previous_rank + 10 * (rows - 2) as i32 + column as i32,
+            array[column]
+        );
+        // The bottom ghost row holds the top interior row of the next process.
+        assert_eq!(
+            1000 * next_rank + 10 + column as i32,
+            array[(rows - 1) * columns + column]
+        );
+    }
+    // The second dimension is not periodic, so the left and right ghost columns stay untouched.
+    for row in 0..rows {
+        assert_eq!(-1, array[row * columns]);
+        assert_eq!(-1, array[row * columns + columns - 1]);
+    }
+}
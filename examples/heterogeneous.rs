@@ -0,0 +1,33 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::heterogeneous;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    // All processes of the test run on the same machine.
+    assert!(heterogeneous::handshake(&world));
+    assert!(!heterogeneous::is_heterogeneous());
+
+    let msg: Vec<usize> = (0..4).map(|i| i * 10).collect();
+    if rank == 0 {
+        let other = world.process_at_rank(1);
+        other.send_portable(&msg[..]);
+
+        let mut x = 0.0f64;
+        let status = other.receive_portable_into_with_tag(&mut x, 3);
+        assert_eq!(status.tag(), 3);
+        assert!((x - 1.5).abs() < f64::EPSILON);
+    } else if rank == 1 {
+        let other = world.process_at_rank(0);
+        let mut received = vec![0usize; 4];
+        other.receive_portable_into(&mut received[..]);
+        assert_eq!(received, msg);
+
+        other.send_portable_with_tag(&1.5f64, 3);
+    }
+}
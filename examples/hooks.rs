@@ -0,0 +1,93 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use mpi::hooks::{self, Call, CommHook};
+use mpi::traits::*;
+
+#[derive(Default)]
+struct Counts {
+    started: AtomicUsize,
+    finished: AtomicUsize,
+    barriers: AtomicUsize,
+    sent: AtomicUsize,
+}
+
+struct Counter(Arc<Counts>);
+
+impl CommHook for Counter {
+    fn before(&self, call: &Call) {
+        self.0.started.fetch_add(1, Ordering::Relaxed);
+        if call.name == "barrier" {
+            self.0.barriers.fetch_add(1, Ordering::Relaxed);
+        }
+        if call.name == "send_with_tag" {
+            assert_eq!(call.tag, Some(5));
+            self.0
+                .sent
+                .fetch_add(call.count.unwrap() as usize, Ordering::Relaxed);
+        }
+    }
+
+    fn after(&self, _call: &Call, _duration: Duration) {
+        self.0.finished.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Registrar {
+    counts: Arc<Counts>,
+    registered: AtomicBool,
+}
+
+impl CommHook for Registrar {
+    fn before(&self, _call: &Call) {
+        if !self.registered.swap(true, Ordering::Relaxed) {
+            hooks::register(Box::new(Counter(self.counts.clone())));
+        }
+    }
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let counts = Arc::new(Counts::default());
+    hooks::register(Box::new(Counter(counts.clone())));
+
+    world.barrier();
+    if rank == 0 {
+        world.process_at_rank(1).send_with_tag(&[1u32, 2, 3][..], 5);
+    } else if rank == 1 {
+        let mut buf = [0u32; 3];
+        world
+            .process_at_rank(0)
+            .receive_into_with_tag(&mut buf[..], 5);
+        assert_eq!(buf, [1, 2, 3]);
+    }
+    world.barrier();
+
+    hooks::clear();
+    world.barrier();
+
+    let expected_calls = if rank < 2 { 3 } else { 2 };
+    assert_eq!(counts.started.load(Ordering::Relaxed), expected_calls);
+    assert_eq!(counts.finished.load(Ordering::Relaxed), expected_calls);
+    assert_eq!(counts.barriers.load(Ordering::Relaxed), 2);
+    let expected_sent = if rank == 0 { 3 } else { 0 };
+    assert_eq!(counts.sent.load(Ordering::Relaxed), expected_sent);
+
+    // A hook can register further hooks, which are invoked starting with the next call.
+    let late = Arc::new(Counts::default());
+    hooks::register(Box::new(Registrar {
+        counts: late.clone(),
+        registered: AtomicBool::new(false),
+    }));
+    world.barrier();
+    world.barrier();
+    hooks::clear();
+    assert_eq!(late.barriers.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,60 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::environment::{self, Feature};
+use mpi::point_to_point;
+use mpi::request;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    if rank == 0 {
+        println!(
+            "MPI_Isendrecv is {}supported",
+            if environment::is_supported(Feature::ImmediateSendReceive) {
+                ""
+            } else {
+                "not "
+            }
+        );
+    }
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let msg = [rank, rank * rank];
+    let mut buf = [-1, -1];
+    request::scope(|scope| {
+        let request = point_to_point::immediate_send_receive_into(
+            scope,
+            &msg[..],
+            &next,
+            &mut buf[..],
+            &previous,
+        );
+        let status = request.wait();
+        assert_eq!(status.source_rank(), previous.rank());
+    });
+    assert_eq!(buf, [previous.rank(), previous.rank() * previous.rank()]);
+
+    let mut value = -1;
+    request::scope(|scope| {
+        let mut request = point_to_point::immediate_send_receive_into_with_tags(
+            scope, &rank, &next, 7, &mut value, &previous, 7,
+        );
+        loop {
+            match request.test() {
+                Ok(status) => {
+                    assert_eq!(status.tag(), 7);
+                    break;
+                }
+                Err(pending) => request = pending,
+            }
+        }
+    });
+    assert_eq!(value, previous.rank());
+}
@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate arrayvec;
+extern crate mpi;
+extern crate smallvec;
+
+use arrayvec::ArrayVec;
+use mpi::traits::*;
+use smallvec::SmallVec;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    // Small messages stay on the stack on both ends.
+    let message: SmallVec<[i32; 4]> = (0..4).map(|i| 10 * rank + i).collect();
+    assert!(!message.spilled());
+    let mut received: ArrayVec<i32, 4> = ArrayVec::from([0; 4]);
+    mpi::request::scope(|scope| {
+        let send = next.immediate_send(scope, &message);
+        previous.receive_into(&mut received);
+        send.wait();
+    });
+    let expected: Vec<i32> = (0..4).map(|i| 10 * previous.rank() + i).collect();
+    assert_eq!(&received[..], &expected[..]);
+
+    // Collective operations work on inline vectors, too.
+    let mut gathered: SmallVec<[i32; 8]> = SmallVec::from_elem(0, size as usize);
+    let root_process = world.process_at_rank(0);
+    if rank == 0 {
+        root_process.gather_into_root(&rank, &mut gathered);
+        assert!((0..size).eq(gathered.iter().copied()));
+    } else {
+        root_process.gather_into(&rank);
+    }
+
+    let mut values: ArrayVec<f64, 3> = ArrayVec::new();
+    values.extend((0..3).map(|i| f64::from(rank + i)));
+    root_process.broadcast_into(&mut values);
+    assert_eq!(&values[..], &[0.0, 1.0, 2.0]);
+}
@@ -0,0 +1,43 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::request::{self, join_or_cancel};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+    // Only `join_or_cancel()` fails in this example and it reports the error code.
+    unsafe {
+        world.set_errors_return();
+    }
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let msg = [rank, rank * 2];
+    let mut from_previous = [0; 2];
+    let mut more = 0;
+    request::scope(|scope| {
+        let requests = vec![
+            previous.immediate_receive_into_with_tag(scope, &mut from_previous[..], 1),
+            previous.immediate_receive_into_with_tag(scope, &mut more, 2),
+            next.immediate_send_with_tag(scope, &msg[..], 1),
+            next.immediate_send_with_tag(scope, &rank, 2),
+        ];
+        match join_or_cancel(requests) {
+            Ok(statuses) => {
+                assert_eq!(statuses.len(), 4);
+                assert_eq!(statuses[0].source_rank(), previous.rank());
+                assert_eq!(statuses[0].tag(), 1);
+                assert_eq!(statuses[1].tag(), 2);
+                assert!(statuses.iter().all(|status| !status.is_cancelled()));
+            }
+            Err(report) => panic!("{}", report),
+        }
+    });
+    assert_eq!(from_previous, [previous.rank(), previous.rank() * 2]);
+    assert_eq!(more, previous.rank());
+}
@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::collections::HashMap;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // Every process counts words in its share of a text.
+    let words = ["alpha", "beta", "gamma", "delta", "epsilon"];
+    let mut local: HashMap<String, u64> = HashMap::new();
+    for (i, word) in words.iter().enumerate() {
+        if i as i32 <= rank {
+            *local.entry(word.to_string()).or_insert(0) += (rank + 1) as u64;
+        }
+    }
+
+    let merged = world.merge_reduce_maps(local, |count, other| *count += other);
+
+    for (i, word) in words.iter().enumerate() {
+        let expected: u64 = (i as i32..size).map(|r| (r + 1) as u64).sum();
+        if expected == 0 {
+            assert!(!merged.contains_key(*word));
+        } else {
+            assert_eq!(Some(&expected), merged.get(*word));
+        }
+    }
+
+    // Distribute a configuration from the root process.
+    let root_process = world.process_at_rank(0);
+    let mut config: HashMap<String, String> = HashMap::new();
+    if rank == 0 {
+        config.insert("output".to_string(), "histogram.dat".to_string());
+        config.insert("bins".to_string(), "64".to_string());
+    }
+    root_process.broadcast_serialized_into(&mut config);
+    assert_eq!(2, config.len());
+    assert_eq!("64", config["bins"]);
+}
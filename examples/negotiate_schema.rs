@@ -0,0 +1,62 @@
+#![deny(warnings)]
+extern crate mpi;
+
+#[macro_use]
+extern crate memoffset;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use mpi::datatype::{StructLayoutBuilder, UserDatatype};
+use mpi::schema;
+use mpi::traits::*;
+
+#[derive(Default, Clone, Copy)]
+#[repr(C)]
+struct Record {
+    id: u32,
+    value: f64,
+}
+
+/// Whether this process runs the "old version" of the program, which describes the value of a
+/// `Record` as a single precision number
+static OLD_VERSION: AtomicBool = AtomicBool::new(false);
+
+unsafe impl Equivalence for Record {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        let value = if OLD_VERSION.load(Ordering::SeqCst) {
+            f32::equivalent_datatype()
+        } else {
+            f64::equivalent_datatype()
+        };
+        StructLayoutBuilder::<Record>::new()
+            .field(offset_of!(Record, id), 1, &u32::equivalent_datatype())
+            .field(offset_of!(Record, value), 1, &value)
+            .build()
+    }
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // All processes agree on the built-in and the current record datatypes.
+    let hash = schema::negotiate_schema::<f64, _>(&world).unwrap();
+    assert_eq!(hash, schema::signature_hash::<f64>());
+    assert_ne!(hash, schema::signature_hash::<i64>());
+    schema::negotiate_schema::<Record, _>(&world).unwrap();
+
+    // After a partial redeploy the odd ranks still run the old version.
+    OLD_VERSION.store(rank % 2 == 1, Ordering::SeqCst);
+    match schema::negotiate_schema::<Record, _>(&world) {
+        Ok(_) => assert_eq!(size, 1),
+        Err(mismatch) => {
+            let odd_ranks: Vec<_> = (1..size).step_by(2).collect();
+            assert_eq!(mismatch.dissenting_ranks(), odd_ranks);
+            assert_eq!(mismatch.hashes().len(), size as usize);
+            assert!(mismatch.type_name().ends_with("Record"));
+        }
+    }
+}
@@ -0,0 +1,34 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::traits::*;
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[repr(transparent)]
+struct NodeId(u64);
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[repr(transparent)]
+struct Joules(i64);
+
+mpi::equivalent_newtype!(NodeId => u64, Joules => i64);
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let mut ids = vec![NodeId::default(); size as usize];
+    world.all_gather_into(&NodeId(rank as u64 + 100), &mut ids[..]);
+    assert!(ids
+        .iter()
+        .enumerate()
+        .all(|(i, &id)| id == NodeId(i as u64 + 100)));
+
+    // Built-in reduction operations apply to the newtype as they do to the wrapped type.
+    let mut total = Joules::default();
+    world.all_reduce_into(&Joules(i64::from(rank)), &mut total, SystemOperation::sum());
+    assert_eq!(total, Joules((0..i64::from(size)).sum()));
+}
@@ -0,0 +1,48 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::shared::NodeExchange;
+use mpi::traits::*;
+
+const LEN: usize = 1 << 16;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    let mut exchange = NodeExchange::<u64>::new(&world, LEN);
+    let node_rank = exchange.node().rank();
+    let node_size = exchange.node().size();
+    assert!(node_size <= world.size());
+    // The processes alternate between writing their own segment and reading the segments of all
+    // processes, separated by `synchronize()`, so no segment is read while its owner writes it.
+    assert!(unsafe { exchange.segment(node_rank) }
+        .iter()
+        .all(|&x| x == 0));
+
+    // Every process writes its segment in place and reads the segments of all others.
+    for (i, x) in unsafe { exchange.local_mut() }.iter_mut().enumerate() {
+        *x = (node_rank as u64) << 32 | i as u64;
+    }
+    exchange.synchronize();
+    for rank in 0..node_size {
+        assert_eq!(exchange.capacity(rank), LEN);
+        let segment = unsafe { exchange.segment(rank) };
+        assert!(segment
+            .iter()
+            .enumerate()
+            .all(|(i, &x)| x == (rank as u64) << 32 | i as u64));
+    }
+
+    // All processes have read the segments before anybody publishes new data.
+    exchange.synchronize();
+    let data: Vec<u64> = (0..10).map(|i| i * node_rank as u64).collect();
+    unsafe {
+        exchange.publish(&data);
+    }
+    let next = (node_rank + 1) % node_size;
+    assert_eq!(
+        unsafe { &exchange.segment(next)[..10] },
+        &(0..10).map(|i| i * next as u64).collect::<Vec<_>>()[..]
+    );
+}
@@ -0,0 +1,85 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::point_to_point as p2p;
+use mpi::traits::*;
+use mpi::Rank;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    assert!(world.checked_process_at_rank(size).is_none());
+    assert!(world.checked_process_at_rank(-1).is_none());
+    assert_eq!(rank, world.checked_process_at_rank(rank).unwrap().rank());
+    assert!(world.null_process().is_null());
+    assert!(!world.this_process().is_null());
+
+    // A non-periodic chain of processes, the ends of the chain have no neighbors.
+    let left_rank = if rank > 0 { Some(rank - 1) } else { None };
+    let right_rank = if rank + 1 < size {
+        Some(rank + 1)
+    } else {
+        None
+    };
+    let left = world.process_at_rank_or_null(left_rank);
+    let right = world.process_at_rank_or_null(right_rank);
+    assert_eq!(left_rank.is_none(), left.is_null());
+    assert_eq!(right_rank.is_none(), right.is_null());
+
+    // Shift values to the right, the leftmost process keeps its initial value.
+    let mut x = -1;
+    p2p::send_receive_into(&rank, &right, &mut x, &left);
+    assert_eq!(left_rank.unwrap_or(-1), x);
+
+    // Shift values to the left, the rightmost process keeps its initial value.
+    let mut y = -1;
+    p2p::send_receive_into(&rank, &left, &mut y, &right);
+    assert_eq!(right_rank.unwrap_or(-1), y);
+
+    // A halo exchange with non-blocking operations, the ends of the chain keep their ghost cells.
+    let cells = [rank; 4];
+    let mut ghosts = [-1; 2];
+    mpi::request::scope(|scope| {
+        let (left_ghost, right_ghost) = ghosts.split_at_mut(1);
+        let mut requests = vec![
+            left.immediate_receive_into(scope, &mut left_ghost[0]),
+            right.immediate_receive_into(scope, &mut right_ghost[0]),
+            left.immediate_send(scope, &cells[0]),
+            right.immediate_send(scope, &cells[3]),
+        ];
+        while mpi::request::wait_any(&mut requests).is_some() {}
+    });
+    assert_eq!([left_rank.unwrap_or(-1), right_rank.unwrap_or(-1)], ghosts);
+
+    // Operations on the null process complete immediately without transferring any data.
+    let null = world.null_process();
+    null.send(&rank);
+    null.synchronous_send(&rank);
+    let mut z = [-1; 3];
+    let status = null.receive_into(&mut z[..]);
+    assert_eq!([-1; 3], z);
+    assert_eq!(null.rank(), status.source_rank());
+    assert_eq!(0, status.count(Rank::equivalent_datatype()));
+
+    let (v, status) = null.receive_vec::<Rank>();
+    assert!(v.is_empty());
+    assert_eq!(null.rank(), status.source_rank());
+
+    let (message, _) = null.matched_probe();
+    assert!(message.is_no_proc());
+    let status = message.matched_receive_into(&mut z[..]);
+    assert_eq!([-1; 3], z);
+    assert_eq!(null.rank(), status.source_rank());
+
+    // A message from the null process need not be received.
+    let (message, _) = null.immediate_matched_probe().unwrap();
+    assert!(message.is_no_proc());
+    drop(message);
+
+    let mut w = rank;
+    p2p::send_receive_replace_into(&mut w, &null, &null);
+    assert_eq!(rank, w);
+}
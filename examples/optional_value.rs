@@ -0,0 +1,36 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::OptionalValue;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Only odd ranks contribute.
+    let local: OptionalValue<i32> = if rank % 2 == 1 { Some(rank) } else { None }.into();
+
+    let mut all = vec![OptionalValue::none(); size as usize];
+    world.all_gather_into(&local, &mut all[..]);
+    for (r, value) in all.iter().enumerate() {
+        assert_eq!(value.is_some(), r % 2 == 1);
+    }
+
+    let min = OptionalValue::reduction(true, |a: i32, b: i32| a.min(b));
+    let mut result = OptionalValue::none();
+    world.all_reduce_into(&local, &mut result, &min);
+    if size > 1 {
+        assert_eq!(result.get(), Some(1));
+    } else {
+        assert_eq!(result.get(), None);
+    }
+
+    let sum = OptionalValue::reduction(true, |a: i32, b: i32| a + b);
+    let mut total = OptionalValue::none();
+    world.all_reduce_into(&local, &mut total, &sum);
+    let expected: i32 = (0..size).filter(|r| r % 2 == 1).sum();
+    assert_eq!(total.get().unwrap_or(0), expected);
+}
@@ -0,0 +1,48 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::pack::PackedBuffer;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let values: Vec<f64> = (0..10).map(|i| f64::from(i) * 0.5).collect();
+    let name = b"pressure";
+
+    if rank == 0 {
+        // A header, the values and a name travel in one message of type `MPI_PACKED`.
+        let mut buffer = PackedBuffer::new();
+        let header = [values.len() as u64, name.len() as u64];
+        buffer.pack_into(&world, &header[..]);
+        buffer.pack_into(&world, &values[..]);
+        buffer.pack_into(&world, &name[..]);
+        assert!(
+            buffer.len()
+                <= PackedBuffer::pack_size(&world, &header[..])
+                    + PackedBuffer::pack_size(&world, &values[..])
+                    + PackedBuffer::pack_size(&world, &name[..])
+        );
+        for destination in 1..size {
+            world.process_at_rank(destination).send(&buffer);
+        }
+    } else {
+        let (mut buffer, status) = PackedBuffer::receive_from(&world.process_at_rank(0));
+        assert_eq!(status.source_rank(), 0);
+
+        let mut header = [0u64; 2];
+        unsafe { buffer.unpack_from(&world, &mut header[..]) };
+        let mut received_values = vec![0.0f64; header[0] as usize];
+        let mut received_name = vec![0u8; header[1] as usize];
+        unsafe {
+            buffer.unpack_from(&world, &mut received_values[..]);
+            buffer.unpack_from(&world, &mut received_name[..]);
+        }
+        assert_eq!(buffer.remaining(), 0);
+        assert_eq!(received_values, values);
+        assert_eq!(&received_name[..], &name[..]);
+    }
+}
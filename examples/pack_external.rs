@@ -0,0 +1,48 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::fs;
+
+use mpi::datatype::pack::{
+    pack_external, pack_external_into, pack_external_size, unpack_external_into,
+};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let step = [rank, 42];
+    let field: Vec<f64> = (0..16)
+        .map(|i| f64::from(i) + f64::from(rank) / 10.0)
+        .collect();
+
+    // `external32` has a fixed size and byte order for every type.
+    assert_eq!(pack_external_size(&step[..]), 2 * 4);
+    assert_eq!(pack_external_size(&field[..]), 16 * 8);
+    let mut checkpoint = pack_external(&step[..]);
+    pack_external_into(&field[..], &mut checkpoint);
+    assert_eq!(checkpoint.len(), 2 * 4 + 16 * 8);
+    // The first integer is stored big-endian.
+    assert_eq!(&checkpoint[..4], &rank.to_be_bytes()[..]);
+
+    let path = std::env::temp_dir().join(format!(
+        "rsmpi-checkpoint-{}-{}.bin",
+        std::process::id(),
+        rank
+    ));
+    fs::write(&path, &checkpoint).unwrap();
+    let restored = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut restored_step = [0i32; 2];
+    let mut restored_field = vec![0.0f64; 16];
+    let position = unsafe {
+        let position = unpack_external_into(&restored, &mut restored_step[..], 0);
+        unpack_external_into(&restored, &mut restored_field[..], position)
+    };
+    assert_eq!(position, restored.len());
+    assert_eq!(restored_step, step);
+    assert_eq!(restored_field, field);
+}
@@ -0,0 +1,52 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::random::Stream;
+use mpi::sort;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    // Every process starts out with a different number of random values.
+    let mut stream = Stream::for_rank(&world, 42);
+    let len = 1000 + 100 * rank as usize;
+    let mut values: Vec<u64> = (0..len).map(|_| stream.next_u64() % 10_000).collect();
+    let mut total = 0u64;
+    world.all_reduce_into(&(values.len() as u64), &mut total, SystemOperation::sum());
+    let mut checksum = 0u64;
+    world.all_reduce_into(
+        &values.iter().sum::<u64>(),
+        &mut checksum,
+        SystemOperation::sum(),
+    );
+
+    assert!(world.size() == 1 || !sort::is_sorted(&world, &values));
+    sort::parallel_sort(&world, &mut values);
+    assert!(sort::is_sorted(&world, &values));
+
+    // No values are lost or duplicated.
+    let mut sorted_total = 0u64;
+    world.all_reduce_into(
+        &(values.len() as u64),
+        &mut sorted_total,
+        SystemOperation::sum(),
+    );
+    assert_eq!(sorted_total, total);
+    let mut sorted_checksum = 0u64;
+    world.all_reduce_into(
+        &values.iter().sum::<u64>(),
+        &mut sorted_checksum,
+        SystemOperation::sum(),
+    );
+    assert_eq!(sorted_checksum, checksum);
+
+    // Sorting sorted values keeps them sorted, and many equal values stay together.
+    let mut constant = vec![7i32; 10];
+    sort::parallel_sort(&world, &mut constant);
+    assert!(sort::is_sorted(&world, &constant));
+    assert!(constant.is_empty() || constant.len() == 10 * world.size() as usize);
+}
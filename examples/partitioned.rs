@@ -0,0 +1,77 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::environment::{self, Feature};
+use mpi::partitioned;
+use mpi::request;
+use mpi::traits::*;
+
+const PARTITIONS: usize = 4;
+const PARTITION_LEN: usize = 3;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    if !environment::is_supported(Feature::Partitioned) {
+        if rank == 0 {
+            println!("Partitioned communication is not supported by the MPI library.");
+        }
+        return;
+    }
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let mut send_buf = vec![0i32; PARTITIONS * PARTITION_LEN];
+    let mut recv_buf = vec![0i32; PARTITIONS * PARTITION_LEN];
+
+    request::scope(|scope| {
+        let mut send = partitioned::send_init(scope, &mut send_buf[..], PARTITIONS, &next, 11);
+        let mut receive =
+            partitioned::receive_init(scope, &mut recv_buf[..], PARTITIONS, &previous, 11);
+        assert_eq!(receive.partitions(), PARTITIONS);
+
+        for round in 0..2 {
+            receive.start();
+            send.start();
+
+            // Fill and release the partitions in reverse order.
+            for partition in (0..PARTITIONS).rev() {
+                for (i, x) in send.partition_mut(partition).iter_mut().enumerate() {
+                    *x = value(rank, round, partition, i);
+                }
+                send.ready(partition);
+            }
+
+            let mut arrived = 0;
+            for (partition, elements) in receive.arrivals() {
+                for (i, &x) in elements.iter().enumerate() {
+                    assert_eq!(x, value(previous.rank(), round, partition, i));
+                }
+                arrived += 1;
+            }
+            assert_eq!(arrived, PARTITIONS);
+            assert!((0..PARTITIONS).all(|partition| receive.arrived(partition)));
+
+            receive.wait();
+            send.wait();
+            assert!(!receive.is_active());
+        }
+
+        // Waiting for a send with unmarked partitions panics, dropping it marks them.
+        receive.start();
+        send.start();
+        send.ready(0);
+        let unmarked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| send.wait()));
+        assert!(unmarked.is_err());
+        drop(send);
+        receive.wait();
+    });
+}
+
+fn value(rank: i32, round: usize, partition: usize, i: usize) -> i32 {
+    rank * 1000 + (round * 100 + partition * 10 + i) as i32
+}
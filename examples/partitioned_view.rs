@@ -0,0 +1,56 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::{MutView, Partition, PartitionMut, UserDatatype, View};
+use mpi::traits::*;
+use mpi::Count;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root = world.process_at_rank(0);
+
+    // Process `r` receives `r + 1` pairs of integers, the partitions count pairs.
+    let pair = UserDatatype::contiguous(2, &i32::equivalent_datatype());
+    let counts: Vec<Count> = (1..=size).collect();
+    let displs: Vec<Count> = counts
+        .iter()
+        .scan(0, |acc, &c| {
+            let d = *acc;
+            *acc += c;
+            Some(d)
+        })
+        .collect();
+    let total: Count = counts.iter().sum();
+
+    let mut received = vec![0; 2 * (rank as usize + 1)];
+    if rank == 0 {
+        let values: Vec<i32> = (0..2 * total).collect();
+        let partition = Partition::new(&values[..], &counts[..], &displs[..]);
+        let pairs = unsafe { View::with_count_and_datatype(&partition, total, &pair) };
+        root.scatter_varcount_into_root(&pairs, &mut received[..]);
+    } else {
+        root.scatter_varcount_into(&mut received[..]);
+    }
+    let first = 2 * displs[rank as usize];
+    assert_eq!(
+        received,
+        (first..first + 2 * (rank + 1)).collect::<Vec<_>>()
+    );
+
+    // Gather the pairs back through a mutable view of a partition.
+    if rank == 0 {
+        let mut values = vec![0; 2 * total as usize];
+        {
+            let mut partition = PartitionMut::new(&mut values[..], &counts[..], &displs[..]);
+            let mut pairs =
+                unsafe { MutView::with_count_and_datatype(&mut partition, total, &pair) };
+            root.gather_varcount_into_root(&received[..], &mut pairs);
+        }
+        assert_eq!(values, (0..2 * total).collect::<Vec<_>>());
+    } else {
+        root.gather_varcount_into(&received[..]);
+    }
+}
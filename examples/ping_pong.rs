@@ -0,0 +1,31 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+const ROUNDS: u64 = 100;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    if rank > 1 {
+        return;
+    }
+
+    let partner = world.process_at_rank(1 - rank);
+    let mut x = 0u64;
+
+    for round in 0..ROUNDS {
+        if rank == 0 {
+            partner.send(&round);
+            partner.receive_into_without_status(&mut x);
+            assert_eq!(round + 1, x);
+        } else {
+            partner.receive_into_without_status(&mut x);
+            assert_eq!(round, x);
+            partner.send(&(x + 1));
+        }
+    }
+}
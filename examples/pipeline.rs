@@ -0,0 +1,57 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::pipeline::Pipeline;
+use mpi::traits::*;
+
+const ITEMS: i32 = 10;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+
+    // generate → simulate → analyze, with the simulation running on all but two processes
+    let builder = match size {
+        1 => Pipeline::builder().stage(1),
+        2 => Pipeline::builder().stage(1).stage(1),
+        _ => Pipeline::builder().stage(1).stage(size - 2).stage(1),
+    };
+    let pipeline = builder.build(&world);
+    let stage_comm = pipeline.communicator();
+
+    let generated: Vec<i32> = (0..ITEMS).collect();
+    let expected_sum: i32 = if pipeline.num_stages() == 3 {
+        generated.iter().map(|x| x * x).sum()
+    } else {
+        generated.iter().sum()
+    };
+
+    if pipeline.is_first() {
+        assert_eq!(pipeline.stage(), 0);
+        assert_eq!(stage_comm.size(), 1);
+        if pipeline.is_last() {
+            assert_eq!(generated.iter().sum::<i32>(), expected_sum);
+        } else {
+            pipeline.forward(&generated[..]);
+        }
+    } else if !pipeline.is_last() {
+        assert_eq!(pipeline.stage(), 1);
+        assert_eq!(stage_comm.size(), size - 2);
+        // Only the first simulation process receives the generated items.
+        let items = pipeline.collect::<i32>();
+        if stage_comm.rank() == 0 {
+            assert_eq!(items, generated);
+        } else {
+            assert!(items.is_empty());
+        }
+        let simulated: Vec<i32> = items.iter().map(|x| x * x).collect();
+        pipeline.forward(&simulated[..]);
+    } else {
+        assert_eq!(pipeline.stage(), pipeline.num_stages() - 1);
+        assert_eq!(stage_comm.size(), 1);
+        let results = pipeline.collect::<i32>();
+        assert_eq!(results.len(), ITEMS as usize);
+        assert_eq!(results.iter().sum::<i32>(), expected_sum);
+    }
+}
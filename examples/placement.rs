@@ -0,0 +1,35 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::placement::{self, Placement};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let report = placement::placement_report(&world);
+    assert_eq!(report.len(), world.size() as usize);
+    for (r, placement) in report.iter().enumerate() {
+        assert_eq!(placement.rank as usize, r);
+        assert!(!placement.node.is_empty());
+    }
+    assert_eq!(report[rank as usize], Placement::local(rank));
+
+    // All processes of a NUMA communicator run on the same node.
+    let numa = placement::split_by_numa_node(&world);
+    let numa_report = placement::placement_report(&numa);
+    assert!(numa_report
+        .iter()
+        .all(|placement| placement.node == report[rank as usize].node));
+
+    if rank == 0 {
+        for placement in &report {
+            println!(
+                "rank {} on {}: CPUs {:?}, NUMA nodes {:?}",
+                placement.rank, placement.node, placement.cpus, placement.numa_nodes
+            );
+        }
+    }
+}
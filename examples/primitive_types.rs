@@ -0,0 +1,40 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let count = world.size() as usize;
+
+    let mut flags = vec![false; count];
+    world.all_gather_into(&(rank % 2 == 0), &mut flags[..]);
+    let expected: Vec<_> = (0..count).map(|i| i % 2 == 0).collect();
+    assert_eq!(expected, flags);
+
+    // `char`s are sent as their code points and checked on receipt.
+    let letters = ['a', 'ä', '∂', '🦀'];
+    let mut code_points = vec![0u32; count];
+    world.all_gather_into(&u32::from(letters[rank as usize % 4]), &mut code_points[..]);
+    let chars: Vec<char> = code_points
+        .into_iter()
+        .map(|c| std::char::from_u32(c).unwrap())
+        .collect();
+    let expected: Vec<_> = (0..count).map(|i| letters[i % 4]).collect();
+    assert_eq!(expected, chars);
+
+    let mut lengths = vec![0usize; count];
+    world.all_gather_into(&(rank as usize * 1000), &mut lengths[..]);
+    let expected: Vec<_> = (0..count).map(|i| i * 1000).collect();
+    assert_eq!(expected, lengths);
+
+    let mut offsets = vec![0isize; count];
+    world.all_gather_into(&-(rank as isize), &mut offsets[..]);
+    let expected: Vec<_> = (0..count).map(|i| -(i as isize)).collect();
+    assert_eq!(expected, offsets);
+
+    let total: usize = world.sum(rank as usize);
+    assert_eq!(count * (count - 1) / 2, total);
+}
@@ -0,0 +1,45 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::priority::PriorityComm;
+use mpi::request::WaitGuard;
+use mpi::traits::*;
+
+const CONTROL: usize = 0;
+const BULK: usize = 1;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let comm = PriorityComm::new(&world, 2);
+    assert_eq!(comm.num_lanes(), 2);
+    assert_eq!((comm.rank(), comm.size()), (rank, size));
+
+    let root = 0;
+    if rank == root {
+        // The control messages overtake the bulk data that was sent before them.
+        for _ in 1..size {
+            let (_, status) = comm.lane(CONTROL).any_process().receive::<i32>();
+            assert!(status.source_rank() > root);
+        }
+        for _ in 1..size {
+            let (priority, data, status) = comm.receive_vec::<f64>();
+            assert_eq!(priority, BULK);
+            assert_eq!(data.len(), 1 << 16);
+            assert_eq!(data[0], f64::from(status.source_rank()));
+        }
+        assert!(comm.immediate_matched_probe().is_none());
+    } else {
+        let bulk = vec![f64::from(rank); 1 << 16];
+        mpi::request::scope(|scope| {
+            let _bulk = WaitGuard::from(
+                comm.process_at_rank(root, BULK)
+                    .immediate_send(scope, &bulk[..]),
+            );
+            comm.process_at_rank(root, CONTROL).synchronous_send(&rank);
+        });
+    }
+}
@@ -0,0 +1,44 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::time::Duration;
+
+use mpi::request;
+use mpi::traits::*;
+use mpi::Threading;
+
+fn main() {
+    let (mut universe, threading) = mpi::initialize_with_threading(Threading::Multiple).unwrap();
+    if threading != Threading::Multiple {
+        println!("The MPI library does not support `Threading::Multiple`.");
+        return;
+    }
+    universe.enable_progress_thread(Duration::from_micros(100));
+    assert!(universe.has_progress_thread());
+
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    // A large message makes progress in the background while the process computes.
+    let message: Vec<u64> = (0..1 << 20).map(|i| i + rank as u64).collect();
+    let mut received = vec![0u64; message.len()];
+    let sum = request::scope(|scope| {
+        let receive = previous.immediate_receive_into(scope, &mut received[..]);
+        let send = next.immediate_send(scope, &message[..]);
+        let sum: u64 = (0..1u64 << 20).sum();
+        receive.wait();
+        send.wait();
+        sum
+    });
+    assert_eq!(sum, (1 << 19) * ((1 << 20) - 1));
+    assert!(received
+        .iter()
+        .enumerate()
+        .all(|(i, &x)| x == i as u64 + previous.rank() as u64));
+
+    universe.disable_progress_thread();
+    assert!(!universe.has_progress_thread());
+}
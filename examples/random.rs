@@ -0,0 +1,49 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::collections::HashSet;
+
+use mpi::random::{self, Stream};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size() as usize;
+
+    let seed = random::broadcast_seed(&world, 0, if rank == 0 { 2021 } else { 0 });
+    assert_eq!(seed, 2021);
+
+    let mut stream = Stream::for_rank(&world, seed);
+    let first = stream.next_u64();
+    for _ in 0..99 {
+        let x = stream.next_f64();
+        assert!((0.0..1.0).contains(&x));
+    }
+
+    // The streams of different processes differ.
+    let mut firsts = vec![0u64; size];
+    world.all_gather_into(&first, &mut firsts[..]);
+    assert_eq!(firsts.iter().collect::<HashSet<_>>().len(), size);
+
+    // Substreams are independent of their parent and reproducible.
+    let mut a = stream.split(1);
+    let mut b = stream.split(1);
+    assert_eq!(a.next_u64(), b.next_u64());
+    assert_ne!(stream.split(2).next_u64(), stream.split(1).next_u64());
+
+    // A stream continues where it left off when restored from its state.
+    let states = random::gather_rng_state(&world.process_at_rank(0), &stream);
+    let expected = stream.clone().next_u64();
+    if let Some(states) = states {
+        assert_eq!(states.len(), size);
+        assert!(states.iter().all(|state| state.counter == 100));
+        let mut restored = Stream::from_state(states[0]);
+        if rank == 0 {
+            assert_eq!(restored.next_u64(), expected);
+        }
+    }
+    let mut restored = Stream::from_state(stream.state());
+    assert_eq!(restored.next_u64(), expected);
+}
@@ -0,0 +1,34 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::Redistribution;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size() as usize;
+    let rank = world.rank() as usize;
+    let n = 5;
+
+    // Cells are numbered globally and stored in blocks of `n` consecutive cells per process. They
+    // are moved to a round robin distribution: cell `g` goes to process `g % size`.
+    let destinations: Vec<_> = (0..n)
+        .map(|i| {
+            let g = rank * n + i;
+            ((g % size) as mpi::topology::Rank, g / size)
+        })
+        .collect();
+    let redistribution = Redistribution::<u64>::new(&world, &destinations, n);
+    assert_eq!(redistribution.source_len(), n);
+    assert_eq!(redistribution.destination_len(), n);
+
+    for step in 0..3u64 {
+        let source: Vec<u64> = (0..n).map(|i| (rank * n + i) as u64 + step).collect();
+        let mut destination = vec![0u64; n];
+        redistribution.redistribute_into(&world, &source[..], &mut destination[..]);
+        for (j, &cell) in destination.iter().enumerate() {
+            assert_eq!(cell, (j * size + rank) as u64 + step);
+        }
+    }
+}
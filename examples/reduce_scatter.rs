@@ -0,0 +1,42 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::datatype::Partition;
+use mpi::request;
+use mpi::traits::*;
+use mpi::Count;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Process `r` receives a segment of `r + 1` elements.
+    let counts: Vec<Count> = (1..=size).collect();
+    let total = size * (size + 1) / 2;
+    let start = rank * (rank + 1) / 2;
+    let sendbuf: Vec<Count> = (0..total).map(|i| i + rank).collect();
+    let segments = Partition::segments(&world, &sendbuf[..], &counts[..]);
+    let expected: Vec<Count> = (start..start + rank + 1)
+        .map(|i| size * i + size * (size - 1) / 2)
+        .collect();
+
+    let mut recvbuf = vec![0; (rank + 1) as usize];
+    world.reduce_scatter_into(&segments, &mut recvbuf[..], SystemOperation::sum());
+    assert_eq!(recvbuf, expected);
+
+    let mut recvbuf = vec![0; (rank + 1) as usize];
+    request::scope(|scope| {
+        world
+            .immediate_reduce_scatter_into(
+                scope,
+                &segments,
+                &mut recvbuf[..],
+                SystemOperation::sum(),
+            )
+            .wait();
+    });
+    assert_eq!(recvbuf, expected);
+}
@@ -0,0 +1,46 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::env;
+use std::fs;
+
+use mpi::container::repack;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Every process writes its own output file of a different length.
+    let contents = |rank: i32| -> Vec<u8> {
+        (0..100 * (rank as usize + 1))
+            .map(|i| (i as u8).wrapping_add(rank as u8))
+            .collect()
+    };
+    let part = env::temp_dir().join(format!("rsmpi_repack_example.{}.bin", rank));
+    fs::write(&part, contents(rank)).unwrap();
+    let output = env::temp_dir().join("rsmpi_repack_example.bin");
+
+    let parts = repack(&world, &part, &output).unwrap();
+    assert_eq!(parts.len(), size as usize);
+    assert_eq!(parts[0].offset(), 0);
+    assert_eq!(parts[rank as usize].len(), contents(rank).len() as u64);
+
+    if rank == 0 {
+        let assembled = fs::read(&output).unwrap();
+        let expected: Vec<u8> = (0..size).flat_map(contents).collect();
+        assert_eq!(assembled, expected);
+        for (r, part) in parts.iter().enumerate() {
+            let start = part.offset() as usize;
+            let end = start + part.len() as usize;
+            assert_eq!(&assembled[start..end], &contents(r as i32)[..]);
+        }
+    }
+    fs::remove_file(&part).unwrap();
+    world.barrier();
+    if rank == 0 {
+        fs::remove_file(&output).unwrap();
+    }
+}
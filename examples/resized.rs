@@ -0,0 +1,40 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::mem::size_of;
+
+use mpi::datatype::{UserDatatype, View};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // A row-major matrix with one column per process
+    let rows = 4;
+    let int = i32::equivalent_datatype();
+    let element = size_of::<i32>() as mpi::Address;
+    let column = UserDatatype::vector(rows, 1, size, &int);
+    let span = ((rows - 1) * size + 1) as mpi::Address;
+    assert_eq!(column.extent(), (0, span * element));
+
+    // Shrinking the extent to a single element makes consecutive columns start one element apart.
+    let interleaved = UserDatatype::resized(&column, 0, element);
+    assert_eq!(interleaved.extent(), (0, element));
+    assert_eq!(interleaved.size(), column.size());
+
+    let mut received = vec![0i32; rows as usize];
+    let root_process = world.process_at_rank(0);
+    if rank == 0 {
+        let matrix: Vec<i32> = (0..rows * size).collect();
+        let columns = unsafe { View::with_count_and_datatype(&matrix[..], size, &interleaved) };
+        root_process.scatter_into_root(&columns, &mut received[..]);
+    } else {
+        root_process.scatter_into(&mut received[..]);
+    }
+
+    let expected: Vec<i32> = (0..rows).map(|i| i * size + rank).collect();
+    assert_eq!(received, expected);
+}
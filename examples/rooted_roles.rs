@@ -0,0 +1,56 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::{Role, SystemOperation};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+    let root_rank = size - 1;
+
+    let mut x = if rank == root_rank { 42u64 } else { 0 };
+    let role = world.at_root(root_rank);
+    assert_eq!(role.root_rank(), root_rank);
+    assert_eq!(role.is_root(), rank == root_rank);
+    role.broadcast_into(&mut x);
+    assert_eq!(x, 42);
+
+    let i = rank as u64 + 1;
+    match role {
+        Role::Root(root) => {
+            let mut all = vec![0u64; size as usize];
+            root.gather_into(&i, &mut all[..]);
+            assert!(all.iter().enumerate().all(|(r, &v)| v == r as u64 + 1));
+
+            let mut sum = 0u64;
+            root.reduce_into(&i, &mut sum, SystemOperation::sum());
+            assert_eq!(sum, (1..=size as u64).sum());
+
+            let table: Vec<u64> = (0..size as u64).map(|r| r * r).collect();
+            let mut mine = 0u64;
+            root.scatter_into(&table[..], &mut mine);
+            assert_eq!(mine, (rank * rank) as u64);
+        }
+        Role::NonRoot(non_root) => {
+            non_root.gather_into(&i);
+            non_root.reduce_into(&i, SystemOperation::sum());
+
+            let mut mine = 0u64;
+            non_root.scatter_into(&mut mine);
+            assert_eq!(mine, (rank * rank) as u64);
+        }
+    }
+
+    let root = world.as_root();
+    assert_eq!(root.root_rank(), rank);
+    let mut y = rank;
+    if rank == 0 {
+        root.broadcast_into(&mut y);
+    } else if let Role::NonRoot(non_root) = world.at_root(0) {
+        non_root.broadcast_into(&mut y);
+    }
+    assert_eq!(y, 0);
+}
@@ -0,0 +1,40 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::columns_of_rank;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // The columns do not divide evenly among the processes.
+    let (rows, ncols) = (5, 2 * size + 1);
+    let my_cols = columns_of_rank(ncols, size, rank);
+    assert_eq!(
+        (0..size)
+            .map(|r| columns_of_rank(ncols, size, r))
+            .sum::<i32>(),
+        ncols
+    );
+    let mut columns = vec![0i32; (rows * my_cols) as usize];
+
+    let root_rank = 0;
+    let root_process = world.process_at_rank(root_rank);
+    if rank == root_rank {
+        let matrix: Vec<i32> = (0..rows)
+            .flat_map(|i| (0..ncols).map(move |j| 100 * i + j))
+            .collect();
+        root_process.scatter_columns_into_root(&matrix[..], ncols, &mut columns[..]);
+    } else {
+        root_process.scatter_columns_into(&mut columns[..]);
+    }
+
+    let expected: Vec<i32> = (rank..ncols)
+        .step_by(size as usize)
+        .flat_map(|j| (0..rows).map(move |i| 100 * i + j))
+        .collect();
+    assert_eq!(expected, columns);
+}
@@ -0,0 +1,37 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::fs;
+
+use mpi::collective::CollectivePlan;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root = world.process_at_rank(0);
+
+    // Process `r` receives `r + 1` bytes.
+    let layout = CollectivePlan::new((1..=size).collect());
+    let path = std::env::temp_dir().join(format!("rsmpi_scatter_mmap_{}", std::process::id()));
+    if rank == 0 {
+        let contents: Vec<u8> = (0..layout.extent()).map(|i| i as u8).collect();
+        fs::write(&path, contents).unwrap();
+    }
+
+    let received = mpi::mmap::scatter_from_mmap(&root, &path, &layout).unwrap();
+    let start = layout.displs()[rank as usize];
+    let expected: Vec<u8> = (start..start + rank + 1).map(|i| i as u8).collect();
+    assert_eq!(received, expected);
+
+    // A file that is too short is reported on all processes.
+    let short = CollectivePlan::new(vec![1000; size as usize]);
+    assert!(mpi::mmap::scatter_from_mmap(&root, &path, &short).is_err());
+
+    world.barrier();
+    if rank == 0 {
+        fs::remove_file(&path).unwrap();
+    }
+}
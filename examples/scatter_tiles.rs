@@ -0,0 +1,56 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::TileLayout;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    // Arrange the processes in a grid that is as square as possible.
+    let grid_rows = (1..=size)
+        .filter(|d| size % d == 0 && d * d <= size)
+        .max()
+        .unwrap();
+    let grid_cols = size / grid_rows;
+
+    // The matrix does not divide evenly into the grid, so tiles differ in shape.
+    let (rows, cols) = (2 * grid_rows + 3, 3 * grid_cols + 1);
+    let layout = TileLayout::even(rows, cols, grid_rows, grid_cols);
+    assert_eq!((rows, cols), layout.shape());
+    assert_eq!(size as usize, layout.num_tiles());
+
+    let (tile_rows, tile_cols) = layout.tile_shape(rank);
+    let (start_row, start_col) = layout.tile_start(rank);
+    let mut tile = vec![0i32; layout.tile_len(rank)];
+
+    let root_rank = 0;
+    let root_process = world.process_at_rank(root_rank);
+    if rank == root_rank {
+        let matrix: Vec<i32> = (0..rows)
+            .flat_map(|i| (0..cols).map(move |j| 100 * i + j))
+            .collect();
+        root_process.scatter_tiles_into_root(&matrix[..], &layout, &mut tile[..]);
+    } else {
+        root_process.scatter_tiles_into(&mut tile[..]);
+    }
+
+    let expected: Vec<i32> = (start_row..start_row + tile_rows)
+        .flat_map(|i| (start_col..start_col + tile_cols).map(move |j| 100 * i + j))
+        .collect();
+    assert_eq!(expected, tile);
+
+    // A single row split into one row band per process leaves all but the first tile empty.
+    let layout = TileLayout::even(1, 3, size, 1);
+    let mut tile = vec![0i32; layout.tile_len(rank)];
+    if rank == root_rank {
+        root_process.scatter_tiles_into_root(&[1, 2, 3][..], &layout, &mut tile[..]);
+    } else {
+        root_process.scatter_tiles_into(&mut tile[..]);
+    }
+    let expected: Vec<i32> = if rank == 0 { vec![1, 2, 3] } else { vec![] };
+    assert_eq!(expected, tile);
+}
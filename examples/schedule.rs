@@ -0,0 +1,95 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::schedule::{BinomialTree, Dissemination, PairwiseRounds, Ring};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root = size - 1;
+
+    // The parent of every child is the process it is a child of.
+    let tree = BinomialTree::new(size, root);
+    assert_eq!(tree.parent(root), None);
+    for r in 0..size {
+        for child in tree.children(r) {
+            assert_eq!(tree.parent(child), Some(r));
+        }
+    }
+
+    // Broadcast along the binomial tree.
+    let mut value = if rank == root { 42u64 } else { 0 };
+    if let Some(parent) = tree.parent(rank) {
+        world.process_at_rank(parent).receive_into(&mut value);
+    }
+    for child in tree.children(rank) {
+        world.process_at_rank(child).send(&value);
+    }
+    assert_eq!(value, 42);
+
+    // All-gather around the ring.
+    let ring = Ring::new(size);
+    let mut blocks = vec![-1; size as usize];
+    blocks[rank as usize] = rank;
+    for step in 0..ring.steps() {
+        let send = blocks[ring.send_block(rank, step) as usize];
+        let mut received = -1;
+        mpi::point_to_point::send_receive_into(
+            &send,
+            &world.process_at_rank(ring.next(rank)),
+            &mut received,
+            &world.process_at_rank(ring.previous(rank)),
+        );
+        blocks[ring.receive_block(rank, step) as usize] = received;
+    }
+    assert_eq!(blocks, (0..size).collect::<Vec<_>>());
+
+    // Every process takes part in every round of the dissemination pattern.
+    let partners = Dissemination::new(size).partners(rank);
+    let mut rounds = 0;
+    while (1 << rounds) < size {
+        rounds += 1;
+    }
+    assert_eq!(partners.len(), rounds);
+
+    // Exchange with the ranks one and three ahead and behind, one peer per round.
+    let mut peers: Vec<_> = [1, 3]
+        .iter()
+        .filter(|&&distance| distance < size)
+        .flat_map(|&distance| vec![(rank + distance) % size, (rank + size - distance) % size])
+        .filter(|&peer| peer != rank)
+        .collect();
+    peers.sort_unstable();
+    peers.dedup();
+    let rounds = PairwiseRounds::gather(&world, &peers);
+    assert!(rounds.num_rounds() <= 2 * peers.len().max(1) - 1);
+    for round in 0..rounds.num_rounds() {
+        let mut ranks: Vec<_> = rounds
+            .round(round)
+            .iter()
+            .flat_map(|&(a, b)| vec![a, b])
+            .collect();
+        let busy = ranks.len();
+        ranks.sort_unstable();
+        ranks.dedup();
+        assert_eq!(ranks.len(), busy);
+    }
+
+    let mut received = Vec::new();
+    rounds.for_each_round(rank, |peer| {
+        let mut value = -1;
+        mpi::point_to_point::send_receive_into(
+            &rank,
+            &world.process_at_rank(peer),
+            &mut value,
+            &world.process_at_rank(peer),
+        );
+        assert_eq!(value, peer);
+        received.push(value);
+    });
+    received.sort_unstable();
+    assert_eq!(received, peers);
+}
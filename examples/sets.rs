@@ -0,0 +1,55 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::sets;
+use mpi::traits::*;
+
+/// Concatenate the ranges of a distributed result on all processes.
+fn concatenate<C: Communicator>(comm: &C, range: &[u32]) -> Vec<u32> {
+    let mut counts = vec![0u64; comm.size() as usize];
+    comm.all_gather_into(&(range.len() as u64), &mut counts[..]);
+    let mut all = Vec::new();
+    for (rank, &count) in counts.iter().enumerate() {
+        let mut part = vec![0u32; count as usize];
+        if rank as mpi::topology::Rank == comm.rank() {
+            part.copy_from_slice(range);
+        }
+        comm.process_at_rank(rank as mpi::topology::Rank)
+            .broadcast_into(&mut part[..]);
+        all.extend(part);
+    }
+    all
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank() as u32;
+    let size = world.size() as u32;
+
+    // Every process holds the multiples of 2 and of `rank + 2` below 100, with duplicates.
+    let mut local: Vec<u32> = (0..100)
+        .filter(|x| x % 2 == 0 || x % (rank + 2) == 0)
+        .collect();
+    local.extend(local.clone());
+    local.sort_unstable();
+
+    let contains = |r: u32, x: u32| x % 2 == 0 || x % (r + 2) == 0;
+    let processes = |x: u32| (0..size).filter(|&r| contains(r, x)).count() as u32;
+
+    let union = concatenate(&world, &sets::distributed_union(&world, &local));
+    let expected: Vec<u32> = (0..100).filter(|&x| processes(x) > 0).collect();
+    assert_eq!(union, expected);
+
+    let intersection = concatenate(&world, &sets::distributed_intersection(&world, &local));
+    let expected: Vec<u32> = (0..100).filter(|&x| processes(x) == size).collect();
+    assert_eq!(intersection, expected);
+
+    let unique = concatenate(&world, &sets::distributed_unique(&world, &local));
+    let expected: Vec<u32> = (0..100).filter(|&x| processes(x) == 1).collect();
+    assert_eq!(unique, expected);
+
+    // Empty inputs result in empty sets.
+    let empty: Vec<u32> = Vec::new();
+    assert!(sets::distributed_union(&world, &empty).is_empty());
+}
@@ -0,0 +1,42 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use mpi::point_to_point as p2p;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let data: Vec<i32> = (0..4).map(|i| rank * 10 + i).collect();
+    let expected: Vec<i32> = (0..4).map(|i| previous.rank() * 10 + i).collect();
+    let mut received = vec![0; 4];
+
+    // Shared slices are sent in place, without cloning them into a `Vec`.
+    let arc: Arc<[i32]> = data.clone().into();
+    p2p::send_receive_into(&arc, &next, &mut received[..], &previous);
+    assert_eq!(received, expected);
+
+    let rc: Rc<[i32]> = data.clone().into();
+    p2p::send_receive_into(&rc, &next, &mut received[..], &previous);
+    assert_eq!(received, expected);
+
+    let borrowed: Cow<[i32]> = Cow::Borrowed(&data[..]);
+    p2p::send_receive_into(&borrowed, &next, &mut received[..], &previous);
+    assert_eq!(received, expected);
+
+    let owned: Cow<[i32]> = Cow::Owned(data.clone());
+    let mut gathered = vec![0; 4 * size as usize];
+    world.all_gather_into(&owned, &mut gathered[..]);
+    for (r, chunk) in gathered.chunks(4).enumerate() {
+        assert!(chunk.iter().zip(0..).all(|(&x, i)| x == r as i32 * 10 + i));
+    }
+}
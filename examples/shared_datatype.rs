@@ -0,0 +1,53 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::{MutView, SharedDatatype, UserDatatype};
+use mpi::traits::*;
+
+/// Two data structures that describe their rows with the same datatype
+struct Grid {
+    cells: Vec<f64>,
+    row: SharedDatatype,
+}
+
+struct Halo {
+    cells: Vec<f64>,
+    row: SharedDatatype,
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let row = SharedDatatype::from(UserDatatype::contiguous(8, &f64::equivalent_datatype()));
+    let grid = Grid {
+        cells: vec![f64::from(rank); 8],
+        row: row.clone(),
+    };
+    let mut halo = Halo {
+        cells: vec![-1.0; 8],
+        row: row.clone(),
+    };
+    assert_eq!(SharedDatatype::handle_count(&row), 3);
+    assert_eq!(grid.row.as_raw(), halo.row.as_raw());
+
+    // Dropping one handle leaves the datatype intact for the others.
+    drop(row);
+    assert_eq!(SharedDatatype::handle_count(&grid.row), 2);
+
+    let root_process = world.process_at_rank(0);
+    if rank == 0 {
+        halo.cells.copy_from_slice(&grid.cells);
+    }
+    {
+        let mut view =
+            unsafe { MutView::with_count_and_datatype(&mut halo.cells[..], 1, &halo.row) };
+        root_process.broadcast_into(&mut view);
+    }
+    assert_eq!(halo.cells, vec![0.0; 8]);
+
+    // Duplicating the datatype creates an independent handle instead.
+    let duplicate = grid.row.dup();
+    assert_ne!(duplicate.as_raw(), grid.row.as_raw());
+}
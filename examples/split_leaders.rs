@@ -0,0 +1,41 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::collective::SystemOperation;
+use mpi::topology::Rank;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let (node, leaders) = world.split_leaders();
+    assert_eq!(leaders.is_some(), node.rank() == 0);
+
+    // Every node is represented by exactly one leader.
+    let is_leader: Rank = if leaders.is_some() { 1 } else { 0 };
+    let mut num_leaders: Rank = 0;
+    world.all_reduce_into(&is_leader, &mut num_leaders, SystemOperation::sum());
+    if let Some(ref leaders) = leaders {
+        assert_eq!(leaders.size(), num_leaders);
+    }
+
+    // A hierarchical sum of all ranks: reduce on the node leaders, combine across the leaders and
+    // broadcast the result within every node.
+    let node_root = node.process_at_rank(0);
+    let mut node_sum: Rank = 0;
+    if node.rank() == 0 {
+        node_root.reduce_into_root(&rank, &mut node_sum, SystemOperation::sum());
+    } else {
+        node_root.reduce_into(&rank, SystemOperation::sum());
+    }
+    let mut sum: Rank = 0;
+    if let Some(ref leaders) = leaders {
+        leaders.all_reduce_into(&node_sum, &mut sum, SystemOperation::sum());
+    }
+    node_root.broadcast_into(&mut sum);
+
+    assert_eq!(sum, size * (size - 1) / 2);
+}
@@ -0,0 +1,62 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::request::WaitGuard;
+use mpi::statistics::{self, Traffic};
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size();
+    let rank = world.rank();
+
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    // Not counted
+    world.barrier();
+
+    statistics::enable();
+    assert!(statistics::is_enabled());
+
+    let msg = [rank; 4];
+    let mut buf = [0; 4];
+    for _ in 0..3 {
+        mpi::request::scope(|scope| {
+            let _sreq = WaitGuard::from(next.immediate_send_with_tag(scope, &msg[..], 7));
+            previous.receive_into_with_tag(&mut buf[..], 7);
+        });
+    }
+    world.barrier();
+    world.barrier();
+
+    statistics::disable();
+    world.barrier();
+
+    let stats = statistics::of(&world);
+    let traffic = Traffic {
+        messages: 3,
+        bytes: 3 * std::mem::size_of_val(&msg) as u64,
+    };
+    assert_eq!(Some(&traffic), stats.sent().get(&(next.rank(), 7)));
+    assert_eq!(Some(&traffic), stats.received().get(&(previous.rank(), 7)));
+    assert_eq!(Some(&2), stats.collectives().get("barrier"));
+    assert_eq!(2, stats.total_collectives());
+
+    let summaries = statistics::summarize(&world);
+    if rank == 0 {
+        let summaries = summaries.unwrap();
+        assert_eq!(size as usize, summaries.len());
+        for summary in summaries {
+            assert_eq!(traffic, summary.sent);
+            assert_eq!(traffic, summary.received);
+            assert_eq!(2, summary.collectives);
+        }
+    } else {
+        assert!(summaries.is_none());
+    }
+
+    statistics::reset();
+    assert_eq!(Traffic::default(), statistics::of(&world).total_sent());
+}
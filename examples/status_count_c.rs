@@ -0,0 +1,42 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::UserDatatype;
+use mpi::traits::*;
+use mpi::LargeCount;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let quad = UserDatatype::contiguous(4, &u32::equivalent_datatype());
+
+    for &len in &[1000, 999] {
+        let message: Vec<u32> = (0..len).collect();
+        mpi::request::scope(|scope| {
+            let send = next.immediate_send(scope, &message[..]);
+            let (received, status) = previous.receive_vec::<u32>();
+            send.wait();
+            assert_eq!(received.len(), len as usize);
+
+            assert_eq!(status.count_c(u32::equivalent_datatype()), Some(len.into()));
+            assert_eq!(
+                status.count_c(u8::equivalent_datatype()),
+                Some(4 * LargeCount::from(len))
+            );
+            assert_eq!(
+                LargeCount::from(status.count(u32::equivalent_datatype())),
+                status.count_c(u32::equivalent_datatype()).unwrap()
+            );
+            assert_eq!(status.elements_c(&quad), Some(len.into()));
+            if len % 4 == 0 {
+                assert_eq!(status.count_c(&quad), Some(LargeCount::from(len / 4)));
+            } else {
+                assert_eq!(status.count_c(&quad), None);
+            }
+        });
+    }
+}
@@ -0,0 +1,42 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use mpi::stdio;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let root_process = world.process_at_rank(0);
+
+    // Collect the output of all processes on rank 0, one line at a time.
+    {
+        let mut capture = stdio::capture(&world).unwrap();
+        println!("Hello from process {} of {}.", rank, world.size());
+        eprint!("Process {} has not finished this line", rank);
+        capture.forward(&root_process).unwrap();
+        eprintln!(" until now.");
+        capture.forward(&root_process).unwrap();
+    }
+
+    // Send the output of every process into files of its own.
+    let mut prefix = env::temp_dir();
+    prefix.push(format!("rsmpi-stdio-{}", process::id()));
+    stdio::redirect_to_files(&world, &prefix).unwrap();
+    println!("This line is written by process {}.", rank);
+
+    let out = stdio::rank_file(&prefix, rank, "out");
+    let err = stdio::rank_file(&prefix, rank, "err");
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        format!("This line is written by process {}.\n", rank)
+    );
+    assert_eq!(fs::read_to_string(&err).unwrap(), "");
+    fs::remove_file(out).unwrap();
+    fs::remove_file(err).unwrap();
+}
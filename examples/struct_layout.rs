@@ -0,0 +1,72 @@
+#![deny(warnings)]
+#![allow(clippy::float_cmp)]
+extern crate mpi;
+
+#[macro_use]
+extern crate memoffset;
+
+use mpi::datatype::{StructLayoutBuilder, UserDatatype};
+use mpi::traits::*;
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+struct Particle {
+    position: [f64; 3],
+    id: u32,
+    charge: i8,
+}
+
+unsafe impl Equivalence for Particle {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        StructLayoutBuilder::<Particle>::new()
+            .field(
+                offset_of!(Particle, position),
+                3,
+                &f64::equivalent_datatype(),
+            )
+            .field(offset_of!(Particle, id), 1, &u32::equivalent_datatype())
+            .field(offset_of!(Particle, charge), 1, &i8::equivalent_datatype())
+            .build()
+    }
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let root_process = world.process_at_rank(0);
+
+    // The extent of the datatype includes the trailing padding of `Particle`.
+    mpi::assert_equivalent_layout!(Particle);
+
+    // A field is checked against the bytes its datatype touches rather than its extent. This
+    // `u32` sits 4 bytes before its lower bound, so it would touch the bytes before `Particle`.
+    let shifted = UserDatatype::resized(
+        &UserDatatype::heterogeneous_indexed(&[1], &[-4], &u32::equivalent_datatype()),
+        0,
+        4,
+    );
+    assert!(std::panic::catch_unwind(|| {
+        StructLayoutBuilder::<Particle>::new().field(offset_of!(Particle, position), 1, &shifted)
+    })
+    .is_err());
+
+    let mut particles = if world.rank() == 0 {
+        (0..4)
+            .map(|i| Particle {
+                position: [f64::from(i), 1.0, 2.0],
+                id: i,
+                charge: -1,
+            })
+            .collect()
+    } else {
+        vec![Particle::default(); 4]
+    };
+    root_process.broadcast_into(&mut particles[..]);
+
+    for (i, particle) in particles.iter().enumerate() {
+        assert_eq!(particle.position, [i as f64, 1.0, 2.0]);
+        assert_eq!(particle.id, i as u32);
+        assert_eq!(particle.charge, -1);
+    }
+}
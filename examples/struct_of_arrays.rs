@@ -0,0 +1,90 @@
+#![deny(warnings)]
+#![allow(clippy::float_cmp)]
+extern crate mpi;
+
+#[macro_use]
+extern crate memoffset;
+
+use mpi::collective::SoaLayout;
+use mpi::datatype::{DynBuffer, DynBufferMut};
+use mpi::traits::*;
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+struct Particle {
+    position: [f64; 3],
+    id: u32,
+    charge: i8,
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let root_process = world.process_at_rank(0);
+
+    let layout = SoaLayout::<Particle>::new()
+        .field(
+            offset_of!(Particle, position),
+            3,
+            &f64::equivalent_datatype(),
+        )
+        .field(offset_of!(Particle, id), 1, &u32::equivalent_datatype())
+        .field(offset_of!(Particle, charge), 1, &i8::equivalent_datatype());
+
+    // Every process holds `rank + 1` particles as one array per field.
+    let first = (rank * (rank + 1) / 2) as u32;
+    let ids: Vec<u32> = (first..first + rank as u32 + 1).collect();
+    let positions: Vec<f64> = ids
+        .iter()
+        .flat_map(|&id| vec![id as f64, 2.0 * id as f64, -(id as f64)])
+        .collect();
+    let charges: Vec<i8> = ids.iter().map(|&id| (id % 3) as i8 - 1).collect();
+    let expected = |id: u32| Particle {
+        position: [id as f64, 2.0 * id as f64, -(id as f64)],
+        id,
+        charge: (id % 3) as i8 - 1,
+    };
+
+    let fields = [
+        DynBuffer::new(&positions[..]),
+        DynBuffer::new(&ids[..]),
+        DynBuffer::new(&charges[..]),
+    ];
+    let total = (size * (size + 1) / 2) as usize;
+    let mut particles = vec![Particle::default(); total];
+    if rank == 0 {
+        root_process.gather_soa_into_root(&layout, &fields, &mut particles[..]);
+        for (id, particle) in particles.iter().enumerate() {
+            assert_eq!(*particle, expected(id as u32));
+        }
+    } else {
+        root_process.gather_soa_into(&layout, &fields);
+    }
+
+    // Scatter the particles back into a struct of arrays, in reverse order.
+    particles.reverse();
+    let mut ids = vec![0u32; rank as usize + 1];
+    let mut positions = vec![0.0f64; 3 * (rank as usize + 1)];
+    let mut charges = vec![0i8; rank as usize + 1];
+    {
+        let mut fields = [
+            DynBufferMut::new(&mut positions[..]),
+            DynBufferMut::new(&mut ids[..]),
+            DynBufferMut::new(&mut charges[..]),
+        ];
+        if rank == 0 {
+            root_process.scatter_soa_into_root(&layout, &particles[..], &mut fields);
+        } else {
+            root_process.scatter_soa_into(&layout, &mut fields);
+        }
+    }
+    let last = (total - rank as usize * (rank as usize + 1) / 2) as u32 - 1;
+    for (i, &id) in ids.iter().enumerate() {
+        assert_eq!(id, last - i as u32);
+        let particle = expected(id);
+        assert_eq!(&positions[3 * i..3 * i + 3], &particle.position[..]);
+        assert_eq!(charges[i], particle.charge);
+    }
+}
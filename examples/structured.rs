@@ -1,7 +1,11 @@
 #![deny(warnings)]
 extern crate mpi;
 
-use mpi::{datatype::UserDatatype, traits::*};
+#[macro_use]
+extern crate memoffset;
+
+use mpi::datatype::{UncommittedDatatypeRef, UserDatatype};
+use mpi::traits::*;
 use std::mem::size_of;
 
 struct MyInts([i32; 3]);
@@ -21,6 +25,34 @@ unsafe impl Equivalence for MyInts {
     }
 }
 
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+#[repr(C)]
+struct Particle {
+    id: i32,
+    mass: f64,
+    label: [u8; 16],
+}
+
+unsafe impl Equivalence for Particle {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        let label = UserDatatype::contiguous(16, &u8::equivalent_datatype());
+        UserDatatype::structured(
+            &[1, 1, 1],
+            &[
+                offset_of!(Particle, id) as mpi::Address,
+                offset_of!(Particle, mass) as mpi::Address,
+                offset_of!(Particle, label) as mpi::Address,
+            ],
+            &[
+                i32::equivalent_datatype().into(),
+                f64::equivalent_datatype().into(),
+                UncommittedDatatypeRef::from(&label),
+            ],
+        )
+    }
+}
+
 fn main() {
     let universe = mpi::initialize().unwrap();
     let world = universe.world();
@@ -35,4 +67,19 @@ fn main() {
 
         assert_eq!([1, 2, 3], ints);
     }
+
+    let mut label = [0u8; 16];
+    label[..8].copy_from_slice(b"electron");
+    let expected = Particle {
+        id: 7,
+        mass: 9.109e-31,
+        label,
+    };
+    let mut particle = if world.rank() == 0 {
+        expected
+    } else {
+        Particle::default()
+    };
+    root_process.broadcast_into(&mut particle);
+    assert_eq!(particle, expected);
 }
@@ -0,0 +1,43 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::datatype::{MutView, Order, UserDatatype, View};
+use mpi::point_to_point as p2p;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    let next_process = world.process_at_rank((rank + 1) % size);
+    let previous_rank = (rank + size - 1) % size;
+    let previous_process = world.process_at_rank(previous_rank);
+
+    // A 4x5 matrix in row-major order, the 2x3 block starting at (1, 2) is exchanged.
+    let matrix: Vec<i32> = (0..20).map(|x| 100 * rank + x).collect();
+    let mut received = vec![-1; 20];
+
+    let t = UserDatatype::subarray(
+        &[4, 5],
+        &[2, 3],
+        &[1, 2],
+        Order::RowMajor,
+        &i32::equivalent_datatype(),
+    );
+    {
+        let v1 = unsafe { View::with_count_and_datatype(&matrix[..], 1, &t) };
+        let mut v2 = unsafe { MutView::with_count_and_datatype(&mut received[..], 1, &t) };
+        p2p::send_receive_into(&v1, &next_process, &mut v2, &previous_process);
+    }
+
+    for (i, &x) in received.iter().enumerate() {
+        let (row, column) = (i / 5, i % 5);
+        if (1..3).contains(&row) && (2..5).contains(&column) {
+            assert_eq!(100 * previous_rank + i as i32, x);
+        } else {
+            assert_eq!(-1, x);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+
+    let even = (0..world.size()).filter(|x| x % 2 == 0).collect::<Vec<_>>();
+    let even_group = world.group().include(&even[..]);
+    let odd_group = world.group().difference(&even_group);
+
+    // Only the members of a group take part in its barrier.
+    if rank % 2 == 0 {
+        even_group
+            .rank()
+            .expect("Even ranks are in the even group.");
+        world.subgroup_barrier_with_tag(&even_group, 7);
+    } else {
+        world.subgroup_barrier_with_tag(&odd_group, 7);
+    }
+
+    // Barriers among overlapping groups use distinct tags.
+    let first_two = world.group().include(&[0, 1][..]);
+    if first_two.rank().is_some() {
+        world.subgroup_barrier_with_tag(&first_two, 8);
+    }
+    world.barrier();
+}
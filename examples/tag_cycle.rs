@@ -0,0 +1,54 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::request::{self, WaitGuard};
+use mpi::topology::TaggedCommunicator;
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = (rank + 1) % size;
+    let previous = (rank + size - 1) % size;
+
+    // A fixed default tag applies to all untagged operations.
+    let fixed = TaggedCommunicator::new(world.duplicate(), 7);
+    assert_eq!(fixed.default_tag(), 7);
+    assert_eq!(fixed.default_receive_tag(), 7);
+    assert_eq!(world.default_tag(), 0);
+
+    let mut comm = TaggedCommunicator::cycling(world.duplicate(), 3);
+    assert_eq!(comm.period(), 3);
+    assert_eq!(comm.iteration(), 0);
+
+    // The sends of three iterations are in flight at the same time, each with its own tag.
+    let messages: Vec<i32> = (0..3).map(|i| 100 * rank + i).collect();
+    request::scope(|scope| {
+        let mut guards = Vec::new();
+        let mut tags = Vec::new();
+        for message in &messages {
+            tags.push(comm.tag());
+            guards.push(WaitGuard::from(
+                comm.process_at_rank(next).immediate_send(scope, message),
+            ));
+            comm.next_iteration();
+        }
+        assert_eq!(comm.iteration(), 3);
+
+        // Receive the later iterations first, the tags keep them apart.
+        let source = comm.process_at_rank(previous);
+        let (third, _) = source.receive_with_tag::<i32>(tags[2]);
+        let (second, _) = source.receive_with_tag::<i32>(tags[1]);
+        assert_eq!(third, 100 * previous + 2);
+        assert_eq!(second, 100 * previous + 1);
+
+        // After a full period the cycle is back at the tag of the first iteration.
+        assert_eq!(comm.tag(), tags[0]);
+        let first: i32 = source.receive().0;
+        assert_eq!(first, 100 * previous);
+    });
+
+    let _world = comm.into_inner();
+}
@@ -0,0 +1,45 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::taskpool::TaskPool;
+use mpi::traits::*;
+
+/// Number of steps of the Collatz sequence starting at `n`
+fn collatz_steps(mut n: u64) -> u64 {
+    let mut steps = 0;
+    while n != 1 {
+        n = if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+        steps += 1;
+    }
+    steps
+}
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    let pool = TaskPool::new(&world, 0);
+    let tasks: Vec<u64> = if pool.is_manager() {
+        (1..=1000).collect()
+    } else {
+        Vec::new()
+    };
+    let results = pool.run(&tasks, collatz_steps);
+
+    if pool.is_manager() {
+        let results = results.expect("The manager receives the results.");
+        assert_eq!(results.len(), tasks.len());
+        for (&n, &steps) in tasks.iter().zip(&results) {
+            assert_eq!(steps, collatz_steps(n));
+        }
+        assert_eq!(results[26], 111);
+    } else {
+        assert!(results.is_none());
+    }
+
+    // A pool can run several batches of tasks.
+    let squares = pool.run(&[1i32, 2, 3, 4][..], |x| x * x);
+    if pool.is_manager() {
+        assert_eq!(squares, Some(vec![1, 4, 9, 16]));
+    }
+}
@@ -0,0 +1,65 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mpi::telemetry::{Counters, Telemetry};
+use mpi::traits::*;
+
+const ITERATIONS: u64 = 20;
+const PERIOD: u64 = 5;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let mut telemetry = Telemetry::new(&world, PERIOD);
+    {
+        let updates = updates.clone();
+        telemetry.on_update(move |counters| {
+            println!(
+                "Iteration {}: max residual {:e}",
+                counters[0].iteration,
+                counters.iter().map(|c| c.residual).fold(0.0, f64::max)
+            );
+            updates.borrow_mut().push(counters.to_vec());
+        });
+    }
+
+    let mut residual = 1.0;
+    let mut bytes = 0;
+    for iteration in 1..=ITERATIONS {
+        // The main communication is not disturbed by the gathers of the telemetry.
+        let (received, _): (f64, _) =
+            mpi::point_to_point::send_receive(&residual, &next, &previous);
+        residual = 0.5 * (residual + received) / (rank + 2) as f64;
+        bytes += 16;
+        telemetry.push(Counters {
+            iteration,
+            residual,
+            bytes_communicated: bytes,
+        });
+    }
+    telemetry.flush();
+
+    let updates = updates.borrow();
+    if rank == 0 {
+        assert_eq!(updates.len() as u64, ITERATIONS / PERIOD);
+        for (i, counters) in updates.iter().enumerate() {
+            assert_eq!(counters.len(), size as usize);
+            let iteration = (i as u64 + 1) * PERIOD;
+            assert!(counters.iter().all(|c| c.iteration == iteration));
+            assert!(counters
+                .iter()
+                .all(|c| c.bytes_communicated == 16 * iteration));
+        }
+    } else {
+        assert!(updates.is_empty());
+    }
+}
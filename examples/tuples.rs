@@ -0,0 +1,43 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+
+    // Gather a rank and a value from every process.
+    let mut pairs = vec![(0i32, 0.0f64); size as usize];
+    world.all_gather_into(&(rank, f64::from(rank) * 0.5), &mut pairs[..]);
+    for (r, &(rank, value)) in pairs.iter().enumerate() {
+        assert_eq!(rank, r as i32);
+        assert_eq!(value, f64::from(rank) * 0.5);
+    }
+
+    // Fields of different sizes are placed wherever the compiler lays them out.
+    let triples: Vec<(u8, f64, u16)> = (0..3)
+        .map(|i| (i as u8, f64::from(rank + i), (rank * 100 + i) as u16))
+        .collect();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+    let mut received = vec![(0u8, 0.0f64, 0u16); 3];
+    mpi::point_to_point::send_receive_into(&triples[..], &next, &mut received[..], &previous);
+    let expected: Vec<(u8, f64, u16)> = (0..3)
+        .map(|i| {
+            let p = previous.rank();
+            (i as u8, f64::from(p + i), (p * 100 + i) as u16)
+        })
+        .collect();
+    assert_eq!(received, expected);
+
+    // Tuples can be nested and combined with arrays.
+    let mut nested = ((0i64, [0u32; 2]), false);
+    if rank == 0 {
+        nested = ((-7, [1, 2]), true);
+    }
+    world.process_at_rank(0).broadcast_into(&mut nested);
+    assert_eq!(nested, ((-7, [1, 2]), true));
+}
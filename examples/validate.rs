@@ -0,0 +1,82 @@
+#![deny(warnings)]
+extern crate mpi;
+
+use mpi::traits::*;
+
+fn main() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let rank = world.rank();
+    let size = world.size();
+    let next = world.process_at_rank((rank + 1) % size);
+    let previous = world.process_at_rank((rank + size - 1) % size);
+
+    if size > 1 {
+        // Messages are sent along a ring, every process sends before it receives on even ranks and
+        // receives before it sends on odd ranks, the last process of an odd ring sends last.
+        let values: Vec<f64> = (0..100).map(|i| f64::from(rank * 100 + i)).collect();
+        let sends_first = rank % 2 == 0 && !(size % 2 == 1 && rank == size - 1);
+        let receive = || {
+            let (received, status) = previous.receive_vec::<f64>();
+            assert_eq!(status.source_rank(), previous.rank());
+            received
+        };
+        let received = if sends_first {
+            next.send(&values[..]);
+            receive()
+        } else {
+            let received = receive();
+            next.send(&values[..]);
+            received
+        };
+        let expected: Vec<f64> = (0..100)
+            .map(|i| f64::from(previous.rank() * 100 + i))
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    // Single values, buffers and matched receives are validated as well.
+    if rank == 0 {
+        for r in 1..size {
+            let process = world.process_at_rank(r);
+            let (value, _) = process.receive::<i32>();
+            assert_eq!(value, r);
+            let mut buf = [0u16; 4];
+            process.receive_into_without_status(&mut buf[..]);
+            assert_eq!(buf, [r as u16; 4]);
+            let (message, _) = process.matched_probe();
+            let (value, _) = message.matched_receive::<u64>();
+            assert_eq!(value, r as u64);
+        }
+    } else {
+        let root = world.process_at_rank(0);
+        root.synchronous_send(&rank);
+        root.send(&[rank as u16; 4][..]);
+        root.send(&(rank as u64));
+    }
+
+    // Immediate sends are paired with blocking receives and vice versa, send-receive operations
+    // with both.
+    let values = [rank; 3];
+    let (received, _) = mpi::request::scope(|scope| {
+        let request = next.immediate_send(scope, &values[..]);
+        let received = previous.receive_vec::<i32>();
+        request.wait();
+        received
+    });
+    assert_eq!(received, vec![previous.rank(); 3]);
+
+    let mut received = [0i32; 3];
+    mpi::request::scope(|scope| {
+        let request = previous.immediate_receive_into(scope, &mut received[..]);
+        next.send(&values[..]);
+        request.wait();
+    });
+    assert_eq!(received, [previous.rank(); 3]);
+
+    let (received, _): (i32, _) = mpi::point_to_point::send_receive(&rank, &next, &previous);
+    assert_eq!(received, previous.rank());
+    let mut buf = [rank; 2];
+    mpi::point_to_point::send_receive_replace_into(&mut buf[..], &next, &previous);
+    assert_eq!(buf, [previous.rank(); 2]);
+}
@@ -6,17 +6,81 @@ type TokenStream2 = proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Fields, Type};
 
+/// Derive `mpi::datatype::Equivalence` for a struct.
+///
+/// The equivalent datatype is a struct datatype with the offset of every field, so it describes
+/// the struct whatever its `repr`. Fields can be of any type that implements `Equivalence`,
+/// including other structs with a derived `Equivalence`, as well as tuples and fixed-size arrays
+/// of such types. `#[repr(transparent)]` structs have the datatype of their non-zero-sized field.
 #[proc_macro_derive(Equivalence)]
 pub fn create_user_datatype(input: TokenStream1) -> TokenStream1 {
     let ast: syn::DeriveInput = syn::parse(input).expect("Couldn't parse struct");
     let result = match ast.data {
         syn::Data::Enum(_) => panic!("#[derive(Equivalence)] is not compatible with enums"),
         syn::Data::Union(_) => panic!("#[derive(Equivalence)] is not compatible with unions"),
+        syn::Data::Struct(ref s) if is_transparent(&ast) => {
+            equivalence_for_transparent_struct(&ast, &s.fields)
+        }
         syn::Data::Struct(ref s) => equivalence_for_struct(&ast, &s.fields),
     };
     result.into()
 }
 
+fn is_transparent(ast: &syn::DeriveInput) -> bool {
+    ast.attrs.iter().any(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::List(ref list)) if list.path.is_ident("repr") => {
+            list.nested.iter().any(|nested| match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path)) => path.is_ident("transparent"),
+                _ => false,
+            })
+        }
+        _ => false,
+    })
+}
+
+fn is_phantom_data(ty: &syn::Type) -> bool {
+    match ty {
+        Type::Path(ref type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PhantomData"),
+        _ => false,
+    }
+}
+
+/// `#[repr(transparent)]` newtypes forward the datatype of their non-zero-sized field, so that
+/// e.g. built-in reduction operations can be applied to them.
+fn equivalence_for_transparent_struct(ast: &syn::DeriveInput, fields: &Fields) -> TokenStream2 {
+    let mut inner_fields = fields.iter().filter(|field| !is_phantom_data(&field.ty));
+    let inner = match (inner_fields.next(), inner_fields.next()) {
+        (Some(inner), None) => &inner.ty,
+        _ => panic!(
+            "#[derive(Equivalence)] on a #[repr(transparent)] struct requires exactly one field \
+             that is not `PhantomData`"
+        ),
+    };
+
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let mut predicates = where_clause
+        .map(|where_clause| where_clause.predicates.clone())
+        .unwrap_or_default();
+    predicates.push(syn::parse_quote!(#inner: ::mpi::datatype::Equivalence));
+
+    quote! {
+        unsafe impl #impl_generics ::mpi::datatype::Equivalence for #ident #ty_generics
+        where
+            #predicates
+        {
+            type Out = <#inner as ::mpi::datatype::Equivalence>::Out;
+            fn equivalent_datatype() -> Self::Out {
+                <#inner as ::mpi::datatype::Equivalence>::equivalent_datatype()
+            }
+        }
+    }
+}
+
 fn equivalence_for_tuple_field(type_tuple: &syn::TypeTuple) -> TokenStream2 {
     let field_blocklengths = type_tuple.elems.iter().map(|_| 1);
 
@@ -32,10 +96,14 @@ fn equivalence_for_tuple_field(type_tuple: &syn::TypeTuple) -> TokenStream2 {
         .map(|elem| equivalence_for_type(&elem));
 
     quote! {
-        &::mpi::datatype::UncommittedUserDatatype::structured(
-            &[#(#field_blocklengths as ::mpi::Count),*],
-            &[#(::mpi::internal::memoffset::offset_of_tuple!(#type_tuple, #fields) as ::mpi::Address),*],
-            &[#(::mpi::datatype::UncommittedDatatypeRef::from(#field_datatypes)),*],
+        &::mpi::datatype::UncommittedUserDatatype::resized(
+            &::mpi::datatype::UncommittedUserDatatype::structured(
+                &[#(#field_blocklengths as ::mpi::Count),*],
+                &[#(::mpi::internal::memoffset::offset_of_tuple!(#type_tuple, #fields) as ::mpi::Address),*],
+                &[#(::mpi::datatype::UncommittedDatatypeRef::from(#field_datatypes)),*],
+            ),
+            0,
+            ::std::mem::size_of::<#type_tuple>() as ::mpi::Address,
         )
     }
 }
@@ -52,13 +120,29 @@ fn equivalence_for_type(ty: &syn::Type) -> TokenStream2 {
                 <#type_path as ::mpi::datatype::Equivalence>::equivalent_datatype()),
         Type::Tuple(ref type_tuple) => equivalence_for_tuple_field(&type_tuple),
         Type::Array(ref type_array) => equivalence_for_array_field(&type_array),
-        _ => panic!("Unsupported type!"),
+        Type::Paren(ref type_paren) => equivalence_for_type(&type_paren.elem),
+        Type::Group(ref type_group) => equivalence_for_type(&type_group.elem),
+        _ => syn::Error::new_spanned(
+            ty,
+            "#[derive(Equivalence)] cannot describe fields of this type with an MPI datatype, \
+             e.g. references, pointers and slices",
+        )
+        .to_compile_error(),
     }
 }
 
 fn equivalence_for_struct(ast: &syn::DeriveInput, fields: &Fields) -> TokenStream2 {
     let ident = &ast.ident;
 
+    if !ast.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &ast.generics,
+            "#[derive(Equivalence)] is not compatible with generic structs, whose layout depends \
+             on their parameters",
+        )
+        .to_compile_error();
+    }
+
     let field_blocklengths = fields.iter().map(|_| 1);
 
     let field_names = fields
@@ -90,12 +174,18 @@ fn equivalence_for_struct(ast: &syn::DeriveInput, fields: &Fields) -> TokenStrea
                 static DATATYPE: Lazy<::mpi::datatype::UserDatatype> = Lazy::new(|| {
                     ::mpi::datatype::internal::check_derive_equivalence_universe_state(#ident_str);
 
-                    ::mpi::datatype::UserDatatype::structured::<
-                        ::mpi::datatype::UncommittedDatatypeRef,
-                    >(
-                        &[#(#field_blocklengths as ::mpi::Count),*],
-                        &[#(::mpi::internal::memoffset::offset_of!(#ident, #field_names) as ::mpi::Address),*],
-                        &[#(::mpi::datatype::UncommittedDatatypeRef::from(#field_datatypes)),*],
+                    // The extent is set to the size of the struct, which MPI cannot infer for
+                    // packed and over-aligned structs.
+                    ::mpi::datatype::UserDatatype::resized(
+                        &::mpi::datatype::UncommittedUserDatatype::structured::<
+                            ::mpi::datatype::UncommittedDatatypeRef,
+                        >(
+                            &[#(#field_blocklengths as ::mpi::Count),*],
+                            &[#(::mpi::internal::memoffset::offset_of!(#ident, #field_names) as ::mpi::Address),*],
+                            &[#(::mpi::datatype::UncommittedDatatypeRef::from(#field_datatypes)),*],
+                        ),
+                        0,
+                        ::std::mem::size_of::<#ident>() as ::mpi::Address,
                     )
                 });
 
@@ -1,10 +1,14 @@
 #include "rsmpi.h"
 
 const MPI_Datatype RSMPI_C_BOOL = MPI_C_BOOL;
+const MPI_Datatype RSMPI_CHAR = MPI_CHAR;
 
 const MPI_Datatype RSMPI_FLOAT = MPI_FLOAT;
 const MPI_Datatype RSMPI_DOUBLE = MPI_DOUBLE;
 
+const MPI_Datatype RSMPI_C_FLOAT_COMPLEX = MPI_C_FLOAT_COMPLEX;
+const MPI_Datatype RSMPI_C_DOUBLE_COMPLEX = MPI_C_DOUBLE_COMPLEX;
+
 const MPI_Datatype RSMPI_INT8_T = MPI_INT8_T;
 const MPI_Datatype RSMPI_INT16_T = MPI_INT16_T;
 const MPI_Datatype RSMPI_INT32_T = MPI_INT32_T;
@@ -15,8 +19,34 @@ const MPI_Datatype RSMPI_UINT16_T = MPI_UINT16_T;
 const MPI_Datatype RSMPI_UINT32_T = MPI_UINT32_T;
 const MPI_Datatype RSMPI_UINT64_T = MPI_UINT64_T;
 
+const MPI_Datatype RSMPI_PACKED = MPI_PACKED;
+
 const MPI_Datatype RSMPI_DATATYPE_NULL = MPI_DATATYPE_NULL;
 
+void* const RSMPI_IN_PLACE = MPI_IN_PLACE;
+
+const int RSMPI_ORDER_C = MPI_ORDER_C;
+const int RSMPI_ORDER_FORTRAN = MPI_ORDER_FORTRAN;
+
+const int RSMPI_DISTRIBUTE_BLOCK = MPI_DISTRIBUTE_BLOCK;
+const int RSMPI_DISTRIBUTE_CYCLIC = MPI_DISTRIBUTE_CYCLIC;
+const int RSMPI_DISTRIBUTE_NONE = MPI_DISTRIBUTE_NONE;
+const int RSMPI_DISTRIBUTE_DFLT_DARG = MPI_DISTRIBUTE_DFLT_DARG;
+
+const int RSMPI_COMBINER_NAMED = MPI_COMBINER_NAMED;
+const int RSMPI_COMBINER_STRUCT = MPI_COMBINER_STRUCT;
+const int RSMPI_COMBINER_DUP = MPI_COMBINER_DUP;
+const int RSMPI_COMBINER_CONTIGUOUS = MPI_COMBINER_CONTIGUOUS;
+const int RSMPI_COMBINER_VECTOR = MPI_COMBINER_VECTOR;
+const int RSMPI_COMBINER_HVECTOR = MPI_COMBINER_HVECTOR;
+const int RSMPI_COMBINER_INDEXED = MPI_COMBINER_INDEXED;
+const int RSMPI_COMBINER_HINDEXED = MPI_COMBINER_HINDEXED;
+const int RSMPI_COMBINER_INDEXED_BLOCK = MPI_COMBINER_INDEXED_BLOCK;
+const int RSMPI_COMBINER_HINDEXED_BLOCK = MPI_COMBINER_HINDEXED_BLOCK;
+const int RSMPI_COMBINER_SUBARRAY = MPI_COMBINER_SUBARRAY;
+const int RSMPI_COMBINER_DARRAY = MPI_COMBINER_DARRAY;
+const int RSMPI_COMBINER_RESIZED = MPI_COMBINER_RESIZED;
+
 const MPI_Comm RSMPI_COMM_WORLD = MPI_COMM_WORLD;
 const MPI_Comm RSMPI_COMM_NULL = MPI_COMM_NULL;
 const MPI_Comm RSMPI_COMM_SELF = MPI_COMM_SELF;
@@ -30,6 +60,7 @@ const int RSMPI_UNDEFINED = MPI_UNDEFINED;
 const int RSMPI_PROC_NULL = MPI_PROC_NULL;
 const int RSMPI_ANY_SOURCE = MPI_ANY_SOURCE;
 const int RSMPI_ANY_TAG = MPI_ANY_TAG;
+const int RSMPI_ROOT = MPI_ROOT;
 
 const MPI_Message RSMPI_MESSAGE_NULL = MPI_MESSAGE_NULL;
 const MPI_Message RSMPI_MESSAGE_NO_PROC = MPI_MESSAGE_NO_PROC;
@@ -56,6 +87,14 @@ const int RSMPI_DIST_GRAPH = MPI_DIST_GRAPH;
 const int RSMPI_MAX_LIBRARY_VERSION_STRING = MPI_MAX_LIBRARY_VERSION_STRING;
 const int RSMPI_MAX_PROCESSOR_NAME = MPI_MAX_PROCESSOR_NAME;
 
+const int RSMPI_TAG_UB = MPI_TAG_UB;
+
+const int RSMPI_MODE_NOCHECK = MPI_MODE_NOCHECK;
+
+const int RSMPI_MODE_RDONLY = MPI_MODE_RDONLY;
+const int RSMPI_MODE_WRONLY = MPI_MODE_WRONLY;
+const int RSMPI_MODE_CREATE = MPI_MODE_CREATE;
+
 const MPI_Op RSMPI_MAX = MPI_MAX;
 const MPI_Op RSMPI_MIN = MPI_MIN;
 const MPI_Op RSMPI_SUM = MPI_SUM;
@@ -66,6 +105,8 @@ const MPI_Op RSMPI_LOR = MPI_LOR;
 const MPI_Op RSMPI_BOR = MPI_BOR;
 const MPI_Op RSMPI_LXOR = MPI_LXOR;
 const MPI_Op RSMPI_BXOR = MPI_BXOR;
+const MPI_Op RSMPI_REPLACE = MPI_REPLACE;
+const MPI_Op RSMPI_NO_OP = MPI_NO_OP;
 
 const MPI_Errhandler RSMPI_ERRORS_ARE_FATAL = MPI_ERRORS_ARE_FATAL;
 const MPI_Errhandler RSMPI_ERRORS_RETURN = MPI_ERRORS_RETURN;
@@ -84,6 +125,144 @@ double RSMPI_Wtick() {
   return MPI_Wtick();
 }
 
+MPI_Aint RSMPI_Aint_add(MPI_Aint base, MPI_Aint disp) {
+  return MPI_Aint_add(base, disp);
+}
+
+MPI_Aint RSMPI_Aint_diff(MPI_Aint addr1, MPI_Aint addr2) {
+  return MPI_Aint_diff(addr1, addr2);
+}
+
+// Functions introduced by MPI 4.0 are only declared by new headers. With GCC and Clang on ELF
+// and Mach-O targets they are declared weak, so that their address is NULL if the library linked
+// at run time does not provide them.
+#if MPI_VERSION >= 4
+#if defined(__GNUC__) && !defined(_WIN32)
+#pragma weak MPI_Isendrecv
+#pragma weak MPI_Send_c
+#pragma weak MPI_Get_count_c
+#pragma weak MPI_Barrier_init
+#pragma weak MPI_Psend_init
+#pragma weak MPI_Precv_init
+#pragma weak MPI_Pready
+#pragma weak MPI_Parrived
+#define RSMPI_RESOLVED(function) (&function != NULL)
+#else
+#define RSMPI_RESOLVED(function) 1
+#endif
+#define RSMPI_HAS_MPI_4 1
+#endif
+
+int RSMPI_Isendrecv_is_supported(void) {
+#ifdef RSMPI_HAS_MPI_4
+  return RSMPI_RESOLVED(MPI_Isendrecv);
+#else
+  return 0;
+#endif
+}
+
+int RSMPI_Isendrecv(const void* sendbuf, int sendcount, MPI_Datatype sendtype, int dest,
+    int sendtag, void* recvbuf, int recvcount, MPI_Datatype recvtype, int source, int recvtag,
+    MPI_Comm comm, MPI_Request* request) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Isendrecv)) {
+    return MPI_Isendrecv(sendbuf, sendcount, sendtype, dest, sendtag, recvbuf, recvcount,
+        recvtype, source, recvtag, comm, request);
+  }
+#endif
+  return MPI_ERR_OTHER;
+}
+
+int RSMPI_Large_count_is_supported(void) {
+#ifdef RSMPI_HAS_MPI_4
+  return RSMPI_RESOLVED(MPI_Send_c);
+#else
+  return 0;
+#endif
+}
+
+int RSMPI_Get_count_c(const MPI_Status* status, MPI_Datatype datatype, MPI_Count* count) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Get_count_c)) {
+    return MPI_Get_count_c(status, datatype, count);
+  }
+#endif
+  // Derive the count from the number of bytes received, which MPI 3.0 can already report as an
+  // MPI_Count.
+  MPI_Count bytes;
+  MPI_Count size;
+  int code = MPI_Get_elements_x(status, MPI_BYTE, &bytes);
+  if (code != MPI_SUCCESS) {
+    return code;
+  }
+  code = MPI_Type_size_x(datatype, &size);
+  if (code != MPI_SUCCESS) {
+    return code;
+  }
+  if (size == 0) {
+    *count = 0;
+  } else if (bytes == MPI_UNDEFINED || bytes % size != 0) {
+    *count = MPI_UNDEFINED;
+  } else {
+    *count = bytes / size;
+  }
+  return MPI_SUCCESS;
+}
+
+int RSMPI_Persistent_collectives_is_supported(void) {
+#ifdef RSMPI_HAS_MPI_4
+  return RSMPI_RESOLVED(MPI_Barrier_init);
+#else
+  return 0;
+#endif
+}
+
+int RSMPI_Partitioned_is_supported(void) {
+#ifdef RSMPI_HAS_MPI_4
+  return RSMPI_RESOLVED(MPI_Psend_init);
+#else
+  return 0;
+#endif
+}
+
+int RSMPI_Psend_init(const void* buf, int partitions, MPI_Count count, MPI_Datatype datatype,
+    int dest, int tag, MPI_Comm comm, MPI_Info info, MPI_Request* request) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Psend_init)) {
+    return MPI_Psend_init(buf, partitions, count, datatype, dest, tag, comm, info, request);
+  }
+#endif
+  return MPI_ERR_OTHER;
+}
+
+int RSMPI_Precv_init(void* buf, int partitions, MPI_Count count, MPI_Datatype datatype,
+    int source, int tag, MPI_Comm comm, MPI_Info info, MPI_Request* request) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Precv_init)) {
+    return MPI_Precv_init(buf, partitions, count, datatype, source, tag, comm, info, request);
+  }
+#endif
+  return MPI_ERR_OTHER;
+}
+
+int RSMPI_Pready(int partition, MPI_Request request) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Pready)) {
+    return MPI_Pready(partition, request);
+  }
+#endif
+  return MPI_ERR_OTHER;
+}
+
+int RSMPI_Parrived(MPI_Request request, int partition, int* flag) {
+#ifdef RSMPI_HAS_MPI_4
+  if (RSMPI_RESOLVED(MPI_Parrived)) {
+    return MPI_Parrived(request, partition, flag);
+  }
+#endif
+  return MPI_ERR_OTHER;
+}
+
 #define RSMPI_c2f_def_base(type, ctype, argname) \
   MPI_Fint RS ## type ## _c2f(ctype     argname) { \
     return type ## _c2f(argname); \
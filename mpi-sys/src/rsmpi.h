@@ -7,10 +7,14 @@
 typedef MPI_Fint RSMPI_Fint;
 
 extern const MPI_Datatype RSMPI_C_BOOL;
+extern const MPI_Datatype RSMPI_CHAR;
 
 extern const MPI_Datatype RSMPI_FLOAT;
 extern const MPI_Datatype RSMPI_DOUBLE;
 
+extern const MPI_Datatype RSMPI_C_FLOAT_COMPLEX;
+extern const MPI_Datatype RSMPI_C_DOUBLE_COMPLEX;
+
 extern const MPI_Datatype RSMPI_INT8_T;
 extern const MPI_Datatype RSMPI_INT16_T;
 extern const MPI_Datatype RSMPI_INT32_T;
@@ -21,8 +25,34 @@ extern const MPI_Datatype RSMPI_UINT16_T;
 extern const MPI_Datatype RSMPI_UINT32_T;
 extern const MPI_Datatype RSMPI_UINT64_T;
 
+extern const MPI_Datatype RSMPI_PACKED;
+
 extern const MPI_Datatype RSMPI_DATATYPE_NULL;
 
+extern void* const RSMPI_IN_PLACE;
+
+extern const int RSMPI_ORDER_C;
+extern const int RSMPI_ORDER_FORTRAN;
+
+extern const int RSMPI_DISTRIBUTE_BLOCK;
+extern const int RSMPI_DISTRIBUTE_CYCLIC;
+extern const int RSMPI_DISTRIBUTE_NONE;
+extern const int RSMPI_DISTRIBUTE_DFLT_DARG;
+
+extern const int RSMPI_COMBINER_NAMED;
+extern const int RSMPI_COMBINER_STRUCT;
+extern const int RSMPI_COMBINER_DUP;
+extern const int RSMPI_COMBINER_CONTIGUOUS;
+extern const int RSMPI_COMBINER_VECTOR;
+extern const int RSMPI_COMBINER_HVECTOR;
+extern const int RSMPI_COMBINER_INDEXED;
+extern const int RSMPI_COMBINER_HINDEXED;
+extern const int RSMPI_COMBINER_INDEXED_BLOCK;
+extern const int RSMPI_COMBINER_HINDEXED_BLOCK;
+extern const int RSMPI_COMBINER_SUBARRAY;
+extern const int RSMPI_COMBINER_DARRAY;
+extern const int RSMPI_COMBINER_RESIZED;
+
 extern const MPI_Comm RSMPI_COMM_WORLD;
 extern const MPI_Comm RSMPI_COMM_NULL;
 extern const MPI_Comm RSMPI_COMM_SELF;
@@ -36,6 +66,7 @@ extern const int RSMPI_UNDEFINED;
 extern const int RSMPI_PROC_NULL;
 extern const int RSMPI_ANY_SOURCE;
 extern const int RSMPI_ANY_TAG;
+extern const int RSMPI_ROOT;
 
 extern const MPI_Message RSMPI_MESSAGE_NULL;
 extern const MPI_Message RSMPI_MESSAGE_NO_PROC;
@@ -62,6 +93,14 @@ extern const int RSMPI_DIST_GRAPH;
 extern const int RSMPI_MAX_LIBRARY_VERSION_STRING;
 extern const int RSMPI_MAX_PROCESSOR_NAME;
 
+extern const int RSMPI_TAG_UB;
+
+extern const int RSMPI_MODE_NOCHECK;
+
+extern const int RSMPI_MODE_RDONLY;
+extern const int RSMPI_MODE_WRONLY;
+extern const int RSMPI_MODE_CREATE;
+
 extern const MPI_Op RSMPI_MAX;
 extern const MPI_Op RSMPI_MIN;
 extern const MPI_Op RSMPI_SUM;
@@ -72,6 +111,8 @@ extern const MPI_Op RSMPI_LOR;
 extern const MPI_Op RSMPI_BOR;
 extern const MPI_Op RSMPI_LXOR;
 extern const MPI_Op RSMPI_BXOR;
+extern const MPI_Op RSMPI_REPLACE;
+extern const MPI_Op RSMPI_NO_OP;
 
 extern const MPI_Errhandler RSMPI_ERRORS_ARE_FATAL;
 extern const MPI_Errhandler RSMPI_ERRORS_RETURN;
@@ -85,6 +126,28 @@ extern const MPI_Win RSMPI_WIN_NULL;
 double RSMPI_Wtime();
 double RSMPI_Wtick();
 
+// MPICH defines the address arithmetic functions as macros.
+MPI_Aint RSMPI_Aint_add(MPI_Aint base, MPI_Aint disp);
+MPI_Aint RSMPI_Aint_diff(MPI_Aint addr1, MPI_Aint addr2);
+
+// Functions of newer versions of the standard are resolved weakly where the toolchain supports
+// it, so that a build against a new library also runs against an old one. The `_is_supported`
+// functions report whether the library that is linked at run time provides them.
+int RSMPI_Isendrecv_is_supported(void);
+int RSMPI_Isendrecv(const void* sendbuf, int sendcount, MPI_Datatype sendtype, int dest,
+    int sendtag, void* recvbuf, int recvcount, MPI_Datatype recvtype, int source, int recvtag,
+    MPI_Comm comm, MPI_Request* request);
+int RSMPI_Large_count_is_supported(void);
+int RSMPI_Get_count_c(const MPI_Status* status, MPI_Datatype datatype, MPI_Count* count);
+int RSMPI_Persistent_collectives_is_supported(void);
+int RSMPI_Partitioned_is_supported(void);
+int RSMPI_Psend_init(const void* buf, int partitions, MPI_Count count, MPI_Datatype datatype,
+    int dest, int tag, MPI_Comm comm, MPI_Info info, MPI_Request* request);
+int RSMPI_Precv_init(void* buf, int partitions, MPI_Count count, MPI_Datatype datatype,
+    int source, int tag, MPI_Comm comm, MPI_Info info, MPI_Request* request);
+int RSMPI_Pready(int partition, MPI_Request request);
+int RSMPI_Parrived(MPI_Request request, int partition, int* flag);
+
 // MPICH uses macros for c2f - explicitly define them.
 #define RSMPI_c2f_decl_base(type, ctype, argname) \
   MPI_Fint RS ## type ## _c2f(ctype     argname); \
@@ -0,0 +1,181 @@
+//! Latency and bandwidth micro-benchmarks
+//!
+//! The benchmarks in this module can be run from any program to check the health of the
+//! interconnect, e.g. as a smoke test at the start of a job, without installing a separate
+//! benchmark suite.
+//!
+//! `ping_pong()` measures the latency and bandwidth between every pair of processes of a
+//! communicator, one pair at a time, so that slow links and processes stand out. `all_reduce()`
+//! measures the time of an all-reduce for a range of message sizes.
+//!
+//! All benchmarks run on a duplicate of the communicator, so they do not interfere with pending
+//! communication of the program.
+//!
+//! # Examples
+//!
+//! See `examples/bench.rs`
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::collective::SystemOperation;
+use crate::point_to_point::traits::*;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+
+/// Latency and bandwidth between a pair of processes
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PingPong {
+    /// Rank of the process that sent the first message of every round trip
+    pub source: Rank,
+    /// Rank of the process that echoed the messages
+    pub destination: Rank,
+    /// Size of the messages in bytes
+    pub bytes: usize,
+    /// Shortest one-way time of all round trips in seconds
+    pub min_latency: f64,
+    /// Average one-way time of all round trips in seconds
+    pub mean_latency: f64,
+    /// Largest one-way time of all round trips in seconds
+    pub max_latency: f64,
+}
+
+impl PingPong {
+    /// Bandwidth in bytes per second, computed from the shortest one-way time
+    pub fn bandwidth(&self) -> f64 {
+        as_f64(self.bytes) / self.min_latency
+    }
+}
+
+/// Time of an all-reduce of a message of a given size
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AllReduceTiming {
+    /// Size of the reduced message in bytes
+    pub bytes: usize,
+    /// Shortest time of an all-reduce in seconds
+    pub min_time: f64,
+    /// Average time of an all-reduce in seconds
+    pub mean_time: f64,
+    /// Largest time of an all-reduce in seconds
+    pub max_time: f64,
+}
+
+/// Measure the latency and bandwidth between all pairs of processes of `comm`.
+///
+/// For every pair of ranks `source < destination`, the two processes exchange `iterations` round
+/// trips of messages of `bytes` bytes after a warm-up round trip, while all other processes wait.
+/// Times are taken as half the round trip time. Returns the statistics of all pairs, ordered by
+/// source and destination rank, on all processes. This is a collective operation.
+///
+/// The pairs are measured one after another, so the benchmark takes a number of rounds that is
+/// quadratic in the size of `comm`.
+pub fn ping_pong<C>(comm: &C, bytes: usize, iterations: usize) -> Vec<PingPong>
+where
+    C: Communicator,
+{
+    assert!(iterations > 0, "A benchmark needs at least one iteration.");
+    let comm = comm.duplicate();
+    let rank = comm.rank();
+    let size = comm.size();
+    let mut message = vec![0u8; bytes];
+
+    let pairs: Vec<(Rank, Rank)> = (0..size)
+        .flat_map(|source| (source + 1..size).map(move |destination| (source, destination)))
+        .collect();
+    let mut local = vec![0.0f64; 3 * pairs.len()];
+
+    for (i, &(source, destination)) in pairs.iter().enumerate() {
+        if rank == source {
+            let process = comm.process_at_rank(destination);
+            let mut times = Vec::with_capacity(iterations);
+            for round in 0..=iterations {
+                let start = crate::time();
+                process.send(&message[..]);
+                process.receive_into(&mut message[..]);
+                if round > 0 {
+                    times.push((crate::time() - start) / 2.0);
+                }
+            }
+            local[3 * i..3 * i + 3].copy_from_slice(&statistics(&times));
+        } else if rank == destination {
+            let process = comm.process_at_rank(source);
+            for _ in 0..=iterations {
+                process.receive_into(&mut message[..]);
+                process.send(&message[..]);
+            }
+        }
+        comm.barrier();
+    }
+
+    // Every pair was measured by exactly one process, all other entries are zero.
+    let mut all = vec![0.0f64; local.len()];
+    comm.all_reduce_into(&local[..], &mut all[..], SystemOperation::sum());
+
+    pairs
+        .into_iter()
+        .zip(all.chunks(3))
+        .map(|((source, destination), times)| PingPong {
+            source,
+            destination,
+            bytes,
+            min_latency: times[0],
+            mean_latency: times[1],
+            max_latency: times[2],
+        })
+        .collect()
+}
+
+/// Measure the time of an all-reduce on `comm` for every message size in `sizes`.
+///
+/// Every size is reduced `iterations` times after a warm-up call. The time of a call is the
+/// longest time any process spent in it. The message sizes are rounded down to a multiple of the
+/// size of an `f64`. Returns the timings in the order of `sizes` on all processes. This is a
+/// collective operation.
+pub fn all_reduce<C>(comm: &C, sizes: &[usize], iterations: usize) -> Vec<AllReduceTiming>
+where
+    C: Communicator,
+{
+    assert!(iterations > 0, "A benchmark needs at least one iteration.");
+    let comm = comm.duplicate();
+    let element = std::mem::size_of::<f64>();
+
+    sizes
+        .iter()
+        .map(|&bytes| {
+            let send = vec![1.0f64; bytes / element];
+            let mut recv = vec![0.0f64; bytes / element];
+            let mut local = Vec::with_capacity(iterations);
+            for round in 0..=iterations {
+                comm.barrier();
+                let start = crate::time();
+                comm.all_reduce_into(&send[..], &mut recv[..], SystemOperation::sum());
+                if round > 0 {
+                    local.push(crate::time() - start);
+                }
+            }
+            let mut times = vec![0.0f64; iterations];
+            comm.all_reduce_into(&local[..], &mut times[..], SystemOperation::max());
+            let [min_time, mean_time, max_time] = statistics(&times);
+            AllReduceTiming {
+                bytes: recv.len() * element,
+                min_time,
+                mean_time,
+                max_time,
+            }
+        })
+        .collect()
+}
+
+/// Minimum, mean and maximum of `times`
+fn statistics(times: &[f64]) -> [f64; 3] {
+    let min = times.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = times.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mean = times.iter().sum::<f64>() / as_f64(times.len());
+    [min, mean, max]
+}
+
+fn as_f64(value: usize) -> f64 {
+    value
+        .value_as()
+        .expect("Benchmark size cannot be expressed as an f64.")
+}
@@ -0,0 +1,224 @@
+//! Packed bit sets for distributed flags
+//!
+//! Mesh codes keep per-element flags like "marked for refinement" or "owned by this process" that
+//! all processes have to agree on. Exchanging them as `bool`s wastes a byte per flag, so a
+//! `BitSetBuffer` packs them into `u64` words. The words are the MPI buffer of the bit set, so it
+//! can be sent, received and broadcast like a slice, and combined across processes with bitwise
+//! `OR` (any process set the flag) or `AND` (all processes set the flag) reductions.
+//!
+//! # Examples
+//!
+//! See `examples/bitset.rs`
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::os::raw::c_void;
+
+use crate::collective::{traits::*, SystemOperation};
+use crate::datatype::traits::*;
+use crate::datatype::CountError;
+use crate::topology::traits::*;
+use crate::Count;
+
+const WORD_BITS: usize = 64;
+
+/// A fixed number of bits packed into `u64` words
+///
+/// Bit `i` is bit `i % 64` of word `i / 64`. The bits of the last word beyond `len()` are always
+/// zero.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitSetBuffer {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSetBuffer {
+    /// A bit set of `len` bits that are all cleared
+    pub fn new(len: usize) -> BitSetBuffer {
+        BitSetBuffer {
+            words: vec![0; words_for(len)],
+            len,
+        }
+    }
+
+    /// A bit set of `len` bits packed into `words`
+    ///
+    /// Panics if `words` does not hold exactly the words needed for `len` bits. Bits beyond `len`
+    /// are cleared.
+    pub fn from_words(words: Vec<u64>, len: usize) -> BitSetBuffer {
+        assert_eq!(
+            words.len(),
+            words_for(len),
+            "{} bits are packed into {} words, not {}.",
+            len,
+            words_for(len),
+            words.len()
+        );
+        let mut bits = BitSetBuffer { words, len };
+        bits.clear_padding();
+        bits
+    }
+
+    /// A bit set with bit `i` set if `flags[i]` is `true`
+    pub fn from_bools(flags: &[bool]) -> BitSetBuffer {
+        flags.iter().copied().collect()
+    }
+
+    /// The bits as `bool`s
+    pub fn to_bools(&self) -> Vec<bool> {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
+
+    /// The number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the bit set holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The words the bits are packed into
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Whether bit `i` is set
+    ///
+    /// Panics if `i` is out of range.
+    pub fn get(&self, i: usize) -> bool {
+        self.check_index(i);
+        self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0
+    }
+
+    /// Set bit `i` to `value`.
+    ///
+    /// Panics if `i` is out of range.
+    pub fn set(&mut self, i: usize, value: bool) {
+        self.check_index(i);
+        let mask = 1 << (i % WORD_BITS);
+        if value {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The indices of the set bits in ascending order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(move |&i| self.get(i))
+    }
+
+    /// Set every bit that is set on any process of `comm`.
+    ///
+    /// All processes have to pass bit sets of the same length. This is a collective operation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.6
+    pub fn all_reduce_or<C: Communicator>(&mut self, comm: &C) {
+        comm.all_reduce_in_place(&mut self.words[..], SystemOperation::bitwise_or());
+    }
+
+    /// Clear every bit that is cleared on any process of `comm`, so the bits that remain set are
+    /// set on all processes.
+    ///
+    /// All processes have to pass bit sets of the same length. This is a collective operation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.6
+    pub fn all_reduce_and<C: Communicator>(&mut self, comm: &C) {
+        comm.all_reduce_in_place(&mut self.words[..], SystemOperation::bitwise_and());
+    }
+
+    fn check_index(&self, i: usize) {
+        assert!(
+            i < self.len,
+            "Bit {} is out of range for a bit set of {} bits.",
+            i,
+            self.len
+        );
+    }
+
+    fn clear_padding(&mut self) {
+        let used = self.len % WORD_BITS;
+        if used != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+}
+
+fn words_for(len: usize) -> usize {
+    (len + WORD_BITS - 1) / WORD_BITS
+}
+
+impl FromIterator<bool> for BitSetBuffer {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitSetBuffer::default();
+        for (i, value) in iter.into_iter().enumerate() {
+            if i % WORD_BITS == 0 {
+                bits.words.push(0);
+            }
+            bits.len += 1;
+            bits.set(i, value);
+        }
+        bits
+    }
+}
+
+impl fmt::Debug for BitSetBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BitSetBuffer(")?;
+        for i in 0..self.len {
+            f.write_str(if self.get(i) { "1" } else { "0" })?;
+        }
+        f.write_str(")")
+    }
+}
+
+unsafe impl AsDatatype for BitSetBuffer {
+    type Out = <u64 as Equivalence>::Out;
+    fn as_datatype(&self) -> Self::Out {
+        u64::equivalent_datatype()
+    }
+}
+
+unsafe impl Collection for BitSetBuffer {
+    fn count(&self) -> Count {
+        self.words[..].count()
+    }
+
+    fn try_count(&self) -> Result<Count, CountError> {
+        self.words[..].try_count()
+    }
+}
+
+unsafe impl Pointer for BitSetBuffer {
+    fn pointer(&self) -> *const c_void {
+        self.words[..].pointer()
+    }
+}
+
+unsafe impl PointerMut for BitSetBuffer {
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.words[..].pointer_mut()
+    }
+}
+
+unsafe impl Buffer for BitSetBuffer {}
+
+// Receiving into a bit set can only set the padding bits if the sender's bit set is longer, which
+// the matching lengths required of the communication partners rule out.
+unsafe impl BufferMut for BitSetBuffer {}
@@ -0,0 +1,163 @@
+//! Exchange of durations, wall-clock times and timer offsets
+//!
+//! `std::time::Duration` and `std::time::SystemTime` have no fixed memory layout, so they cannot
+//! implement `Equivalence` themselves. `WireDuration` is a fixed layout equivalent of `Duration`
+//! with conversions in both directions, `SystemTime`s are sent as the `WireDuration` since the
+//! UNIX epoch.
+//!
+//! `MPI_Wtime()` is only synchronized across processes if the `MPI_WTIME_IS_GLOBAL` attribute is
+//! set. `measure_clock_skew()` estimates the offsets of the timers of all processes to the timer
+//! of a root process, e.g. to merge timestamped event logs of several processes.
+//!
+//! # Examples
+//!
+//! See `examples/clock.rs`
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use conv::ConvUtil;
+use memoffset::offset_of;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::datatype::{UncommittedDatatypeRef, UserDatatype};
+use crate::point_to_point::traits::*;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+use crate::Address;
+
+/// A `Duration` with a fixed memory layout that can be sent in messages
+///
+/// # Standard section(s)
+///
+/// 4.1.2
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct WireDuration {
+    /// Whole seconds
+    pub secs: u64,
+    /// Nanoseconds in addition to the whole seconds, always less than one billion
+    pub nanos: u32,
+}
+
+impl WireDuration {
+    /// The time passed between the UNIX epoch and `time`
+    ///
+    /// Panics if `time` lies before the UNIX epoch.
+    pub fn since_epoch(time: SystemTime) -> WireDuration {
+        time.duration_since(UNIX_EPOCH)
+            .expect("Wall-clock time lies before the UNIX epoch.")
+            .into()
+    }
+
+    /// The wall-clock time lying this duration after the UNIX epoch
+    pub fn after_epoch(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from(self)
+    }
+}
+
+impl From<Duration> for WireDuration {
+    fn from(duration: Duration) -> WireDuration {
+        WireDuration {
+            secs: duration.as_secs(),
+            nanos: duration.subsec_nanos(),
+        }
+    }
+}
+
+impl From<WireDuration> for Duration {
+    fn from(duration: WireDuration) -> Duration {
+        Duration::new(duration.secs, duration.nanos)
+    }
+}
+
+unsafe impl Equivalence for WireDuration {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        UserDatatype::structured(
+            &[1, 1],
+            &[
+                offset(offset_of!(WireDuration, secs)),
+                offset(offset_of!(WireDuration, nanos)),
+            ],
+            &[
+                UncommittedDatatypeRef::from(u64::equivalent_datatype()),
+                UncommittedDatatypeRef::from(u32::equivalent_datatype()),
+            ],
+        )
+    }
+}
+
+fn offset(offset: usize) -> Address {
+    offset
+        .value_as()
+        .expect("Field offset cannot be expressed as an Address.")
+}
+
+/// The wall-clock times of all processes of `comm`, taken at the start of the call
+///
+/// Returns the times in rank order on all processes. This is a collective operation.
+pub fn all_gather_wall_clock<C>(comm: &C) -> Vec<SystemTime>
+where
+    C: Communicator,
+{
+    let local = WireDuration::since_epoch(SystemTime::now());
+    let size: usize = comm
+        .size()
+        .value_as()
+        .expect("Communicator size cannot be expressed as a usize.");
+    let mut all = vec![WireDuration::default(); size];
+    comm.all_gather_into(&local, &mut all[..]);
+    all.into_iter().map(WireDuration::after_epoch).collect()
+}
+
+/// Estimate the offsets of the `MPI_Wtime()` timers of all processes of `comm` to the timer of
+/// process `root`.
+///
+/// The root process exchanges `rounds` ping-pong messages with every other process and takes the
+/// remote time of the exchange with the shortest round trip, assuming that it was read half way
+/// through the round trip. Returns the offsets in seconds in rank order on the root process, so
+/// that `time() - offsets[r]` on process `r` approximates `time()` on the root. Returns `None` on
+/// all other processes. This is a collective operation.
+///
+/// # Standard section(s)
+///
+/// 8.6
+pub fn measure_clock_skew<C>(comm: &C, root: Rank, rounds: usize) -> Option<Vec<f64>>
+where
+    C: Communicator,
+{
+    assert!(rounds > 0, "Measuring clock skew needs at least one round.");
+    let comm = comm.duplicate();
+    if comm.rank() == root {
+        let offsets = (0..comm.size())
+            .map(|rank| {
+                if rank == root {
+                    return 0.0;
+                }
+                let process = comm.process_at_rank(rank);
+                let mut best_round_trip = f64::INFINITY;
+                let mut offset = 0.0;
+                for _ in 0..rounds {
+                    let start = crate::time();
+                    process.send(&start);
+                    let (remote, _) = process.receive::<f64>();
+                    let end = crate::time();
+                    if end - start < best_round_trip {
+                        best_round_trip = end - start;
+                        offset = remote - (start + end) / 2.0;
+                    }
+                }
+                offset
+            })
+            .collect();
+        Some(offsets)
+    } else {
+        let process = comm.process_at_rank(root);
+        for _ in 0..rounds {
+            let _ = process.receive::<f64>();
+            process.send(&crate::time());
+        }
+        None
+    }
+}
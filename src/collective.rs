@@ -5,29 +5,40 @@
 //! # Unfinished features
 //!
 //! - **5.8**: All-to-all, `MPI_Alltoallw()`
-//! - **5.10**: Reduce-scatter, `MPI_Reduce_scatter()`
-//! - **5.12**: Nonblocking collective operations,
-//! `MPI_Ialltoallw()`, `MPI_Ireduce_scatter()`
+//! - **5.12**: Nonblocking collective operations, `MPI_Ialltoallw()`
 
-#[cfg(feature = "user-operations")]
+use std::marker::PhantomData;
 use std::mem;
 use std::os::raw::{c_int, c_void};
 use std::{fmt, ptr};
 
+use conv::ConvUtil;
+use memoffset::offset_of;
+
 #[cfg(feature = "user-operations")]
 use libffi::middle::{Cif, Closure, Type};
 
+use crate::environment;
 use crate::ffi;
-use crate::ffi::MPI_Op;
+use crate::ffi::{MPI_Datatype, MPI_Op};
 
+use crate::counts;
 use crate::datatype::traits::*;
 #[cfg(feature = "user-operations")]
-use crate::datatype::{DatatypeRef, DynBuffer, DynBufferMut};
+use crate::datatype::MutView;
+use crate::datatype::{
+    DatatypeRef, DisjointViews, DynBuffer, DynBufferMut, Order, Partition, PartitionMut,
+};
+use crate::datatype::{StructLayoutBuilder, UncommittedUserDatatype, UserDatatype};
+use crate::hooks::{self, Call};
+use crate::point_to_point::send_receive_into_with_tags;
+use crate::point_to_point::traits::*;
 use crate::raw::traits::*;
-use crate::request::{Request, Scope, StaticScope};
+use crate::request::{self, Request, Scope, StaticScope};
+use crate::schedule::Dissemination;
 use crate::topology::traits::*;
-use crate::topology::{Process, Rank};
-use crate::with_uninitialized;
+use crate::topology::{private, Process, Rank, UserCommunicator};
+use crate::{with_uninitialized, Address, Count, Tag};
 
 /// Collective communication traits
 pub mod traits {
@@ -51,11 +62,59 @@ pub trait CommunicatorCollectives: Communicator {
     ///
     /// 5.3
     fn barrier(&self) {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "barrier"));
         unsafe {
             ffi::MPI_Barrier(self.as_raw());
         }
     }
 
+    /// Barrier synchronization among the processes of `group`, a subgroup of the group of this
+    /// `Communicator`
+    ///
+    /// Blocks until all processes in `group` have entered the barrier. Only the processes in
+    /// `group` take part, no communicator is created for the subgroup, which makes this cheap for
+    /// occasional synchronization among ad-hoc subsets of processes. The barrier exchanges
+    /// `ceil(log2(n))` rounds of empty point to point messages tagged `tag` on this communicator
+    /// (dissemination barrier), so concurrent barriers among overlapping groups need distinct tags.
+    ///
+    /// Panics if the calling process is not a member of `group`.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/subgroup_barrier.rs`
+    fn subgroup_barrier_with_tag<G>(&self, group: &G, tag: Tag)
+    where
+        G: Group,
+        Self: Sized,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "subgroup_barrier_with_tag"));
+        let own = group
+            .rank()
+            .expect("The calling process is not a member of the subgroup.");
+        let n = group.size();
+        let ranks: Vec<Rank> = (0..n).collect();
+        let comm_ranks: Vec<Rank> = group
+            .translate_ranks(&ranks, &self.group())
+            .into_iter()
+            .map(|rank| rank.expect("The subgroup is not a subgroup of the communicator."))
+            .collect();
+
+        let empty: [u8; 0] = [];
+        for (destination, source) in Dissemination::new(n).partners(own) {
+            let destination = self.process_at_rank(comm_ranks[to_usize(destination)]);
+            let source = self.process_at_rank(comm_ranks[to_usize(source)]);
+            let mut received: [u8; 0] = [];
+            send_receive_into_with_tags(
+                &empty[..],
+                &destination,
+                tag,
+                &mut received[..],
+                &source,
+                tag,
+            );
+        }
+    }
+
     /// Gather contents of buffers on all participating processes.
     ///
     /// After the call completes, the contents of the send `Buffer`s on all processes will be
@@ -75,6 +134,7 @@ pub trait CommunicatorCollectives: Communicator {
         S: Buffer,
         R: BufferMut,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_gather_into"));
         unsafe {
             ffi::MPI_Allgather(
                 sendbuf.pointer(),
@@ -108,6 +168,7 @@ pub trait CommunicatorCollectives: Communicator {
         S: Buffer,
         R: PartitionedBufferMut,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_gather_varcount_into"));
         unsafe {
             ffi::MPI_Allgatherv(
                 sendbuf.pointer(),
@@ -138,6 +199,7 @@ pub trait CommunicatorCollectives: Communicator {
         S: Buffer,
         R: BufferMut,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_to_all_into"));
         let c_size = self.size();
         unsafe {
             ffi::MPI_Alltoall(
@@ -152,6 +214,47 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Distribute the send view of `views` from all processes to the receive views on all
+    /// processes, where both views are disjoint parts of the same buffer.
+    ///
+    /// The send and receive counts of `views` are divided evenly among the processes, like in
+    /// `all_to_all_into()`. This makes it possible to e.g. transpose a distributed matrix into
+    /// another part of the buffer that holds it, with strided datatypes for both views.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/disjoint_views.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.8
+    fn all_to_all_disjoint<T, SD, RD>(&self, views: &mut DisjointViews<T, SD, RD>)
+    where
+        SD: Datatype,
+        RD: Datatype,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_to_all_disjoint"));
+        let c_size = self.size();
+        assert!(
+            views.send_count() % c_size == 0 && views.receive_count() % c_size == 0,
+            "The send count {} and receive count {} have to be multiples of the {} processes.",
+            views.send_count(),
+            views.receive_count(),
+            c_size
+        );
+        unsafe {
+            ffi::MPI_Alltoall(
+                views.send_pointer(),
+                views.send_count() / c_size,
+                views.send_datatype().as_raw(),
+                views.receive_pointer(),
+                views.receive_count() / c_size,
+                views.receive_datatype().as_raw(),
+                self.as_raw(),
+            );
+        }
+    }
+
     /// Distribute the send `Buffer`s from all processes to the receive `Buffer`s on all processes.
     ///
     /// The count of elements to send and receive to and from each process can vary and is specified
@@ -165,6 +268,7 @@ pub trait CommunicatorCollectives: Communicator {
         S: PartitionedBuffer,
         R: PartitionedBufferMut,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_to_all_varcount_into"));
         unsafe {
             ffi::MPI_Alltoallv(
                 sendbuf.pointer(),
@@ -180,6 +284,76 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Send every element of `items` to the process whose rank `owner` returns for it and receive
+    /// the elements all processes send to the calling process.
+    ///
+    /// The number of elements sent between every pair of processes is negotiated by an all to all
+    /// exchange of counts, followed by a single all to all exchange of the elements. The received
+    /// elements are ordered by the rank of the sending process and keep their order from `items`
+    /// within the elements of one process. This is the core of particle migration and
+    /// repartitioning.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/exchange_by_owner.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.8
+    fn exchange_by_owner<T, F>(&self, items: &[T], mut owner: F) -> Vec<T>
+    where
+        T: Equivalence + Copy,
+        F: FnMut(&T) -> Rank,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "exchange_by_owner"));
+        let size = self.size();
+        let owners: Vec<usize> = items
+            .iter()
+            .map(|item| {
+                let rank = owner(item);
+                assert!(
+                    0 <= rank && rank < size,
+                    "Owner rank {} is out of range for a communicator of size {}.",
+                    rank,
+                    size
+                );
+                to_usize(rank)
+            })
+            .collect();
+        let mut send_counts: Vec<Count> = vec![0; to_usize(size)];
+        for &owner in &owners {
+            send_counts[owner] += 1;
+        }
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by_key(|&i| owners[i]);
+        let sendbuf: Vec<T> = order.iter().map(|&i| items[i]).collect();
+        let send_displs = counts::displacements(&send_counts);
+
+        let mut recv_counts: Vec<Count> = vec![0; to_usize(size)];
+        self.all_to_all_into(&send_counts[..], &mut recv_counts[..]);
+        let recv_displs = counts::displacements(&recv_counts);
+        let len = recv_counts
+            .iter()
+            .try_fold(0, |len: Count, &count| len.checked_add(count))
+            .expect("Number of received elements cannot be expressed as an MPI Count.");
+
+        let mut received: Vec<T> = Vec::with_capacity(to_usize(len));
+        let datatype = T::equivalent_datatype();
+        unsafe {
+            let mut recvbuf = DynBufferMut::from_raw(
+                received.as_mut_ptr(),
+                len,
+                DatatypeRef::from_raw(datatype.as_raw()),
+            );
+            self.all_to_all_varcount_into(
+                &Partition::new(&sendbuf[..], &send_counts[..], &send_displs[..]),
+                &mut PartitionMut::new(&mut recvbuf, &recv_counts[..], &recv_displs[..]),
+            );
+            received.set_len(to_usize(len));
+        }
+        received
+    }
+
     /// Performs a global reduction under the operation `op` of the input data in `sendbuf` and
     /// stores the result in `recvbuf` on all processes.
     ///
@@ -196,6 +370,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: BufferMut,
         O: Operation,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_reduce_into"));
         unsafe {
             ffi::MPI_Allreduce(
                 sendbuf.pointer(),
@@ -208,6 +383,226 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Performs a global reduction under the operation `op` of the contents of `buf` on all
+    /// processes and replaces the contents of `buf` with the result.
+    ///
+    /// This avoids a separate receive buffer, e.g. for large arrays of derived datatypes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_in_place.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.6
+    fn all_reduce_in_place<B: ?Sized, O>(&self, buf: &mut B, op: O)
+    where
+        B: BufferMut,
+        O: Operation,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "all_reduce_in_place"));
+        unsafe {
+            ffi::MPI_Allreduce(
+                ffi::RSMPI_IN_PLACE,
+                buf.pointer_mut(),
+                buf.count(),
+                buf.as_datatype().as_raw(),
+                op.as_raw(),
+                self.as_raw(),
+            );
+        }
+    }
+
+    /// Performs a global reduction under the operation `op` of `value` on all processes and
+    /// returns the result.
+    ///
+    /// This is `all_reduce_into()` for a single element without the need to declare the result
+    /// beforehand.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_scalar.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.6
+    fn all_reduce<T, O>(&self, value: T, op: O) -> T
+    where
+        T: Equivalence + Copy,
+        O: Operation,
+    {
+        let mut result = value;
+        self.all_reduce_into(&value, &mut result, op);
+        result
+    }
+
+    /// The sum of `value` over all processes
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_scalar.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.2, 5.9.6
+    fn sum<T>(&self, value: T) -> T
+    where
+        T: Equivalence + Copy,
+    {
+        self.all_reduce(value, SystemOperation::sum())
+    }
+
+    /// The maximum of `value` over all processes
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_scalar.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.2, 5.9.6
+    fn max<T>(&self, value: T) -> T
+    where
+        T: Equivalence + Copy,
+    {
+        self.all_reduce(value, SystemOperation::max())
+    }
+
+    /// The minimum of `value` over all processes
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_scalar.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.2, 5.9.6
+    fn min<T>(&self, value: T) -> T
+    where
+        T: Equivalence + Copy,
+    {
+        self.all_reduce(value, SystemOperation::min())
+    }
+
+    /// Performs a global reduction of the contents of `buf` on all processes under the operation
+    /// `function` and replaces the contents of `buf` with the result.
+    ///
+    /// `function(invec, inoutvec)` shall set `inoutvec` to the elementwise combination of `invec`
+    /// and `inoutvec`. It has to be associative and, if `commute` is `true`, commutative.
+    ///
+    /// Many MPI libraries reduce derived datatypes much slower than contiguous data. Instead of the
+    /// datatype equivalent to `T`, the elements are therefore transferred as packed bytes and
+    /// copied into properly aligned slices of `T` before `function` is applied. This requires all
+    /// processes to share the same memory layout of `T`.
+    ///
+    /// **Note:** If `function` panics, the entire program will abort.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_in_place.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.5, 5.9.6
+    #[cfg(feature = "user-operations")]
+    fn all_reduce_in_place_with<T, F>(&self, buf: &mut [T], commute: bool, function: F)
+    where
+        T: Equivalence + Copy,
+        F: Fn(&[T], &mut [T]) + Sync,
+    {
+        let element_size: Count = mem::size_of::<T>()
+            .value_as()
+            .expect("Size of element type cannot be expressed as a Count.");
+        assert!(element_size > 0, "Cannot reduce zero-sized elements.");
+        let element = UserDatatype::contiguous(element_size, &u8::equivalent_datatype());
+        let op = UserOperation::new(commute, |invec: DynBuffer, mut inoutvec: DynBufferMut| {
+            let len = invec.len();
+            let mut input: Vec<T> = Vec::with_capacity(len);
+            let mut output: Vec<T> = Vec::with_capacity(len);
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    invec.as_ptr() as *const u8,
+                    input.as_mut_ptr() as *mut u8,
+                    len * mem::size_of::<T>(),
+                );
+                input.set_len(len);
+                ptr::copy_nonoverlapping(
+                    inoutvec.as_ptr() as *const u8,
+                    output.as_mut_ptr() as *mut u8,
+                    len * mem::size_of::<T>(),
+                );
+                output.set_len(len);
+            }
+            function(&input, &mut output);
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    output.as_ptr() as *const u8,
+                    inoutvec.as_mut_ptr() as *mut u8,
+                    len * mem::size_of::<T>(),
+                );
+            }
+        });
+        let count = buf.count();
+        let mut packed = unsafe { MutView::with_count_and_datatype(buf, count, &element) };
+        self.all_reduce_in_place(&mut packed, &op);
+    }
+
+    /// Performs a global reduction under the operation `op` of the input data in `sendbuf` and
+    /// stores the result in `recvbuf` on all processes, in an order that does not depend on the
+    /// MPI library or the timing of messages.
+    ///
+    /// `MPI_Allreduce()` may combine contributions in any order, so floating-point results can
+    /// differ in the last bits between runs. This reduction combines the contributions along a
+    /// fixed binary tree over the ranks of the communicator, where process `r` combines its partial
+    /// result `a` with the partial result `b` of process `r + 2^k` as `a op b`, and broadcasts the
+    /// result from rank 0. For a fixed number of processes the result is bitwise identical in
+    /// every run, also for non-commutative operations.
+    ///
+    /// The reduction runs on a private duplicate of the communicator so that its messages cannot
+    /// match other point to point messages on the communicator. The duplicate is created by the
+    /// first call on a communicator and reused by later calls.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_deterministic.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2, 5.4, 5.9.7
+    fn all_reduce_deterministic_into<T, O>(&self, sendbuf: &[T], recvbuf: &mut [T], op: O)
+    where
+        T: Equivalence + Clone,
+        O: Operation,
+    {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "all_reduce_deterministic_into"));
+        assert_eq!(
+            sendbuf.len(),
+            recvbuf.len(),
+            "Send and receive buffer of a reduction must have the same length."
+        );
+        let comm = private::duplicate(self);
+        let size = comm.size();
+        let rank = comm.rank();
+
+        recvbuf.clone_from_slice(sendbuf);
+        let mut partner = sendbuf.to_vec();
+        let mut mask = 1;
+        while mask < size {
+            if rank & mask != 0 {
+                comm.process_at_rank(rank - mask).send(&recvbuf[..]);
+                break;
+            } else if rank + mask < size {
+                comm.process_at_rank(rank + mask)
+                    .receive_into(&mut partner[..]);
+                reduce_local_into(&recvbuf[..], &mut partner[..], &op);
+                recvbuf.swap_with_slice(&mut partner[..]);
+            }
+            mask <<= 1;
+        }
+        comm.process_at_rank(0).broadcast_into(recvbuf);
+    }
+
     /// Performs an element-wise global reduction under the operation `op` of the input data in
     /// `sendbuf` and scatters the result into equal sized blocks in the receive buffers on all
     /// processes.
@@ -229,6 +624,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: BufferMut,
         O: Operation,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "reduce_scatter_block_into"));
         assert_eq!(recvbuf.count() * self.size(), sendbuf.count());
         unsafe {
             ffi::MPI_Reduce_scatter_block(
@@ -242,6 +638,41 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Performs an element-wise global reduction under the operation `op` of the input data in
+    /// `sendbuf` and scatters the result into segments of different sizes, so that every process
+    /// receives the reduction of its own segment in `recvbuf`.
+    ///
+    /// `sendbuf` has one partition per process in rank order, which follow each other without
+    /// gaps, e.g. a `Partition::segments()`. `recvbuf` holds as many elements as the partition of
+    /// the calling process. All processes have to pass the same partitioning.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/reduce_scatter.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.10.1
+    fn reduce_scatter_into<S: ?Sized, R: ?Sized, O>(&self, sendbuf: &S, recvbuf: &mut R, op: O)
+    where
+        S: PartitionedBuffer,
+        R: BufferMut,
+        O: Operation,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "reduce_scatter_into"));
+        check_segments(self, sendbuf, recvbuf.count());
+        unsafe {
+            ffi::MPI_Reduce_scatter(
+                sendbuf.pointer(),
+                recvbuf.pointer_mut(),
+                sendbuf.counts().as_ptr(),
+                sendbuf.as_datatype().as_raw(),
+                op.as_raw(),
+                self.as_raw(),
+            );
+        }
+    }
+
     /// Performs a global inclusive prefix reduction of the data in `sendbuf` into `recvbuf` under
     /// operation `op`.
     ///
@@ -258,6 +689,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: BufferMut,
         O: Operation,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "scan_into"));
         unsafe {
             ffi::MPI_Scan(
                 sendbuf.pointer(),
@@ -286,6 +718,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: BufferMut,
         O: Operation,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "exclusive_scan_into"));
         unsafe {
             ffi::MPI_Exscan(
                 sendbuf.pointer(),
@@ -311,6 +744,7 @@ pub trait CommunicatorCollectives: Communicator {
     ///
     /// 5.12.1
     fn immediate_barrier(&self) -> Request<'static> {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "immediate_barrier"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| ffi::MPI_Ibarrier(self.as_raw(), request)).1,
@@ -340,6 +774,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "immediate_all_gather_into"));
         unsafe {
             let recvcount = recvbuf.count() / self.size();
             Request::from_raw(
@@ -382,6 +817,8 @@ pub trait CommunicatorCollectives: Communicator {
         R: 'a + PartitionedBufferMut,
         Sc: Scope<'a>,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "immediate_all_gather_varcount_into"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -423,6 +860,7 @@ pub trait CommunicatorCollectives: Communicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "immediate_all_to_all_into"));
         let c_size = self.size();
         unsafe {
             Request::from_raw(
@@ -460,6 +898,8 @@ pub trait CommunicatorCollectives: Communicator {
         R: 'a + PartitionedBufferMut,
         Sc: Scope<'a>,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "immediate_all_to_all_varcount_into"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -505,6 +945,7 @@ pub trait CommunicatorCollectives: Communicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "immediate_all_reduce_into"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -524,6 +965,41 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Performs a global reduction under the operation `op` of the input data in `sendbuf` into
+    /// `recvbuf` on all processes while the calling process runs `compute`.
+    ///
+    /// Starts a non-blocking reduction, calls `compute()` and waits for the reduction to complete
+    /// before returning the result of `compute()`. The buffers are borrowed until the reduction
+    /// is complete, so `compute` cannot touch them.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_overlapped.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.12.8
+    fn all_reduce_overlapped<S: ?Sized, R: ?Sized, O, F, T>(
+        &self,
+        sendbuf: &S,
+        recvbuf: &mut R,
+        op: O,
+        compute: F,
+    ) -> T
+    where
+        S: Buffer,
+        R: BufferMut,
+        O: Operation,
+        F: FnOnce() -> T,
+    {
+        request::scope(|scope| {
+            let reduction = self.immediate_all_reduce_into(scope, sendbuf, recvbuf, op);
+            let result = compute();
+            reduction.wait();
+            result
+        })
+    }
+
     /// Initiates a non-blocking element-wise global reduction under the operation `op` of the
     /// input data in `sendbuf` and scatters the result into equal sized blocks in the receive
     /// buffers on all processes.
@@ -548,6 +1024,8 @@ pub trait CommunicatorCollectives: Communicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "immediate_reduce_scatter_block_into"));
         assert_eq!(recvbuf.count() * self.size(), sendbuf.count());
         unsafe {
             Request::from_raw(
@@ -568,6 +1046,54 @@ pub trait CommunicatorCollectives: Communicator {
         }
     }
 
+    /// Initiates a non-blocking element-wise global reduction under the operation `op` of the
+    /// input data in `sendbuf` and scatters the result into segments of different sizes, so that
+    /// every process receives the reduction of its own segment in `recvbuf`.
+    ///
+    /// See `reduce_scatter_into()` for the partitioning of `sendbuf`.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/reduce_scatter.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.12.9
+    fn immediate_reduce_scatter_into<'a, Sc, S: ?Sized, R: ?Sized, O>(
+        &self,
+        scope: Sc,
+        sendbuf: &'a S,
+        recvbuf: &'a mut R,
+        op: O,
+    ) -> Request<'a, Sc>
+    where
+        S: 'a + PartitionedBuffer,
+        R: 'a + BufferMut,
+        O: 'a + Operation,
+        Sc: Scope<'a>,
+    {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "immediate_reduce_scatter_into"));
+        check_segments(self, sendbuf, recvbuf.count());
+        unsafe {
+            Request::from_raw(
+                with_uninitialized(|request| {
+                    ffi::MPI_Ireduce_scatter(
+                        sendbuf.pointer(),
+                        recvbuf.pointer_mut(),
+                        sendbuf.counts().as_ptr(),
+                        sendbuf.as_datatype().as_raw(),
+                        op.as_raw(),
+                        self.as_raw(),
+                        request,
+                    )
+                })
+                .1,
+                scope,
+            )
+        }
+    }
+
     /// Initiates a non-blocking global inclusive prefix reduction of the data in `sendbuf` into
     /// `recvbuf` under operation `op`.
     ///
@@ -591,6 +1117,7 @@ pub trait CommunicatorCollectives: Communicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "immediate_scan_into"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -633,6 +1160,8 @@ pub trait CommunicatorCollectives: Communicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_raw(), "immediate_exclusive_scan_into"));
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -651,6 +1180,45 @@ pub trait CommunicatorCollectives: Communicator {
             )
         }
     }
+
+    /// The calling process in the role of the root of a rooted collective operation
+    ///
+    /// The returned `RootProcess` only offers the root side of the rooted collective operations,
+    /// e.g. its `gather_into()` takes the receive buffer that is only significant on the root.
+    /// The other processes have to take part in the operation through `at_root()`.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/rooted_roles.rs`
+    fn as_root(&self) -> RootProcess<Self>
+    where
+        Self: Sized,
+    {
+        RootProcess(self.this_process())
+    }
+
+    /// The role of the calling process in a rooted collective operation with root `rank`
+    ///
+    /// Returns `Role::Root` on process `rank` and `Role::NonRoot` on all other processes. The two
+    /// roles offer different method sets, so the buffers that are only significant on the root
+    /// cannot be passed on the other processes and vice versa.
+    ///
+    /// Panics if `rank` is not a valid rank in the communicator.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/rooted_roles.rs`
+    fn at_root(&self, rank: Rank) -> Role<Self>
+    where
+        Self: Sized,
+    {
+        let root = self.process_at_rank(rank);
+        if self.rank() == rank {
+            Role::Root(RootProcess(root))
+        } else {
+            Role::NonRoot(NonRootProcess(root))
+        }
+    }
 }
 
 impl<C: Communicator> CommunicatorCollectives for C {}
@@ -679,6 +1247,8 @@ pub trait Root: AsCommunicator {
     where
         Buf: BufferMut,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "broadcast_into"));
         unsafe {
             ffi::MPI_Bcast(
                 buffer.pointer_mut(),
@@ -710,6 +1280,8 @@ pub trait Root: AsCommunicator {
     where
         S: Buffer,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "gather_into"));
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Gather(
@@ -746,6 +1318,8 @@ pub trait Root: AsCommunicator {
         S: Buffer,
         R: BufferMut,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "gather_into_root"));
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             let recvcount = recvbuf.count() / self.as_communicator().size();
@@ -783,6 +1357,9 @@ pub trait Root: AsCommunicator {
     where
         S: Buffer,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "gather_varcount_into")
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Gatherv(
@@ -821,6 +1398,9 @@ pub trait Root: AsCommunicator {
         S: Buffer,
         R: PartitionedBufferMut,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "gather_varcount_into_root")
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Gatherv(
@@ -857,6 +1437,8 @@ pub trait Root: AsCommunicator {
     where
         R: BufferMut,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "scatter_into"));
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Scatter(
@@ -893,6 +1475,8 @@ pub trait Root: AsCommunicator {
         S: Buffer,
         R: BufferMut,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "scatter_into_root"));
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         let sendcount = sendbuf.count() / self.as_communicator().size();
         unsafe {
@@ -930,6 +1514,9 @@ pub trait Root: AsCommunicator {
     where
         R: BufferMut,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_varcount_into")
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Scatterv(
@@ -968,6 +1555,12 @@ pub trait Root: AsCommunicator {
         S: PartitionedBuffer,
         R: BufferMut,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "scatter_varcount_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Scatterv(
@@ -1001,6 +1594,8 @@ pub trait Root: AsCommunicator {
         S: Buffer,
         O: Operation,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "reduce_into"));
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Reduce(
@@ -1033,6 +1628,8 @@ pub trait Root: AsCommunicator {
         R: BufferMut,
         O: Operation,
     {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "reduce_into_root"));
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             ffi::MPI_Reduce(
@@ -1065,6 +1662,9 @@ pub trait Root: AsCommunicator {
         Buf: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "immediate_broadcast_into")
+        });
         unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
@@ -1099,6 +1699,9 @@ pub trait Root: AsCommunicator {
         S: 'a + Buffer,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "immediate_gather_into")
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1143,6 +1746,12 @@ pub trait Root: AsCommunicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_gather_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             let recvcount = recvbuf.count() / self.as_communicator().size();
@@ -1186,6 +1795,12 @@ pub trait Root: AsCommunicator {
         S: 'a + Buffer,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_gather_varcount_into",
+            )
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1231,6 +1846,12 @@ pub trait Root: AsCommunicator {
         R: 'a + PartitionedBufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_gather_varcount_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1274,6 +1895,9 @@ pub trait Root: AsCommunicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "immediate_scatter_into")
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1318,6 +1942,12 @@ pub trait Root: AsCommunicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_scatter_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             let sendcount = sendbuf.count() / self.as_communicator().size();
@@ -1361,6 +1991,12 @@ pub trait Root: AsCommunicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_scatter_varcount_into",
+            )
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1406,6 +2042,12 @@ pub trait Root: AsCommunicator {
         R: 'a + BufferMut,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_scatter_varcount_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1452,6 +2094,9 @@ pub trait Root: AsCommunicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "immediate_reduce_into")
+        });
         assert_ne!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1498,6 +2143,12 @@ pub trait Root: AsCommunicator {
         O: 'a + Operation,
         Sc: Scope<'a>,
     {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "immediate_reduce_into_root",
+            )
+        });
         assert_eq!(self.as_communicator().rank(), self.root_rank());
         unsafe {
             Request::from_raw(
@@ -1518,182 +2169,1917 @@ pub trait Root: AsCommunicator {
             )
         }
     }
-}
-
-impl<'a, C: 'a + Communicator> Root for Process<'a, C> {
-    fn root_rank(&self) -> Rank {
-        self.rank()
-    }
-}
 
-/// An operation to be used in a reduction or scan type operation, e.g. `MPI_SUM`
-pub trait Operation: AsRaw<Raw = MPI_Op> {
-    /// Returns whether the operation is commutative.
+    /// Scatter the tiles of a matrix from `Root` to all processes.
+    ///
+    /// Receives the tile of the calling process in the `TileLayout` of the root process into
+    /// `recvbuf` as a contiguous row-major array. `recvbuf` must hold exactly the elements of the
+    /// tile.
+    ///
+    /// This function must be called on all non-root processes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/scatter_tiles.rs`
     ///
     /// # Standard section(s)
     ///
-    /// 5.9.7
-    fn is_commutative(&self) -> bool {
+    /// 4.1.3, 5.8
+    fn scatter_tiles_into<T>(&self, recvbuf: &mut [T])
+    where
+        T: Equivalence,
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_tiles_into")
+        });
+        assert_ne!(self.as_communicator().rank(), self.root_rank());
+        let size = to_usize(self.as_communicator().size());
+        let datatype = T::equivalent_datatype().as_raw();
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(self.root_rank())] = recvbuf.count();
         unsafe {
-            let mut commute = 0;
-            ffi::MPI_Op_commutative(self.as_raw(), &mut commute);
-            commute != 0
+            all_to_all_w(
+                self.as_communicator(),
+                ptr::null(),
+                &zeros,
+                &zeros,
+                &vec![datatype; size],
+                recvbuf.pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![datatype; size],
+            );
         }
     }
-}
-impl<'a, T: 'a + Operation> Operation for &'a T {}
-
-/// A built-in operation like `MPI_SUM`
-///
-/// # Examples
-///
-/// See `examples/reduce.rs`
-///
-/// # Standard section(s)
-///
-/// 5.9.2
-#[derive(Copy, Clone)]
-pub struct SystemOperation(MPI_Op);
-
-macro_rules! system_operation_constructors {
-    ($($ctor:ident => $val:path),*) => (
-        $(pub fn $ctor() -> SystemOperation {
-            //! A built-in operation
-            SystemOperation(unsafe { $val })
-        })*
-    )
-}
-
-impl SystemOperation {
-    system_operation_constructors! {
-        max => ffi::RSMPI_MAX,
-        min => ffi::RSMPI_MIN,
-        sum => ffi::RSMPI_SUM,
-        product => ffi::RSMPI_PROD,
-        logical_and => ffi::RSMPI_LAND,
-        bitwise_and => ffi::RSMPI_BAND,
-        logical_or => ffi::RSMPI_LOR,
-        bitwise_or => ffi::RSMPI_BOR,
-        logical_xor => ffi::RSMPI_LXOR,
-        bitwise_xor => ffi::RSMPI_BXOR
-    }
-}
-
-unsafe impl AsRaw for SystemOperation {
-    type Raw = MPI_Op;
-    fn as_raw(&self) -> Self::Raw {
-        self.0
-    }
-}
-
-impl Operation for SystemOperation {}
-
-trait Erased {}
-
-impl<T> Erased for T {}
-
-/// A user-defined operation.
-///
-/// The lifetime `'a` of the operation is limited by the lifetime of the underlying closure.
-///
-/// For safety reasons, `UserOperation` is in of itself not considered an `Operation`, but a
-/// reference of it is.  This limitation may be lifted in the future when `Request` objects can
-/// store finalizers.
-///
-/// **Note:** When a `UserOperation` is passed to a non-blocking API call, it must outlive the
-/// completion of the request.  This is normally enforced by the safe API, so this is only a concern
-/// if you use the unsafe API.  Do not rely on MPI's internal reference-counting here, because once
-/// `UserOperation` is destroyed, the closure object will be deallocated even if the `MPI_Op` handle
-/// is still alive due to outstanding references.
-///
-/// # Examples
-///
-/// See `examples/reduce.rs` and `examples/immediate_reduce.rs`
-#[cfg(feature = "user-operations")]
-pub struct UserOperation<'a> {
-    op: MPI_Op,
-    _anchor: Box<dyn Erased + 'a>, // keeps the internal data alive
-}
 
-#[cfg(feature = "user-operations")]
-impl<'a> fmt::Debug for UserOperation<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_tuple("UserOperation").field(&self.op).finish()
-    }
-}
+    /// Scatter the tiles of a matrix from `Root` to all processes.
+    ///
+    /// Sends tile `r` of the row-major matrix `sendbuf` partitioned according to `layout` to the
+    /// process of rank `r` and receives the tile of the root process into `recvbuf`. The layout
+    /// must have one tile per process and the tiles can differ in shape.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/scatter_tiles.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.3, 5.8
+    fn scatter_tiles_into_root<T>(&self, sendbuf: &[T], layout: &TileLayout, recvbuf: &mut [T])
+    where
+        T: Equivalence,
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_tiles_into_root")
+        });
+        let root = self.root_rank();
+        assert_eq!(self.as_communicator().rank(), root);
+        let size = to_usize(self.as_communicator().size());
+        assert_eq!(
+            layout.num_tiles(),
+            size,
+            "The tile layout must have one tile per process."
+        );
+        assert_eq!(
+            sendbuf.len(),
+            layout.len(),
+            "The matrix does not match the shape {:?} of the tile layout.",
+            layout.shape()
+        );
+        assert_eq!(
+            recvbuf.len(),
+            layout.tile_len(root),
+            "The receive buffer does not match the tile shape {:?} of the root process.",
+            layout.tile_shape(root)
+        );
 
-#[cfg(feature = "user-operations")]
-impl<'a> Drop for UserOperation<'a> {
-    fn drop(&mut self) {
+        // Empty tiles, e.g. of an even layout of fewer rows than row bands, have no subarray
+        // datatype and are sent as zero elements instead.
+        let tiles: Vec<Option<UserDatatype>> = (0..self.as_communicator().size())
+            .map(|rank| {
+                if layout.tile_len(rank) == 0 {
+                    None
+                } else {
+                    Some(layout.tile_datatype::<T>(rank))
+                }
+            })
+            .collect();
+        let datatype = T::equivalent_datatype().as_raw();
+        let sendtypes: Vec<MPI_Datatype> = tiles
+            .iter()
+            .map(|tile| tile.as_ref().map_or(datatype, |tile| tile.as_raw()))
+            .collect();
+        let sendcounts: Vec<Count> = tiles
+            .iter()
+            .map(|tile| if tile.is_some() { 1 } else { 0 })
+            .collect();
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(root)] = recvbuf.count();
         unsafe {
-            ffi::MPI_Op_free(&mut self.op);
+            all_to_all_w(
+                self.as_communicator(),
+                sendbuf.pointer(),
+                &sendcounts,
+                &zeros,
+                &sendtypes,
+                recvbuf.pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![datatype; size],
+            );
         }
     }
-}
-
-#[cfg(feature = "user-operations")]
-unsafe impl<'a> AsRaw for UserOperation<'a> {
-    type Raw = MPI_Op;
-    fn as_raw(&self) -> Self::Raw {
-        self.op
-    }
-}
-
-#[cfg(feature = "user-operations")]
-impl<'a, 'b> Operation for &'b UserOperation<'a> {}
 
-#[cfg(feature = "user-operations")]
-impl<'a> UserOperation<'a> {
-    /// Define an operation using a closure.  The operation must be associative.
+    /// Scatter the columns of a matrix from `Root` to all processes round-robin.
     ///
-    /// This is a more readable shorthand for the `new` method.  Refer to [`new`](#method.new) for
-    /// more information.
-    pub fn associative<F>(function: F) -> Self
+    /// Receives the columns of the calling process into `recvbuf` one after another, each as a
+    /// contiguous array of the elements of the column. See `scatter_columns_into_root()`.
+    /// `recvbuf` must hold exactly the elements of these columns.
+    ///
+    /// This function must be called on all non-root processes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/scatter_columns.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2, 4.1.7, 5.8
+    fn scatter_columns_into<T>(&self, recvbuf: &mut [T])
     where
-        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+        T: Equivalence,
     {
-        Self::new(false, function)
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_columns_into")
+        });
+        assert_ne!(self.as_communicator().rank(), self.root_rank());
+        let size = to_usize(self.as_communicator().size());
+        let datatype = T::equivalent_datatype().as_raw();
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(self.root_rank())] = recvbuf.count();
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                ptr::null(),
+                &zeros,
+                &zeros,
+                &vec![datatype; size],
+                recvbuf.pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![datatype; size],
+            );
+        }
     }
 
-    /// Define an operation using a closure.  The operation must be both associative and
-    /// commutative.
+    /// Scatter the columns of a matrix from `Root` to all processes round-robin.
     ///
-    /// This is a more readable shorthand for the `new` method.  Refer to [`new`](#method.new) for
-    /// more information.
-    pub fn commutative<F>(function: F) -> Self
+    /// `sendbuf` is a row-major matrix of `ncols` columns. Column `j` is sent to the process of
+    /// rank `j % size`, so process `r` receives `columns_of_rank(ncols, size, r)` columns. Every
+    /// process, including the root process, receives its columns into `recvbuf` one after
+    /// another, each as a contiguous array of the elements of the column.
+    ///
+    /// The columns are described by a vector datatype resized to the extent of a single element,
+    /// so that consecutive columns of a process are `size` elements apart, and moved without
+    /// copying them on the root process.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/scatter_columns.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2, 4.1.7, 5.8
+    fn scatter_columns_into_root<T>(&self, sendbuf: &[T], ncols: Count, recvbuf: &mut [T])
     where
-        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+        T: Equivalence,
     {
-        Self::new(true, function)
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_columns_into_root")
+        });
+        let root = self.root_rank();
+        assert_eq!(self.as_communicator().rank(), root);
+        assert!(ncols > 0, "A matrix needs at least one column.");
+        assert_eq!(
+            sendbuf.len() % to_usize(ncols),
+            0,
+            "A matrix of {} elements cannot have {} columns.",
+            sendbuf.len(),
+            ncols
+        );
+        let rows: Count = (sendbuf.len() / to_usize(ncols))
+            .value_as()
+            .expect("Number of rows cannot be expressed as an MPI Count.");
+        let size = self.as_communicator().size();
+        assert_eq!(
+            recvbuf.len(),
+            to_usize(rows) * to_usize(columns_of_rank(ncols, size, root)),
+            "The receive buffer does not match the columns of the root process."
+        );
+
+        let element_size: Count = mem::size_of::<T>()
+            .value_as()
+            .expect("Size of element type cannot be expressed as an MPI Count.");
+        let element = T::equivalent_datatype();
+        let column = UncommittedUserDatatype::vector(rows, 1, ncols, &element);
+        let extent: Address = element_size
+            .value_as()
+            .expect("Size of element type cannot be expressed as an MPI Address.");
+        let column = UncommittedUserDatatype::resized(&column, 0, extent);
+        let columns: Vec<UserDatatype> = (0..size)
+            .map(|rank| UserDatatype::vector(columns_of_rank(ncols, size, rank), 1, size, &column))
+            .collect();
+        let sendtypes: Vec<MPI_Datatype> = columns.iter().map(|columns| columns.as_raw()).collect();
+        let sendcounts: Vec<Count> = (0..size)
+            .map(|rank| if rank < ncols { 1 } else { 0 })
+            .collect();
+        let sdispls: Vec<Count> = (0..size)
+            .map(|rank| {
+                rank.checked_mul(element_size)
+                    .expect("Displacement of a column cannot be expressed as an MPI Count.")
+            })
+            .collect();
+        let size = to_usize(size);
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(root)] = recvbuf.count();
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                sendbuf.pointer(),
+                &sendcounts,
+                &sdispls,
+                &sendtypes,
+                recvbuf.pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![element.as_raw(); size],
+            );
+        }
     }
 
-    /// Creates an associative and possibly commutative operation using a closure.
+    /// Gather the fields of a struct of arrays from all processes into an array of structs on
+    /// `Root`.
     ///
-    /// The closure receives two arguments `invec` and `inoutvec` as dynamically typed buffers.  It
-    /// shall set `inoutvec` to the value of `f(invec, inoutvec)`, where `f` is a binary associative
-    /// operation.
+    /// `fields` holds one buffer per field of `layout` in the order in which the fields were added
+    /// to the layout. See `gather_soa_into_root()`.
     ///
-    /// If the operation is also commutative, setting `commute` to `true` may yield performance
-    /// benefits.
+    /// This function must be called on all non-root processes.
     ///
-    /// **Note:** If the closure panics, the entire program will abort.
+    /// # Examples
+    ///
+    /// See `examples/struct_of_arrays.rs`
     ///
     /// # Standard section(s)
     ///
-    /// 5.9.5
-    pub fn new<F>(commute: bool, function: F) -> Self
+    /// 4.1.2, 5.5, 5.8
+    fn gather_soa_into<T>(&self, layout: &SoaLayout<T>, fields: &[DynBuffer])
     where
-        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+        T: Copy,
     {
-        struct ClosureAnchor<F> {
-            rust_closure: F,
-            ffi_closure: Option<Closure<'static>>,
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "gather_soa_into"));
+        assert_ne!(self.as_communicator().rank(), self.root_rank());
+        let len = layout.structs_in(fields);
+        self.gather_into(&len);
+
+        let size = to_usize(self.as_communicator().size());
+        let datatype = fields_datatype(fields);
+        let zeros: Vec<Count> = vec![0; size];
+        let mut sendcounts = zeros.clone();
+        sendcounts[to_usize(self.root_rank())] = 1;
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                fields[0].pointer(),
+                &sendcounts,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+                ptr::null_mut(),
+                &zeros,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+            );
         }
+    }
 
-        // must box it to prevent moves
-        let mut anchor = Box::new(ClosureAnchor {
-            rust_closure: function,
-            ffi_closure: None,
+    /// Gather the fields of a struct of arrays from all processes into an array of structs on
+    /// `Root`.
+    ///
+    /// Every process holds a number of structs of type `T` as one buffer per field of `layout`.
+    /// The structs of all processes are concatenated in rank order in `recvbuf`, which must hold
+    /// exactly the structs of all processes. The fields are received directly at their offsets
+    /// within the structs, so no transpose of the gathered data is needed.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/struct_of_arrays.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2, 5.5, 5.8
+    fn gather_soa_into_root<T>(
+        &self,
+        layout: &SoaLayout<T>,
+        fields: &[DynBuffer],
+        recvbuf: &mut [T],
+    ) where
+        T: Copy,
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "gather_soa_into_root")
+        });
+        let root = self.root_rank();
+        assert_eq!(self.as_communicator().rank(), root);
+        let len = layout.structs_in(fields);
+        let size = to_usize(self.as_communicator().size());
+        let mut counts: Vec<Count> = vec![0; size];
+        self.gather_into_root(&len, &mut counts[..]);
+        let displs = counts::displacements(&counts);
+        assert_eq!(
+            recvbuf.len(),
+            to_usize(counts.iter().sum()),
+            "The receive buffer does not match the number of structs of all processes."
+        );
+
+        let datatype = fields_datatype(fields);
+        let structs: Vec<UserDatatype> = counts
+            .iter()
+            .zip(&displs)
+            .map(|(&count, &displ)| layout.datatype(displ, count))
+            .collect();
+        let recvtypes: Vec<MPI_Datatype> = structs.iter().map(|s| s.as_raw()).collect();
+        let zeros: Vec<Count> = vec![0; size];
+        let mut sendcounts = zeros.clone();
+        sendcounts[to_usize(root)] = 1;
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                fields[0].pointer(),
+                &sendcounts,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+                recvbuf.as_mut_ptr() as *mut c_void,
+                &vec![1; size],
+                &zeros,
+                &recvtypes,
+            );
+        }
+    }
+
+    /// Scatter an array of structs from `Root` into the fields of a struct of arrays on all
+    /// processes.
+    ///
+    /// `fields` holds one buffer per field of `layout` in the order in which the fields were added
+    /// to the layout. See `scatter_soa_into_root()`.
+    ///
+    /// This function must be called on all non-root processes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/struct_of_arrays.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2, 5.6, 5.8
+    fn scatter_soa_into<T>(&self, layout: &SoaLayout<T>, fields: &mut [DynBufferMut])
+    where
+        T: Copy,
+    {
+        let _call =
+            hooks::enter(|| Call::collective(self.as_communicator().as_raw(), "scatter_soa_into"));
+        assert_ne!(self.as_communicator().rank(), self.root_rank());
+        let len = layout.structs_in(fields);
+        self.gather_into(&len);
+
+        let size = to_usize(self.as_communicator().size());
+        let datatype = fields_datatype(fields);
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(self.root_rank())] = 1;
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                ptr::null(),
+                &zeros,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+                fields[0].pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+            );
+        }
+    }
+
+    /// Scatter an array of structs from `Root` into the fields of a struct of arrays on all
+    /// processes.
+    ///
+    /// The number of structs received by every process is given by the length of its `fields`,
+    /// the structs are taken from `sendbuf` in rank order. `sendbuf` must hold exactly the structs
+    /// of all processes. The fields are sent directly from their offsets within the structs, so no
+    /// transpose of the data is needed before the scatter.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/struct_of_arrays.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2, 5.6, 5.8
+    fn scatter_soa_into_root<T>(
+        &self,
+        layout: &SoaLayout<T>,
+        sendbuf: &[T],
+        fields: &mut [DynBufferMut],
+    ) where
+        T: Copy,
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "scatter_soa_into_root")
+        });
+        let root = self.root_rank();
+        assert_eq!(self.as_communicator().rank(), root);
+        let len = layout.structs_in(fields);
+        let size = to_usize(self.as_communicator().size());
+        let mut counts: Vec<Count> = vec![0; size];
+        self.gather_into_root(&len, &mut counts[..]);
+        let displs = counts::displacements(&counts);
+        assert_eq!(
+            sendbuf.len(),
+            to_usize(counts.iter().sum()),
+            "The send buffer does not match the number of structs of all processes."
+        );
+
+        let datatype = fields_datatype(fields);
+        let structs: Vec<UserDatatype> = counts
+            .iter()
+            .zip(&displs)
+            .map(|(&count, &displ)| layout.datatype(displ, count))
+            .collect();
+        let sendtypes: Vec<MPI_Datatype> = structs.iter().map(|s| s.as_raw()).collect();
+        let zeros: Vec<Count> = vec![0; size];
+        let mut recvcounts = zeros.clone();
+        recvcounts[to_usize(root)] = 1;
+        unsafe {
+            all_to_all_w(
+                self.as_communicator(),
+                sendbuf.as_ptr() as *const c_void,
+                &vec![1; size],
+                &zeros,
+                &sendtypes,
+                fields[0].pointer_mut(),
+                &recvcounts,
+                &zeros,
+                &vec![datatype.as_raw(); size],
+            );
+        }
+    }
+
+    /// Stream the contents of `sendbuf` to `Root`, which hands it to a callback.
+    ///
+    /// See `gather_streaming_into_root()`.
+    ///
+    /// This function must be called on all non-root processes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/gather_streaming.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.4, 3.8
+    fn gather_streaming_into<T>(&self, sendbuf: &[T])
+    where
+        T: Equivalence,
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(self.as_communicator().as_raw(), "gather_streaming_into")
+        });
+        assert_ne!(self.as_communicator().rank(), self.root_rank());
+        let comm = private::duplicate(self.as_communicator());
+        comm.process_at_rank(self.root_rank())
+            .synchronous_send(sendbuf);
+    }
+
+    /// Gather the contents of `sendbuf` from all processes on `Root` one process at a time.
+    ///
+    /// Instead of materializing the contributions of all processes in one receive buffer,
+    /// `handler` is called with the rank and the contribution of every process in rank order,
+    /// e.g. to write results to disk that would not fit into the memory of the root process. The
+    /// contributions can differ in length. Only one contribution is held in memory at a time and
+    /// the other processes block in a synchronous send until the root process is ready to receive
+    /// their contribution.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/gather_streaming.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.4, 3.8
+    fn gather_streaming_into_root<T, F>(&self, sendbuf: &[T], handler: F)
+    where
+        T: Equivalence,
+        F: FnMut(Rank, &[T]),
+    {
+        self.gather_streaming_into_root_windowed(sendbuf, 1, handler)
+    }
+
+    /// Gather the contents of `sendbuf` from all processes on `Root` in windows of `window`
+    /// processes.
+    ///
+    /// Like `gather_streaming_into_root()`, but the contributions of the processes of a window
+    /// of consecutive ranks are received in the order they arrive. Once the whole window has been
+    /// received, `handler` is called with the rank and the contribution of every process of the
+    /// window in rank order. At most `window` contributions are held in memory at a time.
+    ///
+    /// The non-root processes call `gather_streaming_into()`. This function must be called on the
+    /// root process.
+    ///
+    /// Panics if `window` is zero.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/gather_streaming.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.4, 3.8
+    fn gather_streaming_into_root_windowed<T, F>(
+        &self,
+        sendbuf: &[T],
+        window: usize,
+        mut handler: F,
+    ) where
+        T: Equivalence,
+        F: FnMut(Rank, &[T]),
+    {
+        let _call = hooks::enter(|| {
+            Call::collective(
+                self.as_communicator().as_raw(),
+                "gather_streaming_into_root_windowed",
+            )
+        });
+        let root = self.root_rank();
+        assert_eq!(self.as_communicator().rank(), root);
+        assert!(window > 0, "A window must hold at least one contribution.");
+        let comm = private::duplicate(self.as_communicator());
+        let ranks: Vec<Rank> = (0..comm.size()).collect();
+        for ranks in ranks.chunks(window) {
+            let mut contributions: Vec<Option<Vec<T>>> = ranks.iter().map(|_| None).collect();
+            let mut pending = ranks.iter().filter(|&&rank| rank != root).count();
+            while pending > 0 {
+                for (contribution, &rank) in contributions.iter_mut().zip(ranks) {
+                    if rank == root || contribution.is_some() {
+                        continue;
+                    }
+                    let process = comm.process_at_rank(rank);
+                    // Wait for the last contribution of the window instead of polling for it.
+                    let message = if pending == 1 {
+                        Some(process.matched_probe())
+                    } else {
+                        process.immediate_matched_probe()
+                    };
+                    if let Some((message, _)) = message {
+                        *contribution = Some(message.matched_receive_vec().0);
+                        pending -= 1;
+                    }
+                }
+            }
+            for (contribution, &rank) in contributions.iter().zip(ranks) {
+                match *contribution {
+                    Some(ref contribution) => handler(rank, contribution),
+                    None => handler(rank, sendbuf),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, C: 'a + Communicator> Root for Process<'a, C> {
+    fn root_rank(&self) -> Rank {
+        self.rank()
+    }
+}
+
+/// The role of the calling process in a rooted collective operation
+///
+/// Returned by `CommunicatorCollectives::at_root()`.
+pub enum Role<'a, C>
+where
+    C: 'a + Communicator,
+{
+    /// The calling process is the root
+    Root(RootProcess<'a, C>),
+    /// The calling process is not the root
+    NonRoot(NonRootProcess<'a, C>),
+}
+
+impl<'a, C> Role<'a, C>
+where
+    C: 'a + Communicator,
+{
+    /// Rank of the root process
+    pub fn root_rank(&self) -> Rank {
+        match *self {
+            Role::Root(ref root) => root.root_rank(),
+            Role::NonRoot(ref non_root) => non_root.root_rank(),
+        }
+    }
+
+    /// Whether the calling process is the root
+    pub fn is_root(&self) -> bool {
+        match *self {
+            Role::Root(_) => true,
+            Role::NonRoot(_) => false,
+        }
+    }
+
+    /// Broadcast of the contents of a buffer from the root process
+    ///
+    /// Broadcasting looks the same on all processes, so it is offered without matching on the
+    /// role. See `Root::broadcast_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    pub fn broadcast_into<Buf: ?Sized>(&self, buffer: &mut Buf)
+    where
+        Buf: BufferMut,
+    {
+        match *self {
+            Role::Root(ref root) => root.broadcast_into(buffer),
+            Role::NonRoot(ref non_root) => non_root.broadcast_into(buffer),
+        }
+    }
+}
+
+/// The calling process acting as the root of rooted collective operations
+///
+/// Offers the root side of the operations of `Root`, i.e. the `_root` variants, under the names of
+/// the operations. Obtained via `CommunicatorCollectives::as_root()` or
+/// `CommunicatorCollectives::at_root()`.
+///
+/// # Examples
+///
+/// See `examples/rooted_roles.rs`
+pub struct RootProcess<'a, C>(Process<'a, C>)
+where
+    C: 'a + Communicator;
+
+impl<'a, C> RootProcess<'a, C>
+where
+    C: 'a + Communicator,
+{
+    /// Rank of the root process, the calling process
+    pub fn root_rank(&self) -> Rank {
+        self.0.root_rank()
+    }
+
+    /// Broadcast the contents of `buffer` to all other processes.
+    ///
+    /// See `Root::broadcast_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    pub fn broadcast_into<Buf: ?Sized>(&self, buffer: &mut Buf)
+    where
+        Buf: BufferMut,
+    {
+        self.0.broadcast_into(buffer)
+    }
+
+    /// Gather `sendbuf` of all processes into `recvbuf`.
+    ///
+    /// See `Root::gather_into_root()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5
+    pub fn gather_into<S: ?Sized, R: ?Sized>(&self, sendbuf: &S, recvbuf: &mut R)
+    where
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.0.gather_into_root(sendbuf, recvbuf)
+    }
+
+    /// Gather `sendbuf` of all processes into the partitions of `recvbuf`.
+    ///
+    /// See `Root::gather_varcount_into_root()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5
+    pub fn gather_varcount_into<S: ?Sized, R: ?Sized>(&self, sendbuf: &S, recvbuf: &mut R)
+    where
+        S: Buffer,
+        R: PartitionedBufferMut,
+    {
+        self.0.gather_varcount_into_root(sendbuf, recvbuf)
+    }
+
+    /// Scatter the contents of `sendbuf` to all processes, receiving the own part in `recvbuf`.
+    ///
+    /// See `Root::scatter_into_root()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.6
+    pub fn scatter_into<S: ?Sized, R: ?Sized>(&self, sendbuf: &S, recvbuf: &mut R)
+    where
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.0.scatter_into_root(sendbuf, recvbuf)
+    }
+
+    /// Scatter the partitions of `sendbuf` to all processes, receiving the own partition in
+    /// `recvbuf`.
+    ///
+    /// See `Root::scatter_varcount_into_root()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.6
+    pub fn scatter_varcount_into<S: ?Sized, R: ?Sized>(&self, sendbuf: &S, recvbuf: &mut R)
+    where
+        S: PartitionedBuffer,
+        R: BufferMut,
+    {
+        self.0.scatter_varcount_into_root(sendbuf, recvbuf)
+    }
+
+    /// Reduce `sendbuf` of all processes into `recvbuf` using `op`.
+    ///
+    /// See `Root::reduce_into_root()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.1
+    pub fn reduce_into<S: ?Sized, R: ?Sized, O>(&self, sendbuf: &S, recvbuf: &mut R, op: O)
+    where
+        S: Buffer,
+        R: BufferMut,
+        O: Operation,
+    {
+        self.0.reduce_into_root(sendbuf, recvbuf, op)
+    }
+}
+
+impl<'a, C> AsCommunicator for RootProcess<'a, C>
+where
+    C: 'a + Communicator,
+{
+    type Out = C;
+    fn as_communicator(&self) -> &Self::Out {
+        self.0.as_communicator()
+    }
+}
+
+/// The calling process taking part in rooted collective operations with another process as root
+///
+/// Offers the non-root side of the operations of `Root`, which never takes the buffers that are
+/// only significant on the root. Obtained via `CommunicatorCollectives::at_root()`.
+///
+/// # Examples
+///
+/// See `examples/rooted_roles.rs`
+pub struct NonRootProcess<'a, C>(Process<'a, C>)
+where
+    C: 'a + Communicator;
+
+impl<'a, C> NonRootProcess<'a, C>
+where
+    C: 'a + Communicator,
+{
+    /// Rank of the root process
+    pub fn root_rank(&self) -> Rank {
+        self.0.root_rank()
+    }
+
+    /// Receive the contents of `buffer` broadcast by the root process.
+    ///
+    /// See `Root::broadcast_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    pub fn broadcast_into<Buf: ?Sized>(&self, buffer: &mut Buf)
+    where
+        Buf: BufferMut,
+    {
+        self.0.broadcast_into(buffer)
+    }
+
+    /// Contribute `sendbuf` to a gather on the root process.
+    ///
+    /// See `Root::gather_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5
+    pub fn gather_into<S: ?Sized>(&self, sendbuf: &S)
+    where
+        S: Buffer,
+    {
+        self.0.gather_into(sendbuf)
+    }
+
+    /// Contribute `sendbuf` to a variable count gather on the root process.
+    ///
+    /// See `Root::gather_varcount_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5
+    pub fn gather_varcount_into<S: ?Sized>(&self, sendbuf: &S)
+    where
+        S: Buffer,
+    {
+        self.0.gather_varcount_into(sendbuf)
+    }
+
+    /// Receive the own part of a scatter from the root process in `recvbuf`.
+    ///
+    /// See `Root::scatter_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.6
+    pub fn scatter_into<R: ?Sized>(&self, recvbuf: &mut R)
+    where
+        R: BufferMut,
+    {
+        self.0.scatter_into(recvbuf)
+    }
+
+    /// Receive the own partition of a variable count scatter from the root process in `recvbuf`.
+    ///
+    /// See `Root::scatter_varcount_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.6
+    pub fn scatter_varcount_into<R: ?Sized>(&self, recvbuf: &mut R)
+    where
+        R: BufferMut,
+    {
+        self.0.scatter_varcount_into(recvbuf)
+    }
+
+    /// Contribute `sendbuf` to a reduction on the root process using `op`.
+    ///
+    /// See `Root::reduce_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.1
+    pub fn reduce_into<S: ?Sized, O>(&self, sendbuf: &S, op: O)
+    where
+        S: Buffer,
+        O: Operation,
+    {
+        self.0.reduce_into(sendbuf, op)
+    }
+}
+
+impl<'a, C> AsCommunicator for NonRootProcess<'a, C>
+where
+    C: 'a + Communicator,
+{
+    type Out = C;
+    fn as_communicator(&self) -> &Self::Out {
+        self.0.as_communicator()
+    }
+}
+
+/// A reusable partitioning for variable count collective operations
+///
+/// The `_varcount_` collectives take their counts and displacements from a `Partitioned` buffer.
+/// Applications that perform the same variable count collective in every iteration of a loop can
+/// compute the partitioning once, store it in a `CollectivePlan` and re-execute the plan without
+/// allocating and filling new count and displacement vectors on every call.
+///
+/// A plan only stores the partitioning, every execution issues a blocking collective. Plans do not
+/// build persistent collectives like `MPI_Allgatherv_init()`.
+///
+/// # Examples
+///
+/// See `examples/collective_plan.rs`
+///
+/// # Standard section(s)
+///
+/// 5.5, 5.6, 5.7, 5.8
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectivePlan {
+    counts: Vec<Count>,
+    displs: Vec<Count>,
+}
+
+impl CollectivePlan {
+    /// A plan where the partitions of the buffer follow each other without gaps.
+    ///
+    /// Partition `i` contains `counts[i]` elements.
+    pub fn new(counts: Vec<Count>) -> CollectivePlan {
+        let displs = counts::displacements(&counts);
+        CollectivePlan { counts, displs }
+    }
+
+    /// A plan with explicit displacements.
+    ///
+    /// Partition `i` contains `counts[i]` elements starting at element `displs[i]` of the buffer.
+    ///
+    /// Partitions may overlap, e.g. to send the same elements to several processes. Executing a
+    /// plan with overlapping partitions on the receiving side panics, see `PartitionMut::new()`.
+    ///
+    /// Panics if a count or displacement is negative or the end of a partition cannot be
+    /// expressed as a `Count`.
+    pub fn with_displacements(counts: Vec<Count>, displs: Vec<Count>) -> CollectivePlan {
+        if let Err(error) = counts::check_partitions(&counts, &displs) {
+            panic!("Invalid collective plan: {}.", error);
+        }
+        CollectivePlan { counts, displs }
+    }
+
+    /// Build a plan by gathering the local count of elements from all processes in `comm`.
+    ///
+    /// This is a collective operation. The resulting plan is the same on all processes and lays
+    /// out the contributions of all processes contiguously in rank order.
+    pub fn from_all_gather<C>(comm: &C, local_count: Count) -> CollectivePlan
+    where
+        C: Communicator,
+    {
+        let size: usize = comm
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        let mut counts: Vec<Count> = vec![0; size];
+        comm.all_gather_into(&local_count, &mut counts[..]);
+        CollectivePlan::new(counts)
+    }
+
+    /// The count of elements in each partition
+    pub fn counts(&self) -> &[Count] {
+        &self.counts
+    }
+
+    /// The displacement of each partition from the start of the buffer
+    pub fn displs(&self) -> &[Count] {
+        &self.displs
+    }
+
+    /// The count of elements a buffer must at least contain to be partitioned by this plan.
+    pub fn extent(&self) -> Count {
+        // The constructors check that the end of every partition can be expressed as a `Count`.
+        self.counts
+            .iter()
+            .zip(self.displs.iter())
+            .map(|(&c, &d)| {
+                d.checked_add(c)
+                    .expect("rsmpi internal error: collective plan partition end overflows")
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Partition `buf` according to this plan.
+    pub fn partition<'p, 'b, B: ?Sized>(
+        &'p self,
+        buf: &'b B,
+    ) -> Partition<'b, B, &'p [Count], &'p [Count]>
+    where
+        B: 'b + Buffer,
+    {
+        Partition::new(buf, &self.counts[..], &self.displs[..])
+    }
+
+    /// Partition `buf` according to this plan.
+    pub fn partition_mut<'p, 'b, B: ?Sized>(
+        &'p self,
+        buf: &'b mut B,
+    ) -> PartitionMut<'b, B, &'p [Count], &'p [Count]>
+    where
+        B: 'b + BufferMut,
+    {
+        PartitionMut::new(buf, &self.counts[..], &self.displs[..])
+    }
+
+    fn check_size<C: ?Sized + Communicator>(&self, comm: &C) {
+        assert_eq!(
+            self.counts.len(),
+            comm.size()
+                .value_as::<usize>()
+                .expect("Communicator size cannot be expressed as a usize."),
+            "Collective plan does not match the size of the communicator."
+        );
+    }
+
+    /// Execute `all_gather_varcount_into()` with `recvbuf` partitioned by this plan.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.7
+    pub fn all_gather_varcount_into<C, S: ?Sized, R: ?Sized>(
+        &self,
+        comm: &C,
+        sendbuf: &S,
+        recvbuf: &mut R,
+    ) where
+        C: Communicator,
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.check_size(comm);
+        comm.all_gather_varcount_into(sendbuf, &mut self.partition_mut(recvbuf));
+    }
+
+    /// Execute `gather_varcount_into_root()` with `recvbuf` partitioned by this plan.
+    ///
+    /// Must be called on the root process, all other processes call `gather_varcount_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5
+    pub fn gather_varcount_into_root<P, S: ?Sized, R: ?Sized>(
+        &self,
+        root: &P,
+        sendbuf: &S,
+        recvbuf: &mut R,
+    ) where
+        P: Root,
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.check_size(root.as_communicator());
+        root.gather_varcount_into_root(sendbuf, &mut self.partition_mut(recvbuf));
+    }
+
+    /// Execute `scatter_varcount_into_root()` with `sendbuf` partitioned by this plan.
+    ///
+    /// Must be called on the root process, all other processes call `scatter_varcount_into()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.6
+    pub fn scatter_varcount_into_root<P, S: ?Sized, R: ?Sized>(
+        &self,
+        root: &P,
+        sendbuf: &S,
+        recvbuf: &mut R,
+    ) where
+        P: Root,
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.check_size(root.as_communicator());
+        root.scatter_varcount_into_root(&self.partition(sendbuf), recvbuf);
+    }
+
+    /// Execute `all_to_all_varcount_into()` with `sendbuf` partitioned by this plan and `recvbuf`
+    /// partitioned by `recv_plan`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.8
+    pub fn all_to_all_varcount_into<C, S: ?Sized, R: ?Sized>(
+        &self,
+        comm: &C,
+        sendbuf: &S,
+        recv_plan: &CollectivePlan,
+        recvbuf: &mut R,
+    ) where
+        C: Communicator,
+        S: Buffer,
+        R: BufferMut,
+    {
+        self.check_size(comm);
+        recv_plan.check_size(comm);
+        comm.all_to_all_varcount_into(
+            &self.partition(sendbuf),
+            &mut recv_plan.partition_mut(recvbuf),
+        );
+    }
+}
+
+impl Partitioned for CollectivePlan {
+    fn counts(&self) -> &[Count] {
+        &self.counts
+    }
+    fn displs(&self) -> &[Count] {
+        &self.displs
+    }
+}
+
+/// A partitioning of a row-major matrix into a grid of rectangular tiles of unequal size
+///
+/// The matrix is cut into `row_sizes.len()` bands of rows and `col_sizes.len()` bands of columns.
+/// Tile `(i, j)` consists of the `row_sizes[i]` rows and `col_sizes[j]` columns at the
+/// intersection of row band `i` and column band `j` and is assigned to rank
+/// `i * col_sizes.len() + j`.
+///
+/// # Examples
+///
+/// See `examples/scatter_tiles.rs`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileLayout {
+    row_sizes: Vec<Count>,
+    col_sizes: Vec<Count>,
+    row_starts: Vec<Count>,
+    col_starts: Vec<Count>,
+}
+
+impl TileLayout {
+    /// A layout with row bands of `row_sizes` rows and column bands of `col_sizes` columns
+    pub fn new(row_sizes: Vec<Count>, col_sizes: Vec<Count>) -> TileLayout {
+        assert!(
+            !row_sizes.is_empty() && !col_sizes.is_empty(),
+            "A tile layout needs at least one row and one column band."
+        );
+        let starts = |sizes: &[Count]| -> Vec<Count> {
+            sizes
+                .iter()
+                .scan(0, |acc: &mut Count, &size| {
+                    assert!(size >= 0, "Negative tile size {} in tile layout.", size);
+                    let start = *acc;
+                    *acc = acc
+                        .checked_add(size)
+                        .expect("Matrix dimension cannot be expressed as an MPI Count.");
+                    Some(start)
+                })
+                .collect()
+        };
+        let row_starts = starts(&row_sizes);
+        let col_starts = starts(&col_sizes);
+        TileLayout {
+            row_sizes,
+            col_sizes,
+            row_starts,
+            col_starts,
+        }
+    }
+
+    /// A layout of a `rows` by `cols` matrix into a grid of `grid_rows` by `grid_cols` tiles that
+    /// differ in size by at most one row and one column.
+    pub fn even(rows: Count, cols: Count, grid_rows: Count, grid_cols: Count) -> TileLayout {
+        let split = |n: Count, parts: Count| -> Vec<Count> {
+            assert!(parts > 0, "A tile layout needs at least one band.");
+            (0..parts)
+                .map(|i| n / parts + if i < n % parts { 1 } else { 0 })
+                .collect()
+        };
+        TileLayout::new(split(rows, grid_rows), split(cols, grid_cols))
+    }
+
+    /// The number of rows and columns of the matrix
+    pub fn shape(&self) -> (Count, Count) {
+        (
+            self.row_sizes.iter().sum::<Count>(),
+            self.col_sizes.iter().sum::<Count>(),
+        )
+    }
+
+    /// The number of elements of the matrix
+    pub fn len(&self) -> usize {
+        let (rows, cols) = self.shape();
+        to_usize(rows) * to_usize(cols)
+    }
+
+    /// Whether the matrix has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of row and column bands
+    pub fn grid(&self) -> (usize, usize) {
+        (self.row_sizes.len(), self.col_sizes.len())
+    }
+
+    /// The number of tiles, which is also the number of processes the matrix is distributed over
+    pub fn num_tiles(&self) -> usize {
+        self.row_sizes.len() * self.col_sizes.len()
+    }
+
+    /// The number of rows and columns of the tile of process `rank`
+    pub fn tile_shape(&self, rank: Rank) -> (Count, Count) {
+        let (i, j) = self.tile_index(rank);
+        (self.row_sizes[i], self.col_sizes[j])
+    }
+
+    /// The row and column of the first element of the tile of process `rank`
+    pub fn tile_start(&self, rank: Rank) -> (Count, Count) {
+        let (i, j) = self.tile_index(rank);
+        (self.row_starts[i], self.col_starts[j])
+    }
+
+    /// The number of elements of the tile of process `rank`
+    pub fn tile_len(&self, rank: Rank) -> usize {
+        let (rows, cols) = self.tile_shape(rank);
+        to_usize(rows) * to_usize(cols)
+    }
+
+    /// A datatype describing the tile of process `rank` within the matrix of elements of type `T`
+    ///
+    /// Panics if the tile is empty, since MPI cannot describe an empty subarray.
+    pub fn tile_datatype<T: Equivalence>(&self, rank: Rank) -> UserDatatype {
+        let (rows, cols) = self.shape();
+        let (tile_rows, tile_cols) = self.tile_shape(rank);
+        assert!(
+            tile_rows > 0 && tile_cols > 0,
+            "The tile of rank {} is empty and has no subarray datatype.",
+            rank
+        );
+        let (start_row, start_col) = self.tile_start(rank);
+        UserDatatype::subarray(
+            &[rows, cols],
+            &[tile_rows, tile_cols],
+            &[start_row, start_col],
+            Order::RowMajor,
+            &T::equivalent_datatype(),
+        )
+    }
+
+    fn tile_index(&self, rank: Rank) -> (usize, usize) {
+        let rank = to_usize(rank);
+        assert!(
+            rank < self.num_tiles(),
+            "Rank {} has no tile in a layout of {} tiles.",
+            rank,
+            self.num_tiles()
+        );
+        (rank / self.col_sizes.len(), rank % self.col_sizes.len())
+    }
+}
+
+/// The fields of a struct for conversions between arrays of structs and structs of arrays
+///
+/// Simulation codes often keep their particles as one array per field, a struct of arrays, while
+/// output and analysis work on an array of structs. A `SoaLayout` lists the fields of the struct
+/// `T` with their offsets and datatypes. From it, `gather_soa_into_root()` and
+/// `scatter_soa_into_root()` build one datatype per process that addresses the fields of that
+/// process' structs with strided (hvector) blocks, so the fields are moved directly between the
+/// arrays of the processes and their place within the structs on the root process.
+///
+/// # Examples
+///
+/// See `examples/struct_of_arrays.rs`
+pub struct SoaLayout<T> {
+    offsets: Vec<Address>,
+    counts: Vec<Count>,
+    datatypes: Vec<UserDatatype>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> SoaLayout<T> {
+    /// A layout without fields
+    pub fn new() -> SoaLayout<T> {
+        SoaLayout {
+            offsets: Vec::new(),
+            counts: Vec::new(),
+            datatypes: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Add a field of `count` elements of type `datatype` at byte offset `offset` of `T`.
+    ///
+    /// The array of the field holds `count` elements per struct.
+    pub fn field<D>(mut self, offset: usize, count: Count, datatype: &D) -> SoaLayout<T>
+    where
+        D: UncommittedDatatype,
+    {
+        assert!(
+            offset < mem::size_of::<T>(),
+            "Field offset {} lies outside of a struct of {} bytes.",
+            offset,
+            mem::size_of::<T>()
+        );
+        assert!(count > 0, "A field needs at least one element.");
+        self.offsets.push(
+            offset
+                .value_as()
+                .expect("Field offset cannot be expressed as an MPI Address."),
+        );
+        self.counts.push(count);
+        self.datatypes
+            .push(UserDatatype::contiguous(count, datatype));
+        self
+    }
+
+    /// The number of fields
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Whether the layout has no fields
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// A datatype describing the fields of the `count` structs starting at index `start` of an
+    /// array of `T`, one field after the other
+    pub fn datatype(&self, start: Count, count: Count) -> UserDatatype {
+        let extent: Address = mem::size_of::<T>()
+            .value_as()
+            .expect("Struct size cannot be expressed as an MPI Address.");
+        let start: Address = start
+            .value_as()
+            .expect("Struct index cannot be expressed as an MPI Address.");
+        let columns: Vec<UncommittedUserDatatype> = self
+            .datatypes
+            .iter()
+            .map(|field| UncommittedUserDatatype::heterogeneous_vector(count, 1, extent, field))
+            .collect();
+        let displacements: Vec<Address> = self
+            .offsets
+            .iter()
+            .map(|&offset| start * extent + offset)
+            .collect();
+        UserDatatype::structured(&vec![1; self.len()], &displacements, &columns)
+    }
+
+    /// The number of structs held by the field buffers `fields`
+    fn structs_in<B>(&self, fields: &[B]) -> Count
+    where
+        B: Collection + AsDatatype,
+    {
+        assert!(
+            !self.is_empty(),
+            "A struct of arrays layout needs at least one field."
+        );
+        assert_eq!(
+            fields.len(),
+            self.len(),
+            "A struct of arrays needs one buffer per field of the layout."
+        );
+        let type_size = |datatype: MPI_Datatype| -> Count {
+            unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 }
+        };
+        let len = fields[0].count() / self.counts[0];
+        for (i, field) in fields.iter().enumerate() {
+            assert_eq!(
+                field.count(),
+                len * self.counts[i],
+                "The buffer of field {} does not hold {} elements per struct for {} structs.",
+                i,
+                self.counts[i],
+                len
+            );
+            assert_eq!(
+                type_size(field.as_datatype().as_raw()) * self.counts[i],
+                type_size(self.datatypes[i].as_raw()),
+                "The buffer of field {} does not match the datatype of the field.",
+                i
+            );
+        }
+        len
+    }
+}
+
+impl<T> Default for SoaLayout<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A datatype describing the field buffers of a struct of arrays relative to the first buffer
+fn fields_datatype<B>(fields: &[B]) -> UserDatatype
+where
+    B: Pointer + Collection + AsDatatype,
+{
+    let address = |field: &B| -> Address {
+        unsafe { with_uninitialized(|address| ffi::MPI_Get_address(field.pointer(), address)).1 }
+    };
+    let base = address(&fields[0]);
+    let blocklengths: Vec<Count> = fields.iter().map(Collection::count).collect();
+    let displacements: Vec<Address> = fields.iter().map(|field| address(field) - base).collect();
+    let types: Vec<DatatypeRef> = fields
+        .iter()
+        .map(|field| unsafe { DatatypeRef::from_raw(field.as_datatype().as_raw()) })
+        .collect();
+    UserDatatype::structured(&blocklengths, &displacements, &types)
+}
+
+fn to_usize(n: Count) -> usize {
+    n.value_as().expect("Count cannot be expressed as a usize.")
+}
+
+/// Check that `sendbuf` of a reduce-scatter is split into one segment per process of `comm`, that
+/// the segments follow each other without gaps and that the calling process receives `recvcount`
+/// elements.
+fn check_segments<C, S>(comm: &C, sendbuf: &S, recvcount: Count)
+where
+    C: ?Sized + Communicator,
+    S: ?Sized + Partitioned,
+{
+    let counts = sendbuf.counts();
+    assert_eq!(
+        counts.len(),
+        to_usize(comm.size()),
+        "A reduce-scatter needs one segment for each of the {} processes, not {}.",
+        comm.size(),
+        counts.len()
+    );
+    assert_eq!(
+        sendbuf.displs(),
+        &counts::displacements(counts)[..],
+        "The segments of a reduce-scatter have to follow each other without gaps."
+    );
+    let count = counts[to_usize(comm.rank())];
+    assert_eq!(
+        recvcount, count,
+        "The receive buffer holds {} elements but the segment of the process has {}.",
+        recvcount, count
+    );
+}
+
+/// The number of columns of a matrix of `ncols` columns that process `rank` of `size` processes
+/// receives from `Root::scatter_columns_into_root()`
+pub fn columns_of_rank(ncols: Count, size: Rank, rank: Rank) -> Count {
+    assert!(
+        0 <= rank && rank < size,
+        "Rank {} is out of range for {} processes.",
+        rank,
+        size
+    );
+    (ncols + size - 1 - rank) / size
+}
+
+/// A reusable redistribution of elements between the buffers of all processes
+///
+/// Adaptive mesh refinement codes move their cells between processes after every regrid
+/// according to a mapping that stays fixed until the next regrid. A `Redistribution` is compiled
+/// from such a mapping once: every process states the destination rank and index of each element
+/// of its source buffer. The mapping is turned into one indexed datatype per peer that picks the
+/// elements out of the source buffer and one that places them into the destination buffer, so
+/// every execution is a single `MPI_Alltoallw()` without packing or index exchanges.
+///
+/// # Examples
+///
+/// See `examples/redistribution.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.2, 5.8
+pub struct Redistribution<T> {
+    sendtypes: Vec<UserDatatype>,
+    sendcounts: Vec<Count>,
+    recvtypes: Vec<UserDatatype>,
+    recvcounts: Vec<Count>,
+    source_len: usize,
+    destination_len: usize,
+    phantom: PhantomData<fn(&[T])>,
+}
+
+impl<T> Redistribution<T>
+where
+    T: Equivalence,
+{
+    /// Compile the mapping of the elements of the source buffer of the calling process into a
+    /// redistribution over the processes of `comm`.
+    ///
+    /// Element `i` of the source buffer is sent to element `destinations[i].1` of the destination
+    /// buffer of process `destinations[i].0`. The destination buffer of the calling process holds
+    /// `destination_len` elements. Every element of a destination buffer may be the target of at
+    /// most one source element. Elements of the destination buffer that are not targeted are left
+    /// untouched on execution.
+    ///
+    /// This is a collective operation that exchanges the mapping between all processes.
+    pub fn new<C>(comm: &C, destinations: &[(Rank, usize)], destination_len: usize) -> Self
+    where
+        C: Communicator,
+    {
+        let size = to_usize(comm.size());
+
+        let mut source_indices: Vec<Vec<Count>> = vec![Vec::new(); size];
+        let mut target_indices: Vec<Vec<Count>> = vec![Vec::new(); size];
+        for (i, &(rank, j)) in destinations.iter().enumerate() {
+            assert!(
+                0 <= rank && rank < comm.size(),
+                "Destination rank {} is out of range for a communicator of size {}.",
+                rank,
+                comm.size()
+            );
+            let rank = to_usize(rank);
+            source_indices[rank].push(i.value_as().expect("Index cannot be expressed as a Count."));
+            target_indices[rank].push(j.value_as().expect("Index cannot be expressed as a Count."));
+        }
+
+        let sendcounts: Vec<Count> = source_indices.iter().map(|v| v[..].count()).collect();
+        let mut recvcounts: Vec<Count> = vec![0; size];
+        comm.all_to_all_into(&sendcounts[..], &mut recvcounts[..]);
+
+        let send_plan = CollectivePlan::new(sendcounts.clone());
+        let recv_plan = CollectivePlan::new(recvcounts.clone());
+        let targets: Vec<Count> = target_indices.into_iter().flatten().collect();
+        let mut received: Vec<Count> = vec![0; to_usize(recv_plan.extent())];
+        send_plan.all_to_all_varcount_into(comm, &targets[..], &recv_plan, &mut received[..]);
+
+        let mut targeted = vec![false; destination_len];
+        for &j in &received {
+            let j = to_usize(j);
+            assert!(
+                j < destination_len,
+                "Destination index {} is out of range for a destination buffer of length {}.",
+                j,
+                destination_len
+            );
+            assert!(
+                !targeted[j],
+                "Destination index {} is the target of more than one element.",
+                j
+            );
+            targeted[j] = true;
+        }
+
+        let datatype = T::equivalent_datatype();
+        let sendtypes = source_indices
+            .iter()
+            .map(|indices| UserDatatype::indexed_block(1, indices, &datatype))
+            .collect();
+        let recvtypes = recv_plan
+            .counts()
+            .iter()
+            .zip(recv_plan.displs())
+            .map(|(&count, &displ)| {
+                let start = to_usize(displ);
+                let indices = &received[start..start + to_usize(count)];
+                UserDatatype::indexed_block(1, indices, &datatype)
+            })
+            .collect();
+
+        Redistribution {
+            sendtypes,
+            sendcounts: sendcounts.iter().map(|&n| n.min(1)).collect(),
+            recvtypes,
+            recvcounts: recvcounts.iter().map(|&n| n.min(1)).collect(),
+            source_len: destinations.len(),
+            destination_len,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Length of the source buffer on the calling process
+    pub fn source_len(&self) -> usize {
+        self.source_len
+    }
+
+    /// Length of the destination buffer on the calling process
+    pub fn destination_len(&self) -> usize {
+        self.destination_len
+    }
+
+    /// Move the elements of `source` to their places in the `destination` buffers of all
+    /// processes.
+    ///
+    /// `comm` must consist of the same processes in the same order as the communicator the
+    /// redistribution was compiled for. This is a collective operation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.8
+    pub fn redistribute_into<C>(&self, comm: &C, source: &[T], destination: &mut [T])
+    where
+        C: Communicator,
+    {
+        let _call = hooks::enter(|| Call::collective(comm.as_raw(), "redistribute_into"));
+        assert_eq!(
+            to_usize(comm.size()),
+            self.sendtypes.len(),
+            "The redistribution was compiled for a communicator of a different size."
+        );
+        assert_eq!(
+            source.len(),
+            self.source_len,
+            "The source buffer does not match the redistribution."
+        );
+        assert_eq!(
+            destination.len(),
+            self.destination_len,
+            "The destination buffer does not match the redistribution."
+        );
+
+        let sendtypes: Vec<MPI_Datatype> = self.sendtypes.iter().map(|t| t.as_raw()).collect();
+        let recvtypes: Vec<MPI_Datatype> = self.recvtypes.iter().map(|t| t.as_raw()).collect();
+        let zeros: Vec<Count> = vec![0; self.sendtypes.len()];
+        unsafe {
+            all_to_all_w(
+                comm,
+                source.pointer(),
+                &self.sendcounts,
+                &zeros,
+                &sendtypes,
+                destination.pointer_mut(),
+                &self.recvcounts,
+                &zeros,
+                &recvtypes,
+            );
+        }
+    }
+}
+
+/// A two-level hierarchy of a communicator for hierarchical collective operations
+///
+/// The hierarchy consists of one communicator per shared memory node and a communicator of the
+/// node leaders, see `Communicator::split_leaders()`. Splitting a communicator is expensive, so
+/// the hierarchy is created once and reused for many operations.
+///
+/// # Examples
+///
+/// See `examples/all_reduce_hierarchical.rs`
+pub struct Hierarchy {
+    node: UserCommunicator,
+    leaders: Option<UserCommunicator>,
+}
+
+impl Hierarchy {
+    /// Build the hierarchy of the processes of `comm`.
+    ///
+    /// This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C) -> Hierarchy {
+        let (node, leaders) = comm.split_leaders();
+        Hierarchy { node, leaders }
+    }
+
+    /// The communicator of the processes on the same node as the calling process
+    pub fn node(&self) -> &UserCommunicator {
+        &self.node
+    }
+
+    /// The communicator of the node leaders, `None` on processes that do not lead their node
+    pub fn leaders(&self) -> Option<&UserCommunicator> {
+        self.leaders.as_ref()
+    }
+
+    /// Performs a global reduction under the operation `op` of the input data in `sendbuf` and
+    /// stores the result in `recvbuf` on all processes.
+    ///
+    /// The data is reduced within every node first, then across the node leaders and the result
+    /// is broadcast within every node. On many-core nodes this beats a flat
+    /// `all_reduce_into()` for medium message sizes, since most of the traffic stays on the node.
+    ///
+    /// Panics if `op` is not commutative, because the hierarchy does not preserve the rank order
+    /// of the processes in the reduction.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_reduce_hierarchical.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4, 5.9.1, 5.9.6
+    pub fn all_reduce_hierarchical<S: ?Sized, R: ?Sized, O>(
+        &self,
+        sendbuf: &S,
+        recvbuf: &mut R,
+        op: O,
+    ) where
+        S: Buffer,
+        R: BufferMut,
+        O: Operation,
+    {
+        assert!(
+            op.is_commutative(),
+            "Hierarchical reductions need a commutative operation."
+        );
+        let node_root = self.node.process_at_rank(0);
+        match self.leaders {
+            Some(ref leaders) => {
+                node_root.reduce_into_root(sendbuf, recvbuf, &op);
+                leaders.all_reduce_in_place(recvbuf, &op);
+            }
+            None => node_root.reduce_into(sendbuf, &op),
+        }
+        node_root.broadcast_into(recvbuf);
+    }
+}
+
+/// `MPI_Alltoallw()` with `Count` slices and raw datatypes
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn all_to_all_w<C: ?Sized + Communicator>(
+    comm: &C,
+    sendbuf: *const c_void,
+    sendcounts: &[Count],
+    sdispls: &[Count],
+    sendtypes: &[MPI_Datatype],
+    recvbuf: *mut c_void,
+    recvcounts: &[Count],
+    rdispls: &[Count],
+    recvtypes: &[MPI_Datatype],
+) {
+    let size = to_usize(comm.size());
+    for v in &[sendcounts, sdispls, recvcounts, rdispls] {
+        assert_eq!(v.len(), size, "Alltoallw needs one entry per process.");
+    }
+    assert_eq!(
+        sendtypes.len(),
+        size,
+        "Alltoallw needs one entry per process."
+    );
+    assert_eq!(
+        recvtypes.len(),
+        size,
+        "Alltoallw needs one entry per process."
+    );
+    ffi::MPI_Alltoallw(
+        sendbuf,
+        sendcounts.as_ptr(),
+        sdispls.as_ptr(),
+        sendtypes.as_ptr(),
+        recvbuf,
+        recvcounts.as_ptr(),
+        rdispls.as_ptr(),
+        recvtypes.as_ptr(),
+        comm.as_raw(),
+    );
+}
+
+/// An operation to be used in a reduction or scan type operation, e.g. `MPI_SUM`
+pub trait Operation: AsRaw<Raw = MPI_Op> {
+    /// Returns whether the operation is commutative.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.7
+    fn is_commutative(&self) -> bool {
+        unsafe {
+            let mut commute = 0;
+            ffi::MPI_Op_commutative(self.as_raw(), &mut commute);
+            commute != 0
+        }
+    }
+}
+impl<'a, T: 'a + Operation> Operation for &'a T {}
+
+/// A built-in operation like `MPI_SUM`
+///
+/// # Examples
+///
+/// See `examples/reduce.rs`
+///
+/// # Standard section(s)
+///
+/// 5.9.2
+#[derive(Copy, Clone)]
+pub struct SystemOperation(MPI_Op);
+
+macro_rules! system_operation_constructors {
+    ($($ctor:ident => $val:path),*) => (
+        $(pub fn $ctor() -> SystemOperation {
+            //! A built-in operation
+            SystemOperation(unsafe { $val })
+        })*
+    )
+}
+
+impl SystemOperation {
+    system_operation_constructors! {
+        max => ffi::RSMPI_MAX,
+        min => ffi::RSMPI_MIN,
+        sum => ffi::RSMPI_SUM,
+        product => ffi::RSMPI_PROD,
+        logical_and => ffi::RSMPI_LAND,
+        bitwise_and => ffi::RSMPI_BAND,
+        logical_or => ffi::RSMPI_LOR,
+        bitwise_or => ffi::RSMPI_BOR,
+        logical_xor => ffi::RSMPI_LXOR,
+        bitwise_xor => ffi::RSMPI_BXOR
+    }
+}
+
+unsafe impl AsRaw for SystemOperation {
+    type Raw = MPI_Op;
+    fn as_raw(&self) -> Self::Raw {
+        self.0
+    }
+}
+
+impl Operation for SystemOperation {}
+
+trait Erased {}
+
+impl<T> Erased for T {}
+
+/// A user-defined operation.
+///
+/// The lifetime `'a` of the operation is limited by the lifetime of the underlying closure.
+///
+/// For safety reasons, `UserOperation` is in of itself not considered an `Operation`, but a
+/// reference of it is.  This limitation may be lifted in the future when `Request` objects can
+/// store finalizers.
+///
+/// **Note:** When a `UserOperation` is passed to a non-blocking API call, it must outlive the
+/// completion of the request.  This is normally enforced by the safe API, so this is only a concern
+/// if you use the unsafe API.  Do not rely on MPI's internal reference-counting here, because once
+/// `UserOperation` is destroyed, the closure object will be deallocated even if the `MPI_Op` handle
+/// is still alive due to outstanding references.
+///
+/// # Examples
+///
+/// See `examples/reduce.rs` and `examples/immediate_reduce.rs`
+#[cfg(feature = "user-operations")]
+pub struct UserOperation<'a> {
+    op: MPI_Op,
+    _anchor: Box<dyn Erased + 'a>, // keeps the internal data alive
+}
+
+#[cfg(feature = "user-operations")]
+impl<'a> fmt::Debug for UserOperation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("UserOperation").field(&self.op).finish()
+    }
+}
+
+#[cfg(feature = "user-operations")]
+impl<'a> Drop for UserOperation<'a> {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("UserOperation") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Op_free(&mut self.op);
+        }
+    }
+}
+
+#[cfg(feature = "user-operations")]
+unsafe impl<'a> AsRaw for UserOperation<'a> {
+    type Raw = MPI_Op;
+    fn as_raw(&self) -> Self::Raw {
+        self.op
+    }
+}
+
+#[cfg(feature = "user-operations")]
+impl<'a, 'b> Operation for &'b UserOperation<'a> {}
+
+#[cfg(feature = "user-operations")]
+impl<'a> UserOperation<'a> {
+    /// Define an operation using a closure.  The operation must be associative.
+    ///
+    /// This is a more readable shorthand for the `new` method.  Refer to [`new`](#method.new) for
+    /// more information.
+    pub fn associative<F>(function: F) -> Self
+    where
+        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+    {
+        Self::new(false, function)
+    }
+
+    /// Define an operation using a closure.  The operation must be both associative and
+    /// commutative.
+    ///
+    /// This is a more readable shorthand for the `new` method.  Refer to [`new`](#method.new) for
+    /// more information.
+    pub fn commutative<F>(function: F) -> Self
+    where
+        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+    {
+        Self::new(true, function)
+    }
+
+    /// Creates an associative and possibly commutative operation using a closure.
+    ///
+    /// The closure receives two arguments `invec` and `inoutvec` as dynamically typed buffers.  It
+    /// shall set `inoutvec` to the value of `f(invec, inoutvec)`, where `f` is a binary associative
+    /// operation.
+    ///
+    /// If the operation is also commutative, setting `commute` to `true` may yield performance
+    /// benefits.
+    ///
+    /// **Note:** If the closure panics, the entire program will abort.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.5
+    pub fn new<F>(commute: bool, function: F) -> Self
+    where
+        F: Fn(DynBuffer, DynBufferMut) + Sync + 'a,
+    {
+        struct ClosureAnchor<F> {
+            rust_closure: F,
+            ffi_closure: Option<Closure<'static>>,
+        }
+
+        // must box it to prevent moves
+        let mut anchor = Box::new(ClosureAnchor {
+            rust_closure: function,
+            ffi_closure: None,
         });
 
         let args = [
@@ -1767,6 +4153,151 @@ impl<'a> UserOperation<'a> {
     }
 }
 
+/// An optional value with a datatype, for contributions to reductions that some processes do not
+/// make
+///
+/// `Option<T>` has no fixed memory layout, so it has no equivalent datatype. `OptionalValue<T>`
+/// stores a flag and a value in a `#[repr(C)]` struct that is sent as such. The value of an empty
+/// `OptionalValue` is `T::default()`. Operations built by `OptionalValue::reduction()` skip empty
+/// contributions, so e.g. the minimum of the values of the processes that have one can be reduced
+/// in a single call.
+///
+/// # Examples
+///
+/// See `examples/optional_value.rs`
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct OptionalValue<T> {
+    present: u8,
+    value: T,
+}
+
+impl<T> OptionalValue<T>
+where
+    T: Copy + Default,
+{
+    /// An `OptionalValue` holding `value`
+    pub fn some(value: T) -> Self {
+        OptionalValue { present: 1, value }
+    }
+
+    /// An empty `OptionalValue`
+    pub fn none() -> Self {
+        OptionalValue {
+            present: 0,
+            value: T::default(),
+        }
+    }
+
+    /// Whether a value is present
+    pub fn is_some(&self) -> bool {
+        self.present != 0
+    }
+
+    /// The value, if present
+    pub fn get(&self) -> Option<T> {
+        if self.is_some() {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "user-operations")]
+impl<T> OptionalValue<T>
+where
+    T: Copy + Default,
+{
+    /// An operation that combines two present values with `combine` and skips empty values.
+    ///
+    /// The result of combining two empty values is empty. `combine(a, b)` receives the value of
+    /// the lower ranking process as `a`. Set `commute` if `combine` is commutative. The operation
+    /// may only be applied to buffers of `OptionalValue<T>`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.9.5
+    pub fn reduction<'a, F>(commute: bool, combine: F) -> UserOperation<'a>
+    where
+        T: 'a,
+        F: Fn(T, T) -> T + Sync + 'a,
+    {
+        UserOperation::new(
+            commute,
+            move |invec: DynBuffer, mut inoutvec: DynBufferMut| {
+                let len = invec.len();
+                let input = invec.as_ptr() as *const OptionalValue<T>;
+                let output = inoutvec.as_mut_ptr() as *mut OptionalValue<T>;
+                for i in 0..len {
+                    // The buffers of the MPI library need not be aligned for `T`.
+                    unsafe {
+                        let a = ptr::read_unaligned(input.add(i));
+                        let b = ptr::read_unaligned(output.add(i));
+                        let combined = match (a.get(), b.get()) {
+                            (Some(a), Some(b)) => OptionalValue::some(combine(a, b)),
+                            (Some(_), None) => a,
+                            (None, _) => b,
+                        };
+                        ptr::write_unaligned(output.add(i), combined);
+                    }
+                }
+            },
+        )
+    }
+}
+
+impl<T> Default for OptionalValue<T>
+where
+    T: Copy + Default,
+{
+    fn default() -> Self {
+        OptionalValue::none()
+    }
+}
+
+impl<T> From<Option<T>> for OptionalValue<T>
+where
+    T: Copy + Default,
+{
+    fn from(value: Option<T>) -> Self {
+        value.map_or_else(OptionalValue::none, OptionalValue::some)
+    }
+}
+
+impl<T> From<OptionalValue<T>> for Option<T>
+where
+    T: Copy + Default,
+{
+    fn from(value: OptionalValue<T>) -> Self {
+        value.get()
+    }
+}
+
+impl<T> PartialEq for OptionalValue<T>
+where
+    T: Copy + Default + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+unsafe impl<T> Equivalence for OptionalValue<T>
+where
+    T: Equivalence,
+{
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        let flag = u8::equivalent_datatype();
+        let value = T::equivalent_datatype();
+        StructLayoutBuilder::<OptionalValue<T>>::new()
+            .field(offset_of!(OptionalValue<T>, present), 1, &flag)
+            .field(offset_of!(OptionalValue<T>, value), 1, &value)
+            .build()
+    }
+}
+
 /// An unsafe user-defined operation.
 ///
 /// Unsafe user-defined operations are created from pointers to functions that have the unsafe
@@ -1789,6 +4320,9 @@ impl fmt::Debug for UnsafeUserOperation {
 
 impl Drop for UnsafeUserOperation {
     fn drop(&mut self) {
+        if environment::leak_if_finalized("UnsafeUserOperation") {
+            return;
+        }
         unsafe {
             ffi::MPI_Op_free(&mut self.op);
         }
@@ -0,0 +1,769 @@
+//! A lightweight self-describing container for parallel checkpoints
+//!
+//! A container is a single file holding a number of named datasets. Every dataset is an
+//! n-dimensional array that is distributed over the processes of a communicator as described by
+//! an `ArrayLayout`. All processes write their parts of a dataset collectively via MPI-IO, and a
+//! dataset can be read back with a different distribution, e.g. to restart a simulation on a
+//! different number of processes.
+//!
+//! The file starts with a header of 24 bytes: the magic bytes `RSMPICT1` followed by the offset
+//! and length of the directory as little-endian 64-bit integers. The data of the datasets follows
+//! the header, each stored as a contiguous array in its storage order. The directory at the end
+//! of the file lists the datasets with their name, the size of their elements in bytes, their
+//! storage order, their offset in the file and their global extents. The elements themselves are
+//! stored in the native representation of the writing processes, so containers are not portable
+//! between architectures.
+//!
+//! `repack()` is a post-processing step for codes that write one file per process: it
+//! concatenates the files of all processes into a single file in rank order, with every process
+//! writing its part through a file view.
+//!
+//! This module is only available with the `container` feature.
+//!
+//! # Examples
+//!
+//! See `examples/container.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4.1.4, 13
+
+use std::convert::TryInto;
+use std::ffi::CString;
+use std::fs;
+use std::io::{self, Read};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::Path;
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::datatype::{Distribution, FixedStr, Order, UserDatatype};
+use crate::ffi;
+use crate::ffi::{MPI_Datatype, MPI_File, MPI_Offset};
+use crate::raw::traits::*;
+use crate::topology::traits::*;
+use crate::{with_uninitialized, Count};
+
+/// The first bytes of every container
+const MAGIC: [u8; 8] = *b"RSMPICT1";
+/// Length of the header: the magic bytes, offset and length of the directory
+const HEADER_LEN: u64 = 24;
+/// Maximum length of the name of a dataset in bytes
+pub const MAX_NAME_LEN: usize = 64;
+/// Number of bytes every process copies per collective write of `repack()`
+const REPACK_CHUNK_LEN: usize = 16 << 20;
+
+/// The distribution of an n-dimensional array over the processes of a communicator
+///
+/// The processes of the communicator are arranged in a grid with `psizes[i]` processes in
+/// dimension `i` in row-major order, so the grid must have as many processes as the
+/// communicator.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArrayLayout {
+    gsizes: Vec<Count>,
+    distribs: Vec<Distribution>,
+    psizes: Vec<Count>,
+    order: Order,
+}
+
+impl ArrayLayout {
+    /// An array with extents `gsizes` stored in `order`, where dimension `i` is distributed over
+    /// `psizes[i]` processes according to `distribs[i]`
+    pub fn new(
+        gsizes: &[Count],
+        distribs: &[Distribution],
+        psizes: &[Count],
+        order: Order,
+    ) -> ArrayLayout {
+        assert_eq!(
+            gsizes.len(),
+            distribs.len(),
+            "'gsizes', 'distribs', and 'psizes' must be the same length"
+        );
+        assert_eq!(
+            gsizes.len(),
+            psizes.len(),
+            "'gsizes', 'distribs', and 'psizes' must be the same length"
+        );
+        ArrayLayout {
+            gsizes: gsizes.to_vec(),
+            distribs: distribs.to_vec(),
+            psizes: psizes.to_vec(),
+            order,
+        }
+    }
+
+    /// A row-major array with extents `gsizes`, distributed in blocks over a grid of `psizes`
+    /// processes
+    pub fn block(gsizes: &[Count], psizes: &[Count]) -> ArrayLayout {
+        ArrayLayout::new(
+            gsizes,
+            &vec![Distribution::Block; gsizes.len()],
+            psizes,
+            Order::RowMajor,
+        )
+    }
+
+    /// The global extents of the array
+    pub fn global_sizes(&self) -> &[Count] {
+        &self.gsizes
+    }
+
+    /// The storage order of the array
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// The number of elements of the array held by the calling process of `comm`
+    pub fn local_len<C: Communicator>(&self, comm: &C) -> usize {
+        let datatype = self.datatype(comm, &u8::equivalent_datatype());
+        let size: Count =
+            unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype.as_raw(), size)).1 };
+        size.value_as()
+            .expect("Number of local elements cannot be expressed as a usize.")
+    }
+
+    /// The datatype selecting the elements held by the calling process of `comm`
+    fn datatype<C, D>(&self, comm: &C, oldtype: &D) -> UserDatatype
+    where
+        C: Communicator,
+        D: UncommittedDatatype,
+    {
+        UserDatatype::distributed_array(
+            comm.size(),
+            comm.rank(),
+            &self.gsizes,
+            &self.distribs,
+            &self.psizes,
+            self.order,
+            oldtype,
+        )
+    }
+
+    /// The number of elements of the whole array
+    fn global_len(&self) -> u64 {
+        self.gsizes
+            .iter()
+            .map(|&size| {
+                size.value_as::<u64>()
+                    .expect("Array extent cannot be expressed as a u64.")
+            })
+            .product()
+    }
+}
+
+/// The description of a dataset in the directory of a container
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Dataset {
+    name: String,
+    element_size: u64,
+    order: Order,
+    offset: u64,
+    gsizes: Vec<Count>,
+}
+
+impl Dataset {
+    /// The name of the dataset
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The size of an element of the dataset in bytes
+    pub fn element_size(&self) -> u64 {
+        self.element_size
+    }
+
+    /// The storage order of the dataset
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// The global extents of the dataset
+    pub fn global_sizes(&self) -> &[Count] {
+        &self.gsizes
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(FixedStr::<MAX_NAME_LEN>::nul_padded(&self.name).as_bytes());
+        let order = match self.order {
+            Order::RowMajor => 0,
+            Order::ColumnMajor => 1,
+        };
+        let ndims: u64 = self
+            .gsizes
+            .len()
+            .value_as()
+            .expect("Number of dimensions cannot be expressed as a u64.");
+        for value in [self.element_size, order, self.offset, ndims] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for &size in &self.gsizes {
+            let size: u64 = size
+                .value_as()
+                .expect("Array extent cannot be expressed as a u64.");
+            bytes.extend_from_slice(&size.to_le_bytes());
+        }
+    }
+
+    fn decode(bytes: &mut &[u8]) -> io::Result<Dataset> {
+        let name: [u8; MAX_NAME_LEN] = take(bytes, MAX_NAME_LEN)?
+            .try_into()
+            .map_err(|_| invalid_data("truncated dataset name"))?;
+        let name = FixedStr::from_bytes(name)
+            .to_str()
+            .map_err(|_| invalid_data("dataset name is not valid UTF-8"))?
+            .to_owned();
+        let element_size = take_u64(bytes)?;
+        let order = match take_u64(bytes)? {
+            0 => Order::RowMajor,
+            1 => Order::ColumnMajor,
+            _ => return Err(invalid_data("unknown storage order")),
+        };
+        let offset = take_u64(bytes)?;
+        let ndims = take_u64(bytes)?;
+        let gsizes = (0..ndims)
+            .map(|_| {
+                take_u64(bytes)?
+                    .value_as()
+                    .map_err(|_| invalid_data("array extent cannot be expressed as a Count"))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Dataset {
+            name,
+            element_size,
+            order,
+            offset,
+            gsizes,
+        })
+    }
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid container: {}.", reason),
+    )
+}
+
+fn take<'b>(bytes: &mut &'b [u8], len: usize) -> io::Result<&'b [u8]> {
+    if bytes.len() < len {
+        return Err(invalid_data("truncated directory"));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn take_u64(bytes: &mut &[u8]) -> io::Result<u64> {
+    let mut value = [0; 8];
+    value.copy_from_slice(take(bytes, 8)?);
+    Ok(u64::from_le_bytes(value))
+}
+
+fn to_offset(offset: u64) -> MPI_Offset {
+    offset
+        .value_as()
+        .expect("File offset cannot be expressed as an MPI_Offset.")
+}
+
+fn to_usize(len: u64) -> usize {
+    len.value_as()
+        .expect("Length cannot be expressed as a usize.")
+}
+
+fn element_size(datatype: MPI_Datatype) -> u64 {
+    let size: Count = unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 };
+    size.value_as()
+        .expect("Element size cannot be expressed as a u64.")
+}
+
+/// Turn the error code returned by the MPI-IO function `function` into an `io::Error`.
+///
+/// Errors on files are returned by default instead of aborting the program.
+fn check(code: c_int, function: &str) -> io::Result<()> {
+    if code == ffi::MPI_SUCCESS as c_int {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} failed with error code {}.", function, code),
+        ))
+    }
+}
+
+/// An MPI file handle that is closed when dropped
+struct File(MPI_File);
+
+impl File {
+    fn open<C: Communicator>(comm: &C, path: &Path, amode: c_int) -> io::Result<File> {
+        let path = path
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "The path cannot be passed to MPI-IO.",
+                )
+            })?;
+        let (code, file) = unsafe {
+            with_uninitialized(|file| {
+                ffi::MPI_File_open(
+                    comm.as_raw(),
+                    path.as_ptr(),
+                    amode,
+                    ffi::RSMPI_INFO_NULL,
+                    file,
+                )
+            })
+        };
+        check(code, "MPI_File_open")?;
+        Ok(File(file))
+    }
+
+    /// Make the file appear as a sequence of `filetype`s of `etype`s starting at byte `disp`.
+    fn set_view(&self, disp: u64, etype: MPI_Datatype, filetype: MPI_Datatype) -> io::Result<()> {
+        let datarep = b"native\0";
+        let code = unsafe {
+            ffi::MPI_File_set_view(
+                self.0,
+                to_offset(disp),
+                etype,
+                filetype,
+                datarep.as_ptr() as *const c_char,
+                ffi::RSMPI_INFO_NULL,
+            )
+        };
+        check(code, "MPI_File_set_view")
+    }
+
+    fn close(mut self) -> io::Result<()> {
+        let code = unsafe { ffi::MPI_File_close(&mut self.0) };
+        self.0 = unsafe { ffi::RSMPI_FILE_NULL };
+        check(code, "MPI_File_close")
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        if self.0 == unsafe { ffi::RSMPI_FILE_NULL } {
+            return;
+        }
+        unsafe {
+            ffi::MPI_File_close(&mut self.0);
+        }
+    }
+}
+
+/// Writes datasets into a new container
+///
+/// The container is only complete once `finish()` has been called, which writes the directory.
+pub struct ContainerWriter<'c, C>
+where
+    C: 'c + Communicator,
+{
+    comm: &'c C,
+    file: File,
+    datasets: Vec<Dataset>,
+    end: u64,
+}
+
+impl<'c, C> ContainerWriter<'c, C>
+where
+    C: 'c + Communicator,
+{
+    /// Create the container at `path` for the processes of `comm`, replacing an existing file.
+    ///
+    /// This is a collective operation.
+    pub fn create<P: AsRef<Path>>(comm: &'c C, path: P) -> io::Result<Self> {
+        let file = File::open(comm, path.as_ref(), unsafe {
+            ffi::RSMPI_MODE_CREATE | ffi::RSMPI_MODE_WRONLY
+        })?;
+        check(
+            unsafe { ffi::MPI_File_set_size(file.0, 0) },
+            "MPI_File_set_size",
+        )?;
+        Ok(ContainerWriter {
+            comm,
+            file,
+            datasets: Vec::new(),
+            end: HEADER_LEN,
+        })
+    }
+
+    /// Write the dataset `name` distributed according to `layout`, of which the calling process
+    /// holds the elements `local`.
+    ///
+    /// Panics if a dataset of that name has been written before, the name is longer than
+    /// `MAX_NAME_LEN` bytes or `local` does not hold `layout.local_len()` elements. This is a
+    /// collective operation.
+    pub fn write<T>(&mut self, name: &str, layout: &ArrayLayout, local: &[T]) -> io::Result<()>
+    where
+        T: Equivalence,
+    {
+        assert!(
+            self.datasets.iter().all(|dataset| dataset.name != name),
+            "The container already holds a dataset named '{}'.",
+            name
+        );
+        assert!(
+            name.len() <= MAX_NAME_LEN,
+            "The name '{}' is longer than {} bytes.",
+            name,
+            MAX_NAME_LEN
+        );
+        let local_len = layout.local_len(self.comm);
+        assert_eq!(
+            local.len(),
+            local_len,
+            "The calling process holds {} elements of the dataset, but {} were passed.",
+            local_len,
+            local.len()
+        );
+
+        let etype = T::equivalent_datatype();
+        let filetype = layout.datatype(self.comm, &etype);
+        self.file
+            .set_view(self.end, etype.as_raw(), filetype.as_raw())?;
+        check(
+            unsafe {
+                ffi::MPI_File_write_all(
+                    self.file.0,
+                    local.pointer(),
+                    local.count(),
+                    local.as_datatype().as_raw(),
+                    ffi::RSMPI_STATUS_IGNORE,
+                )
+            },
+            "MPI_File_write_all",
+        )?;
+
+        let dataset = Dataset {
+            name: name.to_owned(),
+            element_size: element_size(etype.as_raw()),
+            order: layout.order,
+            offset: self.end,
+            gsizes: layout.gsizes.clone(),
+        };
+        self.end += layout.global_len() * dataset.element_size;
+        self.datasets.push(dataset);
+        Ok(())
+    }
+
+    /// Write the directory and close the container.
+    ///
+    /// This is a collective operation.
+    pub fn finish(self) -> io::Result<()> {
+        let mut directory = Vec::new();
+        for dataset in &self.datasets {
+            dataset.encode(&mut directory);
+        }
+        let directory_len: u64 = directory
+            .len()
+            .value_as()
+            .expect("Directory length cannot be expressed as a u64.");
+        let mut header = Vec::new();
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&self.end.to_le_bytes());
+        header.extend_from_slice(&directory_len.to_le_bytes());
+
+        let byte = u8::equivalent_datatype().as_raw();
+        self.file.set_view(0, byte, byte)?;
+        if self.comm.rank() == 0 {
+            for (offset, bytes) in [(self.end, &directory), (0, &header)] {
+                check(
+                    unsafe {
+                        ffi::MPI_File_write_at(
+                            self.file.0,
+                            to_offset(offset),
+                            bytes.pointer(),
+                            bytes.count(),
+                            byte,
+                            ffi::RSMPI_STATUS_IGNORE,
+                        )
+                    },
+                    "MPI_File_write_at",
+                )?;
+            }
+        }
+        self.file.close()
+    }
+}
+
+/// Reads datasets from an existing container
+pub struct ContainerReader<'c, C>
+where
+    C: 'c + Communicator,
+{
+    comm: &'c C,
+    file: File,
+    datasets: Vec<Dataset>,
+}
+
+impl<'c, C> ContainerReader<'c, C>
+where
+    C: 'c + Communicator,
+{
+    /// Open the container at `path` for the processes of `comm` and read its directory.
+    ///
+    /// This is a collective operation.
+    pub fn open<P: AsRef<Path>>(comm: &'c C, path: P) -> io::Result<Self> {
+        let file = File::open(comm, path.as_ref(), unsafe { ffi::RSMPI_MODE_RDONLY })?;
+        let header = read_bytes(&file, 0, HEADER_LEN)?;
+        let mut rest = &header[..];
+        if take(&mut rest, MAGIC.len())? != MAGIC {
+            return Err(invalid_data("missing magic bytes"));
+        }
+        let directory_offset = take_u64(&mut rest)?;
+        let directory_len = take_u64(&mut rest)?;
+
+        let directory = read_bytes(&file, directory_offset, directory_len)?;
+        let mut rest = &directory[..];
+        let mut datasets = Vec::new();
+        while !rest.is_empty() {
+            datasets.push(Dataset::decode(&mut rest)?);
+        }
+        Ok(ContainerReader {
+            comm,
+            file,
+            datasets,
+        })
+    }
+
+    /// The datasets in the container
+    pub fn datasets(&self) -> &[Dataset] {
+        &self.datasets
+    }
+
+    /// The dataset named `name`, if the container holds one
+    pub fn dataset(&self, name: &str) -> Option<&Dataset> {
+        self.datasets.iter().find(|dataset| dataset.name == name)
+    }
+
+    /// Read the elements of the dataset `name` that the calling process holds under `layout`.
+    ///
+    /// `layout` has to have the global extents and storage order of the dataset, but may
+    /// distribute it differently than it was written. This is a collective operation.
+    pub fn read<T>(&self, name: &str, layout: &ArrayLayout) -> io::Result<Vec<T>>
+    where
+        T: Equivalence,
+    {
+        let dataset = self.dataset(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("The container holds no dataset named '{}'.", name),
+            )
+        })?;
+        if dataset.gsizes != layout.gsizes || dataset.order != layout.order {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The layout does not match the extents {:?} in {:?} order of dataset '{}'.",
+                    dataset.gsizes, dataset.order, name
+                ),
+            ));
+        }
+        let etype = T::equivalent_datatype();
+        if dataset.element_size != element_size(etype.as_raw()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The elements of dataset '{}' have {} bytes, not {}.",
+                    name,
+                    dataset.element_size,
+                    element_size(etype.as_raw())
+                ),
+            ));
+        }
+
+        let len = layout.local_len(self.comm);
+        let count: Count = len
+            .value_as()
+            .expect("Number of local elements cannot be expressed as a Count.");
+        let filetype = layout.datatype(self.comm, &etype);
+        self.file
+            .set_view(dataset.offset, etype.as_raw(), filetype.as_raw())?;
+        let mut local: Vec<T> = Vec::with_capacity(len);
+        check(
+            unsafe {
+                ffi::MPI_File_read_all(
+                    self.file.0,
+                    local.as_mut_ptr() as *mut c_void,
+                    count,
+                    etype.as_raw(),
+                    ffi::RSMPI_STATUS_IGNORE,
+                )
+            },
+            "MPI_File_read_all",
+        )?;
+        unsafe {
+            local.set_len(len);
+        }
+        Ok(local)
+    }
+
+    /// Close the container.
+    ///
+    /// This is a collective operation.
+    pub fn close(self) -> io::Result<()> {
+        self.file.close()
+    }
+}
+
+/// Read `len` bytes at byte `offset` of `file` on all processes.
+fn read_bytes(file: &File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    let len: usize = len
+        .value_as()
+        .map_err(|_| invalid_data("length cannot be expressed as a usize"))?;
+    let mut bytes = vec![0u8; len];
+    check(
+        unsafe {
+            ffi::MPI_File_read_at_all(
+                file.0,
+                to_offset(offset),
+                bytes.pointer_mut(),
+                bytes.count(),
+                u8::equivalent_datatype().as_raw(),
+                ffi::RSMPI_STATUS_IGNORE,
+            )
+        },
+        "MPI_File_read_at_all",
+    )?;
+    Ok(bytes)
+}
+
+/// The part of one process in a file assembled by `repack()`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RepackedPart {
+    offset: u64,
+    len: u64,
+}
+
+impl RepackedPart {
+    /// The offset of the part in the assembled file in bytes
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The length of the part in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the part is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Concatenate the files `part` of all processes of `comm` in rank order into the file `output`,
+/// replacing an existing file.
+///
+/// Every process reads its own `part`, e.g. the output file it wrote during the run, and writes
+/// it to `output` through a file view that starts at the end of the parts of the lower ranks.
+/// The parts are copied in chunks, so they do not have to fit into memory. Returns the offsets
+/// and lengths of the parts of all processes in rank order, e.g. to record them in an index.
+/// The files `part` are left in place.
+///
+/// This is a collective operation.
+///
+/// # Examples
+///
+/// See `examples/repack.rs`
+///
+/// # Standard section(s)
+///
+/// 13.2, 13.3, 13.4.3
+pub fn repack<C, P, Q>(comm: &C, part: P, output: Q) -> io::Result<Vec<RepackedPart>>
+where
+    C: Communicator,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let input = fs::File::open(part).and_then(|input| Ok((input.metadata()?.len(), input)));
+    // The other processes would wait in the collective writes for a process that cannot copy.
+    if comm.max(u8::from(input.is_err())) != 0 {
+        return Err(input.err().unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "Another process failed to open its part of the file.",
+            )
+        }));
+    }
+    let (len, mut input) = input?;
+    let mut lens = vec![
+        0u64;
+        comm.size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.")
+    ];
+    comm.all_gather_into(&len, &mut lens[..]);
+    let parts: Vec<RepackedPart> = lens
+        .iter()
+        .scan(0, |offset, &len| {
+            let part = RepackedPart {
+                offset: *offset,
+                len,
+            };
+            *offset += len;
+            Some(part)
+        })
+        .collect();
+    let rank: usize = comm
+        .rank()
+        .value_as()
+        .expect("Rank cannot be expressed as a usize.");
+
+    let file = File::open(comm, output.as_ref(), unsafe {
+        ffi::RSMPI_MODE_CREATE | ffi::RSMPI_MODE_WRONLY
+    })?;
+    check(
+        unsafe { ffi::MPI_File_set_size(file.0, 0) },
+        "MPI_File_set_size",
+    )?;
+    let byte = u8::equivalent_datatype().as_raw();
+    file.set_view(parts[rank].offset, byte, byte)?;
+
+    // Every process takes part in as many collective writes as the process with the largest part
+    // needs, writing nothing once its part is copied.
+    let chunk_len: u64 = REPACK_CHUNK_LEN
+        .value_as()
+        .expect("Chunk length cannot be expressed as a u64.");
+    let max_len = lens.iter().copied().max().unwrap_or(0);
+    let rounds = (max_len + chunk_len - 1) / chunk_len;
+    let mut chunk = vec![0u8; REPACK_CHUNK_LEN];
+    let mut remaining = len;
+    let mut read_error = None;
+    for _ in 0..rounds {
+        let mut chunk_len = remaining.min(chunk_len);
+        if read_error.is_none() {
+            if let Err(error) = input.read_exact(&mut chunk[..to_usize(chunk_len)]) {
+                read_error = Some(error);
+            }
+        }
+        if read_error.is_some() {
+            // Keep taking part in the collective writes without writing anything.
+            chunk_len = 0;
+        }
+        remaining -= chunk_len;
+        let written = &chunk[..to_usize(chunk_len)];
+        check(
+            unsafe {
+                ffi::MPI_File_write_all(
+                    file.0,
+                    written.pointer(),
+                    written.count(),
+                    byte,
+                    ffi::RSMPI_STATUS_IGNORE,
+                )
+            },
+            "MPI_File_write_all",
+        )?;
+    }
+    file.close()?;
+    if let Some(error) = read_error {
+        return Err(error);
+    }
+    Ok(parts)
+}
@@ -0,0 +1,161 @@
+//! Checked arithmetic on counts and displacements
+//!
+//! The `_varcount_` collectives describe the partitions of a buffer by counts and displacements.
+//! Mistakes in computing them, e.g. an overflowing prefix sum or overlapping receive partitions,
+//! lead to corrupted data rather than to errors reported by the MPI library. The functions in this
+//! module compute displacements with overflow checks and validate partitions before they are
+//! handed to MPI. `PartitionMut::new()` uses them to reject overlapping receive partitions.
+//!
+//! # Examples
+//!
+//! See `examples/counts.rs`
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use crate::Count;
+
+/// An invalid description of the partitions of a buffer
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CountError {
+    /// The count or displacement of partition `index` is negative
+    Negative {
+        /// Index of the partition
+        index: usize,
+    },
+    /// The sum of the counts or the end of a partition cannot be expressed as a `Count`
+    Overflow,
+    /// There are `counts` counts but `displs` displacements
+    LengthMismatch {
+        /// Number of counts
+        counts: usize,
+        /// Number of displacements
+        displs: usize,
+    },
+    /// Partition `index` ends at element `end` beyond the end of the buffer of `len` elements
+    OutOfBounds {
+        /// Index of the partition
+        index: usize,
+        /// End of the partition
+        end: Count,
+        /// Number of elements in the buffer
+        len: Count,
+    },
+    /// Partitions `first` and `second` overlap
+    Overlap {
+        /// Index of the partition starting first
+        first: usize,
+        /// Index of the partition overlapping it
+        second: usize,
+    },
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CountError::Negative { index } => {
+                write!(
+                    f,
+                    "Partition {} has a negative count or displacement",
+                    index
+                )
+            }
+            CountError::Overflow => write!(f, "Partition bounds cannot be expressed as a Count"),
+            CountError::LengthMismatch { counts, displs } => {
+                write!(f, "{} counts do not match {} displacements", counts, displs)
+            }
+            CountError::OutOfBounds { index, end, len } => write!(
+                f,
+                "Partition {} ends at {} beyond the end of the buffer of length {}",
+                index, end, len
+            ),
+            CountError::Overlap { first, second } => {
+                write!(f, "Partitions {} and {} overlap", first, second)
+            }
+        }
+    }
+}
+
+impl StdError for CountError {}
+
+/// The displacements of partitions of `counts` elements that follow each other without gaps
+///
+/// Returns an error if a count is negative or the sum of the counts cannot be expressed as a
+/// `Count`.
+pub fn try_displacements(counts: &[Count]) -> Result<Vec<Count>, CountError> {
+    let mut displs = Vec::with_capacity(counts.len());
+    let mut end: Count = 0;
+    for (index, &count) in counts.iter().enumerate() {
+        if count < 0 {
+            return Err(CountError::Negative { index });
+        }
+        displs.push(end);
+        end = end.checked_add(count).ok_or(CountError::Overflow)?;
+    }
+    Ok(displs)
+}
+
+/// The displacements of partitions of `counts` elements that follow each other without gaps
+///
+/// Panics if a count is negative or the sum of the counts cannot be expressed as a `Count`.
+pub fn displacements(counts: &[Count]) -> Vec<Count> {
+    try_displacements(counts).unwrap_or_else(|error| panic!("{}.", error))
+}
+
+/// The ends of the partitions described by `counts` and `displs`
+fn ends(counts: &[Count], displs: &[Count]) -> Result<Vec<Count>, CountError> {
+    if counts.len() != displs.len() {
+        return Err(CountError::LengthMismatch {
+            counts: counts.len(),
+            displs: displs.len(),
+        });
+    }
+    counts
+        .iter()
+        .zip(displs)
+        .enumerate()
+        .map(|(index, (&count, &displ))| {
+            if count < 0 || displ < 0 {
+                Err(CountError::Negative { index })
+            } else {
+                displ.checked_add(count).ok_or(CountError::Overflow)
+            }
+        })
+        .collect()
+}
+
+/// Check that `counts` and `displs` describe the same number of partitions with non-negative
+/// counts and displacements whose ends can be expressed as a `Count`.
+pub fn check_partitions(counts: &[Count], displs: &[Count]) -> Result<(), CountError> {
+    ends(counts, displs).map(|_| ())
+}
+
+/// Check that the partitions described by `counts` and `displs` lie within a buffer of `len`
+/// elements.
+pub fn check_bounds(counts: &[Count], displs: &[Count], len: Count) -> Result<(), CountError> {
+    for (index, end) in ends(counts, displs)?.into_iter().enumerate() {
+        if end > len {
+            return Err(CountError::OutOfBounds { index, end, len });
+        }
+    }
+    Ok(())
+}
+
+/// Check that the non-empty partitions described by `counts` and `displs` do not overlap.
+///
+/// Receive partitions have to be disjoint, since MPI does not define the contents of a buffer
+/// that receives several messages into the same element.
+pub fn check_disjoint(counts: &[Count], displs: &[Count]) -> Result<(), CountError> {
+    let ends = ends(counts, displs)?;
+    let mut order: Vec<usize> = (0..counts.len()).filter(|&i| counts[i] > 0).collect();
+    order.sort_by_key(|&i| (displs[i], ends[i]));
+    for pair in order.windows(2) {
+        if ends[pair[0]] > displs[pair[1]] {
+            return Err(CountError::Overlap {
+                first: pair[0],
+                second: pair[1],
+            });
+        }
+    }
+    Ok(())
+}
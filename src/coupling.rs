@@ -0,0 +1,246 @@
+//! Coupling of separately started groups of processes
+//!
+//! In-situ analysis and visualization tools attach to a running simulation, exchange a number of
+//! requests and responses with it and detach again, without the simulation having to know about
+//! the tool when it was started. This module builds such client/server couplings on top of ports
+//! and inter-communicators.
+//!
+//! The server opens an `Endpoint`, optionally publishing it under a service name, and accepts
+//! connections on it. The client connects to the endpoint via its service name or port name. Both
+//! sides then hold a `Connection` over which typed request/response `Channel`s are opened. Both
+//! sides close the `Connection` with `disconnect()`, which detaches the two groups gracefully.
+//!
+//! # Examples
+//!
+//! See `examples/coupling.rs`
+//!
+//! # Standard section(s)
+//!
+//! 6.6, 10.4, 10.5.4
+
+use std::marker::PhantomData;
+use std::mem;
+
+use crate::datatype::traits::*;
+use crate::environment;
+use crate::point_to_point::traits::*;
+use crate::topology::traits::*;
+use crate::topology::{self, InterCommunicator, Port, Rank};
+use crate::Tag;
+
+/// An endpoint on which a group of processes accepts connections from clients
+///
+/// The port of the endpoint is opened on the `root` process of the accepting communicator. If the
+/// endpoint is published under a service name, the name is unpublished when the endpoint is
+/// dropped.
+pub struct Endpoint<'a, C>
+where
+    C: 'a + Communicator,
+{
+    comm: &'a C,
+    root: Rank,
+    port: Option<Port>,
+    service_name: Option<String>,
+}
+
+impl<'a, C> Endpoint<'a, C>
+where
+    C: 'a + Communicator,
+{
+    /// Open an endpoint for the processes of `comm`, with the port held by process `root`.
+    ///
+    /// The name of the port has to be handed to the clients by other means, see `port_name()`.
+    /// This is a collective operation.
+    pub fn open(comm: &'a C, root: Rank) -> Endpoint<'a, C> {
+        let port = if comm.rank() == root {
+            Some(Port::open())
+        } else {
+            None
+        };
+        Endpoint {
+            comm,
+            root,
+            port,
+            service_name: None,
+        }
+    }
+
+    /// Open an endpoint for the processes of `comm` and publish it under `service_name`.
+    ///
+    /// Clients connect to the endpoint via `connect()`. This is a collective operation.
+    pub fn publish(comm: &'a C, root: Rank, service_name: &str) -> Endpoint<'a, C> {
+        let mut endpoint = Endpoint::open(comm, root);
+        if let Some(ref port) = endpoint.port {
+            topology::publish_name(service_name, port.name());
+        }
+        endpoint.service_name = Some(service_name.to_owned());
+        endpoint
+    }
+
+    /// The name of the port of the endpoint on the root process, `None` on all other processes
+    pub fn port_name(&self) -> Option<&str> {
+        self.port.as_ref().map(Port::name)
+    }
+
+    /// The service name the endpoint is published under
+    pub fn service_name(&self) -> Option<&str> {
+        self.service_name.as_deref()
+    }
+
+    /// Wait for a client to connect to the endpoint.
+    ///
+    /// This is a collective operation.
+    pub fn accept(&self) -> Connection {
+        Connection::new(
+            self.comm
+                .accept(self.port_name().unwrap_or_default(), self.root),
+        )
+    }
+}
+
+impl<'a, C> Drop for Endpoint<'a, C>
+where
+    C: 'a + Communicator,
+{
+    fn drop(&mut self) {
+        if let (Some(ref port), Some(ref service_name)) = (&self.port, &self.service_name) {
+            topology::unpublish_name(service_name, port.name());
+        }
+    }
+}
+
+/// Connect the processes of `comm` to the endpoint published under `service_name`.
+///
+/// The service name is looked up on process `root`. This is a collective operation.
+pub fn connect<C>(comm: &C, root: Rank, service_name: &str) -> Connection
+where
+    C: Communicator,
+{
+    let port_name = if comm.rank() == root {
+        topology::lookup_name(service_name)
+    } else {
+        String::new()
+    };
+    connect_to_port(comm, root, &port_name)
+}
+
+/// Connect the processes of `comm` to the endpoint with the port named `port_name`.
+///
+/// `port_name` is only significant on process `root`. This is a collective operation.
+pub fn connect_to_port<C>(comm: &C, root: Rank, port_name: &str) -> Connection
+where
+    C: Communicator,
+{
+    Connection::new(comm.connect(port_name, root))
+}
+
+/// A connection between a server and a client group of processes
+///
+/// A connection has to be closed with `disconnect()` on both sides. Disconnecting is a collective
+/// operation that would hang if only one side dropped the connection, e.g. while unwinding from a
+/// panic, so a connection that is dropped without being disconnected is leaked instead and counted
+/// by `environment::leaked_handles()`.
+pub struct Connection {
+    comm: Option<InterCommunicator>,
+}
+
+impl Connection {
+    fn new(comm: InterCommunicator) -> Connection {
+        Connection { comm: Some(comm) }
+    }
+
+    /// The inter-communicator underlying the connection
+    pub fn intercommunicator(&self) -> &InterCommunicator {
+        self.comm
+            .as_ref()
+            .expect("rsmpi internal error: connection without communicator")
+    }
+
+    /// Number of processes on the other side of the connection
+    pub fn remote_size(&self) -> Rank {
+        self.intercommunicator().remote_size()
+    }
+
+    /// A channel for requests of type `Req` answered by responses of type `Resp`
+    ///
+    /// Requests are tagged with `tag` and responses with `tag + 1`, so channels that are used at
+    /// the same time need tags that are at least two apart.
+    pub fn channel<Req, Resp>(&self, tag: Tag) -> Channel<'_, Req, Resp>
+    where
+        Req: Equivalence,
+        Resp: Equivalence,
+    {
+        Channel {
+            comm: self.intercommunicator(),
+            tag,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Disconnect from the other side of the connection.
+    ///
+    /// This is a collective operation on both groups.
+    pub fn disconnect(mut self) {
+        if let Some(comm) = self.comm.take() {
+            comm.disconnect();
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(comm) = self.comm.take() {
+            environment::record_leak("Connection");
+            mem::forget(comm);
+        }
+    }
+}
+
+/// A typed request/response channel over a `Connection`
+pub struct Channel<'c, Req, Resp> {
+    comm: &'c InterCommunicator,
+    tag: Tag,
+    phantom: PhantomData<(Req, Resp)>,
+}
+
+impl<'c, Req, Resp> Channel<'c, Req, Resp>
+where
+    Req: Equivalence,
+    Resp: Equivalence,
+{
+    /// Send `request` to process `rank` on the other side and wait for its response.
+    pub fn request(&self, rank: Rank, request: &Req) -> Resp {
+        let process = self.comm.remote_process(rank);
+        process.send_with_tag(request, self.tag);
+        process.receive_with_tag(self.tag + 1).0
+    }
+
+    /// Wait for the next request from any process on the other side.
+    ///
+    /// Returns the rank of the requesting process and the request, which has to be answered via
+    /// `respond()`.
+    pub fn receive_request(&self) -> (Rank, Req) {
+        let (request, status) = self.comm.any_remote_process().receive_with_tag(self.tag);
+        (status.source_rank(), request)
+    }
+
+    /// Send `response` to process `rank` on the other side.
+    pub fn respond(&self, rank: Rank, response: &Resp) {
+        self.comm
+            .remote_process(rank)
+            .send_with_tag(response, self.tag + 1);
+    }
+
+    /// Answer the next request from any process on the other side with the response computed by
+    /// `f`.
+    ///
+    /// Returns the rank of the requesting process.
+    pub fn serve<F>(&self, f: F) -> Rank
+    where
+        F: FnOnce(Req) -> Resp,
+    {
+        let (rank, request) = self.receive_request();
+        self.respond(rank, &f(request));
+        rank
+    }
+}
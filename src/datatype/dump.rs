@@ -0,0 +1,193 @@
+//! Printing the contents of buffers as seen through their datatype
+//!
+//! When a message arrives garbled, the cause is often a derived datatype that does not describe
+//! the memory it is used on, e.g. a wrong displacement or extent of a struct field. `dump_typed()`
+//! decodes the datatype of a buffer with `MPI_Type_get_contents()` down to the predefined
+//! datatypes and prints every element it describes at its byte offset, interpreted as the
+//! predefined datatype. The constructors of the derived datatypes are printed as an indented tree
+//! around their elements, so it is easy to see which part of a datatype touches which bytes.
+//!
+//! # Examples
+//!
+//! See `examples/dump_typed.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4.1.13
+
+use std::fmt::{self, Write};
+use std::ptr;
+
+use conv::ConvUtil;
+
+use super::{address, datatype_name, decode_datatype, raw_extent, typemap_blocks, Combiner};
+use crate::datatype::traits::*;
+use crate::ffi::{self, MPI_Datatype};
+use crate::raw::traits::*;
+use crate::{with_uninitialized, Address, Count};
+
+/// The elements of `buf`, one line per element of a predefined datatype
+///
+/// Every line shows the byte offset of an element from the start of the buffer, the name of its
+/// predefined datatype and its value. Values of predefined datatypes that correspond to Rust
+/// types are printed like these, others as bytes. Use a `View` to print a buffer as seen through
+/// a different datatype, e.g. the one a message was received with.
+///
+/// # Examples
+///
+/// See `examples/dump_typed.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.13
+pub fn dump_typed<Buf>(buf: &Buf) -> String
+where
+    Buf: ?Sized + Buffer,
+{
+    let mut out = String::new();
+    write_typed(&mut out, buf).expect("rsmpi internal error: formatting into a String failed");
+    out
+}
+
+fn write_typed<Buf>(out: &mut String, buf: &Buf) -> fmt::Result
+where
+    Buf: ?Sized + Buffer,
+{
+    let datatype = buf.as_datatype();
+    let base = buf.pointer() as *const u8;
+    let extent = raw_extent(datatype.as_raw());
+    writeln!(out, "{} x {}", buf.count(), describe(datatype.as_raw()))?;
+    for i in 0..buf.count() {
+        unsafe {
+            dump(out, base, address(i) * extent, datatype.as_raw(), 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Print the elements of one instance of `datatype` at byte `offset` from `base`.
+unsafe fn dump(
+    out: &mut String,
+    base: *const u8,
+    offset: Address,
+    datatype: MPI_Datatype,
+    depth: usize,
+) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    let decoded = decode_datatype(datatype);
+    if decoded.combiner == Combiner::Named {
+        let value = format_named(base.offset(pointer_offset(offset)), datatype);
+        return writeln!(
+            out,
+            "{}+{} {}: {}",
+            indent,
+            offset,
+            describe(datatype),
+            value
+        );
+    }
+
+    write!(out, "{}+{} {}", indent, offset, decoded.combiner.name())?;
+    if !decoded.integers.is_empty() {
+        write!(out, " {:?}", decoded.integers)?;
+    }
+    if !decoded.addresses.is_empty() {
+        write!(out, " {:?}", decoded.addresses)?;
+    }
+    writeln!(out)?;
+
+    let blocks = match typemap_blocks(&decoded) {
+        Some(blocks) => blocks,
+        None => {
+            return writeln!(out, "{}  (layout not decoded)", indent);
+        }
+    };
+    for (displacement, count, datatype) in blocks {
+        let extent = raw_extent(datatype);
+        for i in 0..count {
+            dump(
+                out,
+                base,
+                offset + displacement + address(i) * extent,
+                datatype,
+                depth + 1,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The value of an element of the predefined `datatype` at `pointer`
+unsafe fn format_named(pointer: *const u8, datatype: MPI_Datatype) -> String {
+    macro_rules! read {
+        ($t:ty) => {
+            format!("{:?}", ptr::read_unaligned(pointer as *const $t))
+        };
+    }
+    macro_rules! complex {
+        ($t:ty) => {{
+            let parts = ptr::read_unaligned(pointer as *const [$t; 2]);
+            format!("{:?} + {:?}i", parts[0], parts[1])
+        }};
+    }
+
+    if datatype == ffi::RSMPI_FLOAT {
+        read!(f32)
+    } else if datatype == ffi::RSMPI_DOUBLE {
+        read!(f64)
+    } else if datatype == ffi::RSMPI_C_FLOAT_COMPLEX {
+        complex!(f32)
+    } else if datatype == ffi::RSMPI_C_DOUBLE_COMPLEX {
+        complex!(f64)
+    } else if datatype == ffi::RSMPI_INT8_T {
+        read!(i8)
+    } else if datatype == ffi::RSMPI_INT16_T {
+        read!(i16)
+    } else if datatype == ffi::RSMPI_INT32_T {
+        read!(i32)
+    } else if datatype == ffi::RSMPI_INT64_T {
+        read!(i64)
+    } else if datatype == ffi::RSMPI_UINT8_T {
+        read!(u8)
+    } else if datatype == ffi::RSMPI_UINT16_T {
+        read!(u16)
+    } else if datatype == ffi::RSMPI_UINT32_T {
+        read!(u32)
+    } else if datatype == ffi::RSMPI_UINT64_T {
+        read!(u64)
+    } else if datatype == ffi::RSMPI_CHAR {
+        format!("{:?}", char::from(*pointer))
+    } else if datatype == ffi::RSMPI_C_BOOL {
+        // A byte that is neither 0 nor 1 would be an invalid `bool`.
+        match *pointer {
+            0 => "false".to_owned(),
+            1 => "true".to_owned(),
+            byte => format!("invalid bool {:#04x}", byte),
+        }
+    } else {
+        let size: Count = with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1;
+        let size: usize = size
+            .value_as()
+            .expect("Datatype size cannot be expressed as a usize.");
+        let bytes: Vec<String> = (0..size)
+            .map(|i| format!("{:02x}", *pointer.add(i)))
+            .collect();
+        format!("[{}]", bytes.join(" "))
+    }
+}
+
+/// The name of `datatype`, or its combiner if it has no name
+fn describe(datatype: MPI_Datatype) -> String {
+    let name = datatype_name(datatype);
+    if name.is_empty() {
+        decode_datatype(datatype).combiner.name().to_owned()
+    } else {
+        name
+    }
+}
+
+fn pointer_offset(offset: Address) -> isize {
+    offset
+        .value_as()
+        .expect("Byte offset cannot be expressed as an isize.")
+}
@@ -0,0 +1,3458 @@
+//! The core function of MPI is getting data from point A to point B (where A and B are e.g. single
+//! processes, multiple processes, the filesystem, ...). It offers facilities to describe that data
+//! (layout in memory, behavior under certain operators) that go beyound a start address and a
+//! number of bytes.
+//!
+//! An MPI datatype describes a memory layout and semantics (e.g. in a collective reduce
+//! operation). There are several pre-defined `SystemDatatype`s which directly correspond to Rust
+//! primitive types, such as `MPI_DOUBLE` and `f64`. A direct relationship between a Rust type and
+//! an MPI datatype is covered by the `Equivalence` trait. Starting from the
+//! `SystemDatatype`s, the user can build various `UserDatatype`s, e.g. to describe the layout of a
+//! struct (which should then implement `Equivalence`) or to intrusively describe parts of
+//! an object in memory like all elements below the diagonal of a dense matrix stored in row-major
+//! order.
+//!
+//! ## Derived Datatypes
+//! Derived MPI datatypes can exist in either an "uncommitted" or "committed" state. Only
+//! "committed" datatypes can be used in MPI communication. There is a cost to committing a
+//! datatype: only a final aggregate type should be committed when building it from component
+//! derived datatypes.
+//!
+//! In order to represent the difference between committed and uncommitted `MPI_Datatype` objects,
+//! two different flavors of types representing the "committed"-ness of the type are exposed.
+//! Orthogonally, there are types representing both "owned" `MPI_Datatype` objects and "borrowed"
+//! `MPI_Datatype` objects. This means there are four different types for `MPI_Datatype`:
+//! - `UserDatatype` represents a committed, owned `MPI_Datatype`
+//! - `DatatypeRef<'_>` represents a committed, borrowed `MPI_Datatype`.
+//!   - All builtin types are `DatatypeRef<'static>`
+//! - `UncommittedUserDatatype` represents an uncommitted, owned `MPI_Datatype`
+//! - `UncommittedDatatypeRef<'_>` represents an uncommitted, borrowed `MPI_Datatype`
+//!
+//! Along with this, there are two traits that are applied to these types:
+//! - `UncommittedDatatype` indicates the type represents a possibly uncommitted `MPI_Datatype`
+//! - `Datatype` indicates the type represents a committed `MPI_Datatype`
+//!
+//! An important concept here is that all committed `Datatype` objects are also
+//! `UncommittedDatatype` objects. This may seem unintuitive at first, but as far as MPI is
+//! concerned, "committing" a datatype is purely a promotion, enabling more capabilities. This
+//! allows `Datatype` and `UncommittedDatatype` objects to be used interchangeably in the same
+//! `Datatype` constructors.
+//!
+//! For more information on derived datatypes, see section 4.1 of the MPI 3.1 Standard.
+//!
+//! ## Data Buffers
+//! A `Buffer` describes a specific piece of data in memory that MPI should operate on. In addition
+//! to specifying the datatype of the data. It knows the address in memory where the data begins
+//! and how many instances of the datatype are contained in the data. The `Buffer` trait is
+//! implemented for slices that contain types implementing `Equivalence`, for shared slices behind
+//! a `Cow`, `Arc` or `Rc`, and for the inline vectors `SmallVec` and, with the `arrayvec` feature,
+//! `ArrayVec`.
+//!
+//! Buffers whose element type is only known at run time, e.g. from a configuration file, are
+//! described by `DynBuffer` and `DynBufferMut`, which pair a pointer and a count with a datatype
+//! handle and can be passed to all communication routines.
+//!
+//! In order to use arbitrary datatypes to describe the contents of a slice, the `View` type is
+//! provided. However, since it can be used to instruct the underlying MPI implementation to
+//! rummage around arbitrary parts of memory, its general constructors are marked unsafe. A view of
+//! a slice can be created safely with `View::try_new()`, which checks the bytes touched by the
+//! datatype against the length of the slice.
+//!
+//! Data of several datatypes can be packed into one `pack::PackedBuffer` and sent as a single
+//! message.
+//!
+//! The contents of a buffer as seen through its datatype can be printed with
+//! `dump::dump_typed()` to debug datatypes that do not match the memory they describe.
+//!
+//! # Unfinished features
+//!
+//! - **4.1.5**: Address and size functions, `MPI_Type_size_x()`
+//! - **4.1.7**: Extent and bounds of datatypes: `MPI_Type_get_extent_x()`
+//! - **4.1.8**: True extent of datatypes, `MPI_Type_get_true_extent_x()`
+//! - **4.1.11**: `MPI_Get_elements()`
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::{any, fmt, mem, slice, str};
+
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayVec;
+use conv::ConvUtil;
+use once_cell::sync::Lazy;
+use smallvec::SmallVec;
+
+use super::{Address, Count};
+
+use crate::counts;
+use crate::environment;
+use crate::ffi;
+use crate::ffi::MPI_Datatype;
+
+use crate::raw::traits::*;
+use crate::topology::{Communicator, Rank};
+
+use crate::{with_uninitialized, with_uninitialized2};
+
+pub mod dump;
+pub mod pack;
+
+/// Datatype traits
+pub mod traits {
+    pub use super::{
+        AsDatatype, Buffer, BufferMut, Collection, Datatype, Equivalence, Partitioned,
+        PartitionedBuffer, PartitionedBufferMut, Pointer, PointerMut, UncommittedDatatype,
+    };
+}
+
+/// A reference to an MPI data type.
+///
+/// This is similar to a raw `MPI_Datatype` but is guaranteed to be a valid for `'a`.
+#[derive(Copy, Clone)]
+pub struct DatatypeRef<'a> {
+    datatype: MPI_Datatype,
+    phantom: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a> AsRaw for DatatypeRef<'a> {
+    type Raw = MPI_Datatype;
+    #[inline]
+    fn as_raw(&self) -> Self::Raw {
+        self.datatype
+    }
+}
+
+impl<'a> FromRaw for DatatypeRef<'a> {
+    #[inline]
+    unsafe fn from_raw(datatype: MPI_Datatype) -> Self {
+        Self {
+            datatype,
+            phantom: PhantomData,
+        }
+    }
+}
+
+unsafe impl<'a> MatchesRaw for DatatypeRef<'a> {}
+
+impl<'a> fmt::Debug for DatatypeRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "DatatypeRef", self.datatype)
+    }
+}
+
+impl<'a> Datatype for DatatypeRef<'a> {}
+impl<'a> UncommittedDatatype for DatatypeRef<'a> {
+    type DuplicatedDatatype = UserDatatype;
+}
+
+impl<'a> From<DatatypeRef<'a>> for UncommittedDatatypeRef<'a> {
+    fn from(datatype_ref: DatatypeRef<'a>) -> Self {
+        unsafe { UncommittedDatatypeRef::from_raw(datatype_ref.as_raw()) }
+    }
+}
+
+/// A reference to an uncommitted, or potentially uncommitted, MPI data type.
+///
+/// This is similar to a raw uncommitted `MPI_Datatype` but is guaranteed to be a valid for `'a`.
+#[derive(Copy, Clone)]
+pub struct UncommittedDatatypeRef<'a> {
+    datatype: MPI_Datatype,
+    phantom: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a> AsRaw for UncommittedDatatypeRef<'a> {
+    type Raw = MPI_Datatype;
+    fn as_raw(&self) -> Self::Raw {
+        self.datatype
+    }
+}
+
+impl<'a> FromRaw for UncommittedDatatypeRef<'a> {
+    unsafe fn from_raw(datatype: MPI_Datatype) -> Self {
+        Self {
+            datatype,
+            phantom: PhantomData,
+        }
+    }
+}
+
+unsafe impl<'a> MatchesRaw for UncommittedDatatypeRef<'a> {}
+
+impl<'a> fmt::Debug for UncommittedDatatypeRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "UncommittedDatatypeRef", self.datatype)
+    }
+}
+
+impl<'a> UncommittedDatatype for UncommittedDatatypeRef<'a> {
+    type DuplicatedDatatype = UncommittedUserDatatype;
+}
+
+/// The name of the combiner that created `datatype`, e.g. `"vector"`
+fn combiner_name(datatype: MPI_Datatype) -> &'static str {
+    Combiner::from_raw(envelope(datatype).3).name()
+}
+
+/// The numbers of integer, address and datatype arguments and the combiner of `datatype`
+fn envelope(datatype: MPI_Datatype) -> (c_int, c_int, c_int, c_int) {
+    let mut envelope: (c_int, c_int, c_int, c_int) = (0, 0, 0, 0);
+    unsafe {
+        ffi::MPI_Type_get_envelope(
+            datatype,
+            &mut envelope.0,
+            &mut envelope.1,
+            &mut envelope.2,
+            &mut envelope.3,
+        );
+    }
+    envelope
+}
+
+/// The constructor that a datatype was created with
+///
+/// # Standard section(s)
+///
+/// 4.1.13
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Combiner {
+    /// A predefined datatype, e.g. `MPI_INT`
+    Named,
+    /// `MPI_Type_dup()`
+    Dup,
+    /// `MPI_Type_contiguous()`
+    Contiguous,
+    /// `MPI_Type_vector()`
+    Vector,
+    /// `MPI_Type_create_hvector()`
+    HVector,
+    /// `MPI_Type_indexed()`
+    Indexed,
+    /// `MPI_Type_create_hindexed()`
+    HIndexed,
+    /// `MPI_Type_create_indexed_block()`
+    IndexedBlock,
+    /// `MPI_Type_create_hindexed_block()`
+    HIndexedBlock,
+    /// `MPI_Type_create_struct()`
+    Struct,
+    /// `MPI_Type_create_subarray()`
+    Subarray,
+    /// `MPI_Type_create_darray()`
+    Darray,
+    /// `MPI_Type_create_resized()`
+    Resized,
+    /// Another constructor, e.g. for Fortran types, identified by its raw `MPI_COMBINER_` value
+    Other(c_int),
+}
+
+impl Combiner {
+    fn from_raw(combiner: c_int) -> Combiner {
+        let combiners = unsafe {
+            [
+                (ffi::RSMPI_COMBINER_NAMED, Combiner::Named),
+                (ffi::RSMPI_COMBINER_DUP, Combiner::Dup),
+                (ffi::RSMPI_COMBINER_CONTIGUOUS, Combiner::Contiguous),
+                (ffi::RSMPI_COMBINER_VECTOR, Combiner::Vector),
+                (ffi::RSMPI_COMBINER_HVECTOR, Combiner::HVector),
+                (ffi::RSMPI_COMBINER_INDEXED, Combiner::Indexed),
+                (ffi::RSMPI_COMBINER_HINDEXED, Combiner::HIndexed),
+                (ffi::RSMPI_COMBINER_INDEXED_BLOCK, Combiner::IndexedBlock),
+                (ffi::RSMPI_COMBINER_HINDEXED_BLOCK, Combiner::HIndexedBlock),
+                (ffi::RSMPI_COMBINER_STRUCT, Combiner::Struct),
+                (ffi::RSMPI_COMBINER_SUBARRAY, Combiner::Subarray),
+                (ffi::RSMPI_COMBINER_DARRAY, Combiner::Darray),
+                (ffi::RSMPI_COMBINER_RESIZED, Combiner::Resized),
+            ]
+        };
+        combiners
+            .iter()
+            .find(|&&(value, _)| value == combiner)
+            .map_or(Combiner::Other(combiner), |&(_, combiner)| combiner)
+    }
+
+    /// The name of the combiner, e.g. `"vector"`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Combiner::Named => "named",
+            Combiner::Dup => "dup",
+            Combiner::Contiguous => "contiguous",
+            Combiner::Vector => "vector",
+            Combiner::HVector => "hvector",
+            Combiner::Indexed => "indexed",
+            Combiner::HIndexed => "hindexed",
+            Combiner::IndexedBlock => "indexed_block",
+            Combiner::HIndexedBlock => "hindexed_block",
+            Combiner::Struct => "struct",
+            Combiner::Subarray => "subarray",
+            Combiner::Darray => "darray",
+            Combiner::Resized => "resized",
+            Combiner::Other(_) => "other",
+        }
+    }
+}
+
+/// A datatype taken apart into the constructor and the arguments it was created with
+///
+/// The arguments are listed in the order of `MPI_Type_get_contents()`, e.g. the integers of a
+/// `Combiner::Vector` are the count, block length and stride and its only datatype is the old
+/// datatype. Predefined datatypes have no arguments.
+///
+/// # Examples
+///
+/// See `examples/datatype_decode.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.13
+#[derive(Debug)]
+pub struct DecodedDatatype {
+    /// The constructor of the datatype
+    pub combiner: Combiner,
+    /// The integer arguments of the constructor
+    pub integers: Vec<Count>,
+    /// The address arguments of the constructor
+    pub addresses: Vec<Address>,
+    /// The datatype arguments of the constructor, which can be decoded in turn
+    pub datatypes: Vec<DatatypeArgument>,
+}
+
+/// A datatype argument of a `DecodedDatatype`
+///
+/// Derived datatypes are handed out as new handles, which may not be committed.
+pub enum DatatypeArgument {
+    /// A predefined datatype
+    Named(SystemDatatype),
+    /// A derived datatype
+    Derived(UncommittedUserDatatype),
+}
+
+unsafe impl AsRaw for DatatypeArgument {
+    type Raw = MPI_Datatype;
+    fn as_raw(&self) -> Self::Raw {
+        match *self {
+            DatatypeArgument::Named(ref datatype) => datatype.as_raw(),
+            DatatypeArgument::Derived(ref datatype) => datatype.as_raw(),
+        }
+    }
+}
+
+unsafe impl MatchesRaw for DatatypeArgument {}
+
+impl UncommittedDatatype for DatatypeArgument {
+    type DuplicatedDatatype = UncommittedUserDatatype;
+}
+
+impl<'a> From<&'a DatatypeArgument> for UncommittedDatatypeRef<'a> {
+    fn from(datatype: &'a DatatypeArgument) -> Self {
+        unsafe { UncommittedDatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+impl fmt::Debug for DatatypeArgument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "DatatypeArgument", self.as_raw())
+    }
+}
+
+fn decode_datatype(datatype: MPI_Datatype) -> DecodedDatatype {
+    let (num_integers, num_addresses, num_datatypes, combiner) = envelope(datatype);
+    let combiner = Combiner::from_raw(combiner);
+    if combiner == Combiner::Named {
+        return DecodedDatatype {
+            combiner,
+            integers: Vec::new(),
+            addresses: Vec::new(),
+            datatypes: Vec::new(),
+        };
+    }
+
+    let len = |n: c_int| -> usize {
+        n.value_as()
+            .expect("Length of datatype envelope cannot be expressed as a usize.")
+    };
+    let mut integers: Vec<c_int> = vec![0; len(num_integers)];
+    let mut addresses: Vec<Address> = vec![0; len(num_addresses)];
+    let mut datatypes: Vec<MPI_Datatype> =
+        vec![unsafe { ffi::RSMPI_DATATYPE_NULL }; len(num_datatypes)];
+    unsafe {
+        ffi::MPI_Type_get_contents(
+            datatype,
+            num_integers,
+            num_addresses,
+            num_datatypes,
+            integers.as_mut_ptr(),
+            addresses.as_mut_ptr(),
+            datatypes.as_mut_ptr(),
+        );
+    }
+    let datatypes = datatypes
+        .into_iter()
+        .map(|datatype| unsafe {
+            // Only handles of derived datatypes are new handles that have to be freed.
+            if Combiner::from_raw(envelope(datatype).3) == Combiner::Named {
+                DatatypeArgument::Named(DatatypeRef::from_raw(datatype))
+            } else {
+                DatatypeArgument::Derived(UncommittedUserDatatype::from_raw(datatype))
+            }
+        })
+        .collect();
+    DecodedDatatype {
+        combiner,
+        integers,
+        addresses,
+        datatypes,
+    }
+}
+
+/// The blocks of the typemap of the datatype that `decoded` was decoded from
+///
+/// Every block is a number of consecutive instances of a datatype, borrowed from `decoded`, at a
+/// byte displacement. Returns `None` for predefined datatypes and for constructors whose layout
+/// is not decoded, e.g. `Combiner::Darray`.
+fn typemap_blocks(decoded: &DecodedDatatype) -> Option<Vec<(Address, Count, MPI_Datatype)>> {
+    let ints = &decoded.integers;
+    let addrs = &decoded.addresses;
+    let child = |i: usize| decoded.datatypes[i].as_raw();
+    let index = |n: Count| -> usize {
+        n.value_as()
+            .expect("Datatype argument cannot be expressed as a usize.")
+    };
+    let blocks: Vec<(Address, Count, MPI_Datatype)> = match decoded.combiner {
+        Combiner::Dup | Combiner::Resized => vec![(0, 1, child(0))],
+        Combiner::Contiguous => vec![(0, ints[0], child(0))],
+        Combiner::Vector => {
+            let stride = address(ints[2]) * raw_extent(child(0));
+            (0..ints[0])
+                .map(|i| (address(i) * stride, ints[1], child(0)))
+                .collect()
+        }
+        Combiner::HVector => (0..ints[0])
+            .map(|i| (address(i) * addrs[0], ints[1], child(0)))
+            .collect(),
+        Combiner::Indexed => {
+            let count = index(ints[0]);
+            (0..count)
+                .map(|i| {
+                    let displacement = address(ints[1 + count + i]) * raw_extent(child(0));
+                    (displacement, ints[1 + i], child(0))
+                })
+                .collect()
+        }
+        Combiner::HIndexed => (0..index(ints[0]))
+            .map(|i| (addrs[i], ints[1 + i], child(0)))
+            .collect(),
+        Combiner::IndexedBlock => (0..index(ints[0]))
+            .map(|i| {
+                (
+                    address(ints[2 + i]) * raw_extent(child(0)),
+                    ints[1],
+                    child(0),
+                )
+            })
+            .collect(),
+        Combiner::HIndexedBlock => (0..index(ints[0]))
+            .map(|i| (addrs[i], ints[1], child(0)))
+            .collect(),
+        Combiner::Struct => (0..index(ints[0]))
+            .map(|i| (addrs[i], ints[1 + i], child(i)))
+            .collect(),
+        Combiner::Subarray => subarray_blocks(ints, child(0)),
+        _ => return None,
+    };
+    Some(blocks)
+}
+
+/// The elements of a subarray, one block of one element each
+fn subarray_blocks(ints: &[Count], oldtype: MPI_Datatype) -> Vec<(Address, Count, MPI_Datatype)> {
+    let ndims: usize = ints[0]
+        .value_as()
+        .expect("Number of dimensions cannot be expressed as a usize.");
+    let sizes = &ints[1..1 + ndims];
+    let subsizes = &ints[1 + ndims..1 + 2 * ndims];
+    let starts = &ints[1 + 2 * ndims..1 + 3 * ndims];
+    let row_major = ints[1 + 3 * ndims] == unsafe { ffi::RSMPI_ORDER_C };
+    // The dimensions from the fastest to the slowest varying one
+    let mut dims: Vec<usize> = (0..ndims).collect();
+    if row_major {
+        dims.reverse();
+    }
+
+    let extent = raw_extent(oldtype);
+    let mut blocks = Vec::new();
+    let mut position: Vec<Count> = vec![0; ndims];
+    if subsizes.iter().any(|&subsize| subsize == 0) {
+        return blocks;
+    }
+    loop {
+        let mut element: Address = 0;
+        for &dim in dims.iter().rev() {
+            element = element * address(sizes[dim]) + address(starts[dim] + position[dim]);
+        }
+        blocks.push((element * extent, 1, oldtype));
+
+        // Advance the position like an odometer, the fastest varying dimension first.
+        let mut carry = true;
+        for &dim in &dims {
+            position[dim] += 1;
+            if position[dim] < subsizes[dim] {
+                carry = false;
+                break;
+            }
+            position[dim] = 0;
+        }
+        if carry {
+            return blocks;
+        }
+    }
+}
+
+/// The extent of `datatype`
+fn raw_extent(datatype: MPI_Datatype) -> Address {
+    unsafe { with_uninitialized2(|lb, extent| ffi::MPI_Type_get_extent(datatype, lb, extent)).2 }
+}
+
+fn address(n: Count) -> Address {
+    n.value_as()
+        .expect("Count cannot be expressed as an MPI Address.")
+}
+
+/// Append a description of the type signature of `datatype` to `signature`.
+///
+/// The description lists the combiners used to construct `datatype` together with their integer
+/// and address arguments, down to the named datatypes, which are described by their name and
+/// size. It does not depend on the values of the handles, so it can be compared between
+/// processes.
+pub(crate) fn write_signature(datatype: MPI_Datatype, signature: &mut Vec<u8>) {
+    let decoded = decode_datatype(datatype);
+    signature.extend_from_slice(decoded.combiner.name().as_bytes());
+    if decoded.combiner == Combiner::Named {
+        let size: Count =
+            unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 };
+        signature.extend_from_slice(datatype_name(datatype).as_bytes());
+        signature.extend_from_slice(&size.to_le_bytes());
+        return;
+    }
+
+    for integer in &decoded.integers {
+        signature.extend_from_slice(&integer.to_le_bytes());
+    }
+    for address in &decoded.addresses {
+        signature.extend_from_slice(&address.to_le_bytes());
+    }
+    for datatype in &decoded.datatypes {
+        write_signature(datatype.as_raw(), signature);
+    }
+}
+
+/// The name of `datatype`, e.g. `"MPI_INT"` for a named datatype
+fn datatype_name(datatype: MPI_Datatype) -> String {
+    type BufType = [c_char; ffi::MPI_MAX_OBJECT_NAME as usize];
+
+    unsafe {
+        let mut buf = mem::MaybeUninit::<BufType>::uninit();
+        with_uninitialized(|resultlen| {
+            ffi::MPI_Type_get_name(datatype, &mut (*buf.as_mut_ptr())[0], resultlen)
+        });
+        CStr::from_ptr(buf.assume_init().as_ptr())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Format `datatype` for `Debug` as a struct named `type_name` with the combiner, name, size and
+/// extent of the datatype
+fn fmt_datatype(f: &mut fmt::Formatter, type_name: &str, datatype: MPI_Datatype) -> fmt::Result {
+    let mut debug = f.debug_struct(type_name);
+    if datatype == unsafe { ffi::RSMPI_DATATYPE_NULL } {
+        return debug.field("datatype", &"MPI_DATATYPE_NULL").finish();
+    }
+    debug.field("combiner", &combiner_name(datatype));
+    let name = datatype_name(datatype);
+    if !name.is_empty() {
+        debug.field("name", &name);
+    }
+    let size: Count = unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 };
+    let (_, lb, extent) =
+        unsafe { with_uninitialized2(|lb, extent| ffi::MPI_Type_get_extent(datatype, lb, extent)) };
+    debug
+        .field("size", &size)
+        .field("lower_bound", &lb)
+        .field("extent", &extent)
+        .finish()
+}
+
+/// A system datatype, e.g. `MPI_FLOAT`
+///
+/// # Standard section(s)
+///
+/// 3.2.2
+pub type SystemDatatype = DatatypeRef<'static>;
+
+/// A direct equivalence exists between the implementing type and an MPI datatype
+///
+/// `char` does not implement `Equivalence`. Receiving into a `char` would accept any 32 bit value
+/// sent by a peer, including values that are not Unicode scalar values. Send `u32::from(c)`
+/// instead and convert the received values with `std::char::from_u32()`, which checks them.
+///
+/// # Standard section(s)
+///
+/// 3.2.2
+pub unsafe trait Equivalence {
+    /// The type of the equivalent MPI datatype (e.g. `SystemDatatype` or `UserDatatype`)
+    type Out: Datatype;
+    /// The MPI datatype that is equivalent to this Rust type
+    fn equivalent_datatype() -> Self::Out;
+}
+
+macro_rules! equivalent_system_datatype {
+    ($rstype:path, $mpitype:path) => {
+        unsafe impl Equivalence for $rstype {
+            type Out = SystemDatatype;
+            #[inline]
+            fn equivalent_datatype() -> Self::Out {
+                unsafe { DatatypeRef::from_raw($mpitype) }
+            }
+        }
+    };
+}
+
+// MPI only matches messages of `MPI_C_BOOL` with receives of `MPI_C_BOOL`, and a C `_Bool` holds
+// 0 or 1, like a Rust `bool`. `char` has no such datatype, see `Equivalence`.
+equivalent_system_datatype!(bool, ffi::RSMPI_C_BOOL);
+
+equivalent_system_datatype!(f32, ffi::RSMPI_FLOAT);
+equivalent_system_datatype!(f64, ffi::RSMPI_DOUBLE);
+
+#[cfg(feature = "complex")]
+equivalent_system_datatype!(num_complex::Complex32, ffi::RSMPI_C_FLOAT_COMPLEX);
+#[cfg(feature = "complex")]
+equivalent_system_datatype!(num_complex::Complex64, ffi::RSMPI_C_DOUBLE_COMPLEX);
+
+equivalent_system_datatype!(i8, ffi::RSMPI_INT8_T);
+equivalent_system_datatype!(i16, ffi::RSMPI_INT16_T);
+equivalent_system_datatype!(i32, ffi::RSMPI_INT32_T);
+equivalent_system_datatype!(i64, ffi::RSMPI_INT64_T);
+
+equivalent_system_datatype!(u8, ffi::RSMPI_UINT8_T);
+equivalent_system_datatype!(u16, ffi::RSMPI_UINT16_T);
+equivalent_system_datatype!(u32, ffi::RSMPI_UINT32_T);
+equivalent_system_datatype!(u64, ffi::RSMPI_UINT64_T);
+
+#[cfg(target_pointer_width = "32")]
+equivalent_system_datatype!(usize, ffi::RSMPI_UINT32_T);
+#[cfg(target_pointer_width = "32")]
+equivalent_system_datatype!(isize, ffi::RSMPI_INT32_T);
+
+#[cfg(target_pointer_width = "64")]
+equivalent_system_datatype!(usize, ffi::RSMPI_UINT64_T);
+#[cfg(target_pointer_width = "64")]
+equivalent_system_datatype!(isize, ffi::RSMPI_INT64_T);
+
+/// A fixed-width text field of `N` bytes
+///
+/// Records of Fortran and C codes often contain text columns of a fixed width, padded with spaces
+/// (Fortran `CHARACTER(LEN=N)`) or NUL characters (C `char[N]`). A `FixedStr<N>` has the same
+/// layout and is equivalent to `N` repetitions of `MPI_CHAR`, so it can be used as a field of
+/// derived datatypes that are exchanged with such codes.
+///
+/// # Examples
+///
+/// See `examples/fixed_str.rs`
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct FixedStr<const N: usize>([u8; N]);
+
+impl<const N: usize> FixedStr<N> {
+    /// A text field containing `s`, padded with NUL characters as in C
+    ///
+    /// Panics if `s` is longer than `N` bytes.
+    pub fn nul_padded(s: &str) -> Self {
+        FixedStr::padded(s, 0)
+    }
+
+    /// A text field containing `s`, padded with spaces as in Fortran
+    ///
+    /// Panics if `s` is longer than `N` bytes.
+    pub fn space_padded(s: &str) -> Self {
+        FixedStr::padded(s, b' ')
+    }
+
+    fn padded(s: &str, padding: u8) -> Self {
+        assert!(
+            s.len() <= N,
+            "String of {} bytes does not fit into a text field of {} bytes.",
+            s.len(),
+            N
+        );
+        let mut bytes = [padding; N];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        FixedStr(bytes)
+    }
+
+    /// A text field with the raw contents `bytes`
+    pub fn from_bytes(bytes: [u8; N]) -> Self {
+        FixedStr(bytes)
+    }
+
+    /// The raw contents of the text field, including the padding
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// The contents of the text field without the padding
+    ///
+    /// The contents end at the first NUL character, trailing spaces are removed as well.
+    pub fn trimmed(&self) -> &[u8] {
+        let end = self.0.iter().position(|&b| b == 0).unwrap_or(N);
+        let end = self.0[..end]
+            .iter()
+            .rposition(|&b| b != b' ')
+            .map_or(0, |last| last + 1);
+        &self.0[..end]
+    }
+
+    /// The contents of the text field without the padding as a string slice
+    pub fn to_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.trimmed())
+    }
+
+    /// The contents of the text field without the padding, with invalid UTF-8 sequences replaced
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.trimmed())
+    }
+}
+
+impl<const N: usize> Default for FixedStr<N> {
+    fn default() -> Self {
+        FixedStr([0; N])
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("FixedStr")
+            .field(&self.to_string_lossy())
+            .finish()
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+/// The datatypes of the text fields of every width in use, keyed by the width
+static FIXED_STR_DATATYPES: Lazy<Mutex<HashMap<usize, UserDatatype>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+unsafe impl<const N: usize> Equivalence for FixedStr<N> {
+    type Out = DatatypeRef<'static>;
+    fn equivalent_datatype() -> Self::Out {
+        let mut datatypes = FIXED_STR_DATATYPES
+            .lock()
+            .expect("rsmpi internal error: FIXED_STR_DATATYPES lock poisoned");
+        let datatype = datatypes.entry(N).or_insert_with(|| {
+            let count: Count = N
+                .value_as()
+                .expect("Width of text field cannot be expressed as a Count.");
+            UserDatatype::contiguous(count, &unsafe { DatatypeRef::from_raw(ffi::RSMPI_CHAR) })
+        });
+        // The datatypes are never removed from the map, so they live as long as the program.
+        unsafe { DatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+/// The datatypes of the arrays of every element type and length in use
+static ARRAY_DATATYPES: Lazy<Mutex<HashMap<(any::TypeId, usize), UserDatatype>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// An array of `N` elements is equivalent to `N` contiguous instances of the datatype of its
+/// elements.
+unsafe impl<T, const N: usize> Equivalence for [T; N]
+where
+    T: 'static + Equivalence,
+{
+    type Out = DatatypeRef<'static>;
+    fn equivalent_datatype() -> Self::Out {
+        let key = (any::TypeId::of::<T>(), N);
+        if let Some(datatype) = array_datatypes().get(&key) {
+            return unsafe { DatatypeRef::from_raw(datatype.as_raw()) };
+        }
+
+        // The lock is not held while the datatype of the elements is built, since the elements
+        // can be arrays themselves.
+        let count: Count = N
+            .value_as()
+            .expect("Length of array cannot be expressed as a Count.");
+        let datatype = UserDatatype::contiguous(count, &T::equivalent_datatype());
+
+        let mut datatypes = array_datatypes();
+        let datatype = datatypes.entry(key).or_insert(datatype);
+        // The datatypes are never removed from the map, so they live as long as the program.
+        unsafe { DatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+fn array_datatypes() -> MutexGuard<'static, HashMap<(any::TypeId, usize), UserDatatype>> {
+    ARRAY_DATATYPES
+        .lock()
+        .expect("rsmpi internal error: ARRAY_DATATYPES lock poisoned")
+}
+
+/// The datatypes of the tuples of every combination of element types in use
+static TUPLE_DATATYPES: Lazy<Mutex<HashMap<any::TypeId, UserDatatype>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn tuple_datatypes() -> MutexGuard<'static, HashMap<any::TypeId, UserDatatype>> {
+    TUPLE_DATATYPES
+        .lock()
+        .expect("rsmpi internal error: TUPLE_DATATYPES lock poisoned")
+}
+
+fn tuple_address(offset: usize) -> Address {
+    offset
+        .value_as()
+        .expect("Offset of tuple field cannot be expressed as an Address.")
+}
+
+/// A tuple is equivalent to a struct datatype of its fields at their offsets, resized to the size
+/// of the tuple, whose layout is chosen by the compiler.
+macro_rules! equivalent_tuple {
+    ($($t:ident $field:tt),+) => {
+        unsafe impl<$($t),+> Equivalence for ($($t,)+)
+        where
+            $($t: 'static + Equivalence),+
+        {
+            type Out = DatatypeRef<'static>;
+            fn equivalent_datatype() -> Self::Out {
+                let key = any::TypeId::of::<Self>();
+                if let Some(datatype) = tuple_datatypes().get(&key) {
+                    return unsafe { DatatypeRef::from_raw(datatype.as_raw()) };
+                }
+
+                // The lock is not held while the datatypes of the fields are built, since the
+                // fields can be tuples themselves.
+                let displacements =
+                    [$(tuple_address(memoffset::offset_of_tuple!(Self, $field))),+];
+                let datatypes = ($($t::equivalent_datatype(),)+);
+                let types = [$(unsafe {
+                    UncommittedDatatypeRef::from_raw(datatypes.$field.as_raw())
+                }),+];
+                let datatype = UserDatatype::resized(
+                    &UncommittedUserDatatype::structured(
+                        &vec![1; types.len()],
+                        &displacements,
+                        &types,
+                    ),
+                    0,
+                    tuple_address(mem::size_of::<Self>()),
+                );
+
+                let mut datatypes = tuple_datatypes();
+                let datatype = datatypes.entry(key).or_insert(datatype);
+                // The datatypes are never removed from the map, so they live as long as the
+                // program.
+                unsafe { DatatypeRef::from_raw(datatype.as_raw()) }
+            }
+        }
+    };
+}
+
+equivalent_tuple!(A 0);
+equivalent_tuple!(A 0, B 1);
+equivalent_tuple!(A 0, B 1, C 2);
+equivalent_tuple!(A 0, B 1, C 2, D 3);
+equivalent_tuple!(A 0, B 1, C 2, D 3, E 4);
+equivalent_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+equivalent_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+equivalent_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Storage order of multi-dimensional arrays
+///
+/// # Standard section(s)
+///
+/// 4.1.3
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Order {
+    /// The last dimension varies fastest, as in C and Rust (`MPI_ORDER_C`)
+    RowMajor,
+    /// The first dimension varies fastest, as in Fortran (`MPI_ORDER_FORTRAN`)
+    ColumnMajor,
+}
+
+impl Order {
+    /// The raw value understood by the MPI C API
+    fn as_raw(self) -> c_int {
+        match self {
+            Order::RowMajor => unsafe { ffi::RSMPI_ORDER_C },
+            Order::ColumnMajor => unsafe { ffi::RSMPI_ORDER_FORTRAN },
+        }
+    }
+}
+
+/// Distribution of one dimension of an array over the processes of a process grid
+///
+/// # Standard section(s)
+///
+/// 4.1.4
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Distribution {
+    /// Contiguous blocks of equal size, one per process (`MPI_DISTRIBUTE_BLOCK`)
+    Block,
+    /// Blocks of the given size dealt out to the processes round-robin
+    /// (`MPI_DISTRIBUTE_CYCLIC`)
+    Cyclic(Count),
+    /// The dimension is not distributed (`MPI_DISTRIBUTE_NONE`)
+    None,
+}
+
+impl Distribution {
+    /// The raw distribution and distribution argument understood by the MPI C API
+    fn as_raw(self) -> (c_int, c_int) {
+        unsafe {
+            match self {
+                Distribution::Block => {
+                    (ffi::RSMPI_DISTRIBUTE_BLOCK, ffi::RSMPI_DISTRIBUTE_DFLT_DARG)
+                }
+                Distribution::Cyclic(block) => (ffi::RSMPI_DISTRIBUTE_CYCLIC, block),
+                Distribution::None => (ffi::RSMPI_DISTRIBUTE_NONE, ffi::RSMPI_DISTRIBUTE_DFLT_DARG),
+            }
+        }
+    }
+}
+
+/// A user defined MPI datatype
+///
+/// Cloning a `UserDatatype` duplicates the underlying MPI datatype via `MPI_Type_dup()`. To share
+/// a single datatype handle between several owners instead, convert it into a `SharedDatatype`.
+///
+/// # Standard section(s)
+///
+/// 4
+pub struct UserDatatype(MPI_Datatype);
+
+impl UserDatatype {
+    /// Constructs a new datatype by concatenating `count` repetitions of `oldtype`
+    ///
+    /// # Examples
+    /// See `examples/contiguous.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn contiguous<D>(count: Count, oldtype: &D) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::contiguous(count, oldtype).commit()
+    }
+
+    /// Construct a new datatype out of `count` blocks of `blocklength` elements of `oldtype`
+    /// concatenated with the start of consecutive blocks placed `stride` elements apart.
+    ///
+    /// # Examples
+    /// See `examples/vector.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn vector<D>(count: Count, blocklength: Count, stride: Count, oldtype: &D) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::vector(count, blocklength, stride, oldtype).commit()
+    }
+
+    /// Constructs a new datatype with the type map of `oldtype`, but a lower bound of
+    /// `lower_bound` and an extent of `extent` bytes
+    ///
+    /// Consecutive instances of the new datatype are placed `extent` bytes apart, e.g. to
+    /// interleave the columns of a row-major matrix described by a vector datatype whose extent
+    /// is shrunk to a single element.
+    ///
+    /// # Examples
+    /// See `examples/resized.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.7
+    pub fn resized<D>(oldtype: &D, lower_bound: Address, extent: Address) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::resized(oldtype, lower_bound, extent).commit()
+    }
+
+    /// Like `vector()` but `stride` is given in bytes rather than elements of `oldtype`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_vector<D>(
+        count: Count,
+        blocklength: Count,
+        stride: Address,
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::heterogeneous_vector(count, blocklength, stride, oldtype).commit()
+    }
+
+    /// Constructs a new type out of multiple blocks of individual length and displacement.
+    /// Block `i` will be `blocklengths[i]` items of datytpe `oldtype` long and displaced by
+    /// `dispplacements[i]` items of the `oldtype`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn indexed<D>(blocklengths: &[Count], displacements: &[Count], oldtype: &D) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::indexed(blocklengths, displacements, oldtype).commit()
+    }
+
+    /// Constructs a new type out of multiple blocks of individual length and displacement.
+    /// Block `i` will be `blocklengths[i]` items of datytpe `oldtype` long and displaced by
+    /// `dispplacements[i]` bytes.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_indexed<D>(
+        blocklengths: &[Count],
+        displacements: &[Address],
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::heterogeneous_indexed(blocklengths, displacements, oldtype)
+            .commit()
+    }
+
+    /// Construct a new type out of blocks of the same length and individual displacements.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn indexed_block<D>(
+        blocklength: Count,
+        displacements: &[Count],
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::indexed_block(blocklength, displacements, oldtype).commit()
+    }
+
+    /// Construct a new type out of blocks of the same length and individual displacements.
+    /// Displacements are in bytes.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_indexed_block<D>(
+        blocklength: Count,
+        displacements: &[Address],
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::heterogeneous_indexed_block(blocklength, displacements, oldtype)
+            .commit()
+    }
+
+    /// Constructs a new datatype out of blocks of different length, displacement and datatypes
+    ///
+    /// See `UncommittedUserDatatype::structured()` for the layout of the blocks.
+    ///
+    /// # Examples
+    /// See `examples/structured.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn structured<D>(
+        blocklengths: &[Count],
+        displacements: &[Address],
+        types: &[D],
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype + MatchesRaw<Raw = MPI_Datatype>,
+    {
+        UncommittedUserDatatype::structured(blocklengths, displacements, types).commit()
+    }
+
+    /// Constructs a new datatype describing an n-dimensional subarray of an n-dimensional array of
+    /// `oldtype`.
+    ///
+    /// The full array has extent `sizes[i]` in dimension `i`, the subarray has extent
+    /// `subsizes[i]` and starts at index `starts[i]`. `order` specifies the storage order of the
+    /// full array.
+    ///
+    /// # Examples
+    /// See `examples/subarray.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.3
+    pub fn subarray<D>(
+        sizes: &[Count],
+        subsizes: &[Count],
+        starts: &[Count],
+        order: Order,
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::subarray(sizes, subsizes, starts, order, oldtype).commit()
+    }
+
+    /// Constructs a new datatype describing the part of an n-dimensional array of `oldtype` that
+    /// process `rank` of a grid of `size` processes holds.
+    ///
+    /// The full array has extent `gsizes[i]` in dimension `i`, which is distributed over
+    /// `psizes[i]` processes according to `distribs[i]`. The processes are arranged in the grid
+    /// in row-major order. `order` specifies the storage order of the full array.
+    ///
+    /// # Examples
+    /// See `examples/container.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.4
+    pub fn distributed_array<D>(
+        size: Rank,
+        rank: Rank,
+        gsizes: &[Count],
+        distribs: &[Distribution],
+        psizes: &[Count],
+        order: Order,
+        oldtype: &D,
+    ) -> UserDatatype
+    where
+        D: UncommittedDatatype,
+    {
+        UncommittedUserDatatype::distributed_array(
+            size, rank, gsizes, distribs, psizes, order, oldtype,
+        )
+        .commit()
+    }
+
+    /// Creates a DatatypeRef from this datatype object.
+    pub fn as_ref(&self) -> DatatypeRef<'_> {
+        unsafe { DatatypeRef::from_raw(self.as_raw()) }
+    }
+}
+
+// TODO and NOTE: These impls are not 100% implemented, but reflect the larger reality that the
+// current API does not strongly enforce the threading requirements of MPI's `MPI_Init_thread`
+// function. In order to enforce uniformity across platforms, these traits are explicitly applied.
+// Due to the nature of MPI implementations, without this declaration some implementations of
+// MPI would otherwise be Send + Sync by default, and others would be !Sync by default.
+unsafe impl Send for UserDatatype {}
+unsafe impl Sync for UserDatatype {}
+
+impl Clone for UserDatatype {
+    fn clone(&self) -> Self {
+        self.dup()
+    }
+}
+
+impl Drop for UserDatatype {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("UserDatatype") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Type_free(&mut self.0);
+        }
+        assert_eq!(self.0, unsafe { ffi::RSMPI_DATATYPE_NULL });
+    }
+}
+
+unsafe impl AsRaw for UserDatatype {
+    type Raw = MPI_Datatype;
+    fn as_raw(&self) -> Self::Raw {
+        self.0
+    }
+}
+
+impl FromRaw for UserDatatype {
+    unsafe fn from_raw(handle: MPI_Datatype) -> Self {
+        assert_ne!(handle, ffi::RSMPI_DATATYPE_NULL);
+        UserDatatype(handle)
+    }
+}
+
+unsafe impl MatchesRaw for UserDatatype {}
+
+impl fmt::Debug for UserDatatype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "UserDatatype", self.0)
+    }
+}
+
+impl Datatype for UserDatatype {}
+impl UncommittedDatatype for UserDatatype {
+    type DuplicatedDatatype = UserDatatype;
+}
+
+/// A reference-counted handle to a committed user defined datatype
+///
+/// Clones of a `SharedDatatype` refer to the same MPI datatype, which is freed once the last
+/// clone is dropped. This avoids duplicating the datatype when it is stored in several data
+/// structures or passed to several threads.
+///
+/// # Examples
+/// See `examples/shared_datatype.rs`
+#[derive(Clone)]
+pub struct SharedDatatype(Arc<UserDatatype>);
+
+impl SharedDatatype {
+    /// The number of handles that share the datatype
+    pub fn handle_count(this: &SharedDatatype) -> usize {
+        Arc::strong_count(&this.0)
+    }
+
+    /// A reference to the shared datatype
+    pub fn as_ref(&self) -> DatatypeRef<'_> {
+        self.0.as_ref()
+    }
+}
+
+impl From<UserDatatype> for SharedDatatype {
+    fn from(datatype: UserDatatype) -> Self {
+        SharedDatatype(Arc::new(datatype))
+    }
+}
+
+unsafe impl AsRaw for SharedDatatype {
+    type Raw = MPI_Datatype;
+    fn as_raw(&self) -> Self::Raw {
+        self.0.as_raw()
+    }
+}
+
+unsafe impl MatchesRaw for SharedDatatype {}
+
+impl fmt::Debug for SharedDatatype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "SharedDatatype", self.as_raw())
+    }
+}
+
+impl Datatype for SharedDatatype {}
+impl UncommittedDatatype for SharedDatatype {
+    type DuplicatedDatatype = UserDatatype;
+}
+
+impl<'a> From<&'a UserDatatype> for DatatypeRef<'a> {
+    fn from(datatype: &'a UserDatatype) -> Self {
+        unsafe { DatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+impl<'a> From<&'a UserDatatype> for UncommittedDatatypeRef<'a> {
+    fn from(datatype: &'a UserDatatype) -> Self {
+        unsafe { UncommittedDatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+/// Represents an MPI datatype that has not yet been committed. Can be used to build up more complex
+/// datatypes before committing.
+///
+/// UncommittedUserDatatype does not implement Datatype - it cannot be used as a datatype, and must be
+/// commited to retrieve a UserDatatype that implements Datatype.
+///
+/// # Standard section(s)
+/// 4.1.9
+pub struct UncommittedUserDatatype(MPI_Datatype);
+
+impl UncommittedUserDatatype {
+    /// Constructs a new datatype with the type map of `oldtype`, but a lower bound of
+    /// `lower_bound` and an extent of `extent` bytes
+    ///
+    /// # Examples
+    /// See `examples/resized.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.7
+    pub fn resized<D>(oldtype: &D, lower_bound: Address, extent: Address) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_resized(oldtype.as_raw(), lower_bound, extent, newtype)
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new datatype by concatenating `count` repetitions of `oldtype`
+    ///
+    /// # Examples
+    /// See `examples/contiguous.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn contiguous<D>(count: Count, oldtype: &D) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_contiguous(count, oldtype.as_raw(), newtype)
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Construct a new datatype out of `count` blocks of `blocklength` elements of `oldtype`
+    /// concatenated with the start of consecutive blocks placed `stride` elements apart.
+    ///
+    /// # Examples
+    /// See `examples/vector.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn vector<D>(count: Count, blocklength: Count, stride: Count, oldtype: &D) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_vector(count, blocklength, stride, oldtype.as_raw(), newtype)
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Like `vector()` but `stride` is given in bytes rather than elements of `oldtype`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_vector<D>(
+        count: Count,
+        blocklength: Count,
+        stride: Address,
+        oldtype: &D,
+    ) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_hvector(
+                        count,
+                        blocklength,
+                        stride,
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new type out of multiple blocks of individual length and displacement.
+    /// Block `i` will be `blocklengths[i]` items of datytpe `oldtype` long and displaced by
+    /// `dispplacements[i]` items of the `oldtype`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn indexed<D>(blocklengths: &[Count], displacements: &[Count], oldtype: &D) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        assert_eq!(
+            blocklengths.len(),
+            displacements.len(),
+            "'blocklengths' and 'displacements' must be the same length"
+        );
+
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_indexed(
+                        blocklengths.count(),
+                        blocklengths.as_ptr(),
+                        displacements.as_ptr(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new type out of multiple blocks of individual length and displacement.
+    /// Block `i` will be `blocklengths[i]` items of datytpe `oldtype` long and displaced by
+    /// `dispplacements[i]` bytes.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_indexed<D>(
+        blocklengths: &[Count],
+        displacements: &[Address],
+        oldtype: &D,
+    ) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        assert_eq!(
+            blocklengths.len(),
+            displacements.len(),
+            "'blocklengths' and 'displacements' must be the same length"
+        );
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_hindexed(
+                        blocklengths.count(),
+                        blocklengths.as_ptr(),
+                        displacements.as_ptr(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Construct a new type out of blocks of the same length and individual displacements.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn indexed_block<D>(blocklength: Count, displacements: &[Count], oldtype: &D) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_indexed_block(
+                        displacements.count(),
+                        blocklength,
+                        displacements.as_ptr(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Construct a new type out of blocks of the same length and individual displacements.
+    /// Displacements are in bytes.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn heterogeneous_indexed_block<D>(
+        blocklength: Count,
+        displacements: &[Address],
+        oldtype: &D,
+    ) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_hindexed_block(
+                        displacements.count(),
+                        blocklength,
+                        displacements.as_ptr(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new datatype out of blocks of different length, displacement and datatypes
+    ///
+    /// Block `i` consists of `blocklengths[i]` instances of `types[i]` starting `displacements[i]`
+    /// bytes after the start of the datatype, e.g. at the `offset_of!` a field of a `#[repr(C)]`
+    /// struct. Blocks of different datatypes are passed as `UncommittedDatatypeRef`s, e.g.
+    /// `i32::equivalent_datatype().into()` or `UncommittedDatatypeRef::from(&user_type)`.
+    ///
+    /// # Examples
+    /// See `examples/structured.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.2
+    pub fn structured<D>(blocklengths: &[Count], displacements: &[Address], types: &[D]) -> Self
+    where
+        D: UncommittedDatatype + MatchesRaw<Raw = MPI_Datatype>,
+    {
+        assert_eq!(
+            blocklengths.len(),
+            displacements.len(),
+            "'displacements', 'blocklengths', and 'types' must be the same length"
+        );
+        assert_eq!(
+            blocklengths.len(),
+            types.len(),
+            "'displacements', 'blocklengths', and 'types' must be the same length"
+        );
+
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_struct(
+                        blocklengths.count(),
+                        blocklengths.as_ptr(),
+                        displacements.as_ptr(),
+                        types.as_ptr() as *const _,
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new datatype describing an n-dimensional subarray of an n-dimensional array of
+    /// `oldtype`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.3
+    pub fn subarray<D>(
+        sizes: &[Count],
+        subsizes: &[Count],
+        starts: &[Count],
+        order: Order,
+        oldtype: &D,
+    ) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        assert_eq!(
+            sizes.len(),
+            subsizes.len(),
+            "'sizes', 'subsizes', and 'starts' must be the same length"
+        );
+        assert_eq!(
+            sizes.len(),
+            starts.len(),
+            "'sizes', 'subsizes', and 'starts' must be the same length"
+        );
+        assert!(
+            sizes.iter().zip(subsizes.iter().zip(starts.iter())).all(
+                |(&size, (&subsize, &start))| subsize >= 1 && start >= 0 && start + subsize <= size
+            ),
+            "Subarray must lie within the full array"
+        );
+
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_subarray(
+                        sizes.count(),
+                        sizes.as_ptr(),
+                        subsizes.as_ptr(),
+                        starts.as_ptr(),
+                        order.as_raw(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Constructs a new datatype describing the part of an n-dimensional array of `oldtype` that
+    /// process `rank` of a grid of `size` processes holds.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.4
+    pub fn distributed_array<D>(
+        size: Rank,
+        rank: Rank,
+        gsizes: &[Count],
+        distribs: &[Distribution],
+        psizes: &[Count],
+        order: Order,
+        oldtype: &D,
+    ) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        assert_eq!(
+            gsizes.len(),
+            distribs.len(),
+            "'gsizes', 'distribs', and 'psizes' must be the same length"
+        );
+        assert_eq!(
+            gsizes.len(),
+            psizes.len(),
+            "'gsizes', 'distribs', and 'psizes' must be the same length"
+        );
+        assert_eq!(
+            psizes.iter().product::<Count>(),
+            size,
+            "The process grid must contain exactly 'size' processes"
+        );
+        assert!(
+            0 <= rank && rank < size,
+            "Rank {} is out of range for a process grid of size {}.",
+            rank,
+            size
+        );
+
+        let (distribs, dargs): (Vec<c_int>, Vec<c_int>) =
+            distribs.iter().map(|distrib| distrib.as_raw()).unzip();
+        unsafe {
+            UncommittedUserDatatype(
+                with_uninitialized(|newtype| {
+                    ffi::MPI_Type_create_darray(
+                        size,
+                        rank,
+                        gsizes.count(),
+                        gsizes.as_ptr(),
+                        distribs.as_ptr(),
+                        dargs.as_ptr(),
+                        psizes.as_ptr(),
+                        order.as_raw(),
+                        oldtype.as_raw(),
+                        newtype,
+                    )
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Commits a datatype to a specific representation so that it can be used in MPI calls.
+    ///
+    /// # Standard section(s)
+    /// 4.1.9
+    pub fn commit(mut self) -> UserDatatype {
+        let handle = self.0;
+        unsafe {
+            ffi::MPI_Type_commit(&mut self.0);
+        }
+        mem::forget(self);
+        UserDatatype(handle)
+    }
+
+    /// Creates an UncommittedDatatypeRef from this datatype object.
+    pub fn as_ref(&self) -> UncommittedDatatypeRef<'_> {
+        unsafe { UncommittedDatatypeRef::from_raw(self.as_raw()) }
+    }
+}
+
+impl Clone for UncommittedUserDatatype {
+    fn clone(&self) -> Self {
+        self.dup()
+    }
+}
+
+impl Drop for UncommittedUserDatatype {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("UncommittedUserDatatype") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Type_free(&mut self.0);
+        }
+        assert_eq!(self.0, unsafe { ffi::RSMPI_DATATYPE_NULL });
+    }
+}
+
+unsafe impl AsRaw for UncommittedUserDatatype {
+    type Raw = MPI_Datatype;
+    fn as_raw(&self) -> Self::Raw {
+        self.0
+    }
+}
+
+impl FromRaw for UncommittedUserDatatype {
+    unsafe fn from_raw(handle: MPI_Datatype) -> Self {
+        assert_ne!(handle, ffi::RSMPI_DATATYPE_NULL);
+        UncommittedUserDatatype(handle)
+    }
+}
+
+unsafe impl MatchesRaw for UncommittedUserDatatype {}
+
+impl fmt::Debug for UncommittedUserDatatype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_datatype(f, "UncommittedUserDatatype", self.0)
+    }
+}
+
+impl UncommittedDatatype for UncommittedUserDatatype {
+    type DuplicatedDatatype = UncommittedUserDatatype;
+}
+
+impl<'a> From<&'a UncommittedUserDatatype> for UncommittedDatatypeRef<'a> {
+    fn from(datatype: &'a UncommittedUserDatatype) -> Self {
+        unsafe { UncommittedDatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+/// Builds the struct datatype equivalent to a type `T` from a list of its fields.
+///
+/// Every field is described by its offset within `T`, e.g. obtained via `memoffset::offset_of!`,
+/// the number of consecutive elements it holds and their datatype. The builder checks that the
+/// fields are listed in order of their offsets, do not overlap and lie within
+/// `mem::size_of::<T>()` bytes. The extent of the resulting datatype is resized to
+/// `mem::size_of::<T>()`, so that it also describes slices of `T` correctly when `T` ends in
+/// padding, is `#[repr(C, packed)]` or is over-aligned with `#[repr(align(N))]`. The derive macro
+/// `Equivalence` sets the extent in the same way.
+///
+/// This is an alternative to `#[derive(Equivalence)]` for implementing `Equivalence` by hand.
+///
+/// # Examples
+///
+/// See `examples/struct_layout.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.2, 4.1.7
+pub struct StructLayoutBuilder<'d, T> {
+    blocklengths: Vec<Count>,
+    displacements: Vec<Address>,
+    datatypes: Vec<UncommittedDatatypeRef<'d>>,
+    end: usize,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<'d, T> StructLayoutBuilder<'d, T> {
+    /// A builder for a datatype with no fields yet
+    pub fn new() -> Self {
+        StructLayoutBuilder {
+            blocklengths: Vec::new(),
+            displacements: Vec::new(),
+            datatypes: Vec::new(),
+            end: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Add a field of `count` elements of `datatype` at byte offset `offset` within `T`.
+    ///
+    /// Panics if the bytes the field touches, from the true lower bound of its first element to the
+    /// true upper bound of its last element, start before the end of the previous field or do not
+    /// lie within `T`.
+    pub fn field<D>(mut self, offset: usize, count: Count, datatype: &'d D) -> Self
+    where
+        D: UncommittedDatatype,
+    {
+        assert!(count > 0, "A field must hold at least one element.");
+        let size = mem::size_of::<T>();
+        let footprint = check_footprint(size, offset, count, datatype).unwrap_or_else(|error| {
+            panic!(
+                "The field at offset {} does not lie within `{}`: {}",
+                offset,
+                any::type_name::<T>(),
+                error
+            )
+        });
+        if let Some((lower, upper)) = footprint {
+            // The footprint lies within `T`, so its bounds can be expressed as a `usize`.
+            let lower: usize = lower
+                .value_as()
+                .expect("rsmpi internal error: field footprint exceeds a usize");
+            assert!(
+                lower >= self.end,
+                "The field at offset {} overlaps or precedes the previous field ending at \
+                 offset {}.",
+                offset,
+                self.end
+            );
+            self.end = upper
+                .value_as()
+                .expect("rsmpi internal error: field footprint exceeds a usize");
+        }
+
+        self.blocklengths.push(count);
+        self.displacements.push(
+            offset
+                .value_as()
+                .expect("Field offset cannot be expressed as an MPI Address."),
+        );
+        self.datatypes
+            .push(unsafe { UncommittedDatatypeRef::from_raw(datatype.as_raw()) });
+        self
+    }
+
+    /// Build the committed datatype with an extent of `mem::size_of::<T>()`.
+    pub fn build(self) -> UserDatatype {
+        let structured = UncommittedUserDatatype::structured(
+            &self.blocklengths,
+            &self.displacements,
+            &self.datatypes,
+        );
+        let extent: Address = mem::size_of::<T>()
+            .value_as()
+            .expect("Size of type cannot be expressed as an MPI Address.");
+        UncommittedUserDatatype::resized(&structured, 0, extent).commit()
+    }
+}
+
+impl<'d, T> Default for StructLayoutBuilder<'d, T> {
+    fn default() -> Self {
+        StructLayoutBuilder::new()
+    }
+}
+
+/// A Datatype describes the layout of messages in memory.
+///
+/// `Datatype` always represents a committed datatype that can be immediately used for sending and
+/// receiving messages. `UncommittedDatatype` is used for datatypes that are possibly uncommitted.
+pub trait Datatype: UncommittedDatatype {}
+impl<'a, D> Datatype for &'a D where D: 'a + Datatype {}
+
+/// An UncommittedDatatype is a partial description of the layout of messages in memory which may
+/// not yet have been committed to an implementation-defined message format.
+///
+/// Committed datatypes can be treated as-if they are uncommitted.
+pub trait UncommittedDatatype: AsRaw<Raw = MPI_Datatype> {
+    /// The type returned when the datatype is duplicated.
+    type DuplicatedDatatype: FromRaw<Raw = MPI_Datatype>;
+
+    /// Creates a new datatype with the same key-values as this datatype.
+    ///
+    /// # Standard section(s)
+    /// 4.1.10
+    fn dup(&self) -> Self::DuplicatedDatatype {
+        unsafe {
+            Self::DuplicatedDatatype::from_raw(
+                with_uninitialized(|newtype| ffi::MPI_Type_dup(self.as_raw(), newtype)).1,
+            )
+        }
+    }
+
+    /// The number of bytes of data in the type map of the datatype, excluding gaps
+    ///
+    /// This is the number of bytes a single instance of the datatype occupies in a message.
+    ///
+    /// # Examples
+    /// See `examples/datatype_extent.rs`
+    ///
+    /// # Standard section(s)
+    /// 4.1.5
+    fn size(&self) -> Count {
+        unsafe { with_uninitialized(|size| ffi::MPI_Type_size(self.as_raw(), size)).1 }
+    }
+
+    /// The lower bound and the extent of the datatype in bytes
+    ///
+    /// Consecutive instances of the datatype in a buffer are placed `extent` bytes apart, so a
+    /// buffer for `n` instances spans `n * extent` bytes starting `lower_bound` bytes after its
+    /// address.
+    ///
+    /// # Examples
+    /// See `examples/datatype_extent.rs`
+    ///
+    /// # Standard section(s)
+    /// 4.1.7
+    fn extent(&self) -> (Address, Address) {
+        unsafe {
+            let (_, lower_bound, extent) = with_uninitialized2(|lower_bound, extent| {
+                ffi::MPI_Type_get_extent(self.as_raw(), lower_bound, extent)
+            });
+            (lower_bound, extent)
+        }
+    }
+
+    /// The lower bound and the extent in bytes of the data the datatype actually touches
+    ///
+    /// Unlike `extent()`, this ignores the bounds set by resizing the datatype.
+    ///
+    /// # Examples
+    /// See `examples/datatype_extent.rs`
+    ///
+    /// # Standard section(s)
+    /// 4.1.8
+    fn true_extent(&self) -> (Address, Address) {
+        unsafe {
+            let (_, lower_bound, extent) = with_uninitialized2(|lower_bound, extent| {
+                ffi::MPI_Type_get_true_extent(self.as_raw(), lower_bound, extent)
+            });
+            (lower_bound, extent)
+        }
+    }
+
+    /// The constructor and arguments the datatype was created with
+    ///
+    /// The datatype arguments can be decoded in turn, down to the predefined datatypes.
+    ///
+    /// # Examples
+    /// See `examples/datatype_decode.rs`
+    ///
+    /// # Standard section(s)
+    /// 4.1.13
+    fn decode(&self) -> DecodedDatatype {
+        decode_datatype(self.as_raw())
+    }
+}
+impl<'a, D> UncommittedDatatype for &'a D
+where
+    D: 'a + UncommittedDatatype,
+{
+    type DuplicatedDatatype = <D as UncommittedDatatype>::DuplicatedDatatype;
+}
+
+/// Something that has an associated datatype
+pub unsafe trait AsDatatype {
+    /// The type of the associated MPI datatype (e.g. `SystemDatatype` or `UserDatatype`)
+    type Out: Datatype;
+    /// The associated MPI datatype
+    fn as_datatype(&self) -> Self::Out;
+}
+
+unsafe impl<T> AsDatatype for T
+where
+    T: Equivalence,
+{
+    type Out = <T as Equivalence>::Out;
+    #[inline]
+    fn as_datatype(&self) -> Self::Out {
+        <T as Equivalence>::equivalent_datatype()
+    }
+}
+
+unsafe impl<T> AsDatatype for [T]
+where
+    T: Equivalence,
+{
+    type Out = <T as Equivalence>::Out;
+    #[inline]
+    fn as_datatype(&self) -> Self::Out {
+        <T as Equivalence>::equivalent_datatype()
+    }
+}
+
+#[doc(hidden)]
+pub mod internal {
+    use std::mem;
+
+    use conv::ConvUtil;
+
+    use super::Combiner;
+    use crate::datatype::traits::*;
+    use crate::ffi;
+    use crate::raw::traits::*;
+    use crate::{with_uninitialized, with_uninitialized2, Address, Count};
+
+    /// Check that the extent, size and true extent of the datatype equivalent to `T` agree with
+    /// the memory layout of `T`. Used by `assert_equivalent_layout!`.
+    pub fn check_equivalent_layout<T: Equivalence>(type_name: &str) {
+        let datatype = T::equivalent_datatype();
+        let size_of: Address = mem::size_of::<T>()
+            .value_as()
+            .expect("Size of type cannot be expressed as an MPI Address.");
+
+        let (_, lb, extent) = unsafe {
+            with_uninitialized2(|lb, extent| {
+                ffi::MPI_Type_get_extent(datatype.as_raw(), lb, extent)
+            })
+        };
+        let (_, true_lb, true_extent) = unsafe {
+            with_uninitialized2(|lb, extent| {
+                ffi::MPI_Type_get_true_extent(datatype.as_raw(), lb, extent)
+            })
+        };
+        let size: Count =
+            unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype.as_raw(), size)).1 };
+
+        assert_eq!(
+            lb, 0,
+            "The datatype equivalent to `{}` has lower bound {} instead of 0.",
+            type_name, lb
+        );
+        assert_eq!(
+            extent, size_of,
+            "The datatype equivalent to `{0}` has extent {1} but `size_of::<{0}>()` is {2}, \
+             elements of a slice of `{0}` would be read from the wrong addresses.",
+            type_name, extent, size_of
+        );
+        assert!(
+            true_lb >= 0 && true_lb + true_extent <= size_of,
+            "The datatype equivalent to `{0}` covers bytes {1} to {2}, which lie outside of the \
+             {3} bytes of `{0}`.",
+            type_name,
+            true_lb,
+            true_lb + true_extent,
+            size_of
+        );
+        let size: Address = size
+            .value_as()
+            .expect("Size of datatype cannot be expressed as an MPI Address.");
+        assert!(
+            size <= size_of,
+            "The datatype equivalent to `{0}` transfers {1} bytes but `{0}` is only {2} bytes large.",
+            type_name,
+            size,
+            size_of
+        );
+    }
+
+    /// Check that the displacements of the struct datatype equivalent to `T` match the offsets of
+    /// the fields of `T` in declaration order. Used by `assert_equivalent_layout!`.
+    pub fn check_equivalent_offsets<T: Equivalence>(type_name: &str, fields: &[(&str, usize)]) {
+        let decoded = T::equivalent_datatype().decode();
+        assert_eq!(
+            decoded.combiner,
+            Combiner::Struct,
+            "The datatype equivalent to `{}` is not a struct datatype.",
+            type_name
+        );
+        let addresses = decoded.addresses;
+
+        assert_eq!(
+            addresses.len(),
+            fields.len(),
+            "The datatype equivalent to `{}` has {} blocks but {} fields were given.",
+            type_name,
+            addresses.len(),
+            fields.len()
+        );
+        for (&displacement, &(field, offset)) in addresses.iter().zip(fields) {
+            let offset: Address = offset
+                .value_as()
+                .expect("Field offset cannot be expressed as an MPI Address.");
+            assert_eq!(
+                displacement, offset,
+                "The datatype equivalent to `{}` places field `{}` at displacement {} but it is \
+                 at offset {}.",
+                type_name, field, displacement, offset
+            );
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    pub fn check_derive_equivalence_universe_state(type_name: &str) {
+        use crate::environment::UNIVERSE_STATE;
+
+        // Take lock on UNIVERSE_STATE to prevent racing with mpi::init and mpi::finalize.
+        let universe_state = UNIVERSE_STATE.read().unwrap();
+
+        if !crate::environment::is_initialized() {
+            panic!(
+                "\n\
+                 RSMPI PANIC: Pre-MPI_Init datatype initialization\n\
+                 \n\
+                 Application attempted to initialize datatype of #[derive(Equivalence)] for \
+                 `{}` before initializing rsmpi. You must first initialize rsmpi before attempting \
+                 to use a custom type in an MPI call.\n",
+                type_name
+            );
+        }
+
+        let universe_state = universe_state.as_ref().unwrap();
+
+        if crate::environment::is_finalized() {
+            panic!(
+                "\n\
+                 RSMPI PANIC: Post-MPI_Finalize datatype initialization.\n\
+                 \n\
+                 Application attempted to initialize datatype of #[derive(Equivalence)] for \
+                 `{0}` after finalizing rsmpi. You must not attempt to use `{0}` in an MPI context \
+                 after finalizing rsmpi.\n",
+                type_name
+            );
+        }
+
+        if crate::environment::threading_support() != crate::Threading::Multiple {
+            if universe_state.main_thread != std::thread::current().id() {
+                panic!(
+                    "\n\
+                     RSMPI PANIC: Invalid threaded datatype initialization\n\
+                     \n\
+                     Application attempted to initialize the datatype of #[derive(Equivalence)]
+                     for `{0}` from a different thread than that which initialized `rsmpi`. This \
+                     is only supported when rsmpi is initialized with \
+                     `mpi::Threading::Multiple`. Please explicitly call \
+                     `{0}::equivalent_datatype()` at least once from the same thread as you call \
+                     `rsmpi::initialize*`, or initialize MPI using \
+                     `mpi::initialize_with_threading(mpi::Threading::Multiple)`.\n",
+                    type_name
+                )
+            }
+        }
+    }
+}
+
+/// Assert that the MPI datatype equivalent to a type matches the memory layout of the type.
+///
+/// Checks that the extent of the datatype equals `mem::size_of()` of the type, that the data
+/// described by the datatype lies within the bounds of the type and, if a list of fields is
+/// given, that the displacements of the struct datatype equal the offsets of the fields in
+/// declaration order. This catches hand-written `Equivalence` implementations and datatypes that
+/// disagree with the layout chosen by the compiler before they corrupt data on the wire.
+///
+/// MPI has to be initialized before the macro is used. Panics with a description of the first
+/// mismatch.
+///
+/// # Examples
+///
+/// ```no_run
+/// let universe = mpi::initialize().unwrap();
+/// mpi::assert_equivalent_layout!(f64);
+/// mpi::assert_equivalent_layout!(bool);
+/// ```
+///
+/// See `examples/equivalent_layout.rs` for checking the field offsets of a struct.
+///
+/// # Standard section(s)
+///
+/// 4.1.5, 4.1.8, 4.1.13
+#[macro_export]
+macro_rules! assert_equivalent_layout {
+    ($t:ident { $($field:tt),* $(,)? }) => {{
+        $crate::assert_equivalent_layout!($t);
+        $crate::datatype::internal::check_equivalent_offsets::<$t>(
+            stringify!($t),
+            &[$((
+                stringify!($field),
+                $crate::internal::memoffset::offset_of!($t, $field),
+            )),*],
+        );
+    }};
+    ($t:ty) => {
+        $crate::datatype::internal::check_equivalent_layout::<$t>(stringify!($t))
+    };
+}
+
+/// Implement `Equivalence` for newtypes by forwarding to the datatype of the wrapped type.
+///
+/// Domain types like `struct Meters(f64)` keep their type safety at MPI boundaries and can be
+/// used with built-in reduction operations, which do not apply to struct datatypes. The newtypes
+/// must be `#[repr(transparent)]` tuple structs over the wrapped type. The type of their field and
+/// their sizes are checked at compile time, so that a newtype cannot forward to the datatype of a
+/// type it does not contain.
+/// With the `derive` feature, `#[derive(Equivalence)]` does the same for `#[repr(transparent)]`
+/// structs.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[repr(transparent)]
+/// struct Meters(f64);
+///
+/// #[repr(transparent)]
+/// struct NodeId(u64);
+///
+/// mpi::equivalent_newtype!(Meters => f64, NodeId => u64);
+/// ```
+///
+/// See `examples/newtype.rs`
+#[macro_export]
+macro_rules! equivalent_newtype {
+    ($($newtype:ty => $inner:ty),* $(,)?) => {$(
+        const _: [(); ::std::mem::size_of::<$inner>()] = [(); ::std::mem::size_of::<$newtype>()];
+        const _: () = {
+            #[allow(dead_code)]
+            fn wraps(newtype: &$newtype) -> &$inner {
+                &newtype.0
+            }
+        };
+
+        unsafe impl $crate::datatype::Equivalence for $newtype {
+            type Out = <$inner as $crate::datatype::Equivalence>::Out;
+            fn equivalent_datatype() -> Self::Out {
+                <$inner as $crate::datatype::Equivalence>::equivalent_datatype()
+            }
+        }
+    )*};
+}
+
+/// The length of a buffer cannot be expressed as an MPI `Count`.
+///
+/// MPI describes the length of a message by a `Count` of elements of its datatype, which is a C
+/// `int`. Buffers of more than `Count::max_value()` elements have to be sent in several chunks or
+/// described by a datatype with larger elements, e.g. `UserDatatype::contiguous()`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CountError {
+    elements: usize,
+    type_name: &'static str,
+}
+
+impl CountError {
+    /// A buffer of `elements` elements of type `T` that exceeds the range of `Count`
+    pub fn new<T: ?Sized>(elements: usize) -> CountError {
+        CountError {
+            elements,
+            type_name: any::type_name::<T>(),
+        }
+    }
+
+    /// The number of elements in the buffer
+    pub fn elements(&self) -> usize {
+        self.elements
+    }
+
+    /// The name of the element type of the buffer
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Buffer of {} elements of type `{}` exceeds the maximum MPI Count of {} elements. \
+             Send the buffer in chunks of at most {} elements or describe it with a datatype \
+             of larger elements, e.g. `UserDatatype::contiguous()`.",
+            self.elements,
+            self.type_name,
+            Count::max_value(),
+            Count::max_value()
+        )
+    }
+}
+
+impl Error for CountError {}
+
+/// A view does not lie within its buffer.
+///
+/// The bytes touched by `count` instances of a datatype range from the true lower bound of the
+/// first instance to the true upper bound of the last instance, see `true_extent()`. Instances
+/// are placed `extent()` bytes apart.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FootprintError {
+    /// The view holds a negative count of instances
+    NegativeCount(Count),
+    /// The view touches bytes outside of the buffer
+    ///
+    /// The bounds are `Address::min_value()` and `Address::max_value()` respectively if they
+    /// cannot be expressed as an `Address`.
+    OutOfBounds {
+        /// First byte touched by the view, relative to the start of the buffer
+        lower: Address,
+        /// End of the bytes touched by the view, relative to the start of the buffer
+        upper: Address,
+        /// Length of the buffer in bytes
+        buffer_len: usize,
+    },
+}
+
+impl fmt::Display for FootprintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FootprintError::NegativeCount(count) => {
+                write!(f, "View holds a negative count of {} instances.", count)
+            }
+            FootprintError::OutOfBounds {
+                lower,
+                upper,
+                buffer_len,
+            } => write!(
+                f,
+                "View touches bytes {} to {} of a buffer of {} bytes.",
+                lower, upper, buffer_len
+            ),
+        }
+    }
+}
+
+impl Error for FootprintError {}
+
+/// Check that `count` instances of `datatype` that start at byte `offset` only touch the bytes of
+/// a buffer of `buffer_len` bytes.
+///
+/// Returns the range of bytes touched, relative to the start of the buffer, or `None` if no bytes
+/// are touched.
+fn check_footprint<D>(
+    buffer_len: usize,
+    offset: usize,
+    count: Count,
+    datatype: &D,
+) -> Result<Option<(Address, Address)>, FootprintError>
+where
+    D: UncommittedDatatype,
+{
+    if count < 0 {
+        return Err(FootprintError::NegativeCount(count));
+    }
+    if count == 0 || datatype.size() == 0 {
+        return Ok(None);
+    }
+
+    let (_, extent) = datatype.extent();
+    let (true_lower_bound, true_extent) = datatype.true_extent();
+    let last: Address = (count - 1)
+        .value_as()
+        .expect("Count cannot be expressed as an MPI Address.");
+    let last = last.checked_mul(extent);
+    let start = offset
+        .value_as::<Address>()
+        .ok()
+        .and_then(|offset| offset.checked_add(true_lower_bound));
+    let lower = start.and_then(|start| start.checked_add(last?.min(0)));
+    let upper = start.and_then(|start| start.checked_add(last?.max(0))?.checked_add(true_extent));
+    match (lower, upper) {
+        (Some(lower), Some(upper))
+            if lower >= 0
+                && upper
+                    .value_as()
+                    .map_or(false, |upper: usize| upper <= buffer_len) =>
+        {
+            Ok(Some((lower, upper)))
+        }
+        _ => Err(FootprintError::OutOfBounds {
+            lower: lower.unwrap_or_else(Address::min_value),
+            upper: upper.unwrap_or_else(Address::max_value),
+            buffer_len,
+        }),
+    }
+}
+
+/// The `Count` of a buffer of `len` elements of type `T`
+pub(crate) fn try_count_of<T>(len: usize) -> Result<Count, CountError> {
+    len.value_as().map_err(|_| CountError::new::<T>(len))
+}
+
+/// A countable collection of things.
+pub unsafe trait Collection {
+    /// How many things are in this collection.
+    ///
+    /// Panics with a `CountError` if the number of things cannot be expressed as a `Count`.
+    fn count(&self) -> Count;
+
+    /// How many things are in this collection or a `CountError` if the number of things cannot
+    /// be expressed as a `Count`.
+    #[inline]
+    fn try_count(&self) -> Result<Count, CountError> {
+        Ok(self.count())
+    }
+}
+
+unsafe impl<T> Collection for T
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn count(&self) -> Count {
+        1
+    }
+}
+
+unsafe impl<T> Collection for [T]
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn count(&self) -> Count {
+        match self.try_count() {
+            Ok(count) => count,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[inline]
+    fn try_count(&self) -> Result<Count, CountError> {
+        try_count_of::<T>(self.len())
+    }
+}
+
+/// Provides a pointer to the starting address in memory.
+pub unsafe trait Pointer {
+    /// A pointer to the starting address in memory
+    fn pointer(&self) -> *const c_void;
+}
+
+unsafe impl<T> Pointer for T
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn pointer(&self) -> *const c_void {
+        let p: *const T = self;
+        p as *const c_void
+    }
+}
+
+unsafe impl<T> Pointer for [T]
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn pointer(&self) -> *const c_void {
+        self.as_ptr() as _
+    }
+}
+
+/// Provides a mutable pointer to the starting address in memory.
+pub unsafe trait PointerMut {
+    /// A mutable pointer to the starting address in memory
+    fn pointer_mut(&mut self) -> *mut c_void;
+}
+
+unsafe impl<T> PointerMut for T
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn pointer_mut(&mut self) -> *mut c_void {
+        let p: *mut T = self;
+        p as *mut c_void
+    }
+}
+
+unsafe impl<T> PointerMut for [T]
+where
+    T: Equivalence,
+{
+    #[inline]
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.as_mut_ptr() as _
+    }
+}
+
+/// A buffer is a region in memory that starts at `pointer()` and contains `count()` copies of
+/// `as_datatype()`.
+pub unsafe trait Buffer: Pointer + Collection + AsDatatype {}
+unsafe impl<T> Buffer for T where T: Equivalence {}
+unsafe impl<T> Buffer for [T] where T: Equivalence {}
+
+/// A mutable buffer is a region in memory that starts at `pointer_mut()` and contains `count()`
+/// copies of `as_datatype()`.
+pub unsafe trait BufferMut: PointerMut + Collection + AsDatatype {}
+unsafe impl<T> BufferMut for T where T: Equivalence {}
+unsafe impl<T> BufferMut for [T] where T: Equivalence {}
+
+// Shared immutable slices can be sent without copying them into a `Vec` first.
+macro_rules! slice_pointer_buffer {
+    ($ptr:ty, $($bounds:tt)*) => {
+        unsafe impl<$($bounds)*> AsDatatype for $ptr {
+            type Out = <T as Equivalence>::Out;
+            #[inline]
+            fn as_datatype(&self) -> Self::Out {
+                <T as Equivalence>::equivalent_datatype()
+            }
+        }
+
+        unsafe impl<$($bounds)*> Collection for $ptr {
+            #[inline]
+            fn count(&self) -> Count {
+                (**self).count()
+            }
+
+            #[inline]
+            fn try_count(&self) -> Result<Count, CountError> {
+                (**self).try_count()
+            }
+        }
+
+        unsafe impl<$($bounds)*> Pointer for $ptr {
+            #[inline]
+            fn pointer(&self) -> *const c_void {
+                (**self).pointer()
+            }
+        }
+
+        unsafe impl<$($bounds)*> Buffer for $ptr {}
+    };
+}
+
+slice_pointer_buffer!(Cow<'a, [T]>, 'a, T: Equivalence + Clone);
+slice_pointer_buffer!(Arc<[T]>, T: Equivalence);
+slice_pointer_buffer!(Rc<[T]>, T: Equivalence);
+
+// Vectors that keep few elements inline avoid allocating small messages on the heap.
+macro_rules! inline_vec_buffer {
+    ($vec:ty, $($bounds:tt)*) => {
+        slice_pointer_buffer!($vec, $($bounds)*);
+
+        unsafe impl<$($bounds)*> PointerMut for $vec {
+            #[inline]
+            fn pointer_mut(&mut self) -> *mut c_void {
+                (**self).pointer_mut()
+            }
+        }
+
+        unsafe impl<$($bounds)*> BufferMut for $vec {}
+    };
+}
+
+inline_vec_buffer!(SmallVec<A>, A: smallvec::Array<Item = T>, T: Equivalence);
+#[cfg(feature = "arrayvec")]
+inline_vec_buffer!(ArrayVec<T, CAP>, T: Equivalence, const CAP: usize);
+
+/// An immutable dynamically-typed buffer.
+///
+/// The buffer has a definite length and MPI datatype, but it is not yet known which Rust type it
+/// corresponds to.  This is the MPI analogue of `&Any`.  It is semantically equivalent to the trait
+/// object reference `&Buffer`.
+///
+/// # Examples
+///
+/// See `examples/dyn_buffer.rs`
+#[derive(Copy, Clone, Debug)]
+pub struct DynBuffer<'a> {
+    ptr: *const c_void,
+    len: Count,
+    datatype: DatatypeRef<'a>,
+}
+
+unsafe impl<'a> Send for DynBuffer<'a> {}
+
+unsafe impl<'a> Sync for DynBuffer<'a> {}
+
+unsafe impl<'a> Collection for DynBuffer<'a> {
+    fn count(&self) -> Count {
+        self.len
+    }
+}
+
+unsafe impl<'a> Pointer for DynBuffer<'a> {
+    fn pointer(&self) -> *const c_void {
+        self.ptr
+    }
+}
+
+unsafe impl<'a> AsDatatype for DynBuffer<'a> {
+    type Out = DatatypeRef<'a>;
+    fn as_datatype(&self) -> Self::Out {
+        self.datatype
+    }
+}
+
+unsafe impl<'a> Buffer for DynBuffer<'a> {}
+
+impl<'a> DynBuffer<'a> {
+    /// Creates a buffer from a slice with whose type has an MPI equivalent.
+    pub fn new<T: Equivalence>(buf: &'a [T]) -> Self {
+        unsafe {
+            let datatype = DatatypeRef::from_raw(T::equivalent_datatype().as_raw());
+            Self::from_raw(buf.as_ptr(), buf.count(), datatype)
+        }
+    }
+
+    /// Tests whether the buffer type matches `T`.
+    pub fn is<T: Equivalence>(&self) -> bool {
+        self.as_datatype().as_raw() == T::equivalent_datatype().as_raw()
+    }
+
+    /// Returns some slice if the type matches `T`, or `None` if it doesn't.
+    pub fn downcast<T: Equivalence>(self) -> Option<&'a [T]> {
+        if self.is::<T>() {
+            unsafe { Some(slice::from_raw_parts(self.as_ptr() as _, self.len())) }
+        } else {
+            None
+        }
+    }
+
+    /// Creates a buffer from its raw components.  The buffer must remain valid for `'a` and the
+    /// pointer must not be null.
+    ///
+    /// # Safety
+    /// - Buffer pointed to by `ptr` must live as long as `'a`
+    /// - `ptr` must point to an object that holds `len` elements of the type described by
+    ///   `datatype`.
+    pub unsafe fn from_raw<T>(ptr: *const T, len: Count, datatype: DatatypeRef<'a>) -> Self {
+        debug_assert!(!ptr.is_null());
+        Self {
+            ptr: ptr as _,
+            len,
+            datatype,
+        }
+    }
+
+    /// Returns the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.count()
+            .value_as()
+            .expect("Length of DynBuffer cannot be expressed as a usize")
+    }
+
+    /// Returns `true` if the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the underlying raw pointer.
+    pub fn as_ptr(&self) -> *const c_void {
+        self.ptr
+    }
+}
+
+/// A mutable dynamically-typed buffer.
+///
+/// The buffer has a definite length and MPI datatype, but it is not yet known which Rust type it
+/// corresponds to.  This is the MPI analogue of `&mut Any`.  It is semantically equivalent to the
+/// mutable trait object reference `&mut BufferMut`.
+///
+/// # Examples
+///
+/// See `examples/dyn_buffer.rs`
+#[derive(Debug)]
+pub struct DynBufferMut<'a> {
+    ptr: *mut c_void,
+    len: Count,
+    datatype: DatatypeRef<'a>,
+}
+
+unsafe impl<'a> Send for DynBufferMut<'a> {}
+
+unsafe impl<'a> Sync for DynBufferMut<'a> {}
+
+unsafe impl<'a> Collection for DynBufferMut<'a> {
+    fn count(&self) -> Count {
+        self.len
+    }
+}
+
+unsafe impl<'a> Pointer for DynBufferMut<'a> {
+    fn pointer(&self) -> *const c_void {
+        self.ptr
+    }
+}
+
+unsafe impl<'a> PointerMut for DynBufferMut<'a> {
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+unsafe impl<'a> Buffer for DynBufferMut<'a> {}
+
+unsafe impl<'a> BufferMut for DynBufferMut<'a> {}
+
+unsafe impl<'a> AsDatatype for DynBufferMut<'a> {
+    type Out = DatatypeRef<'a>;
+    fn as_datatype(&self) -> Self::Out {
+        self.datatype
+    }
+}
+
+impl<'a> DynBufferMut<'a> {
+    /// Creates a mutable buffer from a mutable slice with whose type has an MPI equivalent.
+    pub fn new<T: Equivalence>(buf: &'a mut [T]) -> Self {
+        unsafe {
+            let datatype = DatatypeRef::from_raw(T::equivalent_datatype().as_raw());
+            Self::from_raw(buf.as_mut_ptr(), buf.count(), datatype)
+        }
+    }
+
+    /// Tests whether the buffer type matches `T`.
+    pub fn is<T: Equivalence>(&self) -> bool {
+        self.as_datatype().as_raw() == T::equivalent_datatype().as_raw()
+    }
+
+    /// Returns some mutable slice if the type matches `T`, or `None` if it doesn't.
+    pub fn downcast<T: Equivalence>(mut self) -> Option<&'a mut [T]> {
+        if self.is::<T>() {
+            unsafe {
+                Some(slice::from_raw_parts_mut(
+                    self.as_mut_ptr() as _,
+                    self.len(),
+                ))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Creates a buffer from its raw components.  The buffer must remain valid for `'a` and the
+    /// pointer must not be null.
+    ///
+    /// # Safety
+    /// - Buffer pointed to by `ptr` must live as long as `'a`. There must be no other
+    ///   live reference to the buffer in Rust.
+    /// - `ptr` must point to an object that holds `len` elements of the type described by
+    ///   `datatype`.
+    pub unsafe fn from_raw<T>(ptr: *mut T, len: Count, datatype: DatatypeRef<'a>) -> Self {
+        debug_assert!(!ptr.is_null());
+        Self {
+            ptr: ptr as _,
+            len,
+            datatype,
+        }
+    }
+
+    /// Returns the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.count()
+            .value_as()
+            .expect("Length of DynBufferMut cannot be expressed as a usize")
+    }
+
+    /// Returns `true` if the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the underlying raw pointer.
+    pub fn as_ptr(&self) -> *const c_void {
+        self.ptr
+    }
+
+    /// Returns the underlying raw pointer.
+    pub fn as_mut_ptr(&mut self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// Reborrows the buffer with a shorter lifetime.
+    pub fn reborrow(&self) -> DynBuffer {
+        unsafe { DynBuffer::from_raw(self.as_ptr(), self.count(), self.as_datatype()) }
+    }
+
+    /// Reborrows the buffer mutably with a shorter lifetime.
+    pub fn reborrow_mut(&mut self) -> DynBufferMut {
+        unsafe { DynBufferMut::from_raw(self.as_mut_ptr(), self.count(), self.as_datatype()) }
+    }
+
+    /// Makes the buffer immutable.
+    pub fn downgrade(self) -> DynBuffer<'a> {
+        unsafe { DynBuffer::from_raw(self.as_ptr(), self.count(), self.as_datatype()) }
+    }
+}
+
+/// A buffer with a user specified count and datatype
+///
+/// # Safety
+///
+/// Views can be used to instruct the underlying MPI library to rummage around at arbitrary
+/// locations in memory. `View::try_new()` checks the bounds of the datatype against the length of
+/// a slice, all other View constructors are marked `unsafe`.
+pub struct View<'d, 'b, D, B: ?Sized>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+    datatype: &'d D,
+    count: Count,
+    buffer: &'b B,
+}
+
+impl<'d, 'b, D, B: ?Sized> View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+    /// Return a view of `buffer` containing `count` instances of MPI datatype `datatype`.
+    ///
+    /// # Examples
+    /// See `examples/contiguous.rs`, `examples/vector.rs`
+    ///
+    /// # Safety
+    /// - `datatype` must map an element of `buffer` without exposing any padding bytes or
+    ///   exceeding the bounds of the object.
+    pub unsafe fn with_count_and_datatype(
+        buffer: &'b B,
+        count: Count,
+        datatype: &'d D,
+    ) -> View<'d, 'b, D, B> {
+        View {
+            datatype,
+            count,
+            buffer,
+        }
+    }
+}
+
+impl<'d, 'b, D, T> View<'d, 'b, D, [T]>
+where
+    D: 'd + Datatype,
+    T: 'b + Equivalence,
+{
+    /// Return a view of `buffer` containing `count` instances of MPI datatype `datatype`, if they
+    /// lie within `buffer`.
+    ///
+    /// The view touches the bytes from the true lower bound of the first instance of `datatype`
+    /// to the true upper bound of the last instance, where the instances are placed `extent()`
+    /// bytes apart. Returns an error if any of these bytes lie outside of `buffer`.
+    ///
+    /// # Examples
+    /// See `examples/datatype_extent.rs`
+    ///
+    /// # Standard section(s)
+    /// 4.1.7, 4.1.8
+    pub fn try_new(
+        buffer: &'b [T],
+        count: Count,
+        datatype: &'d D,
+    ) -> Result<View<'d, 'b, D, [T]>, FootprintError> {
+        check_footprint(mem::size_of_val(buffer), 0, count, datatype)?;
+        Ok(View {
+            datatype,
+            count,
+            buffer,
+        })
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> AsDatatype for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+    type Out = &'d D;
+    fn as_datatype(&self) -> Self::Out {
+        self.datatype
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> Collection for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+    fn count(&self) -> Count {
+        self.count
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> Pointer for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+    fn pointer(&self) -> *const c_void {
+        self.buffer.pointer()
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> Buffer for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer,
+{
+}
+
+/// A view of a partitioned buffer is partitioned in units of the datatype of the view.
+impl<'d, 'b, D, B: ?Sized> Partitioned for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer + Partitioned,
+{
+    fn counts(&self) -> &[Count] {
+        self.buffer.counts()
+    }
+    fn displs(&self) -> &[Count] {
+        self.buffer.displs()
+    }
+}
+
+impl<'d, 'b, D, B: ?Sized> PartitionedBuffer for View<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + Pointer + Partitioned,
+{
+}
+
+/// A buffer with a user specified count and datatype
+///
+/// # Safety
+///
+/// Views can be used to instruct the underlying MPI library to rummage around at arbitrary
+/// locations in memory. This might be controlled later on using datatype bounds an slice lengths
+/// but for now, all View constructors are marked `unsafe`.
+pub struct MutView<'d, 'b, D, B: ?Sized>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+    datatype: &'d D,
+    count: Count,
+    buffer: &'b mut B,
+}
+
+impl<'d, 'b, D, B: ?Sized> MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+    /// Return a view of `buffer` containing `count` instances of MPI datatype `datatype`.
+    ///
+    /// # Examples
+    /// See `examples/contiguous.rs`, `examples/vector.rs`
+    ///
+    /// # Safety
+    /// - `datatype` must map an element of `buffer` without exposing any padding bytes or
+    ///   exceeding the bounds of the object.
+    pub unsafe fn with_count_and_datatype(
+        buffer: &'b mut B,
+        count: Count,
+        datatype: &'d D,
+    ) -> MutView<'d, 'b, D, B> {
+        MutView {
+            datatype,
+            count,
+            buffer,
+        }
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> AsDatatype for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+    type Out = &'d D;
+    fn as_datatype(&self) -> Self::Out {
+        self.datatype
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> Collection for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+    fn count(&self) -> Count {
+        self.count
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> PointerMut for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.buffer.pointer_mut()
+    }
+}
+
+unsafe impl<'d, 'b, D, B: ?Sized> BufferMut for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut,
+{
+}
+
+/// A view of a partitioned buffer is partitioned in units of the datatype of the view.
+impl<'d, 'b, D, B: ?Sized> Partitioned for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut + Partitioned,
+{
+    fn counts(&self) -> &[Count] {
+        self.buffer.counts()
+    }
+    fn displs(&self) -> &[Count] {
+        self.buffer.displs()
+    }
+}
+
+impl<'d, 'b, D, B: ?Sized> PartitionedBufferMut for MutView<'d, 'b, D, B>
+where
+    D: 'd + Datatype,
+    B: 'b + PointerMut + Partitioned,
+{
+}
+
+/// A send view and a receive view with their own datatypes over disjoint bytes of one buffer
+///
+/// Exchanges like a distributed matrix transpose send strided parts of a buffer and receive into
+/// other strided parts of the same buffer. A `View` and a `MutView` of the same slice cannot
+/// exist at the same time, so `DisjointViews` holds the buffer together with the offsets, counts
+/// and datatypes of both views and checks at construction that they do not touch any common
+/// byte.
+///
+/// # Examples
+/// See `examples/disjoint_views.rs`
+pub struct DisjointViews<'d, 'b, T, S, R> {
+    buffer: &'b mut [T],
+    send_offset: usize,
+    send_count: Count,
+    send_datatype: &'d S,
+    receive_offset: usize,
+    receive_count: Count,
+    receive_datatype: &'d R,
+}
+
+impl<'d, 'b, T, S, R> DisjointViews<'d, 'b, T, S, R>
+where
+    S: 'd + Datatype,
+    R: 'd + Datatype,
+{
+    /// Views of `send_count` instances of `send_datatype` starting at element `send_offset` and
+    /// of `receive_count` instances of `receive_datatype` starting at element `receive_offset` of
+    /// `buffer`.
+    ///
+    /// Panics if an offset lies beyond the end of `buffer`, even for an empty view, if a view
+    /// exceeds the bounds of `buffer` or if the views touch a common byte.
+    ///
+    /// # Safety
+    /// - The datatypes must map elements of `buffer` without exposing any padding bytes.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn new(
+        buffer: &'b mut [T],
+        send_offset: usize,
+        send_count: Count,
+        send_datatype: &'d S,
+        receive_offset: usize,
+        receive_count: Count,
+        receive_datatype: &'d R,
+    ) -> Self {
+        for &(view, offset) in &[("send", send_offset), ("receive", receive_offset)] {
+            assert!(
+                offset <= buffer.len(),
+                "The {} view starts at element {} beyond the end of a buffer of {} elements.",
+                view,
+                offset,
+                buffer.len()
+            );
+        }
+        let len = mem::size_of_val(buffer);
+        let start = |offset: usize| -> usize {
+            offset
+                .checked_mul(mem::size_of::<T>())
+                .expect("View offset in bytes cannot be expressed as a usize.")
+        };
+        let send_start = start(send_offset);
+        let receive_start = start(receive_offset);
+        if let Err(error) = check_footprint(len, send_start, send_count, send_datatype) {
+            panic!("Invalid send view: {}", error);
+        }
+        if let Err(error) = check_footprint(len, receive_start, receive_count, receive_datatype) {
+            panic!("Invalid receive view: {}", error);
+        }
+        // Both offsets lie within the buffer, so they can be expressed as an `Address`.
+        let to_address = |offset: usize| -> Address {
+            offset
+                .value_as()
+                .expect("rsmpi internal error: view offset exceeds an MPI Address")
+        };
+        let send = touched_ranges(send_datatype.as_raw(), send_count, to_address(send_start));
+        let receive = touched_ranges(
+            receive_datatype.as_raw(),
+            receive_count,
+            to_address(receive_start),
+        );
+        if let Some(byte) = first_common_byte(&send, &receive) {
+            panic!(
+                "The send and receive views overlap in byte {} of the buffer.",
+                byte
+            );
+        }
+        DisjointViews {
+            buffer,
+            send_offset,
+            send_count,
+            send_datatype,
+            receive_offset,
+            receive_count,
+            receive_datatype,
+        }
+    }
+
+    /// The number of instances of the send datatype
+    pub fn send_count(&self) -> Count {
+        self.send_count
+    }
+
+    /// The send datatype
+    pub fn send_datatype(&self) -> &'d S {
+        self.send_datatype
+    }
+
+    /// The number of instances of the receive datatype
+    pub fn receive_count(&self) -> Count {
+        self.receive_count
+    }
+
+    /// The receive datatype
+    pub fn receive_datatype(&self) -> &'d R {
+        self.receive_datatype
+    }
+
+    /// The underlying buffer
+    pub fn buffer(&self) -> &[T] {
+        self.buffer
+    }
+
+    /// Release the underlying buffer.
+    pub fn into_buffer(self) -> &'b mut [T] {
+        self.buffer
+    }
+
+    /// The address of the first element of the send view
+    pub(crate) fn send_pointer(&self) -> *const c_void {
+        self.buffer[self.send_offset..].as_ptr() as *const c_void
+    }
+
+    /// The address of the first element of the receive view
+    pub(crate) fn receive_pointer(&mut self) -> *mut c_void {
+        self.buffer[self.receive_offset..].as_mut_ptr() as *mut c_void
+    }
+}
+
+/// The bytes touched by `count` instances of `datatype` that start at byte `offset`, as sorted
+/// ranges that do not touch each other
+///
+/// The ranges are taken from the typemap of `datatype` as decoded by `decode()`. Datatypes whose
+/// layout is not decoded, e.g. of `Combiner::Darray`, are taken to touch their whole true extent.
+/// The instances must have passed `check_footprint()`, so all ranges lie within a buffer whose
+/// length can be expressed as an `Address`.
+fn touched_ranges(
+    datatype: MPI_Datatype,
+    count: Count,
+    offset: Address,
+) -> Vec<(Address, Address)> {
+    let typemap = typemap_ranges(datatype);
+    let extent = raw_extent(datatype);
+    let mut ranges = Vec::new();
+    for i in 0..count {
+        let start = offset + address(i) * extent;
+        ranges.extend(
+            typemap
+                .iter()
+                .map(|&(lower, upper)| (start + lower, start + upper)),
+        );
+    }
+    merge_ranges(ranges)
+}
+
+/// The bytes touched by a single instance of `datatype`, relative to its start
+fn typemap_ranges(datatype: MPI_Datatype) -> Vec<(Address, Address)> {
+    let decoded = decode_datatype(datatype);
+    if decoded.combiner == Combiner::Named {
+        let size: Count =
+            unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 };
+        return vec![(0, address(size))];
+    }
+    match typemap_blocks(&decoded) {
+        Some(blocks) => merge_ranges(
+            blocks
+                .into_iter()
+                .flat_map(|(displacement, count, datatype)| {
+                    touched_ranges(datatype, count, displacement)
+                })
+                .collect(),
+        ),
+        None => {
+            let (_, lower, extent) = unsafe {
+                with_uninitialized2(|lower, extent| {
+                    ffi::MPI_Type_get_true_extent(datatype, lower, extent)
+                })
+            };
+            vec![(lower, lower + extent)]
+        }
+    }
+}
+
+/// Sort `ranges` and merge the ones that overlap or touch each other.
+fn merge_ranges(mut ranges: Vec<(Address, Address)>) -> Vec<(Address, Address)> {
+    ranges.retain(|&(lower, upper)| lower < upper);
+    ranges.sort_unstable();
+    let mut merged: Vec<(Address, Address)> = Vec::with_capacity(ranges.len());
+    for (lower, upper) in ranges {
+        match merged.last_mut() {
+            Some(last) if lower <= last.1 => last.1 = last.1.max(upper),
+            _ => merged.push((lower, upper)),
+        }
+    }
+    merged
+}
+
+/// The first byte that lies in both `first` and `second`, which are sorted ranges
+fn first_common_byte(
+    first: &[(Address, Address)],
+    second: &[(Address, Address)],
+) -> Option<Address> {
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() && j < second.len() {
+        let lower = first[i].0.max(second[j].0);
+        if lower < first[i].1.min(second[j].1) {
+            return Some(lower);
+        }
+        if first[i].1 <= second[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    None
+}
+
+/// Describes how a `Buffer` is partitioned by specifying the count of elements and displacement
+/// from the start of the buffer for each partition.
+pub trait Partitioned {
+    /// The count of elements in each partition.
+    fn counts(&self) -> &[Count];
+    /// The displacement from the start of the buffer for each partition.
+    fn displs(&self) -> &[Count];
+}
+
+/// A buffer that is `Partitioned`
+pub trait PartitionedBuffer: Partitioned + Pointer + AsDatatype {}
+
+/// A mutable buffer that is `Partitioned`
+pub trait PartitionedBufferMut: Partitioned + PointerMut + AsDatatype {}
+
+/// Adds a partitioning to an existing `Buffer` so that it becomes `Partitioned`
+pub struct Partition<'b, B: 'b + ?Sized, C, D> {
+    buf: &'b B,
+    counts: C,
+    displs: D,
+}
+
+impl<'b, B: ?Sized, C, D> Partition<'b, B, C, D>
+where
+    B: 'b + Buffer,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+    /// Partition `buf` using `counts` and `displs`
+    pub fn new(buf: &B, counts: C, displs: D) -> Partition<B, C, D> {
+        let n = buf.count();
+        assert!(counts
+            .borrow()
+            .iter()
+            .zip(displs.borrow().iter())
+            .all(|(&c, &d)| c + d <= n));
+
+        Partition {
+            buf,
+            counts,
+            displs,
+        }
+    }
+}
+
+impl<'b, B: ?Sized> Partition<'b, B, Vec<Count>, Vec<Count>>
+where
+    B: 'b + Buffer,
+{
+    /// Partition `buf` into segments of `counts` elements, one for every process of `comm` in
+    /// rank order, that follow each other without gaps
+    ///
+    /// This is the partitioning expected by `reduce_scatter_into()`. Panics if `counts` does not
+    /// hold one count per process of `comm` or the segments do not fit into `buf`.
+    ///
+    /// # Examples
+    /// See `examples/reduce_scatter.rs`
+    pub fn segments<C: Communicator>(comm: &C, buf: &'b B, counts: &[Count]) -> Self {
+        let size: usize = comm
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        assert_eq!(
+            counts.len(),
+            size,
+            "{} segment counts were given for {} processes.",
+            counts.len(),
+            size
+        );
+        let displs = counts::displacements(counts);
+        counts::check_bounds(counts, &displs, buf.count())
+            .unwrap_or_else(|error| panic!("Invalid segments: {}.", error));
+        Partition {
+            buf,
+            counts: counts.to_vec(),
+            displs,
+        }
+    }
+}
+
+unsafe impl<'b, B: ?Sized, C, D> AsDatatype for Partition<'b, B, C, D>
+where
+    B: 'b + AsDatatype,
+{
+    type Out = <B as AsDatatype>::Out;
+    fn as_datatype(&self) -> Self::Out {
+        self.buf.as_datatype()
+    }
+}
+
+unsafe impl<'b, B: ?Sized, C, D> Pointer for Partition<'b, B, C, D>
+where
+    B: 'b + Pointer,
+{
+    fn pointer(&self) -> *const c_void {
+        self.buf.pointer()
+    }
+}
+
+impl<'b, B: ?Sized, C, D> Partitioned for Partition<'b, B, C, D>
+where
+    B: 'b,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+    fn counts(&self) -> &[Count] {
+        self.counts.borrow()
+    }
+    fn displs(&self) -> &[Count] {
+        self.displs.borrow()
+    }
+}
+
+impl<'b, B: ?Sized, C, D> PartitionedBuffer for Partition<'b, B, C, D>
+where
+    B: 'b + Pointer + AsDatatype,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+}
+
+/// Adds a partitioning to an existing `BufferMut` so that it becomes `Partitioned`
+pub struct PartitionMut<'b, B: 'b + ?Sized, C, D> {
+    buf: &'b mut B,
+    counts: C,
+    displs: D,
+}
+
+impl<'b, B: ?Sized, C, D> PartitionMut<'b, B, C, D>
+where
+    B: 'b + BufferMut,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+    /// Partition `buf` using `counts` and `displs`
+    ///
+    /// Panics if a partition does not lie within `buf` or if two non-empty partitions overlap,
+    /// since the contents of overlapping receive partitions are undefined.
+    ///
+    /// # Examples
+    /// See `examples/counts.rs`
+    pub fn new(buf: &mut B, counts: C, displs: D) -> PartitionMut<B, C, D> {
+        let n = buf.count();
+        counts::check_bounds(counts.borrow(), displs.borrow(), n)
+            .and_then(|_| counts::check_disjoint(counts.borrow(), displs.borrow()))
+            .unwrap_or_else(|error| panic!("Invalid receive partition: {}.", error));
+
+        PartitionMut {
+            buf,
+            counts,
+            displs,
+        }
+    }
+
+    /// Partition `buf` using `counts` and `displs` without checking the partitions
+    ///
+    /// # Safety
+    /// - All partitions must lie within `buf`.
+    /// - Overlapping partitions must not be used to receive data, MPI does not define the
+    /// result of receiving several messages into the same element.
+    pub unsafe fn new_unchecked(buf: &mut B, counts: C, displs: D) -> PartitionMut<B, C, D> {
+        PartitionMut {
+            buf,
+            counts,
+            displs,
+        }
+    }
+}
+
+unsafe impl<'b, B: ?Sized, C, D> AsDatatype for PartitionMut<'b, B, C, D>
+where
+    B: 'b + AsDatatype,
+{
+    type Out = <B as AsDatatype>::Out;
+    fn as_datatype(&self) -> Self::Out {
+        self.buf.as_datatype()
+    }
+}
+
+unsafe impl<'b, B: ?Sized, C, D> PointerMut for PartitionMut<'b, B, C, D>
+where
+    B: 'b + PointerMut,
+{
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.buf.pointer_mut()
+    }
+}
+
+impl<'b, B: ?Sized, C, D> Partitioned for PartitionMut<'b, B, C, D>
+where
+    B: 'b,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+    fn counts(&self) -> &[Count] {
+        self.counts.borrow()
+    }
+    fn displs(&self) -> &[Count] {
+        self.displs.borrow()
+    }
+}
+
+impl<'b, B: ?Sized, C, D> PartitionedBufferMut for PartitionMut<'b, B, C, D>
+where
+    B: 'b + PointerMut + AsDatatype,
+    C: Borrow<[Count]>,
+    D: Borrow<[Count]>,
+{
+}
+
+/// Returns the address of the argument in a format suitable for use with datatype constructors
+///
+/// # Examples
+/// See `examples/structured.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.5
+pub fn address_of<T>(x: &T) -> Address {
+    let x: *const T = x;
+    unsafe { with_uninitialized(|address| ffi::MPI_Get_address(x as *const c_void, address)).1 }
+}
+
+/// The address `disp` bytes after the address `base`
+///
+/// Unlike adding the integers, this is also correct on platforms with segmented address spaces.
+///
+/// # Examples
+/// See `examples/address.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.5
+pub fn address_add(base: Address, disp: Address) -> Address {
+    unsafe { ffi::RSMPI_Aint_add(base, disp) }
+}
+
+/// The displacement in bytes of the address `addr1` from the address `addr2`, e.g. of a field of
+/// a struct from the start of the struct
+///
+/// Unlike subtracting the integers, this is also correct on platforms with segmented address
+/// spaces.
+///
+/// # Examples
+/// See `examples/address.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.5
+pub fn address_diff(addr1: Address, addr2: Address) -> Address {
+    unsafe { ffi::RSMPI_Aint_diff(addr1, addr2) }
+}
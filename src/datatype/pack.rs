@@ -0,0 +1,386 @@
+//! Packing data of several datatypes into one buffer
+//!
+//! Sending many small messages of different datatypes to the same process costs one message
+//! latency each. A `PackedBuffer` collects the data of several buffers of arbitrary datatypes
+//! one after another with `MPI_Pack()`, so it can be sent as one message of type `MPI_PACKED`.
+//! The receiver unpacks the parts in the same order with `MPI_Unpack()`. The buffer tracks the
+//! position of the next part to unpack, so parts are taken out like from a queue.
+//!
+//! The format of `MPI_Pack()` is specific to the MPI implementation and platform. Data that has to
+//! be read elsewhere, e.g. from a checkpoint file, is packed with `pack_external()` in the
+//! canonical `external32` representation instead, which any MPI implementation on any platform
+//! can unpack with `unpack_external_into()`.
+//!
+//! # Examples
+//!
+//! See `examples/pack_buffer.rs` and `examples/pack_external.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4.2, 4.3
+
+use std::fmt;
+use std::os::raw::{c_char, c_void};
+
+use conv::ConvUtil;
+
+use super::{CountError, DatatypeRef, SystemDatatype};
+use crate::datatype::traits::*;
+use crate::ffi;
+use crate::point_to_point::traits::*;
+use crate::point_to_point::Status;
+use crate::topology::traits::*;
+use crate::{with_uninitialized, Address, Count};
+
+/// A byte buffer holding data packed with `MPI_Pack()`
+///
+/// The packed bytes are a `Buffer` of datatype `MPI_PACKED`, so they can be sent and received
+/// like any other buffer. The format of the bytes is implementation-defined and depends on the
+/// communicator used for packing, which has to be the communicator used for sending and
+/// unpacking as well.
+///
+/// # Examples
+///
+/// See `examples/pack_buffer.rs`
+///
+/// # Standard section(s)
+///
+/// 4.2
+#[derive(Clone, Default)]
+pub struct PackedBuffer {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl PackedBuffer {
+    /// An empty buffer
+    pub fn new() -> PackedBuffer {
+        PackedBuffer::default()
+    }
+
+    /// An empty buffer with room for `capacity` packed bytes
+    pub fn with_capacity(capacity: usize) -> PackedBuffer {
+        PackedBuffer {
+            bytes: Vec::with_capacity(capacity),
+            position: 0,
+        }
+    }
+
+    /// A buffer of `len` zero bytes to receive packed data into
+    ///
+    /// Use `truncate()` with the size of the received message afterwards, or receive with
+    /// `receive_from()` which does both.
+    pub fn zeroed(len: usize) -> PackedBuffer {
+        PackedBuffer {
+            bytes: vec![0; len],
+            position: 0,
+        }
+    }
+
+    /// A buffer holding the packed `bytes`, to be unpacked from the beginning
+    pub fn from_bytes(bytes: Vec<u8>) -> PackedBuffer {
+        PackedBuffer { bytes, position: 0 }
+    }
+
+    /// An upper bound on the number of bytes that packing `buf` on `comm` adds to a buffer
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.2, see MPI_Pack_size
+    pub fn pack_size<C, Buf>(comm: &C, buf: &Buf) -> usize
+    where
+        C: Communicator,
+        Buf: ?Sized + Buffer,
+    {
+        comm.pack_size(buf.count(), &buf.as_datatype())
+            .value_as()
+            .expect("MPI_Pack_size returned a negative buffer size!")
+    }
+
+    /// Receive a message of packed data from `source`, allocating a buffer of the size of the
+    /// message.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.8.2, 3.8.3
+    pub fn receive_from<S>(source: &S) -> (PackedBuffer, Status)
+    where
+        S: Source,
+    {
+        let (message, status) = source.matched_probe();
+        let len = status
+            .count(packed_datatype())
+            .value_as()
+            .expect("Message size cannot be expressed as a usize.");
+        let mut buffer = PackedBuffer::zeroed(len);
+        let status = message.matched_receive_into(&mut buffer);
+        (buffer, status)
+    }
+
+    /// Append the contents of `inbuf`, packed for communication on `comm`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.2, see MPI_Pack
+    pub fn pack_into<C, Buf>(&mut self, comm: &C, inbuf: &Buf)
+    where
+        C: Communicator,
+        Buf: ?Sized + Buffer,
+    {
+        let start = self.bytes.len();
+        let end = start
+            .checked_add(PackedBuffer::pack_size(comm, inbuf))
+            .expect("Size of packed buffer overflows a usize.");
+        self.bytes.resize(end, 0);
+        let position = comm.pack_into(inbuf, &mut self.bytes[..], position_count(start));
+        self.bytes.truncate(
+            position
+                .value_as()
+                .expect("MPI_Pack returned a negative position!"),
+        );
+    }
+
+    /// Unpack the next part of the buffer, which was packed on `comm`, into `outbuf`.
+    ///
+    /// Panics if all packed bytes have been unpacked already and `outbuf` is not empty.
+    ///
+    /// # Safety
+    ///
+    /// The next part of the buffer has to hold packed data of the datatype and count of
+    /// `outbuf`, otherwise `outbuf` may be left holding invalid values.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.2, see MPI_Unpack
+    pub unsafe fn unpack_from<C, Buf>(&mut self, comm: &C, outbuf: &mut Buf)
+    where
+        C: Communicator,
+        Buf: ?Sized + BufferMut,
+    {
+        assert!(
+            outbuf.count() == 0 || self.position < self.bytes.len(),
+            "All {} bytes of the packed buffer have been unpacked.",
+            self.bytes.len()
+        );
+        let position = comm.unpack_into(&self.bytes[..], outbuf, position_count(self.position));
+        self.position = position
+            .value_as()
+            .expect("MPI_Unpack returned a negative position!");
+    }
+
+    /// The position of the next byte to unpack
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of packed bytes that have not been unpacked yet
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Start unpacking from the beginning of the buffer again.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Remove all packed bytes.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.position = 0;
+    }
+
+    /// Shorten the buffer to `len` packed bytes, e.g. to the size of a message received into it.
+    pub fn truncate(&mut self, len: usize) {
+        self.bytes.truncate(len);
+        self.position = self.position.min(len);
+    }
+
+    /// The number of packed bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the buffer holds no packed bytes
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The packed bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The packed bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+const EXTERNAL32: &[u8] = b"external32\0";
+
+/// The number of bytes that packing `buf` in the `external32` representation takes
+///
+/// # Standard section(s)
+///
+/// 4.3
+pub fn pack_external_size<Buf>(buf: &Buf) -> usize
+where
+    Buf: ?Sized + Buffer,
+{
+    let datarep = EXTERNAL32.as_ptr() as *const c_char;
+    unsafe {
+        with_uninitialized(|size| {
+            ffi::MPI_Pack_external_size(datarep, buf.count(), buf.as_datatype().as_raw(), size)
+        })
+        .1
+    }
+    .value_as()
+    .expect("MPI_Pack_external_size returned a negative buffer size!")
+}
+
+/// Pack `inbuf` into a byte vector in the portable `external32` representation.
+///
+/// # Examples
+///
+/// See `examples/pack_external.rs`
+///
+/// # Standard section(s)
+///
+/// 4.3
+pub fn pack_external<Buf>(inbuf: &Buf) -> Vec<u8>
+where
+    Buf: ?Sized + Buffer,
+{
+    let mut outbuf = Vec::with_capacity(pack_external_size(inbuf));
+    pack_external_into(inbuf, &mut outbuf);
+    outbuf
+}
+
+/// Append `inbuf` in the portable `external32` representation to `outbuf`.
+///
+/// # Standard section(s)
+///
+/// 4.3
+pub fn pack_external_into<Buf>(inbuf: &Buf, outbuf: &mut Vec<u8>)
+where
+    Buf: ?Sized + Buffer,
+{
+    let datarep = EXTERNAL32.as_ptr() as *const c_char;
+    let start = outbuf.len();
+    let end = start
+        .checked_add(pack_external_size(inbuf))
+        .expect("Size of packed buffer overflows a usize.");
+    outbuf.resize(end, 0);
+    let mut position = external_position(start);
+    unsafe {
+        ffi::MPI_Pack_external(
+            datarep,
+            inbuf.pointer(),
+            inbuf.count(),
+            inbuf.as_datatype().as_raw(),
+            outbuf.as_mut_ptr() as *mut c_void,
+            external_position(end),
+            &mut position,
+        );
+    }
+    outbuf.truncate(
+        position
+            .value_as()
+            .expect("MPI_Pack_external returned a negative position!"),
+    );
+}
+
+/// Unpack the `external32` representation in `inbuf`, starting at byte `position`, into `outbuf`
+/// and return the position of the next byte to unpack.
+///
+/// # Safety
+///
+/// The bytes of `inbuf` from `position` on have to hold data of the datatype and count of
+/// `outbuf` in the `external32` representation, otherwise `outbuf` may be left holding invalid
+/// values.
+///
+/// # Examples
+///
+/// See `examples/pack_external.rs`
+///
+/// # Standard section(s)
+///
+/// 4.3
+pub unsafe fn unpack_external_into<Buf>(inbuf: &[u8], outbuf: &mut Buf, position: usize) -> usize
+where
+    Buf: ?Sized + BufferMut,
+{
+    let datarep = EXTERNAL32.as_ptr() as *const c_char;
+    let mut position = external_position(position);
+    ffi::MPI_Unpack_external(
+        datarep,
+        inbuf.as_ptr() as *const c_void,
+        external_position(inbuf.len()),
+        &mut position,
+        outbuf.pointer_mut(),
+        outbuf.count(),
+        outbuf.as_datatype().as_raw(),
+    );
+    position
+        .value_as()
+        .expect("MPI_Unpack_external returned a negative position!")
+}
+
+fn packed_datatype() -> SystemDatatype {
+    unsafe { DatatypeRef::from_raw(ffi::RSMPI_PACKED) }
+}
+
+fn position_count(position: usize) -> Count {
+    position
+        .value_as()
+        .expect("Position in packed buffer cannot be expressed as an MPI Count.")
+}
+
+fn external_position(position: usize) -> Address {
+    position
+        .value_as()
+        .expect("Position in packed buffer cannot be expressed as an MPI Address.")
+}
+
+impl fmt::Debug for PackedBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PackedBuffer")
+            .field("len", &self.bytes.len())
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+unsafe impl AsDatatype for PackedBuffer {
+    type Out = SystemDatatype;
+    fn as_datatype(&self) -> Self::Out {
+        packed_datatype()
+    }
+}
+
+unsafe impl Collection for PackedBuffer {
+    fn count(&self) -> Count {
+        self.bytes[..].count()
+    }
+
+    fn try_count(&self) -> Result<Count, CountError> {
+        self.bytes[..].try_count()
+    }
+}
+
+unsafe impl Pointer for PackedBuffer {
+    fn pointer(&self) -> *const c_void {
+        self.bytes[..].pointer()
+    }
+}
+
+unsafe impl PointerMut for PackedBuffer {
+    fn pointer_mut(&mut self) -> *mut c_void {
+        self.bytes[..].pointer_mut()
+    }
+}
+
+unsafe impl Buffer for PackedBuffer {}
+
+// Any bytes are valid packed data, MPI checks the format when unpacking.
+unsafe impl BufferMut for PackedBuffer {}
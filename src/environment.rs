@@ -4,25 +4,37 @@
 //!
 //! # Unfinished features
 //!
-//! - **8.1.2**: `MPI_TAG_UB`, ...
-//! - **8.2**: Memory allocation
+//! - **8.1.2**: `MPI_HOST`, `MPI_IO`, `MPI_WTIME_IS_GLOBAL`
 //! - **8.3, 8.4, and 8.5**: Error handling
 
 use std::{
+    any::{Any, TypeId},
     cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    env, mem,
     os::raw::{c_char, c_double, c_int, c_void},
-    ptr,
+    panic, ptr,
     string::FromUtf8Error,
-    sync::RwLock,
-    thread::{self, ThreadId},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, Mutex, MutexGuard, RwLock,
+    },
+    thread::{self, JoinHandle, ThreadId},
+    time::Duration,
 };
 
 use conv::ConvUtil;
 use once_cell::sync::Lazy;
 
+use crate::collective::traits::*;
 use crate::ffi;
+use crate::statistics;
+use crate::topology::traits::*;
 use crate::topology::SystemCommunicator;
+#[cfg(feature = "validate")]
+use crate::validation;
 use crate::{with_uninitialized, with_uninitialized2};
+use crate::{Count, Tag};
 
 /// Internal data structure used to uphold certain MPI invariants.
 /// State is currently only used with the derive feature.
@@ -34,9 +46,63 @@ pub(crate) struct UniverseState {
 pub(crate) static UNIVERSE_STATE: Lazy<RwLock<Option<UniverseState>>> =
     Lazy::new(|| RwLock::new(None));
 
+type Attributes = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// Values stored with `Universe::store()`, keyed by their type
+static ATTRIBUTES: Lazy<Mutex<Attributes>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn attributes() -> MutexGuard<'static, Attributes> {
+    ATTRIBUTES
+        .lock()
+        .expect("rsmpi internal error: ATTRIBUTES lock poisoned")
+}
+
 /// Global context
 pub struct Universe {
     buffer: Option<Vec<u8>>,
+    progress: Option<ProgressThread>,
+}
+
+/// A background thread that polls the MPI library to drive asynchronous progress
+struct ProgressThread {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl ProgressThread {
+    fn spawn(interval: Duration) -> ProgressThread {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let handle = thread::Builder::new()
+            .name("rsmpi-progress".to_owned())
+            .spawn(move || {
+                while !stopped.load(atomic::Ordering::Acquire) {
+                    // Probing only enters the progress engine of the library, messages are left
+                    // for the application to receive.
+                    unsafe {
+                        let mut flag: c_int = 0;
+                        ffi::MPI_Iprobe(
+                            ffi::RSMPI_ANY_SOURCE,
+                            ffi::RSMPI_ANY_TAG,
+                            ffi::RSMPI_COMM_WORLD,
+                            &mut flag,
+                            ffi::RSMPI_STATUS_IGNORE,
+                        );
+                    }
+                    thread::park_timeout(interval);
+                }
+            })
+            .expect("Failed to spawn the progress thread.");
+        ProgressThread { stop, handle }
+    }
+
+    fn stop(self) {
+        self.stop.store(true, atomic::Ordering::Release);
+        self.handle.thread().unpark();
+        self.handle
+            .join()
+            .expect("rsmpi internal error: progress thread panicked");
+    }
 }
 
 impl Universe {
@@ -93,10 +159,110 @@ impl Universe {
             );
         }
     }
+
+    /// Start a background thread that polls the MPI library every `interval`.
+    ///
+    /// Many MPI libraries only make progress on non-blocking operations while the application is
+    /// inside an MPI call, so e.g. a large immediate send does not advance while the application
+    /// computes. The progress thread enters the library regularly by probing `MPI_COMM_WORLD`
+    /// for messages, which drives all pending operations of the process, without receiving any
+    /// message or completing any request itself. Requests are not tested by the thread, since MPI
+    /// forbids completing the same request from two threads at once. A running progress thread
+    /// is replaced. It is stopped before MPI is finalized.
+    ///
+    /// Panics unless MPI was initialized with `Threading::Multiple`.
+    ///
+    /// # Examples
+    /// See `examples/progress_thread.rs`
+    pub fn enable_progress_thread(&mut self, interval: Duration) {
+        assert_eq!(
+            threading_support(),
+            Threading::Multiple,
+            "A progress thread requires MPI to be initialized with `Threading::Multiple`."
+        );
+        self.disable_progress_thread();
+        self.progress = Some(ProgressThread::spawn(interval));
+    }
+
+    /// Stop the background thread started by `enable_progress_thread()`, if it is running.
+    pub fn disable_progress_thread(&mut self) {
+        if let Some(progress) = self.progress.take() {
+            progress.stop();
+        }
+    }
+
+    /// Whether a progress thread is running
+    pub fn has_progress_thread(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    /// Store `value` as the process-global attribute of type `T`.
+    ///
+    /// Attributes let independently written libraries share MPI resources like committed
+    /// datatypes or duplicated communicators instead of creating their own copies. There is at
+    /// most one attribute per type, so libraries should store values of private types. Returns
+    /// the attribute previously stored for type `T`.
+    ///
+    /// All attributes are dropped before MPI is finalized, so they can safely hold MPI resources
+    /// as long as no clones of the `Arc`s returned by `get()` outlive the `Universe`.
+    ///
+    /// # Examples
+    /// See `examples/attributes.rs`
+    pub fn store<T>(&self, value: T) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        attributes()
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .map(downcast)
+    }
+
+    /// The process-global attribute of type `T`, if one has been stored
+    pub fn get<T>(&self) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        attributes().get(&TypeId::of::<T>()).cloned().map(downcast)
+    }
+
+    /// The process-global attribute of type `T`, which is created by `f` and stored if none has
+    /// been stored so far.
+    ///
+    /// `f` must not access the attributes itself.
+    pub fn get_or_insert_with<T, F>(&self, f: F) -> Arc<T>
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> T,
+    {
+        let attribute = attributes()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(f()))
+            .clone();
+        downcast(attribute)
+    }
+
+    /// Remove the process-global attribute of type `T` and return it.
+    pub fn remove<T>(&self) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        attributes().remove(&TypeId::of::<T>()).map(downcast)
+    }
+}
+
+fn downcast<T: Any + Send + Sync>(attribute: Arc<dyn Any + Send + Sync>) -> Arc<T> {
+    attribute
+        .downcast()
+        .unwrap_or_else(|_| panic!("rsmpi internal error: attribute stored under wrong type"))
 }
 
 impl Drop for Universe {
     fn drop(&mut self) {
+        // Attributes may hold MPI resources that have to be freed before finalizing. They are
+        // dropped after releasing the lock, since their destructors may access the attributes.
+        let stored = mem::take(&mut *attributes());
+        drop(stored);
+
         // This can only ever be called once since it's only possible to initialize a single
         // Universe per application run.
         //
@@ -105,7 +271,10 @@ impl Drop for Universe {
             .write()
             .expect("rsmpi internal error: UNIVERSE_STATE lock poisoned");
 
+        self.disable_progress_thread();
         self.detach_buffer();
+        #[cfg(feature = "validate")]
+        validation::complete_pending();
         unsafe {
             ffi::MPI_Finalize();
         }
@@ -179,20 +348,184 @@ pub(crate) fn is_initialized() -> bool {
     unsafe { with_uninitialized(|initialized| ffi::MPI_Initialized(initialized)).1 != 0 }
 }
 
-/// Whether the MPI library has been initialized
-/// NOTE: Used by "derive" feature
-#[allow(unused)]
+/// Whether the MPI library has been finalized
 pub(crate) fn is_finalized() -> bool {
     unsafe { with_uninitialized(|finalized| ffi::MPI_Finalized(finalized)).1 != 0 }
 }
 
+/// Handles that were leaked instead of freed, counted by their type
+static LEAKED_HANDLES: Lazy<Mutex<BTreeMap<&'static str, usize>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Check whether a handle of type `kind` that is being dropped has to be leaked because MPI has
+/// already been finalized.
+///
+/// Handles are often kept in static caches that are dropped after the `Universe`. Freeing them
+/// would call into a finalized MPI library, so they are recorded as leaked instead, see
+/// `leaked_handles()`.
+pub(crate) fn leak_if_finalized(kind: &'static str) -> bool {
+    if !is_finalized() {
+        return false;
+    }
+    record_leak(kind);
+    true
+}
+
+/// Record that a handle of type `kind` is leaked instead of freed, see `leaked_handles()`.
+pub(crate) fn record_leak(kind: &'static str) {
+    *LEAKED_HANDLES
+        .lock()
+        .expect("rsmpi internal error: LEAKED_HANDLES lock poisoned")
+        .entry(kind)
+        .or_insert(0) += 1;
+}
+
+/// Handles that could not be freed, e.g. because they were dropped after MPI had been finalized
+/// or because a `coupling::Connection` was dropped without being disconnected
+///
+/// Returns the number of leaked handles by the name of their type, e.g. `"UserDatatype"`.
+pub fn leaked_handles() -> Vec<(&'static str, usize)> {
+    LEAKED_HANDLES
+        .lock()
+        .expect("rsmpi internal error: LEAKED_HANDLES lock poisoned")
+        .iter()
+        .map(|(&kind, &count)| (kind, count))
+        .collect()
+}
+
+/// How errors in MPI operations are handled
+///
+/// # Standard section(s)
+///
+/// 8.3
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ErrorMode {
+    /// Abort the program, the default of MPI
+    Abort,
+    /// Return an error code from the failing operation, see `Communicator::set_errors_return()`
+    Return,
+}
+
+/// Settings for initializing MPI, see `builder()`
+///
+/// # Examples
+/// See `examples/builder.rs`
+#[derive(Clone, Debug)]
+pub struct UniverseBuilder {
+    threading: Threading,
+    error_mode: ErrorMode,
+    buffer_size: usize,
+    abort_on_panic: bool,
+    statistics: bool,
+}
+
+impl UniverseBuilder {
+    /// Request the level of multithreading support `threading`, `Threading::Single` by default.
+    ///
+    /// The level actually supported by the implementation is returned by `threading_support()`.
+    pub fn threading(mut self, threading: Threading) -> Self {
+        self.threading = threading;
+        self
+    }
+
+    /// Handle errors on `MPI_COMM_WORLD` and `MPI_COMM_SELF` according to `error_mode`,
+    /// `ErrorMode::Abort` by default.
+    ///
+    /// # Safety
+    /// - With `ErrorMode::Return`, the contract of `Communicator::set_errors_return()` applies to
+    ///   `MPI_COMM_WORLD` and `MPI_COMM_SELF`.
+    pub unsafe fn error_mode(mut self, error_mode: ErrorMode) -> Self {
+        self.error_mode = error_mode;
+        self
+    }
+
+    /// Attach a buffer of `size` bytes for buffered communication, none by default.
+    ///
+    /// See `Universe::set_buffer_size()`.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Whether a panic on any thread aborts all processes with `MPI_Abort()` after the panic
+    /// message has been printed, `false` by default.
+    ///
+    /// Without it, the other processes of a program hang in their next communication with a
+    /// process that panicked.
+    pub fn abort_on_panic(mut self, abort: bool) -> Self {
+        self.abort_on_panic = abort;
+        self
+    }
+
+    /// Whether messages and collective operations are counted from the start, `false` by
+    /// default.
+    ///
+    /// See the `statistics` module.
+    pub fn statistics(mut self, enabled: bool) -> Self {
+        self.statistics = enabled;
+        self
+    }
+
+    /// Initialize MPI with these settings.
+    ///
+    /// Returns `None` if the MPI library has been initialized already.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 8.7, 12.4.3
+    pub fn initialize(self) -> Option<Universe> {
+        let (mut universe, _) = initialize_with_threading(self.threading)?;
+        if self.error_mode == ErrorMode::Return {
+            unsafe {
+                SystemCommunicator::world().set_errors_return();
+                ffi::MPI_Comm_set_errhandler(ffi::RSMPI_COMM_SELF, ffi::RSMPI_ERRORS_RETURN);
+            }
+        }
+        universe.set_buffer_size(self.buffer_size);
+        if self.abort_on_panic {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                previous(info);
+                if is_initialized() && !is_finalized() {
+                    SystemCommunicator::world().abort(101);
+                }
+            }));
+        }
+        if self.statistics {
+            statistics::enable();
+        }
+        Some(universe)
+    }
+}
+
+impl Default for UniverseBuilder {
+    fn default() -> Self {
+        UniverseBuilder {
+            threading: Threading::Single,
+            error_mode: ErrorMode::Abort,
+            buffer_size: 0,
+            abort_on_panic: false,
+            statistics: false,
+        }
+    }
+}
+
+/// Settings for initializing MPI, starting from the defaults of `initialize()`
+///
+/// # Examples
+/// See `examples/builder.rs`
+pub fn builder() -> UniverseBuilder {
+    UniverseBuilder::default()
+}
+
 /// Initialize MPI.
 ///
 /// If the MPI library has not been initialized so far, initializes and returns a representation
 /// of the MPI communication `Universe` which provides access to additional functions.
 /// Otherwise returns `None`.
 ///
-/// Equivalent to: `initialize_with_threading(Threading::Single)`
+/// Equivalent to: `initialize_with_threading(Threading::Single)`, use `builder()` to configure
+/// the `Universe` further.
 ///
 /// # Examples
 /// See `examples/simple.rs`
@@ -248,7 +581,13 @@ pub fn initialize_with_threading(threading: Threading) -> Option<(Universe, Thre
         main_thread: thread::current().id(),
     });
 
-    Some((Universe { buffer: None }, provided.into()))
+    Some((
+        Universe {
+            buffer: None,
+            progress: None,
+        },
+        provided.into(),
+    ))
 }
 
 /// Level of multithreading supported by this MPI universe
@@ -337,6 +676,134 @@ pub fn processor_name() -> Result<String, FromUtf8Error> {
     String::from_utf8(buf)
 }
 
+/// Functionality of newer versions of the MPI standard that the MPI library may not provide
+///
+/// rsmpi resolves the functions of newer versions of the standard when the program is started, so
+/// that the same build runs against older and newer MPI libraries. Operations built on these
+/// functions fall back to emulations if the library linked at run time does not provide them.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Feature {
+    /// `MPI_Isendrecv()`, see `point_to_point::immediate_send_receive_into()`
+    ImmediateSendReceive,
+    /// The large count `_c` variants of the communication functions
+    LargeCount,
+    /// Persistent collective operations, e.g. `MPI_Barrier_init()`
+    PersistentCollectives,
+    /// Partitioned point to point communication, see the `partitioned` module
+    Partitioned,
+}
+
+/// Whether the MPI library linked at run time provides `feature`
+///
+/// Can be called without initializing MPI.
+pub fn is_supported(feature: Feature) -> bool {
+    let supported = unsafe {
+        match feature {
+            Feature::ImmediateSendReceive => ffi::RSMPI_Isendrecv_is_supported(),
+            Feature::LargeCount => ffi::RSMPI_Large_count_is_supported(),
+            Feature::PersistentCollectives => ffi::RSMPI_Persistent_collectives_is_supported(),
+            Feature::Partitioned => ffi::RSMPI_Partitioned_is_supported(),
+        }
+    };
+    supported != 0
+}
+
+/// The largest tag value that can be used in point to point communication
+///
+/// The MPI standard guarantees an upper bound of at least 32767.
+///
+/// # Standard section(s)
+///
+/// 8.1.2
+pub fn tag_upper_bound() -> Tag {
+    unsafe {
+        let mut value: *const c_int = ptr::null();
+        let (_, flag) = with_uninitialized(|flag| {
+            ffi::MPI_Comm_get_attr(
+                ffi::RSMPI_COMM_WORLD,
+                ffi::RSMPI_TAG_UB,
+                &mut value as *mut *const c_int as *mut c_void,
+                flag,
+            )
+        });
+        assert!(
+            flag != 0 && !value.is_null(),
+            "The MPI library does not provide the MPI_TAG_UB attribute."
+        );
+        *value
+    }
+}
+
+/// Read the environment variables `keys` on rank 0 of `comm` and broadcast them to all processes.
+///
+/// Returns the variables that are set on rank 0, keyed by their names. Launchers differ in which
+/// environment variables they propagate to the processes they start, reading configuration
+/// through this function guarantees that all processes see the values of rank 0. All processes
+/// have to pass the same `keys`. This is a collective operation.
+///
+/// Panics if the value of one of the variables on rank 0 is not valid unicode.
+///
+/// # Examples
+///
+/// See `examples/broadcast_env.rs`
+///
+/// # Standard section(s)
+///
+/// 5.4
+pub fn broadcast_env<C: Communicator>(comm: &C, keys: &[&str]) -> HashMap<String, String> {
+    let root = comm.process_at_rank(0);
+    // The length of every value in bytes, or -1 if the variable is not set
+    let mut lengths: Vec<Count> = vec![-1; keys.len()];
+    let mut bytes = Vec::new();
+    if comm.rank() == 0 {
+        for (length, key) in lengths.iter_mut().zip(keys) {
+            match env::var(key) {
+                Ok(value) => {
+                    *length = value
+                        .len()
+                        .value_as()
+                        .expect("Length of environment variable cannot be expressed as a Count.");
+                    bytes.extend_from_slice(value.as_bytes());
+                }
+                Err(env::VarError::NotPresent) => {}
+                Err(env::VarError::NotUnicode(_)) => {
+                    panic!("Environment variable {} is not valid unicode.", key)
+                }
+            }
+        }
+    }
+    root.broadcast_into(&mut lengths[..]);
+    let total = lengths
+        .iter()
+        .filter(|&&length| length > 0)
+        .try_fold(0usize, |total, &length| {
+            length
+                .value_as::<usize>()
+                .ok()
+                .and_then(|length| total.checked_add(length))
+        });
+    bytes.resize(
+        total.expect("Total length of environment variables cannot be expressed as a usize."),
+        0,
+    );
+    root.broadcast_into(&mut bytes[..]);
+
+    let mut values = HashMap::new();
+    let mut rest = &bytes[..];
+    for (&length, &key) in lengths.iter().zip(keys) {
+        if let Ok(length) = length.value_as::<usize>() {
+            let (value, tail) = rest.split_at(length);
+            values.insert(
+                key.to_owned(),
+                String::from_utf8(value.to_vec())
+                    .expect("Broadcast environment variable is not valid UTF-8."),
+            );
+            rest = tail;
+        }
+    }
+    values
+}
+
 /// Time in seconds since an arbitrary time in the past.
 ///
 /// The cheapest high-resolution timer available will be used.
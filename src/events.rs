@@ -0,0 +1,212 @@
+//! Time-stamped event records
+//!
+//! Tracing and logging frameworks record events with a time stamp on every process. This module
+//! provides a record type that pairs a payload with a time stamp in nanoseconds since the Unix
+//! epoch, which has an equivalent MPI datatype as long as the payload has one, and a collective
+//! operation that merges the per-process logs into one globally ordered trace.
+//!
+//! This module is only available with the `chrono` feature.
+//!
+//! # Examples
+//!
+//! See `examples/events.rs`
+
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use conv::ConvUtil;
+use once_cell::sync::Lazy;
+
+use crate::collective::{CollectivePlan, Root};
+use crate::datatype::traits::*;
+use crate::datatype::{DatatypeRef, DynBufferMut, UncommittedDatatypeRef, UserDatatype};
+use crate::raw::traits::*;
+use crate::topology::traits::*;
+use crate::{Address, Count};
+
+/// A payload of type `T` together with the time at which it was recorded
+///
+/// The time is stored as the number of nanoseconds since the Unix epoch, which covers the years
+/// 1678 to 2262.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[repr(C)]
+pub struct Timestamped<T> {
+    /// Nanoseconds since the Unix epoch
+    pub nanos: i64,
+    /// The recorded payload
+    pub payload: T,
+}
+
+impl<T> Timestamped<T> {
+    /// A record of `payload` at `time`
+    ///
+    /// Panics if `time` is outside of the range of the time stamps.
+    pub fn new(time: DateTime<Utc>, payload: T) -> Self {
+        Timestamped {
+            nanos: to_nanos(SystemTime::from(time)),
+            payload,
+        }
+    }
+
+    /// A record of `payload` at the current time
+    pub fn now(payload: T) -> Self {
+        Timestamped::new(Utc::now(), payload)
+    }
+
+    /// The time at which the payload was recorded
+    pub fn time(&self) -> DateTime<Utc> {
+        DateTime::from(from_nanos(self.nanos))
+    }
+}
+
+fn to_nanos(time: SystemTime) -> i64 {
+    let nanos = |duration: Duration| -> i64 {
+        duration
+            .as_nanos()
+            .value_as()
+            .expect("Time stamp cannot be expressed in nanoseconds since the Unix epoch.")
+    };
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => nanos(after),
+        Err(before) => -nanos(before.duration()),
+    }
+}
+
+fn from_nanos(nanos: i64) -> SystemTime {
+    let duration = Duration::from_nanos(nanos.unsigned_abs());
+    if nanos < 0 {
+        UNIX_EPOCH - duration
+    } else {
+        UNIX_EPOCH + duration
+    }
+}
+
+/// The datatypes of the records of every payload type in use, keyed by the payload type
+static DATATYPES: Lazy<Mutex<HashMap<TypeId, UserDatatype>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+unsafe impl<T> Equivalence for Timestamped<T>
+where
+    T: 'static + Equivalence,
+{
+    type Out = DatatypeRef<'static>;
+    fn equivalent_datatype() -> Self::Out {
+        let key = TypeId::of::<T>();
+        if let Some(datatype) = datatypes().get(&key) {
+            return unsafe { DatatypeRef::from_raw(datatype.as_raw()) };
+        }
+
+        // The lock is not held while the datatype of the payload is built, since the payload can
+        // be a record itself.
+        // With `repr(C)` the payload follows the time stamp at the next multiple of its alignment.
+        let payload_offset: Address = mem::size_of::<i64>()
+            .max(mem::align_of::<T>())
+            .value_as()
+            .expect("Offset of payload cannot be expressed as an Address.");
+        let payload_datatype = T::equivalent_datatype();
+        let datatype = UserDatatype::structured::<UncommittedDatatypeRef>(
+            &[1, 1],
+            &[0, payload_offset],
+            &[i64::equivalent_datatype().into(), unsafe {
+                UncommittedDatatypeRef::from_raw(payload_datatype.as_raw())
+            }],
+        );
+
+        let mut datatypes = datatypes();
+        let datatype = datatypes.entry(key).or_insert(datatype);
+        // The datatypes are never removed from the map, so they live as long as the program.
+        unsafe { DatatypeRef::from_raw(datatype.as_raw()) }
+    }
+}
+
+fn datatypes() -> MutexGuard<'static, HashMap<TypeId, UserDatatype>> {
+    DATATYPES
+        .lock()
+        .expect("rsmpi internal error: DATATYPES lock poisoned")
+}
+
+/// Merge the time-ordered `events` of all processes into one trace ordered by time on the root
+/// process.
+///
+/// The events of every process have to be sorted by time. Events with the same time stamp are
+/// ordered by the rank of the recording process and keep their order within a process. Returns
+/// the merged trace on the root process and `None` on all other processes. This is a collective
+/// operation.
+///
+/// # Examples
+///
+/// See `examples/events.rs`
+///
+/// # Standard section(s)
+///
+/// 5.5
+pub fn merge_sorted_by_time<R, T>(
+    root: &R,
+    events: &[Timestamped<T>],
+) -> Option<Vec<Timestamped<T>>>
+where
+    R: Root,
+    T: 'static + Equivalence + Copy,
+{
+    assert!(
+        events.windows(2).all(|pair| pair[0].nanos <= pair[1].nanos),
+        "Events have to be sorted by time before they are merged."
+    );
+    let comm = root.as_communicator();
+    let plan = CollectivePlan::from_all_gather(comm, events.count());
+    if comm.rank() != root.root_rank() {
+        root.gather_varcount_into(events);
+        return None;
+    }
+
+    let len: Count = plan.extent();
+    let len_usize: usize = len
+        .value_as()
+        .expect("Number of events cannot be expressed as a usize.");
+    let mut gathered: Vec<Timestamped<T>> = Vec::with_capacity(len_usize);
+    unsafe {
+        let mut recvbuf = DynBufferMut::from_raw(
+            gathered.as_mut_ptr(),
+            len,
+            DatatypeRef::from_raw(Timestamped::<T>::equivalent_datatype().as_raw()),
+        );
+        plan.gather_varcount_into_root(root, events, &mut recvbuf);
+        gathered.set_len(len_usize);
+    }
+
+    // Merge the sorted runs of all processes, the run of a process is continued once its
+    // current head has been taken.
+    let runs: Vec<(usize, usize)> = plan
+        .counts()
+        .iter()
+        .zip(plan.displs())
+        .map(|(&count, &displ)| {
+            let start: usize = displ
+                .value_as()
+                .expect("Displacement cannot be expressed as a usize.");
+            let count: usize = count
+                .value_as()
+                .expect("Number of events cannot be expressed as a usize.");
+            (start, start + count)
+        })
+        .collect();
+    let mut heads: BinaryHeap<Reverse<(i64, usize, usize)>> = runs
+        .iter()
+        .enumerate()
+        .filter(|&(_, &(start, end))| start < end)
+        .map(|(rank, &(start, _))| Reverse((gathered[start].nanos, rank, start)))
+        .collect();
+    let mut merged = Vec::with_capacity(len_usize);
+    while let Some(Reverse((_, rank, i))) = heads.pop() {
+        merged.push(gathered[i]);
+        if i + 1 < runs[rank].1 {
+            heads.push(Reverse((gathered[i + 1].nanos, rank, i + 1)));
+        }
+    }
+    Some(merged)
+}
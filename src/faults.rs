@@ -0,0 +1,202 @@
+//! Injection of artificial faults for testing
+//!
+//! Recovery logic is hard to test without a faulty cluster. The rules registered with `inject()`
+//! make rsmpi delay matching operations, drop the messages of sends or fail operations with an
+//! error code, so applications can exercise their timeouts and error handling in ordinary test
+//! runs. Operations are matched by the names reported to the `hooks`, e.g. `"send_with_tag"` or
+//! `"all_reduce_into"`, and optionally by the rank of their peer.
+//!
+//! Delays apply to all point to point and collective operations. Messages are dropped by the
+//! blocking and immediate sends of a `Destination`, the send-receive operations are not affected.
+//! Errors are only injected into the wrappers that report MPI errors as a `Result`, which at the
+//! moment is `request::join_or_cancel()` alone. It then reports the request that completed next
+//! as failed. Since the peer of a completed request is not known, rules that inject errors cannot
+//! be restricted to a peer.
+//!
+//! This module is only available with the `fault-injection` feature, which is meant for tests
+//! and should not be enabled in production builds.
+//!
+//! # Examples
+//!
+//! See `examples/faults.rs`
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+use crate::hooks::Call;
+use crate::topology::Rank;
+use crate::Error;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+static INJECTED: AtomicUsize = AtomicUsize::new(0);
+
+static RULES: Lazy<Mutex<Vec<Rule>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// A fault injected into an operation
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Fault {
+    /// Delay the start of the operation.
+    Delay(Duration),
+    /// Drop the message of a send, which then completes as if it was sent to the null process.
+    Drop,
+    /// Fail the operation with an error code.
+    Error(Error),
+}
+
+/// A rule describing which operations a fault is injected into
+#[derive(Clone, Debug)]
+pub struct Rule {
+    operation: Option<&'static str>,
+    peer: Option<Rank>,
+    fault: Fault,
+    skip: usize,
+    remaining: Option<usize>,
+}
+
+impl Rule {
+    /// Inject `fault` into every call of the operation named `operation`.
+    pub fn new(operation: &'static str, fault: Fault) -> Rule {
+        Rule {
+            operation: Some(operation),
+            ..Rule::any(fault)
+        }
+    }
+
+    /// Inject `fault` into every call of any operation.
+    pub fn any(fault: Fault) -> Rule {
+        Rule {
+            operation: None,
+            peer: None,
+            fault,
+            skip: 0,
+            remaining: None,
+        }
+    }
+
+    /// Only inject the fault into point to point operations with the process `peer`.
+    ///
+    /// Rules that inject errors cannot be restricted to a peer, see `inject()`.
+    pub fn peer(mut self, peer: Rank) -> Rule {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Let the first `calls` matching calls pass without a fault.
+    pub fn skip(mut self, calls: usize) -> Rule {
+        self.skip = calls;
+        self
+    }
+
+    /// Inject the fault into at most `calls` calls.
+    pub fn times(mut self, calls: usize) -> Rule {
+        self.remaining = Some(calls);
+        self
+    }
+
+    fn matches(&self, operation: &str, peer: Option<Rank>) -> bool {
+        self.operation.map_or(true, |name| name == operation)
+            && self.peer.map_or(true, |rank| peer == Some(rank))
+            && self.remaining != Some(0)
+    }
+
+    /// Count a matching call, returns whether the fault is injected into it.
+    fn fire(&mut self) -> bool {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return false;
+        }
+        if let Some(ref mut remaining) = self.remaining {
+            *remaining -= 1;
+        }
+        INJECTED.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+/// Register `rule` for all subsequent operations of the calling process.
+///
+/// Panics if `rule` injects an error and is restricted to a peer.
+pub fn inject(rule: Rule) {
+    if let Fault::Error(_) = rule.fault {
+        assert!(
+            rule.peer.is_none(),
+            "Rules that inject errors cannot be restricted to a peer."
+        );
+    }
+    lock().push(rule);
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Remove all rules.
+pub fn clear() {
+    lock().clear();
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+/// The number of faults injected so far on the calling process
+pub fn injected() -> usize {
+    INJECTED.load(Ordering::Relaxed)
+}
+
+/// Delay the call described by `call` according to the registered rules.
+///
+/// `call` is only evaluated if rules are registered.
+#[inline]
+pub(crate) fn delay<F>(call: F)
+where
+    F: FnOnce() -> Call,
+{
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let call = call();
+    let mut total = Duration::from_secs(0);
+    for rule in lock().iter_mut() {
+        if let Fault::Delay(duration) = rule.fault {
+            if rule.matches(call.name, call.peer) && rule.fire() {
+                total += duration;
+            }
+        }
+    }
+    if total > Duration::from_secs(0) {
+        thread::sleep(total);
+    }
+}
+
+/// Whether the message of the send named `operation` to `destination` is dropped
+#[inline]
+pub(crate) fn dropped(operation: &'static str, destination: Rank) -> bool {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return false;
+    }
+    lock().iter_mut().any(|rule| {
+        rule.fault == Fault::Drop && rule.matches(operation, Some(destination)) && rule.fire()
+    })
+}
+
+/// The error code to fail the operation named `operation` with, if any
+#[inline]
+pub(crate) fn error(operation: &'static str) -> Option<Error> {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return None;
+    }
+    for rule in lock().iter_mut() {
+        if let Fault::Error(code) = rule.fault {
+            if rule.matches(operation, None) && rule.fire() {
+                return Some(code);
+            }
+        }
+    }
+    None
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<Rule>> {
+    RULES
+        .lock()
+        .expect("rsmpi internal error: fault injection lock poisoned")
+}
@@ -0,0 +1,261 @@
+//! Many-to-one communication with credit-based flow control
+//!
+//! When thousands of processes report results to a single root process, messages that arrive
+//! faster than the root consumes them pile up in the unexpected message queue of the root, which
+//! can exhaust its memory. A funnel bounds this queue: every sender holds a number of credits and
+//! spends one per message. Once it runs out of credits it waits until the root grants new ones,
+//! which the root does after it has consumed a batch of messages of that sender. At most `window`
+//! messages per sender are in flight at any time.
+//!
+//! # Examples
+//!
+//! See `examples/funnel.rs`
+
+use std::mem;
+
+use conv::ConvUtil;
+
+use crate::datatype::traits::*;
+use crate::point_to_point::traits::*;
+use crate::point_to_point::{Message, Status};
+use crate::request::{Request, StaticScope};
+use crate::topology::traits::*;
+use crate::topology::{Rank, UserCommunicator};
+use crate::{Count, Tag};
+
+/// Tag of the messages carrying data to the root
+const DATA: Tag = 1;
+/// Tag of the message with which a sender announces that it has finished
+const DONE: Tag = 2;
+/// Tag of the messages granting a batch of credits to a sender
+const CREDIT: Tag = 3;
+
+/// The contents of control messages, which carry their meaning in their tag
+static EMPTY: [u8; 0] = [];
+
+/// The number of credits granted at once for a window of `window` messages
+fn batch(window: Count) -> Count {
+    assert!(
+        window > 0,
+        "The window of a funnel has to allow at least one message."
+    );
+    (window / 2).max(1)
+}
+
+/// The receiving end of a funnel on the root process
+///
+/// Creating the receiver duplicates the communicator, so the messages of the funnel do not
+/// interfere with other communication. All other processes of the communicator have to create a
+/// `FunnelSender` at the same time.
+pub struct FunnelReceiver {
+    comm: UserCommunicator,
+    batch: Count,
+    consumed: Vec<Count>,
+    senders: Rank,
+    grants: Vec<Request<'static>>,
+}
+
+impl FunnelReceiver {
+    /// The receiver of a funnel into the calling process over `comm`, allowing `window` messages
+    /// per sender in flight.
+    ///
+    /// `window` has to be the same on all processes. This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, window: Count) -> FunnelReceiver {
+        let comm = comm.duplicate();
+        let size: usize = comm
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        let senders = comm.size() - 1;
+        FunnelReceiver {
+            batch: batch(window),
+            consumed: vec![0; size],
+            senders,
+            grants: Vec::new(),
+            comm,
+        }
+    }
+
+    /// Whether all senders have finished
+    pub fn is_finished(&self) -> bool {
+        self.senders == 0
+    }
+
+    /// Wait for the next message from any sender.
+    ///
+    /// Returns the rank of the sender and the contents of the message, or `None` once all
+    /// senders have finished.
+    pub fn receive<Msg: Equivalence>(&mut self) -> Option<(Rank, Vec<Msg>)> {
+        while !self.is_finished() {
+            let probed = self.comm.any_process().matched_probe();
+            if let Some(message) = self.accept(probed) {
+                return Some(message);
+            }
+        }
+        None
+    }
+
+    /// Receive the next message from any sender if one has arrived.
+    ///
+    /// Returns `None` if no message is available at the moment or all senders have finished.
+    pub fn try_receive<Msg: Equivalence>(&mut self) -> Option<(Rank, Vec<Msg>)> {
+        while !self.is_finished() {
+            let probed = self.comm.any_process().immediate_matched_probe()?;
+            if let Some(message) = self.accept(probed) {
+                return Some(message);
+            }
+        }
+        None
+    }
+
+    /// Receive a probed message, returning it if it carries data.
+    fn accept<Msg: Equivalence>(
+        &mut self,
+        (message, status): (Message, Status),
+    ) -> Option<(Rank, Vec<Msg>)> {
+        self.complete_grants();
+        let source = status.source_rank();
+        if status.tag() == DONE {
+            let mut empty: [u8; 0] = [];
+            message.matched_receive_into(&mut empty[..]);
+            self.senders -= 1;
+            return None;
+        }
+        let (data, _) = (message, status).matched_receive_vec();
+
+        let index: usize = source
+            .value_as()
+            .expect("Rank cannot be expressed as a usize.");
+        let consumed = &mut self.consumed[index];
+        *consumed += 1;
+        if *consumed == self.batch {
+            *consumed = 0;
+            let grant = self
+                .comm
+                .process_at_rank(source)
+                .immediate_synchronous_send_with_tag(StaticScope, &EMPTY[..], CREDIT);
+            self.grants.push(grant);
+        }
+        Some((source, data))
+    }
+
+    /// Drop the grants that have been received by their senders.
+    fn complete_grants(&mut self) {
+        let grants = mem::take(&mut self.grants);
+        self.grants = grants
+            .into_iter()
+            .filter_map(|grant| grant.test().err())
+            .collect();
+    }
+}
+
+impl Drop for FunnelReceiver {
+    fn drop(&mut self) {
+        for grant in self.grants.drain(..) {
+            grant.wait();
+        }
+    }
+}
+
+/// The sending end of a funnel into the root process
+///
+/// Dropping the sender announces to the root that the sender has finished, see `finish()`.
+pub struct FunnelSender {
+    comm: UserCommunicator,
+    root: Rank,
+    batch: Count,
+    credits: Count,
+    sent: usize,
+    grants: usize,
+    finished: bool,
+}
+
+impl FunnelSender {
+    /// The sender of a funnel into process `root` of `comm`, allowing `window` messages in
+    /// flight.
+    ///
+    /// `window` has to be the same on all processes. This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, root: Rank, window: Count) -> FunnelSender {
+        assert!(
+            0 <= root && root < comm.size(),
+            "Root rank {} is out of range for a communicator of size {}.",
+            root,
+            comm.size()
+        );
+        assert_ne!(
+            comm.rank(),
+            root,
+            "The root of a funnel has to create a FunnelReceiver."
+        );
+        FunnelSender {
+            comm: comm.duplicate(),
+            root,
+            batch: batch(window),
+            credits: window,
+            sent: 0,
+            grants: 0,
+            finished: false,
+        }
+    }
+
+    /// The number of messages that can be sent before the sender has to wait for the root
+    pub fn credits(&self) -> Count {
+        self.credits
+    }
+
+    /// Send `msg` to the root, waiting for new credits first if the sender has run out of them.
+    pub fn send<Buf: ?Sized>(&mut self, msg: &Buf)
+    where
+        Buf: Buffer,
+    {
+        if self.credits == 0 {
+            self.receive_grant();
+        }
+        self.comm
+            .process_at_rank(self.root)
+            .send_with_tag(msg, DATA);
+        self.credits -= 1;
+        self.sent += 1;
+    }
+
+    /// Announce to the root that this sender has finished.
+    ///
+    /// Also receives the outstanding credits granted by the root, so no messages are left
+    /// pending.
+    pub fn finish(mut self) {
+        self.finish_mut();
+    }
+
+    fn finish_mut(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.comm
+            .process_at_rank(self.root)
+            .send_with_tag(&EMPTY[..], DONE);
+        // The root grants a batch of credits for every full batch of messages it consumes.
+        let batch: usize = self
+            .batch
+            .value_as()
+            .expect("Batch size cannot be expressed as a usize.");
+        while self.grants < self.sent / batch {
+            self.receive_grant();
+        }
+    }
+
+    fn receive_grant(&mut self) {
+        let mut empty: [u8; 0] = [];
+        self.comm
+            .process_at_rank(self.root)
+            .receive_into_with_tag(&mut empty[..], CREDIT);
+        self.credits += self.batch;
+        self.grants += 1;
+    }
+}
+
+impl Drop for FunnelSender {
+    fn drop(&mut self) {
+        self.finish_mut();
+    }
+}
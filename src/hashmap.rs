@@ -0,0 +1,388 @@
+//! A hash map distributed over the memory of all processes
+//!
+//! A `DistributedHashMap` spreads the slots of an open addressing hash table over RMA windows of
+//! all processes of a communicator. Every key is owned by the process its hash selects and placed
+//! in the slots of that process by linear probing. Any process can look up, insert and update the
+//! entries of any other process with one-sided operations, without the owner taking part: a slot
+//! is claimed or locked by an atomic compare and swap on its state word, its key and value are
+//! transferred with `MPI_Put()` and `MPI_Get()` and the slot is released by an atomic replace of
+//! the state word.
+//!
+//! The capacity of the map is fixed when it is created and entries cannot be removed.
+//!
+//! # Examples
+//!
+//! See `examples/distributed_hash_map.rs`
+//!
+//! # Standard section(s)
+//!
+//! 11.2.2, 11.3, 11.5.3
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::{self, MaybeUninit};
+use std::os::raw::{c_int, c_void};
+use std::{ptr, thread};
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::environment;
+use crate::ffi::{self, MPI_Op, MPI_Win};
+use crate::raw::traits::*;
+use crate::topology::traits::*;
+use crate::topology::{Rank, UserCommunicator};
+use crate::Address;
+
+/// The slot holds no entry.
+const EMPTY: u64 = 0;
+/// The slot is claimed or updated by a process.
+const LOCKED: u64 = 1;
+/// The slot holds an entry.
+const FULL: u64 = 2;
+
+/// A hash map with a fixed number of slots on every process of a communicator
+///
+/// All operations except `new()`, `synchronize()` and dropping the map are one-sided, so
+/// processes can access the map independently of each other. Each operation is complete when it
+/// returns. Concurrent `insert()`s and `update()`s of the same key are applied one after another.
+///
+/// # Examples
+///
+/// See `examples/distributed_hash_map.rs`
+pub struct DistributedHashMap<K, V> {
+    states: Window<u64>,
+    keys: Window<K>,
+    values: Window<V>,
+    comm: UserCommunicator,
+    capacity: usize,
+}
+
+impl<K, V> DistributedHashMap<K, V>
+where
+    K: Equivalence + Copy + Hash + Eq,
+    V: Equivalence + Copy,
+{
+    /// Create an empty map with `capacity` slots on every process of `comm`.
+    ///
+    /// This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, capacity: usize) -> DistributedHashMap<K, V> {
+        assert!(
+            capacity > 0,
+            "A distributed hash map needs at least one slot."
+        );
+        let comm = comm.duplicate();
+        let states = Window::allocate(&comm, capacity);
+        unsafe {
+            ptr::write_bytes(states.base, 0, capacity);
+        }
+        let map = DistributedHashMap {
+            states,
+            keys: Window::allocate(&comm, capacity),
+            values: Window::allocate(&comm, capacity),
+            comm,
+            capacity,
+        };
+        map.states.sync();
+        map.comm.barrier();
+        map
+    }
+
+    /// The communicator of the processes that hold the map
+    pub fn communicator(&self) -> &UserCommunicator {
+        &self.comm
+    }
+
+    /// The number of slots on every process
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The rank of the process that owns `key`
+    pub fn owner(&self, key: &K) -> Rank {
+        self.locate(key).0
+    }
+
+    /// The value of `key`, if the map contains it
+    pub fn get(&self, key: &K) -> Option<V> {
+        let (owner, slot, _) = self.lock(key, false)?;
+        let value = self.values.get(owner, slot);
+        self.states.replace(owner, slot, FULL);
+        Some(value)
+    }
+
+    /// Whether the map contains `key`
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Set the value of `key` to `value` and return the previous value, if any.
+    ///
+    /// Panics if all slots of the owner of `key` hold other keys.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.modify(key, |_| value).0
+    }
+
+    /// Set the value of `key` to `f(value)`, where `value` is the current value of `key` if the
+    /// map contains it, and return the new value.
+    ///
+    /// The slot of `key` is locked while `f` runs, so concurrent updates of the same key from
+    /// different processes, e.g. of a counter, are not lost.
+    ///
+    /// Panics if all slots of the owner of `key` hold other keys.
+    pub fn update<F>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce(Option<V>) -> V,
+    {
+        self.modify(key, f).1
+    }
+
+    /// The entries held by the calling process
+    ///
+    /// Concurrent modifications of these entries by other processes may or may not be included.
+    pub fn local_entries(&self) -> Vec<(K, V)> {
+        let rank = self.comm.rank();
+        (0..self.capacity)
+            .filter(|&slot| self.states.fetch(rank, slot) == FULL)
+            .map(|slot| (self.keys.get(rank, slot), self.values.get(rank, slot)))
+            .collect()
+    }
+
+    /// Wait until all processes have completed their operations on the map.
+    ///
+    /// This is a collective operation.
+    pub fn synchronize(&self) {
+        self.comm.barrier();
+    }
+
+    fn modify<F>(&self, key: K, f: F) -> (Option<V>, V)
+    where
+        F: FnOnce(Option<V>) -> V,
+    {
+        let (owner, slot, occupied) = self.lock(&key, true).unwrap_or_else(|| {
+            panic!(
+                "All {} slots of process {} of the distributed hash map are taken.",
+                self.capacity,
+                self.owner(&key)
+            )
+        });
+        let old = if occupied {
+            Some(self.values.get(owner, slot))
+        } else {
+            self.keys.put(owner, slot, &key);
+            None
+        };
+        let value = f(old);
+        self.values.put(owner, slot, &value);
+        self.states.replace(owner, slot, FULL);
+        (old, value)
+    }
+
+    /// Lock the slot of `key` and return its owner, index and whether it holds `key` already.
+    ///
+    /// If the map does not contain `key`, claims the first free slot if `claim` is `true` and
+    /// returns `None` otherwise.
+    fn lock(&self, key: &K, claim: bool) -> Option<(Rank, usize, bool)> {
+        let (owner, start) = self.locate(key);
+        for probe in 0..self.capacity {
+            let slot = (start + probe) % self.capacity;
+            loop {
+                let state = if claim {
+                    self.states.compare_and_swap(owner, slot, EMPTY, LOCKED)
+                } else {
+                    self.states.fetch(owner, slot)
+                };
+                match state {
+                    EMPTY if claim => return Some((owner, slot, false)),
+                    EMPTY => return None,
+                    FULL if self.keys.get(owner, slot) != *key => break,
+                    FULL => {
+                        if self.states.compare_and_swap(owner, slot, FULL, LOCKED) == FULL {
+                            return Some((owner, slot, true));
+                        }
+                    }
+                    _ => thread::yield_now(),
+                }
+            }
+        }
+        None
+    }
+
+    fn locate(&self, key: &K) -> (Rank, usize) {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+        let size: u64 = self
+            .comm
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a u64.");
+        let owner = (hash % size)
+            .value_as()
+            .expect("Owner cannot be expressed as a Rank.");
+        let capacity: u64 = self
+            .capacity
+            .value_as()
+            .expect("Capacity cannot be expressed as a u64.");
+        let start = ((hash / size) % capacity)
+            .value_as()
+            .expect("Slot index cannot be expressed as a usize.");
+        (owner, start)
+    }
+}
+
+/// An RMA window of `capacity` elements of type `T` on every process, locked for passive target
+/// access by all processes
+struct Window<T> {
+    window: MPI_Win,
+    base: *mut T,
+}
+
+impl<T: Equivalence + Copy> Window<T> {
+    fn allocate(comm: &UserCommunicator, capacity: usize) -> Window<T> {
+        let bytes: Address = capacity
+            .checked_mul(mem::size_of::<T>())
+            .and_then(|bytes| bytes.value_as().ok())
+            .expect("Window size cannot be expressed as an MPI Address.");
+        let disp_unit: c_int = mem::size_of::<T>()
+            .max(1)
+            .value_as()
+            .expect("Element size cannot be expressed as a C int.");
+
+        let mut base: *mut c_void = ptr::null_mut();
+        let base_ptr: *mut *mut c_void = &mut base;
+        let mut window = unsafe { ffi::RSMPI_WIN_NULL };
+        unsafe {
+            ffi::MPI_Win_allocate(
+                bytes,
+                disp_unit,
+                ffi::RSMPI_INFO_NULL,
+                comm.as_raw(),
+                base_ptr as *mut c_void,
+                &mut window,
+            );
+            ffi::MPI_Win_lock_all(ffi::RSMPI_MODE_NOCHECK, window);
+        }
+        Window {
+            window,
+            base: base as *mut T,
+        }
+    }
+
+    fn get(&self, target: Rank, index: usize) -> T {
+        let mut value = MaybeUninit::<T>::uninit();
+        let datatype = T::equivalent_datatype();
+        unsafe {
+            ffi::MPI_Get(
+                value.as_mut_ptr() as *mut c_void,
+                1,
+                datatype.as_raw(),
+                target,
+                displacement(index),
+                1,
+                datatype.as_raw(),
+                self.window,
+            );
+            self.flush(target);
+            value.assume_init()
+        }
+    }
+
+    fn put(&self, target: Rank, index: usize, value: &T) {
+        let datatype = T::equivalent_datatype();
+        unsafe {
+            ffi::MPI_Put(
+                value as *const T as *const c_void,
+                1,
+                datatype.as_raw(),
+                target,
+                displacement(index),
+                1,
+                datatype.as_raw(),
+                self.window,
+            );
+            self.flush(target);
+        }
+    }
+
+    /// Make local stores to the window visible to the other processes.
+    fn sync(&self) {
+        unsafe {
+            ffi::MPI_Win_sync(self.window);
+        }
+    }
+
+    fn flush(&self, target: Rank) {
+        unsafe {
+            ffi::MPI_Win_flush(target, self.window);
+        }
+    }
+}
+
+impl Window<u64> {
+    /// Atomically read the element.
+    fn fetch(&self, target: Rank, index: usize) -> u64 {
+        self.fetch_and_op(target, index, 0, unsafe { ffi::RSMPI_NO_OP })
+    }
+
+    /// Atomically replace the element with `value`.
+    fn replace(&self, target: Rank, index: usize, value: u64) {
+        self.fetch_and_op(target, index, value, unsafe { ffi::RSMPI_REPLACE });
+    }
+
+    /// Atomically replace the element with `swap` if it equals `compare` and return its previous
+    /// value.
+    fn compare_and_swap(&self, target: Rank, index: usize, compare: u64, swap: u64) -> u64 {
+        let mut result = 0u64;
+        unsafe {
+            ffi::MPI_Compare_and_swap(
+                &swap as *const u64 as *const c_void,
+                &compare as *const u64 as *const c_void,
+                &mut result as *mut u64 as *mut c_void,
+                u64::equivalent_datatype().as_raw(),
+                target,
+                displacement(index),
+                self.window,
+            );
+            self.flush(target);
+        }
+        result
+    }
+
+    fn fetch_and_op(&self, target: Rank, index: usize, value: u64, op: MPI_Op) -> u64 {
+        let mut result = 0u64;
+        unsafe {
+            ffi::MPI_Fetch_and_op(
+                &value as *const u64 as *const c_void,
+                &mut result as *mut u64 as *mut c_void,
+                u64::equivalent_datatype().as_raw(),
+                target,
+                displacement(index),
+                op,
+                self.window,
+            );
+            self.flush(target);
+        }
+        result
+    }
+}
+
+impl<T> Drop for Window<T> {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("DistributedHashMap") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Win_unlock_all(self.window);
+            ffi::MPI_Win_free(&mut self.window);
+        }
+        assert_eq!(self.window, unsafe { ffi::RSMPI_WIN_NULL });
+    }
+}
+
+fn displacement(index: usize) -> Address {
+    index
+        .value_as()
+        .expect("Slot index cannot be expressed as an MPI Address.")
+}
@@ -0,0 +1,168 @@
+//! Detection of and communication across heterogeneous processes
+//!
+//! `Equivalence` maps Rust types to MPI datatypes by their size on the calling process, e.g.
+//! `usize` becomes a 32 or 64 bit integer depending on the pointer width. If processes run on
+//! machines with different endianness, pointer width or primitive sizes, messages between them can
+//! be corrupted silently. `handshake()` compares a description of the platform of every process
+//! and records whether they differ.
+//!
+//! The sends and receives of `PortableDestination` and `PortableSource` transfer messages in the
+//! portable `external32` representation if the handshake detected differing platforms, and as
+//! ordinary typed messages otherwise. Both sides of a message have to use the portable operations.
+//!
+//! # Examples
+//!
+//! See `examples/heterogeneous.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4.3
+
+use std::mem;
+use std::os::raw::{c_int, c_long, c_longlong, c_short};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::pack::{pack_external, unpack_external_into};
+use crate::datatype::traits::*;
+use crate::point_to_point::traits::*;
+use crate::point_to_point::Status;
+use crate::topology::traits::*;
+use crate::Tag;
+
+/// Portable communication traits
+pub mod traits {
+    pub use super::{PortableDestination, PortableSource};
+}
+
+static HETEROGENEOUS: AtomicBool = AtomicBool::new(false);
+
+/// Number of entries in a platform description
+const PLATFORM_LEN: usize = 9;
+
+/// A description of the data representation on the calling process
+fn platform() -> [u32; PLATFORM_LEN] {
+    fn size<T>() -> u32 {
+        mem::size_of::<T>()
+            .value_as()
+            .expect("Size of primitive type cannot be expressed as a u32.")
+    }
+
+    [
+        // Differs between processes with different endianness.
+        u32::from_ne_bytes([1, 2, 3, 4]),
+        size::<usize>(),
+        size::<c_short>(),
+        size::<c_int>(),
+        size::<c_long>(),
+        size::<c_longlong>(),
+        size::<f32>(),
+        size::<f64>(),
+        size::<bool>(),
+    ]
+}
+
+/// Compare the platforms of all processes of `comm`.
+///
+/// Returns whether all processes share the same endianness, pointer width and primitive sizes. If
+/// they do not, the portable operations of this module switch to the `external32` representation
+/// for the rest of the program run. This is a collective operation.
+pub fn handshake<C>(comm: &C) -> bool
+where
+    C: Communicator,
+{
+    let local = platform();
+    let size: usize = comm
+        .size()
+        .value_as()
+        .expect("Communicator size cannot be expressed as a usize.");
+    let mut all = vec![0u32; size * PLATFORM_LEN];
+    comm.all_gather_into(&local[..], &mut all[..]);
+    let homogeneous = all.chunks(PLATFORM_LEN).all(|other| other == local);
+    if !homogeneous {
+        HETEROGENEOUS.store(true, Ordering::Relaxed);
+    }
+    homogeneous
+}
+
+/// Whether a previous `handshake()` detected processes with differing platforms
+pub fn is_heterogeneous() -> bool {
+    HETEROGENEOUS.load(Ordering::Relaxed)
+}
+
+/// Sends that stay intact between processes with differing platforms
+pub trait PortableDestination: Destination {
+    /// Send the contents of `buf` in a portable representation, tagged `tag`.
+    ///
+    /// The message has to be received by `PortableSource::receive_portable_into_with_tag()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.1, 4.3
+    fn send_portable_with_tag<Buf: ?Sized>(&self, buf: &Buf, tag: Tag)
+    where
+        Buf: Buffer,
+    {
+        if is_heterogeneous() {
+            self.send_with_tag(&pack_external(buf)[..], tag);
+        } else {
+            self.send_with_tag(buf, tag);
+        }
+    }
+
+    /// Send the contents of `buf` in a portable representation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.1, 4.3
+    fn send_portable<Buf: ?Sized>(&self, buf: &Buf)
+    where
+        Buf: Buffer,
+    {
+        self.send_portable_with_tag(buf, self.as_peer_communicator().send_tag())
+    }
+}
+
+impl<D: Destination> PortableDestination for D {}
+
+/// Receives of messages sent by `PortableDestination`
+pub trait PortableSource: Source {
+    /// Receive a message tagged `tag` sent by `PortableDestination::send_portable_with_tag()` into
+    /// `buf`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.4, 4.3
+    fn receive_portable_into_with_tag<Buf: ?Sized>(&self, buf: &mut Buf, tag: Tag) -> Status
+    where
+        Buf: BufferMut,
+    {
+        if is_heterogeneous() {
+            let (msg, _) = self.matched_probe_with_tag(tag);
+            let (packed, status) = msg.matched_receive_vec::<u8>();
+            // The sender packed a buffer of the same datatype and count.
+            unsafe {
+                unpack_external_into(&packed, buf, 0);
+            }
+            status
+        } else {
+            self.receive_into_with_tag(buf, tag)
+        }
+    }
+
+    /// Receive a message sent by `PortableDestination::send_portable()` into `buf`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.4, 4.3
+    fn receive_portable_into<Buf: ?Sized>(&self, buf: &mut Buf) -> Status
+    where
+        Buf: BufferMut,
+    {
+        self.receive_portable_into_with_tag(buf, self.as_peer_communicator().receive_tag())
+    }
+}
+
+impl<S: Source> PortableSource for S {}
@@ -0,0 +1,267 @@
+//! Interception of communication calls
+//!
+//! Hooks registered with `register()` are invoked before and after the point to point and
+//! collective operations of rsmpi, receiving the name of the operation, a summary of its arguments
+//! and the time it took. This allows writing profilers and tracers in Rust without relying on the
+//! C profiling interface (PMPI).
+//!
+//! While no hook is registered and statistics are disabled, a call costs two atomic loads.
+//! Communication issued from within a hook is not reported to the hooks again. The statistics of
+//! the `statistics` module are gathered by a built-in hook that is invoked before the registered
+//! hooks.
+//!
+//! # Examples
+//!
+//! See `examples/hooks.rs`
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+#[cfg(feature = "fault-injection")]
+use crate::faults;
+use crate::ffi::{MPI_Comm, MPI_Datatype};
+use crate::point_to_point::Status;
+use crate::statistics;
+use crate::topology::Rank;
+use crate::{Count, Tag};
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+type Hooks = Arc<Vec<Arc<dyn CommHook>>>;
+
+static HOOKS: Lazy<RwLock<Hooks>> = Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+thread_local! {
+    static IN_HOOK: Cell<bool> = Cell::new(false);
+}
+
+/// A hook invoked around communication calls
+///
+/// Both methods do nothing by default.
+pub trait CommHook: Send + Sync {
+    /// Invoked before the operation described by `call` starts.
+    fn before(&self, _call: &Call) {}
+
+    /// Invoked after the operation described by `call` has completed, which took `duration`.
+    ///
+    /// For nonblocking operations, `duration` covers starting the operation only.
+    fn after(&self, _call: &Call, _duration: Duration) {}
+}
+
+/// Register `hook` to be invoked around all subsequent communication calls.
+///
+/// Hooks are invoked in the order they were registered. A hook registered from within a hook is
+/// invoked starting with the next communication call.
+pub fn register(hook: Box<dyn CommHook>) {
+    let mut hooks = HOOKS
+        .write()
+        .expect("rsmpi internal error: hook registry lock poisoned");
+    let mut registered = Vec::clone(&hooks);
+    registered.push(Arc::from(hook));
+    *hooks = Arc::new(registered);
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Remove all registered hooks.
+///
+/// Hooks removed from within a hook are still invoked for the call that is being reported.
+pub fn clear() {
+    *HOOKS
+        .write()
+        .expect("rsmpi internal error: hook registry lock poisoned") = Arc::new(Vec::new());
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+/// A summary of a communication call
+#[derive(Copy, Clone, Debug)]
+pub struct Call {
+    /// Name of the operation, e.g. `"send_with_tag"` or `"all_reduce_into"`
+    pub name: &'static str,
+    /// The communicator the operation is performed on
+    pub comm: MPI_Comm,
+    /// Rank of the destination or source process of a point to point operation
+    pub peer: Option<Rank>,
+    /// Tag of a point to point operation
+    pub tag: Option<Tag>,
+    /// Number of elements sent or received by a point to point operation
+    pub count: Option<Count>,
+    /// Datatype of the message sent to `peer` with `tag`, consisting of `count` elements
+    pub(crate) sent: Option<MPI_Datatype>,
+    /// Datatype of the message received by the operation
+    pub(crate) receives: Option<MPI_Datatype>,
+    /// Status of the received message, once the operation has completed
+    pub(crate) received: Option<Status>,
+}
+
+impl Call {
+    pub(crate) fn collective(comm: MPI_Comm, name: &'static str) -> Call {
+        Call {
+            name,
+            comm,
+            peer: None,
+            tag: None,
+            count: None,
+            sent: None,
+            receives: None,
+            received: None,
+        }
+    }
+
+    pub(crate) fn send(
+        comm: MPI_Comm,
+        name: &'static str,
+        destination: Rank,
+        tag: Tag,
+        count: Count,
+        datatype: MPI_Datatype,
+    ) -> Call {
+        Call {
+            name,
+            comm,
+            peer: Some(destination),
+            tag: Some(tag),
+            count: Some(count),
+            sent: Some(datatype),
+            receives: None,
+            received: None,
+        }
+    }
+
+    pub(crate) fn receive(
+        comm: MPI_Comm,
+        name: &'static str,
+        source: Rank,
+        tag: Tag,
+        count: Option<Count>,
+        datatype: MPI_Datatype,
+    ) -> Call {
+        Call {
+            name,
+            comm,
+            peer: Some(source),
+            tag: Some(tag),
+            count,
+            sent: None,
+            receives: Some(datatype),
+            received: None,
+        }
+    }
+
+    pub(crate) fn send_receive(
+        comm: MPI_Comm,
+        name: &'static str,
+        destination: Rank,
+        tag: Tag,
+        count: Count,
+        sendtype: MPI_Datatype,
+        receivetype: MPI_Datatype,
+    ) -> Call {
+        Call {
+            receives: Some(receivetype),
+            ..Call::send(comm, name, destination, tag, count, sendtype)
+        }
+    }
+}
+
+/// Reports the completion of a call to the hooks when dropped
+pub(crate) struct CallGuard {
+    call: Call,
+    start: Instant,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        with_hooks(|hooks| {
+            if statistics::is_enabled() {
+                statistics::Recorder.after(&self.call, duration);
+            }
+            for hook in hooks.iter() {
+                hook.after(&self.call, duration);
+            }
+        });
+    }
+}
+
+/// Report the start of the call described by `call` to the hooks.
+///
+/// The returned guard reports the completion of the call when it is dropped. `call` is only
+/// evaluated if hooks are registered or statistics are enabled.
+#[inline]
+pub(crate) fn enter<F>(call: F) -> Option<CallGuard>
+where
+    F: FnOnce() -> Call,
+{
+    if !(ACTIVE.load(Ordering::Relaxed) || statistics::is_enabled()) || IN_HOOK.with(Cell::get) {
+        #[cfg(feature = "fault-injection")]
+        faults::delay(call);
+        return None;
+    }
+    let call = call();
+    #[cfg(feature = "fault-injection")]
+    faults::delay(|| call);
+    with_hooks(|hooks| {
+        if statistics::is_enabled() {
+            statistics::Recorder.before(&call);
+        }
+        for hook in hooks.iter() {
+            hook.before(&call);
+        }
+    });
+    Some(CallGuard {
+        call,
+        start: Instant::now(),
+    })
+}
+
+/// Attach the status of the received message to the call reported by `guard`.
+#[inline]
+pub(crate) fn received(guard: &mut Option<CallGuard>, status: &Status) {
+    if let Some(guard) = guard {
+        guard.call.received = Some(*status);
+    }
+}
+
+/// Sets `IN_HOOK` and restores its previous value when dropped, even if a hook panics
+struct InHook(bool);
+
+impl InHook {
+    fn set() -> InHook {
+        InHook(IN_HOOK.with(|in_hook| in_hook.replace(true)))
+    }
+}
+
+impl Drop for InHook {
+    fn drop(&mut self) {
+        let previous = self.0;
+        IN_HOOK.with(|in_hook| in_hook.set(previous));
+    }
+}
+
+fn with_hooks<F>(f: F)
+where
+    F: FnOnce(&[Arc<dyn CommHook>]),
+{
+    // Invoke a snapshot of the hooks without holding the lock, so that hooks can register or
+    // clear hooks.
+    let hooks = HOOKS
+        .read()
+        .expect("rsmpi internal error: hook registry lock poisoned")
+        .clone();
+    let _in_hook = InHook::set();
+    f(&hooks);
+}
+
+/// Run `f` without reporting the calls it makes to the hooks or the statistics, like
+/// communication issued from within a hook.
+pub(crate) fn unobserved<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _in_hook = InHook::set();
+    f()
+}
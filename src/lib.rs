@@ -83,7 +83,7 @@
 //!
 //! - **Groups, Contexts, Communicators**:
 //!   - Group and (Intra-)Communicator management from section 6 is mostly complete.
-//!   - no Inter-Communicators
+//!   - Inter-Communicators from connecting separately started groups of processes
 //!   - no process topologies
 //! - **Point to point communication**:
 //!   - standard, buffered, synchronous and ready mode send in blocking and non-blocking variants
@@ -105,9 +105,11 @@
 //!
 //! Not supported (yet):
 //!
-//! - Process management
-//! - One-sided communication (RMA)
-//! - MPI parallel I/O
+//! - Process management, except for connecting separately started groups of processes
+//! - One-sided communication (RMA), except as used by `hashmap::DistributedHashMap` and the
+//! node-local shared memory of `shared`
+//! - MPI parallel I/O, except for the checkpoint containers of `container` (`container`
+//! feature)
 //! - A million small things
 //!
 //! The sub-modules contain a more detailed description of which features are and are not
@@ -135,20 +137,62 @@ pub mod ffi {
     pub use mpi_sys::*;
 }
 
+pub mod bench;
+pub mod bitset;
+pub mod clock;
 pub mod collective;
+#[cfg(feature = "container")]
+pub mod container;
+pub mod counts;
+pub mod coupling;
 pub mod datatype;
 pub mod environment;
+#[cfg(feature = "chrono")]
+pub mod events;
+#[cfg(feature = "fault-injection")]
+pub mod faults;
+pub mod funnel;
+pub mod hashmap;
+pub mod heterogeneous;
+pub mod hooks;
+pub mod memory;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod partitioned;
+pub mod pipeline;
+pub mod placement;
 pub mod point_to_point;
+pub mod priority;
+pub mod random;
 pub mod raw;
 pub mod request;
+pub mod schedule;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serialized;
+pub mod sets;
+pub mod shared;
+pub mod sort;
+pub mod staging;
+pub mod statistics;
+#[cfg(unix)]
+pub mod stdio;
+pub mod stream;
+pub mod taskpool;
+pub mod telemetry;
 pub mod topology;
+#[cfg(feature = "validate")]
+pub mod validation;
 
 /// Re-exports all traits.
 pub mod traits {
     pub use crate::collective::traits::*;
     pub use crate::datatype::traits::*;
+    pub use crate::heterogeneous::traits::*;
     pub use crate::point_to_point::traits::*;
     pub use crate::raw::traits::*;
+    #[cfg(feature = "serde")]
+    pub use crate::serialized::traits::*;
     pub use crate::topology::traits::*;
 
     // Re-export derives
@@ -169,12 +213,14 @@ pub use crate::environment::{
     initialize, initialize_with_threading, time, time_resolution, Threading,
 };
 
-use crate::ffi::MPI_Aint;
+use crate::ffi::{MPI_Aint, MPI_Count};
 
 /// Encodes error values returned by MPI functions.
 pub type Error = c_int;
 /// Encodes number of values in multi-value messages.
 pub type Count = c_int;
+/// Encodes number of values in messages that may exceed the range of a `Count`.
+pub type LargeCount = MPI_Count;
 /// Can be used to tag messages on the sender side and match on the receiver side.
 pub type Tag = c_int;
 /// An address in memory
@@ -0,0 +1,309 @@
+//! Memory allocation
+//!
+//! High-throughput pipelines that send many large messages benefit from reusing the same memory
+//! for consecutive messages. Interconnects that use remote direct memory access (RDMA) have to
+//! register memory with the network hardware before it can be used in communication and cache
+//! these registrations. A `BufferPool` hands out page-aligned buffers and takes them back when they
+//! are dropped, so that the same registered pages are used again and again.
+//!
+//! # Standard section(s)
+//!
+//! 8.2
+
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::{c_int, c_void};
+use std::ptr::{self, NonNull};
+use std::sync::Mutex;
+use std::{fmt, slice};
+
+use conv::ConvUtil;
+
+use crate::environment::is_finalized;
+use crate::ffi;
+
+/// The alignment of buffers handed out by a `BufferPool` unless specified otherwise
+pub const PAGE_SIZE: usize = 4096;
+
+/// How the memory of a `BufferPool` is allocated
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Allocation {
+    /// Memory is allocated by the Rust global allocator.
+    PageAligned,
+    /// Memory is allocated by the MPI library via `MPI_Alloc_mem()`.
+    ///
+    /// MPI libraries can hand out memory that is already registered with the network hardware or,
+    /// in the case of CUDA-aware MPI libraries, pinned host memory. The buffers must be released
+    /// before MPI is finalized.
+    Registered,
+}
+
+/// A block of memory owned by a `BufferPool`
+struct Block {
+    /// Start of the allocation as returned by the allocator
+    base: NonNull<u8>,
+    /// Start of the aligned part of the allocation
+    data: NonNull<u8>,
+    /// Size of the aligned part of the allocation in bytes
+    capacity: usize,
+}
+
+// A `Block` is plain memory exclusively owned by either the pool or a single `PooledBuffer`.
+unsafe impl Send for Block {}
+
+/// A pool of page-aligned buffers that are reused across messages
+///
+/// Buffers are handed out in size classes that are powers of two and at least one page large.
+/// Dropping a `PooledBuffer` returns its memory to the pool.
+///
+/// # Examples
+///
+/// See `examples/buffer_pool.rs`
+///
+/// # Standard section(s)
+///
+/// 8.2
+pub struct BufferPool {
+    allocation: Allocation,
+    alignment: usize,
+    free: Mutex<HashMap<usize, Vec<Block>>>,
+}
+
+impl BufferPool {
+    /// A pool of page-aligned buffers allocated via `allocation`
+    pub fn new(allocation: Allocation) -> BufferPool {
+        BufferPool::with_alignment(allocation, PAGE_SIZE)
+    }
+
+    /// A pool of buffers aligned to `alignment` bytes allocated via `allocation`
+    ///
+    /// `alignment` must be a power of two.
+    pub fn with_alignment(allocation: Allocation, alignment: usize) -> BufferPool {
+        assert!(
+            alignment.is_power_of_two(),
+            "Buffer alignment {} is not a power of two.",
+            alignment
+        );
+        BufferPool {
+            allocation,
+            alignment,
+            free: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How the memory of this pool is allocated
+    pub fn allocation(&self) -> Allocation {
+        self.allocation
+    }
+
+    /// The alignment of the buffers of this pool in bytes
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    /// Take a buffer of `len` bytes from the pool.
+    ///
+    /// Allocates new, zeroed memory if the pool does not contain a buffer of the right size class.
+    /// The contents of a reused buffer are whatever the previous user left in it.
+    pub fn acquire(&self, len: usize) -> PooledBuffer {
+        let capacity = len
+            .max(self.alignment)
+            .checked_next_power_of_two()
+            .expect("Requested buffer size exceeds the address space.");
+        let block = self
+            .free
+            .lock()
+            .expect("rsmpi internal error: buffer pool lock poisoned")
+            .get_mut(&capacity)
+            .and_then(Vec::pop);
+        let block = block.unwrap_or_else(|| self.allocate(capacity));
+        PooledBuffer {
+            block: Some(block),
+            len,
+            pool: self,
+        }
+    }
+
+    /// Total size in bytes of the buffers currently kept in the pool for reuse
+    pub fn cached_bytes(&self) -> usize {
+        self.free
+            .lock()
+            .expect("rsmpi internal error: buffer pool lock poisoned")
+            .values()
+            .flat_map(|blocks| blocks.iter().map(|block| block.capacity))
+            .sum()
+    }
+
+    /// Release all buffers currently kept in the pool for reuse.
+    pub fn clear(&self) {
+        let blocks: Vec<Block> = self
+            .free
+            .lock()
+            .expect("rsmpi internal error: buffer pool lock poisoned")
+            .drain()
+            .flat_map(|(_, blocks)| blocks)
+            .collect();
+        for block in blocks {
+            self.deallocate(block);
+        }
+    }
+
+    fn layout(&self, capacity: usize) -> Layout {
+        Layout::from_size_align(capacity, self.alignment)
+            .expect("Requested buffer size exceeds the address space.")
+    }
+
+    fn allocate(&self, capacity: usize) -> Block {
+        let size = capacity
+            .checked_add(self.alignment - 1)
+            .expect("Requested buffer size exceeds the address space.");
+        // The memory is zeroed, since a `PooledBuffer` hands it out as initialized bytes.
+        let base = match self.allocation {
+            Allocation::PageAligned => {
+                let layout = self.layout(capacity);
+                let base = unsafe { alloc::alloc_zeroed(layout) };
+                NonNull::new(base).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+            }
+            Allocation::Registered => unsafe {
+                let mut base: *mut u8 = ptr::null_mut();
+                let base_ptr: *mut *mut u8 = &mut base;
+                let code = ffi::MPI_Alloc_mem(
+                    size.value_as()
+                        .expect("Requested buffer size cannot be expressed as an MPI Address."),
+                    ffi::RSMPI_INFO_NULL,
+                    base_ptr as *mut c_void,
+                );
+                assert_eq!(
+                    code,
+                    ffi::MPI_SUCCESS as c_int,
+                    "MPI_Alloc_mem() failed to allocate {} bytes.",
+                    size
+                );
+                let base = NonNull::new(base).expect("MPI_Alloc_mem() returned a null pointer.");
+                ptr::write_bytes(base.as_ptr(), 0, size);
+                base
+            },
+        };
+        let offset = base.as_ptr().align_offset(self.alignment);
+        let data = unsafe { NonNull::new_unchecked(base.as_ptr().add(offset)) };
+        Block {
+            base,
+            data,
+            capacity,
+        }
+    }
+
+    fn deallocate(&self, block: Block) {
+        match self.allocation {
+            Allocation::PageAligned => unsafe {
+                alloc::dealloc(block.base.as_ptr(), self.layout(block.capacity));
+            },
+            Allocation::Registered => {
+                // Memory from `MPI_Alloc_mem()` can not be released after `MPI_Finalize()`.
+                if !is_finalized() {
+                    unsafe {
+                        ffi::MPI_Free_mem(block.base.as_ptr() as *mut c_void);
+                    }
+                }
+            }
+        }
+    }
+
+    fn release(&self, block: Block) {
+        self.free
+            .lock()
+            .expect("rsmpi internal error: buffer pool lock poisoned")
+            .entry(block.capacity)
+            .or_insert_with(Vec::new)
+            .push(block);
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> BufferPool {
+        BufferPool::new(Allocation::PageAligned)
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("allocation", &self.allocation)
+            .field("alignment", &self.alignment)
+            .field("cached_bytes", &self.cached_bytes())
+            .finish()
+    }
+}
+
+impl Drop for BufferPool {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A buffer taken from a `BufferPool`
+///
+/// Dereferences to a byte slice that can be used as a `Buffer` or `BufferMut` in communication
+/// operations. Dropping the buffer returns its memory to the pool.
+pub struct PooledBuffer<'p> {
+    block: Option<Block>,
+    len: usize,
+    pool: &'p BufferPool,
+}
+
+impl<'p> PooledBuffer<'p> {
+    /// The size of the memory backing this buffer, which can be larger than its length
+    pub fn capacity(&self) -> usize {
+        self.block().capacity
+    }
+
+    /// Change the length of this buffer without reallocating.
+    ///
+    /// `len` must not exceed the capacity of the buffer.
+    pub fn set_len(&mut self, len: usize) {
+        assert!(
+            len <= self.capacity(),
+            "Length {} exceeds capacity {} of pooled buffer.",
+            len,
+            self.capacity()
+        );
+        self.len = len;
+    }
+
+    fn block(&self) -> &Block {
+        self.block
+            .as_ref()
+            .expect("rsmpi internal error: pooled buffer without memory")
+    }
+}
+
+impl<'p> Deref for PooledBuffer<'p> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.block().data.as_ptr(), self.len) }
+    }
+}
+
+impl<'p> DerefMut for PooledBuffer<'p> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.block().data.as_ptr(), self.len) }
+    }
+}
+
+impl<'p> fmt::Debug for PooledBuffer<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<'p> Drop for PooledBuffer<'p> {
+    fn drop(&mut self) {
+        if let Some(block) = self.block.take() {
+            self.pool.release(block);
+        }
+    }
+}
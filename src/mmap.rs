@@ -0,0 +1,95 @@
+//! Distribution of memory-mapped files
+//!
+//! Input files of large simulations often do not fit into the memory of a single process. Instead
+//! of reading such a file on the root process and scattering it from an in-memory copy, the root
+//! process can map the file into its address space and scatter directly from the mapping, so that
+//! the operating system pages the file in as the MPI library reads from it.
+//!
+//! Memory-mapped files are plain byte slices, so `&mmap[..]` can be passed to any operation that
+//! takes a `Buffer`. `scatter_from_mmap()` wraps the mapping and scattering of a whole file.
+//!
+//! This module is only available with the `mmap` feature.
+//!
+//! # Examples
+//!
+//! See `examples/scatter_mmap.rs`
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use conv::ConvUtil;
+use memmap2::Mmap;
+
+use crate::collective::{CollectivePlan, Root};
+use crate::topology::traits::*;
+
+/// Map the file at `path` on the root process and scatter its bytes to all processes.
+///
+/// `layout` assigns the bytes of the file to the processes, process `r` receives the
+/// `layout.counts()[r]` bytes starting at byte `layout.displs()[r]` of the file. `path` is only
+/// significant on the root process, `layout` has to be the same on all processes. Returns the
+/// received bytes on every process.
+///
+/// If the root process fails to map the file or the file is shorter than `layout.extent()`, all
+/// processes return an error. This is a collective operation.
+///
+/// The file must not be modified while it is mapped, see `memmap2::Mmap::map()`.
+///
+/// # Standard section(s)
+///
+/// 5.6
+pub fn scatter_from_mmap<R, P>(root: &R, path: P, layout: &CollectivePlan) -> io::Result<Vec<u8>>
+where
+    R: Root,
+    P: AsRef<Path>,
+{
+    let comm = root.as_communicator();
+    let rank: usize = comm
+        .rank()
+        .value_as()
+        .expect("Rank cannot be expressed as a usize.");
+    let count: usize = layout.counts()[rank]
+        .value_as()
+        .expect("Message length cannot be expressed as a usize.");
+    let mut recv = vec![0u8; count];
+
+    if comm.rank() == root.root_rank() {
+        let map = map(path.as_ref(), layout);
+        let mut mapped = map.is_ok();
+        root.broadcast_into(&mut mapped);
+        let map = map?;
+        layout.scatter_varcount_into_root(root, &map[..], &mut recv[..]);
+    } else {
+        let mut mapped = false;
+        root.broadcast_into(&mut mapped);
+        if !mapped {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "The root process failed to map the file.",
+            ));
+        }
+        root.scatter_varcount_into(&mut recv[..]);
+    }
+    Ok(recv)
+}
+
+fn map(path: &Path, layout: &CollectivePlan) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    let map = unsafe { Mmap::map(&file)? };
+    let extent: usize = layout
+        .extent()
+        .value_as()
+        .expect("Extent of layout cannot be expressed as a usize.");
+    if map.len() < extent {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "File of {} bytes is shorter than the extent of the layout ({} bytes).",
+                map.len(),
+                extent
+            ),
+        ));
+    }
+    Ok(map)
+}
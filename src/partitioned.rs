@@ -0,0 +1,450 @@
+//! Partitioned point to point communication
+//!
+//! A partitioned send transfers a buffer that is split into a number of equally sized partitions.
+//! The sending side marks partitions as ready one by one, e.g. from several threads that fill the
+//! buffer, and the receiving side can poll for the partitions that have arrived and start
+//! processing them before the whole message is complete.
+//!
+//! A `PartitionedRequest` is neither `Send` nor `Sync` and is polled by the thread that owns it.
+//! To process arrived partitions on other threads, that thread hands the slices yielded by
+//! `arrivals()` to the workers, the request cannot be shared with them.
+//!
+//! Partitioned requests are persistent: they are initialized once, can be started and completed
+//! many times and are freed when they are dropped. Partitioned communication was introduced by
+//! MPI 4.0, check `environment::is_supported(Feature::Partitioned)` before using this module.
+//!
+//! # Examples
+//!
+//! See `examples/partitioned.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use std::slice;
+
+use conv::ConvUtil;
+
+use crate::datatype::traits::*;
+use crate::environment::{self, Feature};
+use crate::ffi;
+use crate::ffi::{MPI_Request, MPI_Status};
+use crate::point_to_point::{Destination, Source, Status};
+use crate::raw::traits::*;
+use crate::request::{Scope, StaticScope};
+use crate::{with_uninitialized, Tag};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Direction {
+    Send,
+    Receive,
+}
+
+/// A persistent request for a partitioned send or receive of a buffer of `T`s
+///
+/// The request mutably borrows its buffer for the lifetime `'a` and is registered with a `Scope`
+/// like the requests of non-blocking operations. Dropping the request waits for an active
+/// operation to complete and frees the request, the partitions of an active send that have not
+/// been marked as ready are marked before waiting.
+///
+/// # Standard section(s)
+///
+/// 4.2
+pub struct PartitionedRequest<'a, T, S: Scope<'a> = StaticScope> {
+    request: MPI_Request,
+    direction: Direction,
+    buf: *mut T,
+    partitions: usize,
+    partition_len: usize,
+    active: bool,
+    // Partitions that were marked ready (sends) or reported as arrived (receives) since the last
+    // start.
+    marked: Vec<bool>,
+    scope: S,
+    phantom: PhantomData<Cell<&'a mut [T]>>,
+}
+
+/// Initialize a partitioned send of `buf` in `partitions` equally sized partitions to
+/// `destination`.
+///
+/// The length of `buf` has to be a multiple of `partitions`. The matching receive has to be
+/// initialized by `receive_init()` with the same tag.
+///
+/// # Standard section(s)
+///
+/// 4.2.2
+pub fn send_init<'a, T, S, D>(
+    scope: S,
+    buf: &'a mut [T],
+    partitions: usize,
+    destination: &D,
+    tag: Tag,
+) -> PartitionedRequest<'a, T, S>
+where
+    T: 'a + Equivalence,
+    S: Scope<'a>,
+    D: Destination,
+{
+    let (partitions_c, count) = partitioning(buf.len(), partitions);
+    let request = unsafe {
+        with_uninitialized(|request| {
+            ffi::RSMPI_Psend_init(
+                buf.as_ptr() as _,
+                partitions_c,
+                count,
+                T::equivalent_datatype().as_raw(),
+                destination.destination_rank(),
+                tag,
+                destination.as_peer_communicator().as_raw(),
+                ffi::RSMPI_INFO_NULL,
+                request,
+            )
+        })
+        .1
+    };
+    PartitionedRequest::new(request, Direction::Send, buf, partitions, scope)
+}
+
+/// Initialize a partitioned receive into `buf` in `partitions` equally sized partitions from
+/// `source`.
+///
+/// The length of `buf` has to be a multiple of `partitions`. Partitioned receives cannot use
+/// `any_process()` as the source.
+///
+/// # Standard section(s)
+///
+/// 4.2.2
+pub fn receive_init<'a, T, S, Src>(
+    scope: S,
+    buf: &'a mut [T],
+    partitions: usize,
+    source: &Src,
+    tag: Tag,
+) -> PartitionedRequest<'a, T, S>
+where
+    T: 'a + Equivalence,
+    S: Scope<'a>,
+    Src: Source,
+{
+    assert_ne!(
+        source.source_rank(),
+        unsafe { ffi::RSMPI_ANY_SOURCE },
+        "Partitioned receives need a specific source process."
+    );
+    let (partitions_c, count) = partitioning(buf.len(), partitions);
+    let request = unsafe {
+        with_uninitialized(|request| {
+            ffi::RSMPI_Precv_init(
+                buf.as_mut_ptr() as _,
+                partitions_c,
+                count,
+                T::equivalent_datatype().as_raw(),
+                source.source_rank(),
+                tag,
+                source.as_peer_communicator().as_raw(),
+                ffi::RSMPI_INFO_NULL,
+                request,
+            )
+        })
+        .1
+    };
+    PartitionedRequest::new(request, Direction::Receive, buf, partitions, scope)
+}
+
+/// Check the partitioning of a buffer of `len` elements and return the number of partitions and
+/// the number of elements per partition as passed to MPI.
+fn partitioning(len: usize, partitions: usize) -> (c_int, ffi::MPI_Count) {
+    assert!(
+        environment::is_supported(Feature::Partitioned),
+        "The MPI library does not support partitioned communication."
+    );
+    assert!(
+        partitions > 0 && len % partitions == 0,
+        "A buffer of length {} cannot be split into {} equally sized partitions.",
+        len,
+        partitions
+    );
+    (
+        partitions
+            .value_as()
+            .expect("Number of partitions cannot be expressed as a c_int."),
+        (len / partitions)
+            .value_as()
+            .expect("Length of a partition cannot be expressed as an MPI_Count."),
+    )
+}
+
+impl<'a, T, S: Scope<'a>> PartitionedRequest<'a, T, S> {
+    fn new(
+        request: MPI_Request,
+        direction: Direction,
+        buf: &'a mut [T],
+        partitions: usize,
+        scope: S,
+    ) -> Self {
+        scope.register();
+        PartitionedRequest {
+            request,
+            direction,
+            buf: buf.as_mut_ptr(),
+            partitions,
+            partition_len: buf.len() / partitions,
+            active: false,
+            marked: vec![false; partitions],
+            scope,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Number of partitions of the buffer
+    pub fn partitions(&self) -> usize {
+        self.partitions
+    }
+
+    /// Whether the request has been started and not yet completed
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Start the operation.
+    ///
+    /// Panics if the operation is already active.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.9, 4.2.2
+    pub fn start(&mut self) {
+        assert!(!self.active, "The partitioned request is already active.");
+        for marked in &mut self.marked {
+            *marked = false;
+        }
+        unsafe {
+            ffi::MPI_Start(&mut self.request);
+        }
+        self.active = true;
+    }
+
+    /// Wait for the operation to complete.
+    ///
+    /// The request stays allocated and can be started again. Panics if this is a send and not
+    /// every partition has been marked as ready, since the operation could never complete.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.7.3
+    pub fn wait(&mut self) -> Status {
+        assert!(self.active, "The partitioned request is not active.");
+        if self.direction == Direction::Send {
+            if let Some(partition) = self.marked.iter().position(|&marked| !marked) {
+                panic!("Partition {} has not been marked as ready.", partition);
+            }
+        }
+        let status = unsafe {
+            with_uninitialized(|status: *mut MPI_Status| ffi::MPI_Wait(&mut self.request, status)).1
+        };
+        self.active = false;
+        Status::from_raw(status)
+    }
+
+    /// The elements of partition `partition` for filling them before they are marked as ready
+    ///
+    /// Panics if this is not a send request or if the partition has already been marked as ready
+    /// by the active operation.
+    pub fn partition_mut(&mut self, partition: usize) -> &mut [T] {
+        assert_eq!(
+            self.direction,
+            Direction::Send,
+            "Only the partitions of a partitioned send can be modified."
+        );
+        self.check_partition(partition);
+        assert!(
+            !(self.active && self.marked[partition]),
+            "Partition {} has already been marked as ready.",
+            partition
+        );
+        unsafe { slice::from_raw_parts_mut(self.partition_ptr(partition), self.partition_len) }
+    }
+
+    /// Mark partition `partition` of an active send as ready to be transferred.
+    ///
+    /// Every partition has to be marked exactly once per operation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.2.3
+    pub fn ready(&mut self, partition: usize) {
+        assert_eq!(
+            self.direction,
+            Direction::Send,
+            "Only the partitions of a partitioned send can be marked as ready."
+        );
+        assert!(self.active, "The partitioned request is not active.");
+        self.check_partition(partition);
+        assert!(
+            !self.marked[partition],
+            "Partition {} has already been marked as ready.",
+            partition
+        );
+        unsafe {
+            ffi::RSMPI_Pready(self.partition_c(partition), self.request);
+        }
+        self.marked[partition] = true;
+    }
+
+    /// Whether partition `partition` of an active receive has arrived
+    ///
+    /// This is a non-blocking test, the contents of an arrived partition can be read via
+    /// `partition()`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.2.4
+    pub fn arrived(&mut self, partition: usize) -> bool {
+        assert_eq!(
+            self.direction,
+            Direction::Receive,
+            "Only partitioned receives have arriving partitions."
+        );
+        assert!(self.active, "The partitioned request is not active.");
+        self.check_partition(partition);
+        if !self.marked[partition] {
+            let flag = unsafe {
+                with_uninitialized(|flag| {
+                    ffi::RSMPI_Parrived(self.request, self.partition_c(partition), flag)
+                })
+                .1
+            };
+            self.marked[partition] = flag != 0;
+        }
+        self.marked[partition]
+    }
+
+    /// The elements of partition `partition` of a receive
+    ///
+    /// Returns `None` if the partition has not been reported as arrived by `arrived()` or
+    /// `arrivals()` since the operation was started. All partitions can be read after the
+    /// operation has completed.
+    pub fn partition(&self, partition: usize) -> Option<&[T]> {
+        assert_eq!(
+            self.direction,
+            Direction::Receive,
+            "Only the partitions of a partitioned receive can be read."
+        );
+        self.check_partition(partition);
+        if self.active && !self.marked[partition] {
+            None
+        } else {
+            Some(unsafe {
+                slice::from_raw_parts(self.partition_ptr(partition), self.partition_len)
+            })
+        }
+    }
+
+    /// Iterate over the partitions of an active receive as they arrive.
+    ///
+    /// Yields the index and the elements of every partition that has not been reported as
+    /// arrived before, polling the pending partitions until the next one arrives. The iteration
+    /// ends once all partitions have arrived, the operation still has to be completed by `wait()`.
+    pub fn arrivals(&mut self) -> Arrivals<'_, 'a, T, S> {
+        assert_eq!(
+            self.direction,
+            Direction::Receive,
+            "Only partitioned receives have arriving partitions."
+        );
+        assert!(self.active, "The partitioned request is not active.");
+        let yielded = self.marked.clone();
+        Arrivals {
+            request: self,
+            yielded,
+            next: 0,
+        }
+    }
+
+    fn check_partition(&self, partition: usize) {
+        assert!(
+            partition < self.partitions,
+            "Partition {} is out of range for a request with {} partitions.",
+            partition,
+            self.partitions
+        );
+    }
+
+    fn partition_ptr(&self, partition: usize) -> *mut T {
+        unsafe { self.buf.add(partition * self.partition_len) }
+    }
+
+    fn partition_c(&self, partition: usize) -> c_int {
+        partition
+            .value_as()
+            .expect("Partition index cannot be expressed as a c_int.")
+    }
+}
+
+unsafe impl<'a, T, S: Scope<'a>> AsRaw for PartitionedRequest<'a, T, S> {
+    type Raw = MPI_Request;
+    fn as_raw(&self) -> Self::Raw {
+        self.request
+    }
+}
+
+impl<'a, T, S: Scope<'a>> Drop for PartitionedRequest<'a, T, S> {
+    fn drop(&mut self) {
+        unsafe {
+            if environment::leak_if_finalized("PartitionedRequest") {
+                self.scope.unregister();
+                return;
+            }
+            if self.active {
+                // An active send only completes once every partition has been marked as ready.
+                if self.direction == Direction::Send {
+                    for partition in 0..self.partitions {
+                        if !self.marked[partition] {
+                            ffi::RSMPI_Pready(self.partition_c(partition), self.request);
+                        }
+                    }
+                }
+                ffi::MPI_Wait(&mut self.request, ffi::RSMPI_STATUS_IGNORE);
+            }
+            ffi::MPI_Request_free(&mut self.request);
+            self.scope.unregister();
+        }
+    }
+}
+
+/// An iterator over the newly arrived partitions of a partitioned receive
+///
+/// See `PartitionedRequest::arrivals()`.
+pub struct Arrivals<'r, 'a, T, S: Scope<'a>> {
+    request: &'r mut PartitionedRequest<'a, T, S>,
+    yielded: Vec<bool>,
+    next: usize,
+}
+
+impl<'r, 'a, T, S: Scope<'a>> Iterator for Arrivals<'r, 'a, T, S> {
+    type Item = (usize, &'r [T]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.yielded.iter().all(|&yielded| yielded) {
+            return None;
+        }
+        loop {
+            let partition = self.next;
+            self.next = (self.next + 1) % self.request.partitions;
+            if !self.yielded[partition] && self.request.arrived(partition) {
+                self.yielded[partition] = true;
+                // The partition is not written to again before the request is restarted, which
+                // needs the mutable borrow held by this iterator.
+                let elements = unsafe {
+                    slice::from_raw_parts(
+                        self.request.partition_ptr(partition),
+                        self.request.partition_len,
+                    )
+                };
+                return Some((partition, elements));
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Workflow pipelines of process groups
+//!
+//! Workflow-style applications run a sequence of stages, e.g. generate → simulate → analyze,
+//! where every stage runs on its own group of processes and feeds its results to the next one.
+//! A `Pipeline` splits a communicator into one communicator per stage and connects every stage
+//! to its neighbours with inter-communicators. Data is passed down the pipeline with `forward()`
+//! on one stage and `collect()` on the next one.
+//!
+//! # Examples
+//!
+//! See `examples/pipeline.rs`
+//!
+//! # Standard section(s)
+//!
+//! 6.4.2, 6.6
+
+use conv::ConvUtil;
+
+use crate::datatype::traits::*;
+use crate::point_to_point::traits::*;
+use crate::topology::traits::*;
+use crate::topology::{Color, InterCommunicator, Rank, UserCommunicator};
+use crate::Tag;
+
+/// Tag of the messages passed between stages
+const FORWARD: Tag = 0;
+
+/// A builder for a `Pipeline`, listing the number of processes of every stage
+///
+/// # Examples
+///
+/// See `examples/pipeline.rs`
+pub struct PipelineBuilder {
+    sizes: Vec<Rank>,
+}
+
+impl PipelineBuilder {
+    /// A builder for a pipeline with no stages yet
+    pub fn new() -> Self {
+        PipelineBuilder { sizes: Vec::new() }
+    }
+
+    /// Append a stage running on `processes` processes.
+    pub fn stage(mut self, processes: Rank) -> Self {
+        assert!(processes > 0, "A stage must run on at least one process.");
+        self.sizes.push(processes);
+        self
+    }
+
+    /// Split `comm` into the stages of the pipeline.
+    ///
+    /// The stages are assigned to consecutive ranks of `comm` in the order they were added.
+    /// Panics if the stages do not add up to the size of `comm`. This is a collective operation.
+    pub fn build<C: Communicator>(self, comm: &C) -> Pipeline {
+        assert!(
+            !self.sizes.is_empty(),
+            "A pipeline needs at least one stage."
+        );
+        let total: Rank = self.sizes.iter().sum();
+        assert_eq!(
+            total,
+            comm.size(),
+            "The stages of the pipeline run on {} processes, but the communicator has {}.",
+            total,
+            comm.size()
+        );
+
+        // The rank of the first process of every stage within `comm`
+        let leaders: Vec<Rank> = self
+            .sizes
+            .iter()
+            .scan(0, |start, &size| {
+                let leader = *start;
+                *start += size;
+                Some(leader)
+            })
+            .collect();
+        let rank = comm.rank();
+        let stage = leaders
+            .iter()
+            .rposition(|&leader| leader <= rank)
+            .expect("rsmpi internal error: rank before the first stage");
+        let color = Color::with_value(
+            stage
+                .value_as()
+                .expect("Stage index cannot be expressed as a Color."),
+        );
+        let stage_comm = comm
+            .split_by_color(color)
+            .expect("rsmpi internal error: process without a stage");
+
+        // The leaders talk on a private duplicate of `comm`, every link between two stages uses
+        // the index of the upstream stage as tag. Every stage connects upstream before
+        // downstream, so the links are created in order along the pipeline.
+        let peer = comm.duplicate();
+        let link = |upstream_stage: usize, remote_stage: usize| {
+            let tag: Tag = upstream_stage
+                .value_as()
+                .expect("Stage index cannot be expressed as a Tag.");
+            stage_comm.create_intercommunicator(0, &peer, leaders[remote_stage], tag)
+        };
+        let upstream = if stage > 0 {
+            Some(link(stage - 1, stage - 1))
+        } else {
+            None
+        };
+        let downstream = if stage + 1 < leaders.len() {
+            Some(link(stage, stage + 1))
+        } else {
+            None
+        };
+
+        Pipeline {
+            stage,
+            num_stages: leaders.len(),
+            comm: stage_comm,
+            upstream,
+            downstream,
+        }
+    }
+}
+
+impl Default for PipelineBuilder {
+    fn default() -> Self {
+        PipelineBuilder::new()
+    }
+}
+
+/// The stage of a pipeline the calling process belongs to
+///
+/// # Examples
+///
+/// See `examples/pipeline.rs`
+pub struct Pipeline {
+    stage: usize,
+    num_stages: usize,
+    comm: UserCommunicator,
+    upstream: Option<InterCommunicator>,
+    downstream: Option<InterCommunicator>,
+}
+
+impl Pipeline {
+    /// A builder for a pipeline
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder::new()
+    }
+
+    /// The index of the stage of the calling process
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// The number of stages of the pipeline
+    pub fn num_stages(&self) -> usize {
+        self.num_stages
+    }
+
+    /// Whether the calling process belongs to the first stage
+    pub fn is_first(&self) -> bool {
+        self.upstream.is_none()
+    }
+
+    /// Whether the calling process belongs to the last stage
+    pub fn is_last(&self) -> bool {
+        self.downstream.is_none()
+    }
+
+    /// The communicator of the processes of this stage
+    pub fn communicator(&self) -> &UserCommunicator {
+        &self.comm
+    }
+
+    /// The inter-communicator to the previous stage, `None` on the first stage
+    pub fn upstream(&self) -> Option<&InterCommunicator> {
+        self.upstream.as_ref()
+    }
+
+    /// The inter-communicator to the next stage, `None` on the last stage
+    pub fn downstream(&self) -> Option<&InterCommunicator> {
+        self.downstream.as_ref()
+    }
+
+    /// Pass `data` on to the next stage.
+    ///
+    /// Process `r` of this stage sends to process `r % n` of the next stage with `n` processes.
+    /// Every call has to be matched by a call to `collect()` on all processes of the next stage.
+    /// Panics on the last stage.
+    pub fn forward<T: Equivalence>(&self, data: &[T]) {
+        let downstream = self
+            .downstream
+            .as_ref()
+            .expect("The last stage of a pipeline cannot forward data.");
+        let target = self.comm.rank() % downstream.remote_size();
+        downstream
+            .remote_process(target)
+            .send_with_tag(data, FORWARD);
+    }
+
+    /// Receive the data forwarded by the previous stage.
+    ///
+    /// Returns the data of all processes of the previous stage that send to this process,
+    /// concatenated in the order of their ranks. The result is empty if the previous stage has
+    /// fewer processes than this one and no process sends to this process. Panics on the first
+    /// stage.
+    pub fn collect<T: Equivalence>(&self) -> Vec<T> {
+        let upstream = self
+            .upstream
+            .as_ref()
+            .expect("The first stage of a pipeline cannot collect data.");
+        let size = self.comm.size();
+        let mut collected = Vec::new();
+        let mut source = self.comm.rank();
+        while source < upstream.remote_size() {
+            let (mut data, _) = upstream
+                .remote_process(source)
+                .receive_vec_with_tag(FORWARD);
+            collected.append(&mut data);
+            source += size;
+        }
+        collected
+    }
+}
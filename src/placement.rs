@@ -0,0 +1,211 @@
+//! Introspection of process placement and binding
+//!
+//! Performance of MPI programs depends on where the processes run: on which node, on which NUMA
+//! node of that node and on which CPUs they are allowed to run. `placement_report()` collects
+//! this information from all processes, e.g. to check the binding requested from the process
+//! manager or to log it alongside benchmark results. `split_by_numa_node()` builds
+//! locality-aware communicators from it.
+//!
+//! The node name is the processor name reported by the MPI library. CPU and NUMA information is
+//! read from `/proc` and `/sys` and is only available on Linux, on other platforms the lists of
+//! CPUs and NUMA nodes are empty.
+//!
+//! # Examples
+//!
+//! See `examples/placement.rs`
+
+use std::fs;
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::collective::CollectivePlan;
+use crate::datatype::traits::*;
+use crate::environment;
+use crate::topology::traits::*;
+use crate::topology::{Color, Rank, UserCommunicator};
+
+/// The placement of a single process
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Placement {
+    /// Rank of the process in the communicator the report was made for
+    pub rank: Rank,
+    /// Name of the node the process runs on
+    pub node: String,
+    /// CPUs the process is allowed to run on
+    pub cpus: Vec<usize>,
+    /// NUMA nodes of the CPUs the process is allowed to run on
+    pub numa_nodes: Vec<usize>,
+}
+
+impl Placement {
+    /// The placement of the calling process, with `rank` as its rank
+    pub fn local(rank: Rank) -> Placement {
+        let node = environment::processor_name()
+            .expect("Processor name returned by the MPI library is not valid UTF-8");
+        let cpus = allowed_cpus();
+        let numa_nodes = numa_nodes_of(&cpus);
+        Placement {
+            rank,
+            node,
+            cpus,
+            numa_nodes,
+        }
+    }
+
+    /// Whether the process is bound to a subset of the CPUs of its node
+    pub fn is_bound(&self) -> bool {
+        let online = fs::read_to_string("/sys/devices/system/cpu/online")
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default();
+        !self.cpus.is_empty() && self.cpus.len() < online.len()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}",
+            self.node,
+            format_list(&self.cpus),
+            format_list(&self.numa_nodes)
+        )
+        .into_bytes()
+    }
+
+    fn decode(rank: Rank, bytes: &[u8]) -> Placement {
+        let text = String::from_utf8_lossy(bytes);
+        let mut lines = text.split('\n');
+        let mut next = || lines.next().unwrap_or_default().to_owned();
+        let node = next();
+        let cpus = parse_cpu_list(&next());
+        let numa_nodes = parse_cpu_list(&next());
+        Placement {
+            rank,
+            node,
+            cpus,
+            numa_nodes,
+        }
+    }
+}
+
+/// The placements of all processes of `comm`
+///
+/// Returns the placements in rank order on all processes. This is a collective operation.
+pub fn placement_report<C>(comm: &C) -> Vec<Placement>
+where
+    C: Communicator,
+{
+    let local = Placement::local(comm.rank()).encode();
+    let plan = CollectivePlan::from_all_gather(comm, local[..].count());
+    let extent: usize = plan
+        .extent()
+        .value_as()
+        .expect("Length of placement report cannot be expressed as a usize.");
+    let mut all = vec![0u8; extent];
+    plan.all_gather_varcount_into(comm, &local[..], &mut all[..]);
+    plan.counts()
+        .iter()
+        .zip(plan.displs())
+        .zip(0..)
+        .map(|((&count, &displ), rank)| {
+            let start: usize = displ
+                .value_as()
+                .expect("Displacement cannot be expressed as a usize.");
+            let count: usize = count
+                .value_as()
+                .expect("Length cannot be expressed as a usize.");
+            Placement::decode(rank, &all[start..start + count])
+        })
+        .collect()
+}
+
+/// Split `comm` into communicators of the processes that share a node and the first NUMA node
+/// they are allowed to run on.
+///
+/// Processes whose NUMA node is unknown are grouped by node only. Ranks keep their relative
+/// order. This is a collective operation.
+pub fn split_by_numa_node<C>(comm: &C) -> UserCommunicator
+where
+    C: Communicator,
+{
+    let shared = comm.split_shared(comm.rank());
+    let numa_node = numa_nodes_of(&allowed_cpus()).first().map_or(0, |&node| {
+        node.value_as::<i32>()
+            .expect("NUMA node cannot be expressed as a color.")
+            + 1
+    });
+    shared
+        .split_by_color(Color::with_value(numa_node))
+        .expect("rsmpi internal error: split with a defined color returned no communicator")
+}
+
+/// The CPUs the calling process is allowed to run on
+fn allowed_cpus() -> Vec<usize> {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find(|line| line.starts_with("Cpus_allowed_list:"))
+                .map(|line| parse_cpu_list(&line["Cpus_allowed_list:".len()..]))
+        })
+        .unwrap_or_default()
+}
+
+/// The NUMA nodes containing any of `cpus`
+fn numa_nodes_of(cpus: &[usize]) -> Vec<usize> {
+    let entries = match fs::read_dir("/sys/devices/system/node") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut nodes: Vec<usize> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let node = name.strip_prefix("node")?.parse().ok()?;
+            let list = fs::read_to_string(entry.path().join("cpulist")).ok()?;
+            let node_cpus = parse_cpu_list(&list);
+            if cpus.iter().any(|cpu| node_cpus.contains(cpu)) {
+                Some(node)
+            } else {
+                None
+            }
+        })
+        .collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Parse a list of the form `0-3,8,10-11`, ignoring malformed entries.
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        let mut bounds = part.splitn(2, '-');
+        let first = bounds.next().and_then(|first| first.trim().parse().ok());
+        let last = bounds.next().and_then(|last| last.trim().parse().ok());
+        match (first, last) {
+            (Some(first), Some(last)) => cpus.extend(first..=last),
+            (Some(first), None) => cpus.push(first),
+            _ => {}
+        }
+    }
+    cpus
+}
+
+/// Format a sorted list in the form parsed by `parse_cpu_list()`.
+fn format_list(list: &[usize]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < list.len() {
+        let mut j = i;
+        while j + 1 < list.len() && list[j + 1] == list[j] + 1 {
+            j += 1;
+        }
+        if i == j {
+            parts.push(list[i].to_string());
+        } else {
+            parts.push(format!("{}-{}", list[i], list[j]));
+        }
+        i = j + 1;
+    }
+    parts.join(",")
+}
@@ -4,29 +4,44 @@
 //! `Destination` trait. Communication operations are implemented as default methods on those
 //! traits.
 //!
+//! The null process, see `null_process()` and `process_at_rank_or_null()` of `Communicator`, can be
+//! used as the destination or source of all operations. Sends to it and receives from it complete
+//! immediately without transferring any data, receives leave their buffer unchanged and return a
+//! `Status` with source `MPI_PROC_NULL` and a count of `0`. Boundary processes of a stencil can
+//! thus exchange halos with the same code as interior processes. Only the receives that return a
+//! single value, like `receive()` and `send_receive()`, panic as there is no value to return.
+//!
 //! # Unfinished features
 //!
-//! - **3.2.6**: `MPI_STATUS_IGNORE`
 //! - **3.6**: Buffer usage, `MPI_Buffer_attach()`, `MPI_Buffer_detach()`
 //! - **3.9**: Persistent requests, `MPI_Send_init()`, `MPI_Bsend_init()`, `MPI_Ssend_init()`,
 //! `MPI_Rsend_init()`, `MPI_Recv_init()`, `MPI_Start()`, `MPI_Startall()`
 
 use std::alloc::{self, Layout};
 use std::mem::{transmute, MaybeUninit};
+#[cfg(feature = "validate")]
+use std::os::raw::c_void;
 use std::{fmt, ptr};
 
 use conv::ConvUtil;
 
-use super::{Count, Tag};
+use super::{Count, LargeCount, Tag};
 
 use crate::ffi;
-use crate::ffi::{MPI_Message, MPI_Status};
+use crate::ffi::{MPI_Comm, MPI_Message, MPI_Status};
 
 use crate::datatype::traits::*;
+use crate::datatype::{try_count_of, DisjointViews};
+use crate::environment::{self, Feature};
+#[cfg(feature = "fault-injection")]
+use crate::faults;
+use crate::hooks::{self, Call};
 use crate::raw::traits::*;
 use crate::request::{Request, Scope, StaticScope};
 use crate::topology::traits::*;
 use crate::topology::{AnyProcess, CommunicatorRelation, Process, Rank};
+#[cfg(feature = "validate")]
+use crate::validation;
 use crate::{with_uninitialized, with_uninitialized2};
 
 // TODO: rein in _with_tag ugliness, use optional tags or make tag part of Source and Destination
@@ -47,7 +62,7 @@ pub mod traits {
 /// # Standard section(s)
 ///
 /// 3.2.3
-pub unsafe trait Source: AsCommunicator {
+pub unsafe trait Source: AsPeerCommunicator {
     /// `Rank` that identifies the source
     fn source_rank(&self) -> Rank;
 
@@ -70,7 +85,7 @@ pub unsafe trait Source: AsCommunicator {
                     ffi::MPI_Probe(
                         self.source_rank(),
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         status,
                     )
                 })
@@ -92,7 +107,7 @@ pub unsafe trait Source: AsCommunicator {
     ///
     /// 3.8.1
     fn probe(&self) -> Status {
-        self.probe_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.probe_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Probe a source for incoming messages with guaranteed reception.
@@ -112,13 +127,17 @@ pub unsafe trait Source: AsCommunicator {
                 ffi::MPI_Mprobe(
                     self.source_rank(),
                     tag,
-                    self.as_communicator().as_raw(),
+                    self.as_peer_communicator().as_raw(),
                     message,
                     status,
                 )
             })
         };
-        (Message(message), Status(status))
+        let status = Status(status);
+        (
+            Message::matched(message, self.as_peer_communicator().as_raw(), &status),
+            status,
+        )
     }
 
     /// Probe a source for incoming messages with guaranteed reception.
@@ -133,7 +152,7 @@ pub unsafe trait Source: AsCommunicator {
     ///
     /// 3.8.2
     fn matched_probe(&self) -> (Message, Status) {
-        self.matched_probe_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.matched_probe_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Receive a message containing a single instance of type `Msg`.
@@ -144,10 +163,21 @@ pub unsafe trait Source: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.4
+    #[inline]
     fn receive_with_tag<Msg>(&self, tag: Tag) -> (Msg, Status)
     where
         Msg: Equivalence,
     {
+        let mut call = hooks::enter(|| {
+            Call::receive(
+                self.as_peer_communicator().as_raw(),
+                "receive_with_tag",
+                self.source_rank(),
+                tag,
+                Some(1),
+                Msg::equivalent_datatype().as_raw(),
+            )
+        });
         unsafe {
             let (_, msg, status) = with_uninitialized2(|msg, status| {
                 ffi::MPI_Recv(
@@ -156,14 +186,22 @@ pub unsafe trait Source: AsCommunicator {
                     Msg::equivalent_datatype().as_raw(),
                     self.source_rank(),
                     tag,
-                    self.as_communicator().as_raw(),
+                    self.as_peer_communicator().as_raw(),
                     status,
                 )
             });
             let status = Status(status);
+            hooks::received(&mut call, &status);
             if status.count(Msg::equivalent_datatype()) == 0 {
                 panic!("Received an empty message.");
             }
+            #[cfg(feature = "validate")]
+            validation::check_received(
+                self.as_peer_communicator().as_raw(),
+                &status,
+                &msg as *const Msg as *const c_void,
+                Msg::equivalent_datatype().as_raw(),
+            );
             (msg, status)
         }
     }
@@ -186,11 +224,12 @@ pub unsafe trait Source: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.4
+    #[inline]
     fn receive<Msg>(&self) -> (Msg, Status)
     where
         Msg: Equivalence,
     {
-        self.receive_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.receive_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Receive a message into a `Buffer`.
@@ -200,11 +239,22 @@ pub unsafe trait Source: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.4
+    #[inline]
     fn receive_into_with_tag<Buf: ?Sized>(&self, buf: &mut Buf, tag: Tag) -> Status
     where
         Buf: BufferMut,
     {
-        unsafe {
+        let mut call = hooks::enter(|| {
+            Call::receive(
+                self.as_peer_communicator().as_raw(),
+                "receive_into_with_tag",
+                self.source_rank(),
+                tag,
+                Some(buf.count()),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let status = unsafe {
             Status(
                 with_uninitialized(|status| {
                     ffi::MPI_Recv(
@@ -213,13 +263,22 @@ pub unsafe trait Source: AsCommunicator {
                         buf.as_datatype().as_raw(),
                         self.source_rank(),
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         status,
                     )
                 })
                 .1,
             )
-        }
+        };
+        hooks::received(&mut call, &status);
+        #[cfg(feature = "validate")]
+        validation::check_received(
+            self.as_peer_communicator().as_raw(),
+            &status,
+            buf.pointer_mut(),
+            buf.as_datatype().as_raw(),
+        );
+        status
     }
 
     /// Receive a message into a `Buffer`.
@@ -229,11 +288,64 @@ pub unsafe trait Source: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.4
+    #[inline]
     fn receive_into<Buf: ?Sized>(&self, buf: &mut Buf) -> Status
     where
         Buf: BufferMut,
     {
-        self.receive_into_with_tag(buf, unsafe { ffi::RSMPI_ANY_TAG })
+        self.receive_into_with_tag(buf, self.as_peer_communicator().receive_tag())
+    }
+
+    /// Receive a message into a `Buffer` without producing a `Status`.
+    ///
+    /// Receive a message from `Source` `&self` tagged `tag` into `Buffer` `buf`. The status of
+    /// the receive operation is discarded by passing `MPI_STATUS_IGNORE` to the MPI library,
+    /// which saves filling in and copying the status object on latency sensitive paths, e.g. when
+    /// exchanging single values.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.4, 3.2.6
+    #[inline]
+    fn receive_into_with_tag_without_status<Buf: ?Sized>(&self, buf: &mut Buf, tag: Tag)
+    where
+        Buf: BufferMut,
+    {
+        // Validation needs the status to find the checksum of the message.
+        if cfg!(feature = "validate") {
+            self.receive_into_with_tag(buf, tag);
+            return;
+        }
+        unsafe {
+            ffi::MPI_Recv(
+                buf.pointer_mut(),
+                buf.count(),
+                buf.as_datatype().as_raw(),
+                self.source_rank(),
+                tag,
+                self.as_peer_communicator().as_raw(),
+                ffi::RSMPI_STATUS_IGNORE,
+            );
+        }
+    }
+
+    /// Receive a message into a `Buffer` without producing a `Status`.
+    ///
+    /// Receive a message from `Source` `&self` into `Buffer` `buf`, discarding the status of the
+    /// receive operation.
+    ///
+    /// # Examples
+    /// See `examples/ping_pong.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.4, 3.2.6
+    #[inline]
+    fn receive_into_without_status<Buf: ?Sized>(&self, buf: &mut Buf)
+    where
+        Buf: BufferMut,
+    {
+        self.receive_into_with_tag_without_status(buf, self.as_peer_communicator().receive_tag())
     }
 
     /// Receive a message containing multiple instances of type `Msg` into a `Vec`.
@@ -266,7 +378,7 @@ pub unsafe trait Source: AsCommunicator {
     where
         Msg: Equivalence,
     {
-        self.receive_vec_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.receive_vec_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Initiate an immediate (non-blocking) receive operation.
@@ -286,7 +398,7 @@ pub unsafe trait Source: AsCommunicator {
         Buf: 'a + BufferMut,
         Sc: Scope<'a>,
     {
-        unsafe {
+        let request = unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
                     ffi::MPI_Irecv(
@@ -295,14 +407,21 @@ pub unsafe trait Source: AsCommunicator {
                         buf.as_datatype().as_raw(),
                         self.source_rank(),
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         request,
                     )
                 })
                 .1,
                 scope,
             )
-        }
+        };
+        #[cfg(feature = "validate")]
+        validation::skip_checksum(
+            self.as_peer_communicator().as_raw(),
+            self.source_rank(),
+            tag,
+        );
+        request
     }
 
     /// Initiate an immediate (non-blocking) receive operation.
@@ -324,7 +443,7 @@ pub unsafe trait Source: AsCommunicator {
         Buf: 'a + BufferMut,
         Sc: Scope<'a>,
     {
-        self.immediate_receive_into_with_tag(scope, buf, unsafe { ffi::RSMPI_ANY_TAG })
+        self.immediate_receive_into_with_tag(scope, buf, self.as_peer_communicator().receive_tag())
     }
 
     /// Initiate a non-blocking receive operation for messages matching tag `tag`.
@@ -345,10 +464,16 @@ pub unsafe trait Source: AsCommunicator {
                     Msg::equivalent_datatype().as_raw(),
                     self.source_rank(),
                     tag,
-                    self.as_communicator().as_raw(),
+                    self.as_peer_communicator().as_raw(),
                     request,
                 )
             });
+            #[cfg(feature = "validate")]
+            validation::skip_checksum(
+                self.as_peer_communicator().as_raw(),
+                self.source_rank(),
+                tag,
+            );
             ReceiveFuture {
                 val,
                 req: Request::from_raw(request, StaticScope),
@@ -368,7 +493,7 @@ pub unsafe trait Source: AsCommunicator {
     where
         Msg: Equivalence,
     {
-        self.immediate_receive_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.immediate_receive_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Asynchronously probe a source for incoming messages.
@@ -388,7 +513,7 @@ pub unsafe trait Source: AsCommunicator {
                 ffi::MPI_Iprobe(
                     self.source_rank(),
                     tag,
-                    self.as_communicator().as_raw(),
+                    self.as_peer_communicator().as_raw(),
                     flag,
                     status.as_mut_ptr(),
                 )
@@ -412,7 +537,7 @@ pub unsafe trait Source: AsCommunicator {
     ///
     /// 3.8.1
     fn immediate_probe(&self) -> Option<Status> {
-        self.immediate_probe_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.immediate_probe_with_tag(self.as_peer_communicator().receive_tag())
     }
 
     /// Asynchronously probe a source for incoming messages with guaranteed reception.
@@ -434,7 +559,7 @@ pub unsafe trait Source: AsCommunicator {
                 ffi::MPI_Improbe(
                     self.source_rank(),
                     tag,
-                    self.as_communicator().as_raw(),
+                    self.as_peer_communicator().as_raw(),
                     flag,
                     message.as_mut_ptr(),
                     status.as_mut_ptr(),
@@ -442,7 +567,15 @@ pub unsafe trait Source: AsCommunicator {
             });
 
             if flag != 0 {
-                Some((Message(message.assume_init()), Status(status.assume_init())))
+                let status = Status(status.assume_init());
+                Some((
+                    Message::matched(
+                        message.assume_init(),
+                        self.as_peer_communicator().as_raw(),
+                        &status,
+                    ),
+                    status,
+                ))
             } else {
                 None
             }
@@ -460,7 +593,7 @@ pub unsafe trait Source: AsCommunicator {
     ///
     /// 3.8.2
     fn immediate_matched_probe(&self) -> Option<(Message, Status)> {
-        self.immediate_matched_probe_with_tag(unsafe { ffi::RSMPI_ANY_TAG })
+        self.immediate_matched_probe_with_tag(self.as_peer_communicator().receive_tag())
     }
 }
 
@@ -468,6 +601,7 @@ unsafe impl<'a, C> Source for AnyProcess<'a, C>
 where
     C: 'a + Communicator,
 {
+    #[inline]
     fn source_rank(&self) -> Rank {
         unsafe { ffi::RSMPI_ANY_SOURCE }
     }
@@ -477,6 +611,7 @@ unsafe impl<'a, C> Source for Process<'a, C>
 where
     C: 'a + Communicator,
 {
+    #[inline]
     fn source_rank(&self) -> Rank {
         self.rank()
     }
@@ -490,7 +625,7 @@ where
 /// # Standard section(s)
 ///
 /// 3.2.3
-pub trait Destination: AsCommunicator {
+pub trait Destination: AsPeerCommunicator {
     /// `Rank` that identifies the destination
     fn destination_rank(&self) -> Rank;
 
@@ -501,20 +636,41 @@ pub trait Destination: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.1
+    #[inline]
     fn send_with_tag<Buf: ?Sized>(&self, buf: &Buf, tag: Tag)
     where
         Buf: Buffer,
     {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "send_with_tag");
         unsafe {
             ffi::MPI_Send(
                 buf.pointer(),
                 buf.count(),
                 buf.as_datatype().as_raw(),
-                self.destination_rank(),
+                destination,
                 tag,
-                self.as_communicator().as_raw(),
+                self.as_peer_communicator().as_raw(),
             );
         }
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
     }
 
     /// Blocking standard mode send operation
@@ -538,11 +694,12 @@ pub trait Destination: AsCommunicator {
     /// # Standard section(s)
     ///
     /// 3.2.1
+    #[inline]
     fn send<Buf: ?Sized>(&self, buf: &Buf)
     where
         Buf: Buffer,
     {
-        self.send_with_tag(buf, Tag::default())
+        self.send_with_tag(buf, self.as_peer_communicator().send_tag())
     }
 
     /// Blocking buffered mode send operation
@@ -556,16 +713,36 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "buffered_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "buffered_send_with_tag");
         unsafe {
             ffi::MPI_Bsend(
                 buf.pointer(),
                 buf.count(),
                 buf.as_datatype().as_raw(),
-                self.destination_rank(),
+                destination,
                 tag,
-                self.as_communicator().as_raw(),
+                self.as_peer_communicator().as_raw(),
             );
         }
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
     }
 
     /// Blocking buffered mode send operation
@@ -579,7 +756,7 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
-        self.buffered_send_with_tag(buf, Tag::default())
+        self.buffered_send_with_tag(buf, self.as_peer_communicator().send_tag())
     }
 
     /// Blocking synchronous mode send operation
@@ -595,16 +772,36 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "synchronous_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "synchronous_send_with_tag");
         unsafe {
             ffi::MPI_Ssend(
                 buf.pointer(),
                 buf.count(),
                 buf.as_datatype().as_raw(),
-                self.destination_rank(),
+                destination,
                 tag,
-                self.as_communicator().as_raw(),
+                self.as_peer_communicator().as_raw(),
             );
         }
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
     }
 
     /// Blocking synchronous mode send operation
@@ -620,7 +817,7 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
-        self.synchronous_send_with_tag(buf, Tag::default())
+        self.synchronous_send_with_tag(buf, self.as_peer_communicator().send_tag())
     }
 
     /// Blocking ready mode send operation
@@ -636,16 +833,36 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "ready_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "ready_send_with_tag");
         unsafe {
             ffi::MPI_Rsend(
                 buf.pointer(),
                 buf.count(),
                 buf.as_datatype().as_raw(),
-                self.destination_rank(),
+                destination,
                 tag,
-                self.as_communicator().as_raw(),
+                self.as_peer_communicator().as_raw(),
             );
         }
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
     }
 
     /// Blocking ready mode send operation
@@ -661,7 +878,7 @@ pub trait Destination: AsCommunicator {
     where
         Buf: Buffer,
     {
-        self.ready_send_with_tag(buf, Tag::default())
+        self.ready_send_with_tag(buf, self.as_peer_communicator().send_tag())
     }
 
     /// Initiate an immediate (non-blocking) standard mode send operation.
@@ -681,23 +898,44 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        unsafe {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "immediate_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "immediate_send_with_tag");
+        let request = unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
                     ffi::MPI_Isend(
                         buf.pointer(),
                         buf.count(),
                         buf.as_datatype().as_raw(),
-                        self.destination_rank(),
+                        destination,
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         request,
                     )
                 })
                 .1,
                 scope,
             )
-        }
+        };
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
+        request
     }
 
     /// Initiate an immediate (non-blocking) standard mode send operation.
@@ -715,7 +953,7 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        self.immediate_send_with_tag(scope, buf, Tag::default())
+        self.immediate_send_with_tag(scope, buf, self.as_peer_communicator().send_tag())
     }
 
     /// Initiate an immediate (non-blocking) buffered mode send operation.
@@ -735,23 +973,44 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        unsafe {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "immediate_buffered_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "immediate_buffered_send_with_tag");
+        let request = unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
                     ffi::MPI_Ibsend(
                         buf.pointer(),
                         buf.count(),
                         buf.as_datatype().as_raw(),
-                        self.destination_rank(),
+                        destination,
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         request,
                     )
                 })
                 .1,
                 scope,
             )
-        }
+        };
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
+        request
     }
 
     /// Initiate an immediate (non-blocking) buffered mode send operation.
@@ -770,7 +1029,7 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        self.immediate_buffered_send_with_tag(scope, buf, Tag::default())
+        self.immediate_buffered_send_with_tag(scope, buf, self.as_peer_communicator().send_tag())
     }
 
     /// Initiate an immediate (non-blocking) synchronous mode send operation.
@@ -790,23 +1049,44 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        unsafe {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "immediate_synchronous_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "immediate_synchronous_send_with_tag");
+        let request = unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
                     ffi::MPI_Issend(
                         buf.pointer(),
                         buf.count(),
                         buf.as_datatype().as_raw(),
-                        self.destination_rank(),
+                        destination,
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         request,
                     )
                 })
                 .1,
                 scope,
             )
-        }
+        };
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
+        request
     }
 
     /// Initiate an immediate (non-blocking) synchronous mode send operation.
@@ -825,7 +1105,7 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        self.immediate_synchronous_send_with_tag(scope, buf, Tag::default())
+        self.immediate_synchronous_send_with_tag(scope, buf, self.as_peer_communicator().send_tag())
     }
 
     /// Initiate an immediate (non-blocking) ready mode send operation.
@@ -845,23 +1125,44 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        unsafe {
+        let _call = hooks::enter(|| {
+            Call::send(
+                self.as_peer_communicator().as_raw(),
+                "immediate_ready_send_with_tag",
+                self.destination_rank(),
+                tag,
+                buf.count(),
+                buf.as_datatype().as_raw(),
+            )
+        });
+        let destination = send_destination(self, "immediate_ready_send_with_tag");
+        let request = unsafe {
             Request::from_raw(
                 with_uninitialized(|request| {
                     ffi::MPI_Irsend(
                         buf.pointer(),
                         buf.count(),
                         buf.as_datatype().as_raw(),
-                        self.destination_rank(),
+                        destination,
                         tag,
-                        self.as_communicator().as_raw(),
+                        self.as_peer_communicator().as_raw(),
                         request,
                     )
                 })
                 .1,
                 scope,
             )
-        }
+        };
+        #[cfg(feature = "validate")]
+        validation::send_checksum(
+            self.as_peer_communicator().as_raw(),
+            destination,
+            tag,
+            buf.pointer(),
+            buf.count(),
+            buf.as_datatype().as_raw(),
+        );
+        request
     }
 
     /// Initiate an immediate (non-blocking) ready mode send operation.
@@ -880,7 +1181,7 @@ pub trait Destination: AsCommunicator {
         Buf: 'a + Buffer,
         Sc: Scope<'a>,
     {
-        self.immediate_ready_send_with_tag(scope, buf, Tag::default())
+        self.immediate_ready_send_with_tag(scope, buf, self.as_peer_communicator().send_tag())
     }
 }
 
@@ -888,11 +1189,45 @@ impl<'a, C> Destination for Process<'a, C>
 where
     C: 'a + Communicator,
 {
+    #[inline]
     fn destination_rank(&self) -> Rank {
         self.rank()
     }
 }
 
+/// The rank that the send named `operation` to `destination` sends its message to, the null
+/// process if fault injection drops the message
+#[inline]
+#[allow(unused_variables)]
+fn send_destination<D: ?Sized + Destination>(destination: &D, operation: &'static str) -> Rank {
+    #[cfg(feature = "fault-injection")]
+    {
+        if faults::dropped(operation, destination.destination_rank()) {
+            return unsafe { ffi::RSMPI_PROC_NULL };
+        }
+    }
+    destination.destination_rank()
+}
+
+/// Panics unless `destination` and `source` communicate in the same context.
+fn assert_same_communicator<D: ?Sized + Destination, S: ?Sized + Source>(
+    destination: &D,
+    source: &S,
+) {
+    let relation: CommunicatorRelation = unsafe {
+        with_uninitialized(|cmp| {
+            ffi::MPI_Comm_compare(
+                source.as_peer_communicator().as_raw(),
+                destination.as_peer_communicator().as_raw(),
+                cmp,
+            )
+        })
+        .1
+        .into()
+    };
+    assert_eq!(relation, CommunicatorRelation::Identical);
+}
+
 /// Describes the result of a point to point receive operation.
 ///
 /// # Standard section(s)
@@ -921,16 +1256,90 @@ impl Status {
     pub fn count<D: Datatype>(&self, d: D) -> Count {
         unsafe { with_uninitialized(|count| ffi::MPI_Get_count(&self.0, d.as_raw(), count)).1 }
     }
+
+    /// Number of instances of the type contained in the message as a `LargeCount`
+    ///
+    /// Unlike `count()`, this reports the size of messages of more than `Count::MAX` instances,
+    /// e.g. of large count or chunked transfers. Returns `None` if the message does not contain a
+    /// whole number of instances. Falls back to counting the received bytes if the MPI library
+    /// does not provide `MPI_Get_count_c()`.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/status_count_c.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.2.5
+    pub fn count_c<D: Datatype>(&self, d: D) -> Option<LargeCount> {
+        let count = unsafe {
+            with_uninitialized(|count| ffi::RSMPI_Get_count_c(&self.0, d.as_raw(), count)).1
+        };
+        if count == LargeCount::from(ffi::RSMPI_UNDEFINED) {
+            None
+        } else {
+            Some(count)
+        }
+    }
+
+    /// Number of basic elements of the type contained in the message as a `LargeCount`
+    ///
+    /// Unlike `count_c()`, this also counts the elements of a partially received instance of a
+    /// derived datatype. Returns `None` if the message does not contain a whole number of basic
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/status_count_c.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 4.1.11
+    pub fn elements_c<D: Datatype>(&self, d: D) -> Option<LargeCount> {
+        let elements = unsafe {
+            with_uninitialized(|elements| ffi::MPI_Get_elements_x(&self.0, d.as_raw(), elements)).1
+        };
+        if elements == LargeCount::from(ffi::RSMPI_UNDEFINED) {
+            None
+        } else {
+            Some(elements)
+        }
+    }
+
+    /// Whether the operation this status belongs to was cancelled
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.8.4
+    pub fn is_cancelled(&self) -> bool {
+        unsafe { with_uninitialized(|flag| ffi::MPI_Test_cancelled(&self.0, flag)).1 != 0 }
+    }
 }
 
 impl fmt::Debug for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Status")
+            .field("source_rank", &self.source_rank())
+            .field("tag", &self.tag())
+            .field("bytes", &self.count(u8::equivalent_datatype()))
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(
             f,
-            "Status {{ source_rank: {}, tag: {} }}",
+            "message of {} bytes from process {} with tag {}",
+            self.count(u8::equivalent_datatype()),
             self.source_rank(),
             self.tag()
-        )
+        )?;
+        if self.is_cancelled() {
+            write!(f, " (cancelled)")?;
+        }
+        Ok(())
     }
 }
 
@@ -940,14 +1349,38 @@ impl fmt::Debug for Status {
 ///
 /// 3.8.2
 #[must_use]
-pub struct Message(MPI_Message);
+pub struct Message(
+    MPI_Message,
+    /// Checksum of the message, received right after matching it
+    #[cfg(feature = "validate")]
+    Option<u32>,
+);
 
 impl Message {
+    /// Wrap the message `message` matched on `comm` and described by `status`.
+    #[allow(unused_variables)]
+    fn matched(message: MPI_Message, comm: MPI_Comm, status: &Status) -> Message {
+        #[cfg(feature = "validate")]
+        {
+            // The message has been removed from the queue of pending messages by the probe, so
+            // its companion message is the next one to match.
+            Message(message, validation::receive_checksum(comm, status))
+        }
+        #[cfg(not(feature = "validate"))]
+        Message(message)
+    }
+
     /// True if the `Source` for the probe was the null process.
     pub fn is_no_proc(&self) -> bool {
         self.as_raw() == unsafe { ffi::RSMPI_MESSAGE_NO_PROC }
     }
 
+    /// Whether nothing is left to receive, i.e. the message has been received or came from the
+    /// null process
+    fn is_received(&self) -> bool {
+        self.as_raw() == unsafe { ffi::RSMPI_MESSAGE_NULL } || self.is_no_proc()
+    }
+
     /// Receive a previously probed message containing a single instance of type `Msg`.
     ///
     /// Receives the message `&self` which contains a single instance of type `Msg`.
@@ -973,6 +1406,13 @@ impl Message {
             if status.count(Msg::equivalent_datatype()) == 0 {
                 panic!("Received an empty message.");
             }
+            #[cfg(feature = "validate")]
+            validation::verify(
+                self.1,
+                &status,
+                &res as *const Msg as *const c_void,
+                Msg::equivalent_datatype().as_raw(),
+            );
             (res, status)
         }
     }
@@ -1000,9 +1440,17 @@ impl Message {
                 )
             })
             .1;
-            assert_eq!(self.as_raw(), ffi::RSMPI_MESSAGE_NULL);
+            assert!(self.is_received());
         };
-        Status(status)
+        let status = Status(status);
+        #[cfg(feature = "validate")]
+        validation::verify(
+            self.1,
+            &status,
+            buf.pointer_mut(),
+            buf.as_datatype().as_raw(),
+        );
+        status
     }
 
     /// Asynchronously receive a previously probed message into a `Buffer`.
@@ -1032,7 +1480,7 @@ impl Message {
                 )
             })
             .1;
-            assert_eq!(self.as_raw(), ffi::RSMPI_MESSAGE_NULL);
+            assert!(self.is_received());
             Request::from_raw(request, scope)
         }
     }
@@ -1053,9 +1501,8 @@ unsafe impl AsRawMut for Message {
 
 impl Drop for Message {
     fn drop(&mut self) {
-        assert_eq!(
-            self.as_raw(),
-            unsafe { ffi::RSMPI_MESSAGE_NULL },
+        assert!(
+            self.is_received(),
             "matched message dropped without receiving."
         );
     }
@@ -1126,12 +1573,18 @@ where
     R: Equivalence,
     S: Source,
 {
-    assert_eq!(
-        source
-            .as_communicator()
-            .compare(destination.as_communicator()),
-        CommunicatorRelation::Identical
-    );
+    assert_same_communicator(destination, source);
+    let mut call = hooks::enter(|| {
+        Call::send_receive(
+            source.as_peer_communicator().as_raw(),
+            "send_receive_with_tags",
+            destination.destination_rank(),
+            sendtag,
+            msg.count(),
+            msg.as_datatype().as_raw(),
+            R::equivalent_datatype().as_raw(),
+        )
+    });
     unsafe {
         let (_, res, status) = with_uninitialized2(|res, status| {
             ffi::MPI_Sendrecv(
@@ -1145,11 +1598,29 @@ where
                 R::equivalent_datatype().as_raw(),
                 source.source_rank(),
                 receivetag,
-                source.as_communicator().as_raw(),
+                source.as_peer_communicator().as_raw(),
                 status,
             )
         });
         let status = Status(status);
+        hooks::received(&mut call, &status);
+        #[cfg(feature = "validate")]
+        {
+            validation::send_checksum(
+                source.as_peer_communicator().as_raw(),
+                destination.destination_rank(),
+                sendtag,
+                msg.pointer(),
+                msg.count(),
+                msg.as_datatype().as_raw(),
+            );
+            validation::check_received(
+                source.as_peer_communicator().as_raw(),
+                &status,
+                &res as *const R as *const c_void,
+                R::equivalent_datatype().as_raw(),
+            );
+        }
         (res, status)
     }
 }
@@ -1170,9 +1641,107 @@ where
     R: Equivalence,
     S: Source,
 {
-    send_receive_with_tags(msg, destination, Tag::default(), source, unsafe {
-        ffi::RSMPI_ANY_TAG
-    })
+    send_receive_with_tags(
+        msg,
+        destination,
+        destination.as_peer_communicator().send_tag(),
+        source,
+        source.as_peer_communicator().receive_tag(),
+    )
+}
+
+/// Sends the `N` elements of `msg` to `destination` tagging them `sendtag` and simultaneously
+/// receives `N` elements tagged `receivetag` from `source`.
+///
+/// The count of elements is a compile-time constant, so no probing or allocation is needed to
+/// receive the message. Panics if the received message does not contain exactly `N` elements.
+///
+/// # Standard section(s)
+///
+/// 3.10
+pub fn exchange_fixed_with_tags<T, D, S, const N: usize>(
+    msg: &[T; N],
+    destination: &D,
+    sendtag: Tag,
+    source: &S,
+    receivetag: Tag,
+) -> [T; N]
+where
+    T: Equivalence,
+    D: Destination,
+    S: Source,
+{
+    assert_same_communicator(destination, source);
+    let count = try_count_of::<T>(N).unwrap_or_else(|err| panic!("{}", err));
+    let datatype = T::equivalent_datatype();
+    let mut res = MaybeUninit::<[T; N]>::uninit();
+    unsafe {
+        let (_, status) = with_uninitialized(|status| {
+            ffi::MPI_Sendrecv(
+                msg.as_ptr() as _,
+                count,
+                datatype.as_raw(),
+                destination.destination_rank(),
+                sendtag,
+                res.as_mut_ptr() as _,
+                count,
+                datatype.as_raw(),
+                source.source_rank(),
+                receivetag,
+                source.as_peer_communicator().as_raw(),
+                status,
+            )
+        });
+        let status = Status(status);
+        let received = status.count(datatype);
+        assert_eq!(
+            received, count,
+            "Received {} elements in fixed-size exchange of {} elements.",
+            received, count
+        );
+        #[cfg(feature = "validate")]
+        {
+            validation::send_checksum(
+                source.as_peer_communicator().as_raw(),
+                destination.destination_rank(),
+                sendtag,
+                msg.as_ptr() as _,
+                count,
+                datatype.as_raw(),
+            );
+            validation::check_received(
+                source.as_peer_communicator().as_raw(),
+                &status,
+                res.as_ptr() as _,
+                datatype.as_raw(),
+            );
+        }
+        res.assume_init()
+    }
+}
+
+/// Sends the `N` elements of `msg` to `destination` and simultaneously receives `N` elements from
+/// `source`.
+///
+/// # Examples
+/// See `examples/exchange_fixed.rs`
+///
+/// # Standard section(s)
+///
+/// 3.10
+pub fn exchange_fixed<T, D, S, const N: usize>(msg: &[T; N], destination: &D, source: &S) -> [T; N]
+where
+    T: Equivalence,
+    D: Destination,
+    S: Source,
+{
+    exchange_fixed_with_tags(
+        msg,
+        destination,
+        destination.as_peer_communicator().send_tag(),
+        source,
+        source.as_peer_communicator().receive_tag(),
+    )
 }
 
 /// Sends the contents of `msg` to `destination` tagging it `sendtag` and
@@ -1196,13 +1765,19 @@ where
     B: BufferMut,
     S: Source,
 {
-    assert_eq!(
-        source
-            .as_communicator()
-            .compare(destination.as_communicator()),
-        CommunicatorRelation::Identical
-    );
-    unsafe {
+    assert_same_communicator(destination, source);
+    let mut call = hooks::enter(|| {
+        Call::send_receive(
+            source.as_peer_communicator().as_raw(),
+            "send_receive_into_with_tags",
+            destination.destination_rank(),
+            sendtag,
+            msg.count(),
+            msg.as_datatype().as_raw(),
+            buf.as_datatype().as_raw(),
+        )
+    });
+    let status = unsafe {
         Status(
             with_uninitialized(|status| {
                 ffi::MPI_Sendrecv(
@@ -1216,13 +1791,108 @@ where
                     buf.as_datatype().as_raw(),
                     source.source_rank(),
                     receivetag,
-                    source.as_communicator().as_raw(),
+                    source.as_peer_communicator().as_raw(),
                     status,
                 )
             })
             .1,
         )
+    };
+    hooks::received(&mut call, &status);
+    #[cfg(feature = "validate")]
+    {
+        validation::send_checksum(
+            source.as_peer_communicator().as_raw(),
+            destination.destination_rank(),
+            sendtag,
+            msg.pointer(),
+            msg.count(),
+            msg.as_datatype().as_raw(),
+        );
+        validation::check_received(
+            source.as_peer_communicator().as_raw(),
+            &status,
+            buf.pointer_mut(),
+            buf.as_datatype().as_raw(),
+        );
     }
+    status
+}
+
+/// Sends the send view of `views` to `destination` and simultaneously receives a message from
+/// `source` into the receive view, where both views are disjoint parts of the same buffer.
+///
+/// # Examples
+/// See `examples/disjoint_views.rs`
+///
+/// # Standard section(s)
+///
+/// 3.10
+pub fn send_receive_disjoint<T, SD, RD, D, S>(
+    views: &mut DisjointViews<T, SD, RD>,
+    destination: &D,
+    source: &S,
+) -> Status
+where
+    SD: Datatype,
+    RD: Datatype,
+    D: Destination,
+    S: Source,
+{
+    assert_same_communicator(destination, source);
+    let sendtag = destination.as_peer_communicator().send_tag();
+    let receivetag = source.as_peer_communicator().receive_tag();
+    let mut call = hooks::enter(|| {
+        Call::send_receive(
+            source.as_peer_communicator().as_raw(),
+            "send_receive_disjoint",
+            destination.destination_rank(),
+            sendtag,
+            views.send_count(),
+            views.send_datatype().as_raw(),
+            views.receive_datatype().as_raw(),
+        )
+    });
+    let status = unsafe {
+        Status(
+            with_uninitialized(|status| {
+                ffi::MPI_Sendrecv(
+                    views.send_pointer(),
+                    views.send_count(),
+                    views.send_datatype().as_raw(),
+                    destination.destination_rank(),
+                    sendtag,
+                    views.receive_pointer(),
+                    views.receive_count(),
+                    views.receive_datatype().as_raw(),
+                    source.source_rank(),
+                    receivetag,
+                    source.as_peer_communicator().as_raw(),
+                    status,
+                )
+            })
+            .1,
+        )
+    };
+    hooks::received(&mut call, &status);
+    #[cfg(feature = "validate")]
+    {
+        validation::send_checksum(
+            source.as_peer_communicator().as_raw(),
+            destination.destination_rank(),
+            sendtag,
+            views.send_pointer(),
+            views.send_count(),
+            views.send_datatype().as_raw(),
+        );
+        validation::check_received(
+            source.as_peer_communicator().as_raw(),
+            &status,
+            views.receive_pointer(),
+            views.receive_datatype().as_raw(),
+        );
+    }
+    status
 }
 
 /// Sends the contents of `msg` to `destination` and
@@ -1244,9 +1914,187 @@ where
     B: BufferMut,
     S: Source,
 {
-    send_receive_into_with_tags(msg, destination, Tag::default(), buf, source, unsafe {
-        ffi::RSMPI_ANY_TAG
-    })
+    send_receive_into_with_tags(
+        msg,
+        destination,
+        destination.as_peer_communicator().send_tag(),
+        buf,
+        source,
+        source.as_peer_communicator().receive_tag(),
+    )
+}
+
+/// Initiate sending the contents of `msg` to `destination` tagging it `sendtag` and receiving a
+/// message tagged `receivetag` from `source` into `buf`.
+///
+/// Uses `MPI_Isendrecv()` if the MPI library provides it, see
+/// `environment::is_supported(Feature::ImmediateSendReceive)`. Otherwise the operation is emulated
+/// by an immediate receive and an immediate send, which the returned request completes together.
+///
+/// # Examples
+/// See `examples/immediate_send_receive.rs`
+///
+/// # Standard section(s)
+///
+/// 3.10
+pub fn immediate_send_receive_into_with_tags<'a, Sc, M: ?Sized, D, B: ?Sized, S>(
+    scope: Sc,
+    msg: &'a M,
+    destination: &D,
+    sendtag: Tag,
+    buf: &'a mut B,
+    source: &S,
+    receivetag: Tag,
+) -> SendReceiveRequest<'a, Sc>
+where
+    Sc: Scope<'a> + Copy,
+    M: 'a + Buffer,
+    D: Destination,
+    B: 'a + BufferMut,
+    S: Source,
+{
+    assert_same_communicator(destination, source);
+    if !environment::is_supported(Feature::ImmediateSendReceive) {
+        let receive = source.immediate_receive_into_with_tag(scope, buf, receivetag);
+        let send = destination.immediate_send_with_tag(scope, msg, sendtag);
+        return SendReceiveRequest {
+            first: receive,
+            second: Some(send),
+        };
+    }
+
+    let _call = hooks::enter(|| {
+        Call::send(
+            source.as_peer_communicator().as_raw(),
+            "immediate_send_receive_into_with_tags",
+            destination.destination_rank(),
+            sendtag,
+            msg.count(),
+            msg.as_datatype().as_raw(),
+        )
+    });
+    let request = unsafe {
+        Request::from_raw(
+            with_uninitialized(|request| {
+                ffi::RSMPI_Isendrecv(
+                    msg.pointer(),
+                    msg.count(),
+                    msg.as_datatype().as_raw(),
+                    destination.destination_rank(),
+                    sendtag,
+                    buf.pointer_mut(),
+                    buf.count(),
+                    buf.as_datatype().as_raw(),
+                    source.source_rank(),
+                    receivetag,
+                    source.as_peer_communicator().as_raw(),
+                    request,
+                )
+            })
+            .1,
+            scope,
+        )
+    };
+    #[cfg(feature = "validate")]
+    {
+        validation::send_checksum(
+            source.as_peer_communicator().as_raw(),
+            destination.destination_rank(),
+            sendtag,
+            msg.pointer(),
+            msg.count(),
+            msg.as_datatype().as_raw(),
+        );
+        validation::skip_checksum(
+            source.as_peer_communicator().as_raw(),
+            source.source_rank(),
+            receivetag,
+        );
+    }
+    SendReceiveRequest {
+        first: request,
+        second: None,
+    }
+}
+
+/// Initiate sending the contents of `msg` to `destination` and receiving a message from `source`
+/// into `buf`.
+///
+/// See `immediate_send_receive_into_with_tags()`.
+///
+/// # Standard section(s)
+///
+/// 3.10
+pub fn immediate_send_receive_into<'a, Sc, M: ?Sized, D, B: ?Sized, S>(
+    scope: Sc,
+    msg: &'a M,
+    destination: &D,
+    buf: &'a mut B,
+    source: &S,
+) -> SendReceiveRequest<'a, Sc>
+where
+    Sc: Scope<'a> + Copy,
+    M: 'a + Buffer,
+    D: Destination,
+    B: 'a + BufferMut,
+    S: Source,
+{
+    immediate_send_receive_into_with_tags(
+        scope,
+        msg,
+        destination,
+        destination.as_peer_communicator().send_tag(),
+        buf,
+        source,
+        source.as_peer_communicator().receive_tag(),
+    )
+}
+
+/// A request for an immediate send-receive operation
+///
+/// The request consists of a single request if the MPI library provides `MPI_Isendrecv()` and of
+/// a receive and a send request otherwise. Like a `Request` it must be completed before it is
+/// dropped.
+#[must_use]
+#[derive(Debug)]
+pub struct SendReceiveRequest<'a, S: Scope<'a> = StaticScope> {
+    /// The send-receive request or the receive request of the emulation
+    first: Request<'a, S>,
+    /// The send request of the emulation
+    second: Option<Request<'a, S>>,
+}
+
+impl<'a, S: Scope<'a>> SendReceiveRequest<'a, S> {
+    /// Wait for the operation to finish.
+    ///
+    /// Returns the status of the receive.
+    pub fn wait(self) -> Status {
+        if let Some(send) = self.second {
+            send.wait();
+        }
+        self.first.wait()
+    }
+
+    /// Test whether the operation has finished.
+    ///
+    /// Returns the status of the receive if it has, the request otherwise.
+    pub fn test(self) -> Result<Status, Self> {
+        let second = match self.second {
+            Some(send) => match send.test() {
+                Ok(_) => None,
+                Err(send) => {
+                    return Err(SendReceiveRequest {
+                        first: self.first,
+                        second: Some(send),
+                    })
+                }
+            },
+            None => None,
+        };
+        self.first
+            .test()
+            .map_err(|first| SendReceiveRequest { first, second })
+    }
 }
 
 /// Sends the contents of `buf` to `destination` tagging it `sendtag` and
@@ -1268,13 +2116,22 @@ where
     D: Destination,
     S: Source,
 {
-    assert_eq!(
-        source
-            .as_communicator()
-            .compare(destination.as_communicator()),
-        CommunicatorRelation::Identical
-    );
-    unsafe {
+    assert_same_communicator(destination, source);
+    let mut call = hooks::enter(|| {
+        Call::send_receive(
+            source.as_peer_communicator().as_raw(),
+            "send_receive_replace_into_with_tags",
+            destination.destination_rank(),
+            sendtag,
+            buf.count(),
+            buf.as_datatype().as_raw(),
+            buf.as_datatype().as_raw(),
+        )
+    });
+    // The contents are replaced by the received message, so the checksum is taken beforehand.
+    #[cfg(feature = "validate")]
+    let sent = validation::checksum(buf.pointer_mut(), buf.count(), buf.as_datatype().as_raw());
+    let status = unsafe {
         Status(
             with_uninitialized(|status| {
                 ffi::MPI_Sendrecv_replace(
@@ -1285,13 +2142,30 @@ where
                     sendtag,
                     source.source_rank(),
                     receivetag,
-                    source.as_communicator().as_raw(),
+                    source.as_peer_communicator().as_raw(),
                     status,
                 )
             })
             .1,
         )
+    };
+    hooks::received(&mut call, &status);
+    #[cfg(feature = "validate")]
+    {
+        validation::send_companion(
+            source.as_peer_communicator().as_raw(),
+            destination.destination_rank(),
+            sendtag,
+            sent,
+        );
+        validation::check_received(
+            source.as_peer_communicator().as_raw(),
+            &status,
+            buf.pointer_mut(),
+            buf.as_datatype().as_raw(),
+        );
     }
+    status
 }
 
 /// Sends the contents of `buf` to `destination` and
@@ -1311,9 +2185,13 @@ where
     D: Destination,
     S: Source,
 {
-    send_receive_replace_into_with_tags(buf, destination, Tag::default(), source, unsafe {
-        ffi::RSMPI_ANY_TAG
-    })
+    send_receive_replace_into_with_tags(
+        buf,
+        destination,
+        destination.as_peer_communicator().send_tag(),
+        source,
+        source.as_peer_communicator().receive_tag(),
+    )
 }
 
 /// Will contain a value of type `T` received via a non-blocking receive operation.
@@ -0,0 +1,129 @@
+//! Priority lanes for point to point communication
+//!
+//! Messages from one process to another on the same communicator are non-overtaking, so a small
+//! control message that is sent after a large bulk transfer can only be received after it. A
+//! `PriorityComm` duplicates a communicator into a fixed number of lanes, one per priority.
+//! Messages on different lanes never match each other, so a receiver that serves the most urgent
+//! lane first, e.g. via `receive_vec()`, gets control messages without waiting for bulk data that
+//! was sent before them.
+//!
+//! # Examples
+//!
+//! See `examples/priority_lanes.rs`
+
+use std::thread;
+
+use crate::datatype::traits::*;
+use crate::point_to_point::traits::*;
+use crate::point_to_point::{Message, Status};
+use crate::topology::traits::*;
+use crate::topology::{Process, Rank, UserCommunicator};
+
+/// The priority of a message, `0` being the most urgent
+pub type Priority = usize;
+
+/// A communicator split into lanes for messages of different priorities
+///
+/// Lane `p` carries the messages of priority `p`. Sends pick their lane via
+/// `process_at_rank()`, receives either address a lane via `lane()` or serve all lanes in order
+/// of priority.
+pub struct PriorityComm {
+    lanes: Vec<UserCommunicator>,
+}
+
+impl PriorityComm {
+    /// Duplicate `comm` into `lanes` lanes.
+    ///
+    /// `lanes` has to be the same on all processes. This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, lanes: usize) -> PriorityComm {
+        assert!(
+            lanes > 0,
+            "A priority communicator needs at least one lane."
+        );
+        PriorityComm {
+            lanes: (0..lanes).map(|_| comm.duplicate()).collect(),
+        }
+    }
+
+    /// The number of lanes
+    pub fn num_lanes(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// The lane of messages of priority `priority`
+    pub fn lane(&self, priority: Priority) -> &UserCommunicator {
+        assert!(
+            priority < self.lanes.len(),
+            "Priority {} is out of range for {} lanes.",
+            priority,
+            self.lanes.len()
+        );
+        &self.lanes[priority]
+    }
+
+    /// The rank of the calling process
+    pub fn rank(&self) -> Rank {
+        self.lanes[0].rank()
+    }
+
+    /// The number of processes
+    pub fn size(&self) -> Rank {
+        self.lanes[0].size()
+    }
+
+    /// Process `rank` as the destination or source of messages of priority `priority`
+    pub fn process_at_rank(&self, rank: Rank, priority: Priority) -> Process<UserCommunicator> {
+        self.lane(priority).process_at_rank(rank)
+    }
+
+    /// Match the most urgent message that is pending on any lane from any process, if there is
+    /// one.
+    ///
+    /// Returns the priority of the message along with the message and its status.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.8.2
+    pub fn immediate_matched_probe(&self) -> Option<(Priority, Message, Status)> {
+        self.lanes.iter().enumerate().find_map(|(priority, lane)| {
+            lane.any_process()
+                .immediate_matched_probe()
+                .map(|(message, status)| (priority, message, status))
+        })
+    }
+
+    /// Wait for a message on any lane from any process and match the most urgent one.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.8.2
+    pub fn matched_probe(&self) -> (Priority, Message, Status) {
+        loop {
+            if let Some(matched) = self.immediate_matched_probe() {
+                return matched;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Receive the most urgent message on any lane from any process into a `Vec`.
+    ///
+    /// If messages are pending on several lanes when this is called, the message with the lowest
+    /// priority value is received first.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/priority_lanes.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.8.2, 3.8.3
+    pub fn receive_vec<Msg>(&self) -> (Priority, Vec<Msg>, Status)
+    where
+        Msg: Equivalence,
+    {
+        let (priority, message, status) = self.matched_probe();
+        let (data, status) = (message, status).matched_receive_vec();
+        (priority, data, status)
+    }
+}
@@ -0,0 +1,162 @@
+//! Reproducible random number streams for parallel programs
+//!
+//! Monte-Carlo codes need one random number stream per process that is independent of the streams
+//! of all other processes and reproducible from a single master seed. Seeding every process with
+//! `seed + rank` produces correlated streams for many generators and forgetting to broadcast the
+//! seed makes runs irreproducible.
+//!
+//! A `Stream` is a counter-based generator: the `n`-th number of a stream is a strong mix of a key
+//! and `n`. The key is derived from the master seed, the rank and a stream index, so streams never
+//! share state and can be split into further independent substreams. The complete state of a
+//! stream is its key and its counter, which `gather_rng_state()` collects on a root process for
+//! checkpointing.
+//!
+//! The generator is not cryptographically secure.
+//!
+//! # Examples
+//!
+//! See `examples/random.rs`
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::datatype::UserDatatype;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+
+/// Increment of the Weyl sequence used by SplitMix64
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The finalizer of SplitMix64, a bijective mix of the bits of `z`
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Broadcast the master seed `seed` of process `root` to all processes of `comm`.
+///
+/// `seed` is only significant on the root process. Returns the seed of the root process on all
+/// processes. This is a collective operation.
+pub fn broadcast_seed<C>(comm: &C, root: Rank, seed: u64) -> u64
+where
+    C: Communicator,
+{
+    let mut seed = seed;
+    comm.process_at_rank(root).broadcast_into(&mut seed);
+    seed
+}
+
+/// A counter-based stream of pseudo-random numbers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stream {
+    key: u64,
+    counter: u64,
+}
+
+impl Stream {
+    /// Stream number `index` of process `rank` for the master seed `seed`
+    pub fn new(seed: u64, rank: Rank, index: u64) -> Stream {
+        let rank: u64 = rank.value_as().expect("Rank cannot be expressed as a u64.");
+        Stream {
+            key: mix(mix(mix(seed) ^ rank.wrapping_mul(GAMMA)) ^ index),
+            counter: 0,
+        }
+    }
+
+    /// The first stream of the calling process of `comm` for the master seed `seed`
+    pub fn for_rank<C>(comm: &C, seed: u64) -> Stream
+    where
+        C: Communicator,
+    {
+        Stream::new(seed, comm.rank(), 0)
+    }
+
+    /// A substream with index `index` that is independent of this stream and of all other
+    /// substreams
+    pub fn split(&self, index: u64) -> Stream {
+        Stream {
+            key: mix(self.key ^ mix(index.wrapping_add(1).wrapping_mul(GAMMA))),
+            counter: 0,
+        }
+    }
+
+    /// The next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let value = mix(self.key.wrapping_add(self.counter.wrapping_mul(GAMMA)));
+        self.counter = self.counter.wrapping_add(1);
+        value
+    }
+
+    /// The next random number uniformly distributed in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        let bits: f64 = (self.next_u64() >> 11)
+            .value_as()
+            .expect("53 bit integer cannot be expressed as an f64.");
+        bits / (1u64 << 53).value_as::<f64>().expect("2^53 is an f64.")
+    }
+
+    /// Skip the next `n` numbers of the stream.
+    pub fn skip(&mut self, n: u64) {
+        self.counter = self.counter.wrapping_add(n);
+    }
+
+    /// The complete state of the stream
+    pub fn state(&self) -> StreamState {
+        StreamState {
+            key: self.key,
+            counter: self.counter,
+        }
+    }
+
+    /// Continue a stream from a state returned by `state()`.
+    pub fn from_state(state: StreamState) -> Stream {
+        Stream {
+            key: state.key,
+            counter: state.counter,
+        }
+    }
+}
+
+/// The state of a `Stream`, e.g. for checkpointing
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StreamState {
+    /// Key identifying the stream
+    pub key: u64,
+    /// Number of values drawn from the stream so far
+    pub counter: u64,
+}
+
+unsafe impl Equivalence for StreamState {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        UserDatatype::contiguous(2, &u64::equivalent_datatype())
+    }
+}
+
+/// Gather the states of the streams of all processes on the root process `root`.
+///
+/// Returns the states in rank order on the root process and `None` on all other processes. The
+/// streams can be restored with `Stream::from_state()`, e.g. after scattering the states when the
+/// program restarts. This is a collective operation.
+pub fn gather_rng_state<R>(root: &R, stream: &Stream) -> Option<Vec<StreamState>>
+where
+    R: Root,
+{
+    let comm = root.as_communicator();
+    let state = stream.state();
+    if comm.rank() == root.root_rank() {
+        let size: usize = comm
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        let mut states = vec![StreamState::default(); size];
+        root.gather_into_root(&state, &mut states[..]);
+        Some(states)
+    } else {
+        root.gather_into(&state);
+        None
+    }
+}
@@ -19,16 +19,54 @@
 //! follow the respective policy for completing the operation.  When the guard is dropped, the
 //! request will be automatically unregistered from its `Scope`.
 //!
+//! # Buffer lifetimes
+//!
+//! Blocking operations accept any borrowed buffer, including temporaries on the stack. Immediate
+//! operations borrow their buffers for the lifetime `'a` of the `Scope<'a>` the request is
+//! registered with, so no request can outlive its buffers: with a `StaticScope` the buffers have
+//! to be `'static`, with a `LocalScope` they have to be declared outside of the
+//! [`scope`](fn.scope.html) closure. A request that is leaked, e.g. via `mem::forget()`, is still
+//! registered with its scope when the scope ends, which aborts the program before the buffers
+//! could be freed.
+//!
+//! A temporary therefore cannot be used with an immediate operation that outlives it:
+//!
+//! ```compile_fail
+//! use mpi::request::StaticScope;
+//! use mpi::traits::*;
+//!
+//! let universe = mpi::initialize().unwrap();
+//! let world = universe.world();
+//! let request = {
+//!     let x = 5;
+//!     world.this_process().immediate_send(StaticScope, &x)
+//! };
+//! request.wait();
+//! ```
+//!
+//! Neither can a buffer that is declared inside of a local scope:
+//!
+//! ```compile_fail
+//! use mpi::traits::*;
+//!
+//! let universe = mpi::initialize().unwrap();
+//! let world = universe.world();
+//! mpi::request::scope(|scope| {
+//!     let x = 5;
+//!     world.this_process().immediate_send(scope, &x).wait();
+//! });
+//! ```
+//!
 //! # Unfinished features
 //!
 //! - **3.7**: Nonblocking mode:
 //!   - Completion, `MPI_Waitall()`, `MPI_Waitsome()`,
 //!   `MPI_Testany()`, `MPI_Testall()`, `MPI_Testsome()`, `MPI_Request_get_status()`
-//! - **3.8**:
-//!   - Cancellation, `MPI_Test_cancelled()`
 
 use std::cell::Cell;
 use std::convert::TryInto;
+use std::error::Error as StdError;
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
@@ -36,9 +74,11 @@ use std::ptr;
 use crate::ffi;
 use crate::ffi::{MPI_Request, MPI_Status};
 
+#[cfg(feature = "fault-injection")]
+use crate::faults;
 use crate::point_to_point::Status;
 use crate::raw::traits::*;
-use crate::with_uninitialized;
+use crate::{with_uninitialized, Error};
 
 /// Check if the request is `MPI_REQUEST_NULL`.
 fn is_null(request: MPI_Request) -> bool {
@@ -63,7 +103,6 @@ fn is_null(request: MPI_Request) -> bool {
 ///
 /// 3.7.1
 #[must_use]
-#[derive(Debug)]
 pub struct Request<'a, S: Scope<'a> = StaticScope> {
     request: MPI_Request,
     scope: S,
@@ -83,6 +122,29 @@ impl<'a, S: Scope<'a>> Drop for Request<'a, S> {
     }
 }
 
+impl<'a, S: Scope<'a>> fmt::Debug for Request<'a, S> {
+    /// Shows whether the operation is still pending, without completing the request.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if is_null(self.request) {
+            "null"
+        } else {
+            let mut status = MaybeUninit::<MPI_Status>::uninit();
+            let flag = unsafe {
+                with_uninitialized(|flag| {
+                    ffi::MPI_Request_get_status(self.request, flag, status.as_mut_ptr())
+                })
+                .1
+            };
+            if flag != 0 {
+                "complete"
+            } else {
+                "pending"
+            }
+        };
+        f.debug_struct("Request").field("state", &state).finish()
+    }
+}
+
 /// Wait for the completion of one of the requests in the vector,
 /// returns the index of the request completed and the status of the request.
 ///
@@ -123,6 +185,157 @@ pub fn wait_any<'a, S: Scope<'a>>(requests: &mut Vec<Request<'a, S>>) -> Option<
     }
 }
 
+/// Wait for the completion of all requests in the vector, cancelling the remaining requests when
+/// one of them fails.
+///
+/// Returns the statuses of all requests in the order of the vector if all of them completed
+/// successfully. Otherwise, the first failing request stops the wait, all requests that are still
+/// pending are cancelled and a `JoinError` reports which requests completed, which one failed and
+/// which ones were cancelled. This is useful for speculative queries to several peers, where one
+/// failure makes the remaining answers worthless.
+///
+/// Failures are only reported if errors are returned instead of aborting the program, see
+/// `Communicator::set_errors_return()`.
+///
+/// # Examples
+///
+/// See `examples/join_or_cancel.rs`
+///
+/// # Standard section(s)
+///
+/// 3.7.5, 3.8.4
+pub fn join_or_cancel<'a, S: Scope<'a>>(
+    requests: Vec<Request<'a, S>>,
+) -> Result<Vec<Status>, JoinError> {
+    let mut mpi_requests: Vec<MPI_Request> = requests
+        .into_iter()
+        .map(|r| unsafe { r.into_raw().0 })
+        .collect();
+    let size: i32 = mpi_requests
+        .len()
+        .try_into()
+        .expect("Error while casting usize to i32");
+    let mut statuses: Vec<Option<Status>> = vec![None; mpi_requests.len()];
+
+    loop {
+        let mut index: i32 = mpi_sys::MPI_UNDEFINED;
+        let (code, status) = unsafe {
+            with_uninitialized(|s| ffi::MPI_Waitany(size, mpi_requests.as_mut_ptr(), &mut index, s))
+        };
+        if index == mpi_sys::MPI_UNDEFINED && code == ffi::MPI_SUCCESS as Error {
+            break;
+        }
+        if code != ffi::MPI_SUCCESS as Error {
+            let failed = index.try_into().ok();
+            return Err(cancel_pending(mpi_requests, statuses, failed, code));
+        }
+        let index: usize = index.try_into().expect("Error while casting i32 to usize");
+        assert!(is_null(mpi_requests[index]));
+        #[cfg(feature = "fault-injection")]
+        {
+            if let Some(code) = faults::error("join_or_cancel") {
+                return Err(cancel_pending(mpi_requests, statuses, Some(index), code));
+            }
+        }
+        statuses[index] = Some(Status::from_raw(status));
+    }
+
+    Ok(statuses
+        .into_iter()
+        .map(|status| status.expect("rsmpi internal error: request completed without status"))
+        .collect())
+}
+
+fn cancel_pending(
+    mut mpi_requests: Vec<MPI_Request>,
+    statuses: Vec<Option<Status>>,
+    failed: Option<usize>,
+    code: Error,
+) -> JoinError {
+    let mut completed: Vec<(usize, Status)> = statuses
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, status)| status.map(|status| (i, status)))
+        .collect();
+    let mut cancelled = Vec::new();
+    // The failed request may still be active and borrow a buffer of the scope, so it is cancelled
+    // and waited for like the pending requests rather than freed.
+    for (i, request) in mpi_requests.iter_mut().enumerate() {
+        if is_null(*request) {
+            continue;
+        }
+        let status = unsafe {
+            ffi::MPI_Cancel(request);
+            Status::from_raw(with_uninitialized(|s| ffi::MPI_Wait(request, s)).1)
+        };
+        if Some(i) == failed {
+            continue;
+        }
+        if status.is_cancelled() {
+            cancelled.push(i);
+        } else {
+            completed.push((i, status));
+        }
+    }
+    completed.sort_by_key(|&(i, _)| i);
+    JoinError {
+        failed,
+        code,
+        completed,
+        cancelled,
+    }
+}
+
+/// The report of `join_or_cancel()` when one of the requests failed
+#[derive(Clone, Debug)]
+pub struct JoinError {
+    failed: Option<usize>,
+    code: Error,
+    completed: Vec<(usize, Status)>,
+    cancelled: Vec<usize>,
+}
+
+impl JoinError {
+    /// Index of the request that failed, if the MPI library reported it
+    pub fn failed(&self) -> Option<usize> {
+        self.failed
+    }
+
+    /// The error code returned by the MPI library
+    pub fn code(&self) -> Error {
+        self.code
+    }
+
+    /// Indices and statuses of the requests that completed successfully, in ascending order
+    ///
+    /// Includes requests that completed before they could be cancelled.
+    pub fn completed(&self) -> &[(usize, Status)] {
+        &self.completed
+    }
+
+    /// Indices of the requests that were cancelled, in ascending order
+    pub fn cancelled(&self) -> &[usize] {
+        &self.cancelled
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.failed {
+            Some(failed) => write!(f, "Request {} failed with error code {}", failed, self.code)?,
+            None => write!(f, "A request failed with error code {}", self.code)?,
+        }
+        write!(
+            f,
+            ", {} requests completed and {} were cancelled.",
+            self.completed.len(),
+            self.cancelled.len()
+        )
+    }
+}
+
+impl StdError for JoinError {}
+
 impl<'a, S: Scope<'a>> Request<'a, S> {
     /// Construct a request object from the raw MPI type.
     ///
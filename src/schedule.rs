@@ -0,0 +1,334 @@
+//! Communication schedules for user-defined collective operations
+//!
+//! Collective operations are usually built from point to point messages that follow one of a few
+//! well-known patterns. This module describes these patterns as pure rank arithmetic, so custom
+//! collective operations, e.g. topology-aware variants of the standard ones, can be written on top
+//! of the point to point operations and requests of this crate:
+//!
+//! - `BinomialTree` for rooted operations like broadcasts and reductions in a logarithmic number
+//! of rounds,
+//! - `Ring` for bandwidth-optimal all-gathers and reduce-scatters in `size - 1` steps,
+//! - `Dissemination` for barriers and all-to-all synchronization in a logarithmic number of rounds,
+//! - `PairwiseRounds` for sparse pairwise exchanges in rounds without conflicts.
+//!
+//! All schedules work on ranks `0..size`, which are typically the ranks of a communicator or of a
+//! group whose ranks are translated before communicating.
+//!
+//! # Examples
+//!
+//! See `examples/schedule.rs`
+
+use std::os::raw::c_int;
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::counts;
+use crate::datatype::PartitionMut;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+use crate::Count;
+
+fn assert_member(rank: Rank, size: Rank) {
+    assert!(
+        0 <= rank && rank < size,
+        "Rank {} is out of range for a schedule of size {}.",
+        rank,
+        size
+    );
+}
+
+/// A binomial tree spanning ranks `0..size` rooted at `root`
+///
+/// In a broadcast along the tree every process receives from its parent and then sends to its
+/// children. In a reduction every process receives from its children and then sends to its parent.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BinomialTree {
+    size: Rank,
+    root: Rank,
+}
+
+impl BinomialTree {
+    /// A binomial tree spanning ranks `0..size` rooted at `root`
+    pub fn new(size: Rank, root: Rank) -> BinomialTree {
+        assert!(size > 0, "A schedule needs at least one process.");
+        assert_member(root, size);
+        BinomialTree { size, root }
+    }
+
+    /// Number of ranks spanned by the tree
+    pub fn size(&self) -> Rank {
+        self.size
+    }
+
+    /// The root of the tree
+    pub fn root(&self) -> Rank {
+        self.root
+    }
+
+    fn relative(&self, rank: Rank) -> Rank {
+        assert_member(rank, self.size);
+        (rank - self.root + self.size) % self.size
+    }
+
+    fn absolute(&self, relative: Rank) -> Rank {
+        (relative + self.root) % self.size
+    }
+
+    /// The parent of `rank`, `None` for the root
+    pub fn parent(&self, rank: Rank) -> Option<Rank> {
+        let relative = self.relative(rank);
+        if relative == 0 {
+            None
+        } else {
+            Some(self.absolute(relative & (relative - 1)))
+        }
+    }
+
+    /// The children of `rank`, ordered from the largest to the smallest subtree
+    ///
+    /// Sending to the children in this order lets the largest subtrees start forwarding first.
+    pub fn children(&self, rank: Rank) -> Vec<Rank> {
+        let relative = self.relative(rank);
+        let mut children = Vec::new();
+        let mut mask = 1;
+        while mask < self.size && relative & mask == 0 {
+            if relative + mask < self.size {
+                children.push(self.absolute(relative + mask));
+            }
+            mask <<= 1;
+        }
+        children.reverse();
+        children
+    }
+}
+
+/// A ring over ranks `0..size` in which every rank passes messages to the next rank
+///
+/// In step `s` of a ring all-gather of one block per rank, every rank sends block
+/// `send_block(rank, s)` to `next(rank)` and receives block `receive_block(rank, s)` from
+/// `previous(rank)`. After `steps()` steps every rank holds all blocks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Ring {
+    size: Rank,
+}
+
+impl Ring {
+    /// A ring over ranks `0..size`
+    pub fn new(size: Rank) -> Ring {
+        assert!(size > 0, "A schedule needs at least one process.");
+        Ring { size }
+    }
+
+    /// Number of ranks in the ring
+    pub fn size(&self) -> Rank {
+        self.size
+    }
+
+    /// Number of steps needed to pass every block around the ring
+    pub fn steps(&self) -> Rank {
+        self.size - 1
+    }
+
+    /// The rank that `rank` sends to
+    pub fn next(&self, rank: Rank) -> Rank {
+        assert_member(rank, self.size);
+        (rank + 1) % self.size
+    }
+
+    /// The rank that `rank` receives from
+    pub fn previous(&self, rank: Rank) -> Rank {
+        assert_member(rank, self.size);
+        (rank + self.size - 1) % self.size
+    }
+
+    /// The block sent by `rank` in step `step`
+    pub fn send_block(&self, rank: Rank, step: Rank) -> Rank {
+        assert_member(rank, self.size);
+        (rank - step % self.size + self.size) % self.size
+    }
+
+    /// The block received by `rank` in step `step`
+    pub fn receive_block(&self, rank: Rank, step: Rank) -> Rank {
+        self.send_block(self.previous(rank), step)
+    }
+}
+
+/// A dissemination pattern over ranks `0..size`
+///
+/// In round `r`, every rank sends to the rank `2^r` ahead of it and receives from the rank `2^r`
+/// behind it. After all rounds, every rank has transitively heard from every other rank.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Dissemination {
+    size: Rank,
+}
+
+impl Dissemination {
+    /// A dissemination pattern over ranks `0..size`
+    pub fn new(size: Rank) -> Dissemination {
+        assert!(size > 0, "A schedule needs at least one process.");
+        Dissemination { size }
+    }
+
+    /// Number of ranks in the pattern
+    pub fn size(&self) -> Rank {
+        self.size
+    }
+
+    /// The destination and source of `rank` in every round
+    pub fn partners(&self, rank: Rank) -> Vec<(Rank, Rank)> {
+        assert_member(rank, self.size);
+        let mut partners = Vec::new();
+        let mut distance = 1;
+        while distance < self.size {
+            partners.push((
+                (rank + distance) % self.size,
+                (rank + self.size - distance) % self.size,
+            ));
+            distance *= 2;
+        }
+        partners
+    }
+}
+
+/// Pairwise exchanges between ranks `0..size`, scheduled in rounds in which every rank has at most
+/// one peer
+///
+/// Codes that exchange data with a few peers each per step contend for the network when all
+/// exchanges run at once, and blocking exchanges in an arbitrary order can wait on each other. The
+/// rounds of a `PairwiseRounds` are a coloring of the edges of the communication graph: in every
+/// round, every rank exchanges with its peer of the round, if it has one, e.g. with
+/// `send_receive_into()`. The greedy coloring needs at most `2 d - 1` rounds if no rank has more
+/// than `d` peers.
+///
+/// # Examples
+///
+/// See `examples/schedule.rs`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PairwiseRounds {
+    size: Rank,
+    rounds: Vec<Vec<(Rank, Rank)>>,
+}
+
+impl PairwiseRounds {
+    /// A schedule of exchanges between ranks `0..size` along the undirected `edges`
+    ///
+    /// Edges that are listed repeatedly or in both directions are exchanged once. Panics if an
+    /// edge connects a rank to itself.
+    pub fn new(size: Rank, edges: &[(Rank, Rank)]) -> PairwiseRounds {
+        assert!(size > 0, "A schedule needs at least one process.");
+        let mut edges: Vec<(Rank, Rank)> = edges
+            .iter()
+            .map(|&(a, b)| {
+                assert_member(a, size);
+                assert_member(b, size);
+                assert_ne!(a, b, "Rank {} cannot exchange with itself.", a);
+                (a.min(b), a.max(b))
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        // The rounds in which every rank is busy already
+        let mut busy: Vec<Vec<bool>> = vec![Vec::new(); to_usize(size)];
+        let mut rounds: Vec<Vec<(Rank, Rank)>> = Vec::new();
+        for (a, b) in edges {
+            let free = |busy: &[bool], round: usize| !busy.get(round).cloned().unwrap_or(false);
+            let round = (0..)
+                .find(|&round| free(&busy[to_usize(a)], round) && free(&busy[to_usize(b)], round))
+                .expect("rsmpi internal error: no free round");
+            for &rank in &[a, b] {
+                let busy = &mut busy[to_usize(rank)];
+                if busy.len() <= round {
+                    busy.resize(round + 1, false);
+                }
+                busy[round] = true;
+            }
+            if rounds.len() <= round {
+                rounds.resize(round + 1, Vec::new());
+            }
+            rounds[round].push((a, b));
+        }
+        PairwiseRounds { size, rounds }
+    }
+
+    /// A schedule of exchanges between every process of `comm` and its `peers`
+    ///
+    /// A process is exchanged with if either of the two processes lists the other one as a peer.
+    /// This is a collective operation.
+    pub fn gather<C: Communicator>(comm: &C, peers: &[Rank]) -> PairwiseRounds {
+        let count: Count = peers
+            .len()
+            .value_as()
+            .expect("Number of peers cannot be expressed as an MPI Count.");
+        let mut counts: Vec<Count> = vec![0; to_usize(comm.size())];
+        comm.all_gather_into(&count, &mut counts[..]);
+        let displs = counts::displacements(&counts);
+        let len = counts.iter().map(|&count| to_usize(count)).sum();
+        let mut all_peers: Vec<Rank> = vec![0; len];
+        comm.all_gather_varcount_into(
+            peers,
+            &mut PartitionMut::new(&mut all_peers[..], &counts[..], &displs[..]),
+        );
+
+        let mut edges = Vec::with_capacity(len);
+        for (source, (&count, &displ)) in counts.iter().zip(&displs).enumerate() {
+            let source: Rank = source
+                .value_as()
+                .expect("Process index cannot be expressed as a Rank.");
+            let (start, end) = (to_usize(displ), to_usize(displ + count));
+            edges.extend(all_peers[start..end].iter().map(|&peer| (source, peer)));
+        }
+        PairwiseRounds::new(comm.size(), &edges)
+    }
+
+    /// Number of ranks in the schedule
+    pub fn size(&self) -> Rank {
+        self.size
+    }
+
+    /// Number of rounds
+    pub fn num_rounds(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// The pairs of ranks that exchange in round `round`
+    pub fn round(&self, round: usize) -> &[(Rank, Rank)] {
+        &self.rounds[round]
+    }
+
+    /// The peer of `rank` in round `round`, `None` if `rank` is idle in that round
+    pub fn peer(&self, rank: Rank, round: usize) -> Option<Rank> {
+        assert_member(rank, self.size);
+        self.rounds[round].iter().find_map(|&(a, b)| {
+            if a == rank {
+                Some(b)
+            } else if b == rank {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The peers of `rank` in the order of the rounds
+    pub fn peers(&self, rank: Rank) -> Vec<Rank> {
+        (0..self.rounds.len())
+            .filter_map(|round| self.peer(rank, round))
+            .collect()
+    }
+
+    /// Call `f` with the peer of `rank` in every round in which `rank` is not idle, in the order
+    /// of the rounds.
+    pub fn for_each_round<F>(&self, rank: Rank, f: F)
+    where
+        F: FnMut(Rank),
+    {
+        self.peers(rank).into_iter().for_each(f)
+    }
+}
+
+fn to_usize(n: c_int) -> usize {
+    n.value_as()
+        .expect("Rank or count cannot be expressed as a usize.")
+}
@@ -0,0 +1,128 @@
+//! Agreement on the layout of message types between processes
+//!
+//! If only some of the processes of a job are redeployed with a new version of a program, e.g.
+//! after a partial restart, processes with different definitions of a message type end up
+//! exchanging messages. MPI does not notice, since datatypes are not transmitted along with the
+//! messages, and the receiver silently misinterprets the data. `negotiate_schema()` compares a
+//! hash of the decoded type signature of the datatype equivalent to a type on all processes and
+//! reports a mismatch before the first message is exchanged.
+//!
+//! # Examples
+//!
+//! See `examples/negotiate_schema.rs`
+//!
+//! # Standard section(s)
+//!
+//! 4.1.13, 5.7
+
+use std::any;
+use std::error::Error as StdError;
+use std::fmt;
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::datatype::write_signature;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+
+/// A hash of the type signature of the datatype equivalent to `T`
+///
+/// The hash covers the combiners used to construct the datatype with their arguments and the
+/// names and sizes of the named datatypes it is built from. It is the same on all processes that
+/// agree on the datatype, independent of the values of the MPI handles.
+///
+/// # Standard section(s)
+///
+/// 4.1.13
+pub fn signature_hash<T: Equivalence>() -> u64 {
+    let mut signature = Vec::new();
+    write_signature(T::equivalent_datatype().as_raw(), &mut signature);
+    fnv1a(&signature)
+}
+
+/// Check that all processes of `comm` agree on the datatype equivalent to `T`.
+///
+/// Returns the hash of the type signature, see `signature_hash()`, or a `SchemaMismatch` listing
+/// the hashes of all processes if they disagree. The result is the same on all processes. This is
+/// a collective operation.
+///
+/// # Examples
+///
+/// See `examples/negotiate_schema.rs`
+///
+/// # Standard section(s)
+///
+/// 4.1.13, 5.7
+pub fn negotiate_schema<T, C>(comm: &C) -> Result<u64, SchemaMismatch>
+where
+    T: Equivalence,
+    C: Communicator,
+{
+    let hash = signature_hash::<T>();
+    let size: usize = comm
+        .size()
+        .value_as()
+        .expect("Communicator size cannot be expressed as a usize.");
+    let mut hashes = vec![0u64; size];
+    comm.all_gather_into(&hash, &mut hashes[..]);
+    if hashes.iter().all(|&other| other == hash) {
+        Ok(hash)
+    } else {
+        Err(SchemaMismatch {
+            type_name: any::type_name::<T>(),
+            hashes,
+        })
+    }
+}
+
+/// The report of `negotiate_schema()` when the processes disagree on a datatype
+#[derive(Clone, Debug)]
+pub struct SchemaMismatch {
+    type_name: &'static str,
+    hashes: Vec<u64>,
+}
+
+impl SchemaMismatch {
+    /// The name of the type the processes disagree on
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The hash of the type signature on every process, indexed by rank
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    /// The ranks of the processes that disagree with process 0, in ascending order
+    pub fn dissenting_ranks(&self) -> Vec<Rank> {
+        (0..)
+            .zip(&self.hashes)
+            .filter(|&(_, &hash)| hash != self.hashes[0])
+            .map(|(rank, _)| rank)
+            .collect()
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The processes disagree on the datatype equivalent to `{}`, ranks {:?} differ from \
+             rank 0.",
+            self.type_name,
+            self.dissenting_ranks()
+        )
+    }
+}
+
+impl StdError for SchemaMismatch {}
+
+/// The 64-bit FNV-1a hash of `bytes`, which unlike `DefaultHasher` is stable across versions of
+/// Rust
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,358 @@
+//! Communication of values that implement `serde::Serialize`
+//!
+//! The operations in this module serialize values into byte buffers, exchange the byte buffers
+//! and deserialize them on the receiving side. This allows communicating arbitrarily-sized values
+//! like `String`s, `Vec`s or nested data structures that do not have an equivalent MPI datatype.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! With the `compress` feature, serialized values that are larger than the threshold set by
+//! `set_compression_threshold()` are compressed with LZ4 before they are sent, which trades CPU
+//! time for bandwidth on slow interconnects. All processes have to be built with the same set of
+//! features, since the feature changes the format of the messages.
+
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use conv::ConvUtil;
+use serde_crate::de::DeserializeOwned;
+use serde_crate::Serialize;
+
+use crate::collective::{CollectivePlan, CommunicatorCollectives, Root};
+use crate::datatype::traits::*;
+use crate::topology::traits::*;
+use crate::Count;
+
+/// Serialized communication traits
+pub mod traits {
+    pub use super::{SerializedCollectives, SerializedRoot};
+}
+
+/// Serialize `value` into a byte buffer.
+pub(crate) fn encode<T: ?Sized + Serialize>(value: &T) -> Vec<u8> {
+    compression::compress(bincode::serialize(value).expect("Serialization of message failed."))
+}
+
+/// Deserialize a value from a byte buffer produced by `encode()`.
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    bincode::deserialize(&compression::decompress(bytes))
+        .expect("Deserialization of message failed.")
+}
+
+#[cfg(feature = "compress")]
+pub use self::compression::{compression_threshold, set_compression_threshold};
+
+#[cfg(feature = "compress")]
+mod compression {
+    use std::borrow::Cow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Marks a message that is sent as serialized
+    const RAW: u8 = 0;
+    /// Marks a message that is compressed with LZ4
+    const LZ4: u8 = 1;
+
+    static THRESHOLD: AtomicUsize = AtomicUsize::new(64 * 1024);
+
+    /// The size in bytes above which serialized values are compressed
+    pub fn compression_threshold() -> usize {
+        THRESHOLD.load(Ordering::Relaxed)
+    }
+
+    /// Compress serialized values that are larger than `threshold` bytes.
+    ///
+    /// The threshold only affects the sending side, so processes can use different thresholds.
+    /// Set it to `usize::MAX` to disable compression. The default is 64 KiB.
+    pub fn set_compression_threshold(threshold: usize) {
+        THRESHOLD.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Prefix `bytes` with a marker, compressing them if they exceed the threshold.
+    pub(super) fn compress(bytes: Vec<u8>) -> Vec<u8> {
+        if bytes.len() > compression_threshold() {
+            let compressed = lz4_flex::compress_prepend_size(&bytes);
+            if compressed.len() < bytes.len() {
+                let mut framed = Vec::with_capacity(compressed.len() + 1);
+                framed.push(LZ4);
+                framed.extend_from_slice(&compressed);
+                return framed;
+            }
+        }
+        let mut framed = Vec::with_capacity(bytes.len() + 1);
+        framed.push(RAW);
+        framed.extend_from_slice(&bytes);
+        framed
+    }
+
+    /// Undo `compress()`.
+    pub(super) fn decompress(bytes: &[u8]) -> Cow<'_, [u8]> {
+        match bytes.split_first() {
+            Some((&RAW, payload)) => Cow::Borrowed(payload),
+            Some((&LZ4, payload)) => Cow::Owned(
+                lz4_flex::decompress_size_prepended(payload)
+                    .expect("Decompression of message failed."),
+            ),
+            _ => panic!("Received a serialized message with an unknown compression marker."),
+        }
+    }
+}
+
+#[cfg(not(feature = "compress"))]
+mod compression {
+    use std::borrow::Cow;
+
+    pub(super) fn compress(bytes: Vec<u8>) -> Vec<u8> {
+        bytes
+    }
+
+    pub(super) fn decompress(bytes: &[u8]) -> Cow<'_, [u8]> {
+        Cow::Borrowed(bytes)
+    }
+}
+
+/// Deserialize the partitions of `buf` described by `plan`.
+pub(crate) fn decode_partitions<T: DeserializeOwned>(plan: &CollectivePlan, buf: &[u8]) -> Vec<T> {
+    plan.counts()
+        .iter()
+        .zip(plan.displs())
+        .map(|(&count, &displ)| {
+            let start: usize = displ
+                .value_as()
+                .expect("Message displacement cannot be expressed as a usize.");
+            let count: usize = count
+                .value_as()
+                .expect("Message length cannot be expressed as a usize.");
+            decode(&buf[start..start + count])
+        })
+        .collect()
+}
+
+/// Collective operations with a root process on serialized values
+pub trait SerializedRoot: Root {
+    /// Gather a serialized value from all processes on the root process.
+    ///
+    /// The serialized values can have different sizes on every process. The lengths of the
+    /// serialized values are gathered on the root process first, followed by a gather of the
+    /// values themselves.
+    ///
+    /// This function must be called on all non-root processes.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5, 5.7
+    fn gather_serialized_into<T: ?Sized>(&self, value: &T)
+    where
+        T: Serialize,
+    {
+        let bytes = encode(value);
+        self.gather_into(&bytes[..].count());
+        self.gather_varcount_into(&bytes[..]);
+    }
+
+    /// Gather a serialized value from all processes on the root process.
+    ///
+    /// Returns the deserialized values in rank order. See `gather_serialized_into()`.
+    ///
+    /// This function must be called on the root process.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/gather_serialized.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.5, 5.7
+    fn gather_serialized_into_root<T>(&self, value: &T) -> Vec<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let bytes = encode(value);
+        let size: usize = self
+            .as_communicator()
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        let mut counts: Vec<Count> = vec![0; size];
+        self.gather_into_root(&bytes[..].count(), &mut counts[..]);
+        let plan = CollectivePlan::new(counts);
+        let mut buf = buffer_for(&plan);
+        plan.gather_varcount_into_root(self, &bytes[..], &mut buf[..]);
+        decode_partitions(&plan, &buf)
+    }
+
+    /// Broadcast a serialized value from the root process to all processes.
+    ///
+    /// On the root process `value` is sent, on all other processes it is replaced with the value
+    /// of the root process. The length of the serialized value is broadcast first, followed by
+    /// the value itself.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/merge_maps.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    fn broadcast_serialized_into<T>(&self, value: &mut T)
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        if self.as_communicator().rank() == self.root_rank() {
+            let mut bytes = encode(value);
+            let mut len = bytes[..].count();
+            self.broadcast_into(&mut len);
+            self.broadcast_into(&mut bytes[..]);
+        } else {
+            let mut len: Count = 0;
+            self.broadcast_into(&mut len);
+            let len: usize = len
+                .value_as()
+                .expect("Length of serialized message cannot be expressed as a usize.");
+            let mut bytes = vec![0u8; len];
+            self.broadcast_into(&mut bytes[..]);
+            *value = decode(&bytes);
+        }
+    }
+}
+
+impl<R: Root> SerializedRoot for R {}
+
+/// Collective operations on serialized values
+pub trait SerializedCollectives: Communicator {
+    /// Gather a serialized value from all processes on all processes.
+    ///
+    /// Returns the deserialized values in rank order.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.7
+    fn all_gather_serialized<T>(&self, value: &T) -> Vec<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let bytes = encode(value);
+        let plan = CollectivePlan::from_all_gather(self, bytes[..].count());
+        let mut buf = buffer_for(&plan);
+        plan.all_gather_varcount_into(self, &bytes[..], &mut buf[..]);
+        decode_partitions(&plan, &buf)
+    }
+
+    /// Send the serialized value `values[r]` to the process of rank `r` and receive one
+    /// serialized value from every process.
+    ///
+    /// Returns the deserialized values received from all processes in rank order. The values can
+    /// differ in size, e.g. `Vec`s of the edges of a graph that cross into the part of another
+    /// process: the lengths of the serialized values are exchanged first, followed by the bytes of
+    /// all values in a single all to all exchange.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/all_to_all_serialized.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.8
+    fn all_to_all_serialized<T>(&self, values: &[T]) -> Vec<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let size: usize = self
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+        assert_eq!(
+            values.len(),
+            size,
+            "All to all communication needs one value per process."
+        );
+
+        let mut send_counts: Vec<Count> = Vec::with_capacity(size);
+        let mut send_bytes = Vec::new();
+        for value in values {
+            let bytes = encode(value);
+            send_counts.push(bytes[..].count());
+            send_bytes.extend_from_slice(&bytes);
+        }
+        let mut recv_counts: Vec<Count> = vec![0; size];
+        self.all_to_all_into(&send_counts[..], &mut recv_counts[..]);
+
+        let send_plan = CollectivePlan::new(send_counts);
+        let recv_plan = CollectivePlan::new(recv_counts);
+        let mut buf = buffer_for(&recv_plan);
+        send_plan.all_to_all_varcount_into(self, &send_bytes[..], &recv_plan, &mut buf[..]);
+        decode_partitions(&recv_plan, &buf)
+    }
+
+    /// Merge the key-value maps of all processes into one map on all processes.
+    ///
+    /// Values with the same key are combined by `merge(accumulator, value)`, e.g. to add up
+    /// distributed counters or histograms. Every key is assigned to an owning process by its hash.
+    /// The entries are sent to their owners in one all to all exchange, merged by the owners in
+    /// rank order of the contributing processes and the merged entries are gathered on all
+    /// processes.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/merge_maps.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.7, 5.8
+    fn merge_reduce_maps<K, V, F>(&self, local: HashMap<K, V>, mut merge: F) -> HashMap<K, V>
+    where
+        K: Hash + Eq + Serialize + DeserializeOwned,
+        V: Serialize + DeserializeOwned,
+        F: FnMut(&mut V, V),
+    {
+        let size: usize = self
+            .size()
+            .value_as()
+            .expect("Communicator size cannot be expressed as a usize.");
+
+        let mut buckets: Vec<Vec<(K, V)>> = (0..size).map(|_| Vec::new()).collect();
+        for (key, value) in local {
+            buckets[owner(&key, size)].push((key, value));
+        }
+
+        let mut owned = HashMap::new();
+        for (key, value) in self.all_to_all_serialized(&buckets).into_iter().flatten() {
+            match owned.entry(key) {
+                Entry::Occupied(mut entry) => merge(entry.get_mut(), value),
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+        }
+
+        let owned: Vec<(K, V)> = owned.into_iter().collect();
+        self.all_gather_serialized(&owned)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+impl<C: Communicator> SerializedCollectives for C {}
+
+/// A receive buffer for the serialized messages described by `plan`
+fn buffer_for(plan: &CollectivePlan) -> Vec<u8> {
+    let len: usize = plan
+        .extent()
+        .value_as()
+        .expect("Length of serialized messages cannot be expressed as a usize.");
+    vec![0u8; len]
+}
+
+/// The process owning `key` in a communicator of `size` processes
+fn owner<K: Hash>(key: &K, size: usize) -> usize {
+    // `DefaultHasher::new()` uses fixed keys, so all processes agree on the owner.
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    let size: u64 = size
+        .value_as()
+        .expect("Communicator size cannot be expressed as a u64.");
+    (hasher.finish() % size)
+        .value_as()
+        .expect("Rank cannot be expressed as a usize.")
+}
@@ -0,0 +1,99 @@
+//! Set operations on sorted vectors distributed over the processes of a communicator
+//!
+//! Every process contributes a sorted vector of values, which is interpreted as a set (duplicates
+//! within the vector of a process are ignored). The operations compute the union, the
+//! intersection or the values unique to a single process of these sets. Like the input, the
+//! result is distributed: every process receives a sorted, contiguous range of the result and
+//! the ranges are ordered by rank, so concatenating the results of all processes in rank order
+//! yields the complete, sorted result.
+//!
+//! The values are exchanged like in `sort::parallel_sort()`, which sends equal values to the same
+//! process, and every process evaluates the operation on its range of values.
+//!
+//! # Examples
+//!
+//! See `examples/sets.rs`
+//!
+//! # Standard section(s)
+//!
+//! 5.7, 5.8
+
+use conv::ConvUtil;
+
+use crate::datatype::traits::*;
+use crate::sort;
+use crate::topology::traits::*;
+
+/// The union of the sets of all processes of `comm`
+///
+/// Returns the range of the sorted union assigned to the calling process. This is a collective
+/// operation.
+pub fn distributed_union<C, T>(comm: &C, local: &[T]) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    select(comm, local, |_| true)
+}
+
+/// The intersection of the sets of all processes of `comm`
+///
+/// Returns the range of the sorted intersection assigned to the calling process. This is a
+/// collective operation.
+pub fn distributed_intersection<C, T>(comm: &C, local: &[T]) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    let size: usize = comm
+        .size()
+        .value_as()
+        .expect("Communicator size cannot be expressed as a usize.");
+    select(comm, local, |processes| processes == size)
+}
+
+/// The values that are contained in the set of exactly one process of `comm`
+///
+/// Returns the range of the sorted values assigned to the calling process. This is a collective
+/// operation.
+pub fn distributed_unique<C, T>(comm: &C, local: &[T]) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    select(comm, local, |processes| processes == 1)
+}
+
+/// The values of the union for which `keep` returns `true` when given the number of processes
+/// whose set contains the value
+fn select<C, T, F>(comm: &C, local: &[T], keep: F) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+    F: Fn(usize) -> bool,
+{
+    assert!(
+        local.windows(2).all(|pair| pair[0] <= pair[1]),
+        "The input of a distributed set operation must be sorted."
+    );
+    let mut values = local.to_vec();
+    values.dedup();
+
+    let mut received = sort::exchange(comm, &values);
+    received.sort_unstable();
+    let mut result = Vec::new();
+    let mut start = 0;
+    while start < received.len() {
+        let value = received[start];
+        let end = start
+            + received[start..]
+                .iter()
+                .take_while(|&&other| other == value)
+                .count();
+        if keep(end - start) {
+            result.push(value);
+        }
+        start = end;
+    }
+    result
+}
@@ -0,0 +1,211 @@
+//! Exchange of data between processes on the same node through shared memory
+//!
+//! Processes on the same node can exchange large buffers without going through the message
+//! passing stack of the MPI library. A `NodeExchange` allocates a shared memory window on every
+//! node with one segment per process. Every process writes into its own segment and, after all
+//! processes of the node have called `synchronize()`, reads the data published by the other
+//! processes of the node directly from their segments, without an intermediate copy.
+//!
+//! The segments are plain memory that other processes access concurrently, so the accessors are
+//! `unsafe`: between two calls to `synchronize()` a process must only write its own segment and
+//! only read data that was written to the other segments before the last call to `synchronize()`
+//! and that their owners do not overwrite before the next call.
+//!
+//! # Examples
+//!
+//! See `examples/node_exchange.rs`
+//!
+//! # Standard section(s)
+//!
+//! 11.2.3, 11.5.4
+
+use std::marker::PhantomData;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::datatype::traits::*;
+use crate::environment;
+use crate::ffi::{self, MPI_Win};
+use crate::raw::traits::*;
+use crate::topology::traits::*;
+use crate::topology::{Rank, UserCommunicator};
+use crate::Address;
+
+/// Shared memory segments of the processes on a node
+///
+/// Every process of the node owns a segment of elements of type `T`, which it can write via
+/// `local_mut()` and which all processes of the node can read via `segment()`.
+pub struct NodeExchange<T> {
+    node: UserCommunicator,
+    window: MPI_Win,
+    segments: Vec<(*mut T, usize)>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> NodeExchange<T>
+where
+    T: Equivalence + Copy,
+{
+    /// Allocate segments of `capacity` elements for all processes of `comm`.
+    ///
+    /// The processes are grouped by the node they run on, see `node()`. The segments are zero
+    /// initialized by the MPI library. This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, capacity: usize) -> NodeExchange<T> {
+        let node = comm.split_shared(comm.rank());
+        let bytes: Address = capacity
+            .checked_mul(mem::size_of::<T>())
+            .and_then(|bytes| bytes.value_as().ok())
+            .expect("Segment size cannot be expressed as an MPI Address.");
+        let disp_unit: c_int = mem::size_of::<T>()
+            .max(1)
+            .value_as()
+            .expect("Element size cannot be expressed as a C int.");
+
+        let mut base: *mut c_void = ptr::null_mut();
+        let base_ptr: *mut *mut c_void = &mut base;
+        let mut window = unsafe { ffi::RSMPI_WIN_NULL };
+        unsafe {
+            ffi::MPI_Win_allocate_shared(
+                bytes,
+                disp_unit,
+                ffi::RSMPI_INFO_NULL,
+                node.as_raw(),
+                base_ptr as *mut c_void,
+                &mut window,
+            );
+        }
+
+        let segments = (0..node.size())
+            .map(|rank| {
+                let mut size: Address = 0;
+                let mut unit: c_int = 0;
+                let mut segment: *mut c_void = ptr::null_mut();
+                let segment_ptr: *mut *mut c_void = &mut segment;
+                unsafe {
+                    ffi::MPI_Win_shared_query(
+                        window,
+                        rank,
+                        &mut size,
+                        &mut unit,
+                        segment_ptr as *mut c_void,
+                    );
+                }
+                let size: usize = size
+                    .value_as()
+                    .expect("Segment size cannot be expressed as a usize.");
+                let len = size / mem::size_of::<T>().max(1);
+                // The MPI library may return a null base for an empty segment.
+                if segment.is_null() || len == 0 {
+                    (NonNull::dangling().as_ptr(), 0)
+                } else {
+                    (segment as *mut T, len)
+                }
+            })
+            .collect();
+        unsafe {
+            ffi::MPI_Win_lock_all(ffi::RSMPI_MODE_NOCHECK, window);
+        }
+
+        NodeExchange {
+            node,
+            window,
+            segments,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The communicator of the processes on the node of the calling process
+    ///
+    /// Segments are addressed by the rank of their owner in this communicator.
+    pub fn node(&self) -> &UserCommunicator {
+        &self.node
+    }
+
+    /// The number of elements of the segment of process `rank` of the node
+    pub fn capacity(&self, rank: Rank) -> usize {
+        self.segments[self.index(rank)].1
+    }
+
+    /// The segment of the calling process
+    ///
+    /// # Safety
+    /// - No other process of the node reads the segment of the calling process through
+    ///   `segment()` while the returned slice is alive.
+    pub unsafe fn local_mut(&mut self) -> &mut [T] {
+        let (segment, len) = self.segments[self.index(self.node.rank())];
+        slice::from_raw_parts_mut(segment, len)
+    }
+
+    /// The segment of process `rank` of the node
+    ///
+    /// Only the data written to the segment before the last call to `synchronize()` is visible.
+    ///
+    /// # Safety
+    /// - Process `rank` does not write its segment through `local_mut()` or `publish()` while the
+    ///   returned slice is alive.
+    pub unsafe fn segment(&self, rank: Rank) -> &[T] {
+        let (segment, len) = self.segments[self.index(rank)];
+        slice::from_raw_parts(segment, len)
+    }
+
+    /// Copy `data` to the start of the segment of the calling process and synchronize.
+    ///
+    /// This is a collective operation on the processes of the node.
+    ///
+    /// # Safety
+    /// - No other process of the node reads the segment of the calling process through
+    ///   `segment()` until it has called `synchronize()` or `publish()` as well.
+    pub unsafe fn publish(&mut self, data: &[T]) {
+        let local = self.local_mut();
+        assert!(
+            data.len() <= local.len(),
+            "Cannot publish {} elements in a segment of {} elements.",
+            data.len(),
+            local.len()
+        );
+        local[..data.len()].copy_from_slice(data);
+        self.synchronize();
+    }
+
+    /// Make the writes of all processes of the node to their segments visible to each other.
+    ///
+    /// This is a collective operation on the processes of the node.
+    pub fn synchronize(&self) {
+        unsafe {
+            ffi::MPI_Win_sync(self.window);
+        }
+        self.node.barrier();
+        unsafe {
+            ffi::MPI_Win_sync(self.window);
+        }
+    }
+
+    fn index(&self, rank: Rank) -> usize {
+        assert!(
+            0 <= rank && rank < self.node.size(),
+            "Rank {} is out of range for a node of {} processes.",
+            rank,
+            self.node.size()
+        );
+        rank.value_as()
+            .expect("Rank cannot be expressed as a usize.")
+    }
+}
+
+impl<T> Drop for NodeExchange<T> {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("NodeExchange") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Win_unlock_all(self.window);
+            ffi::MPI_Win_free(&mut self.window);
+        }
+        assert_eq!(self.window, unsafe { ffi::RSMPI_WIN_NULL });
+    }
+}
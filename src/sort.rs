@@ -0,0 +1,128 @@
+//! Sorting of vectors distributed over the processes of a communicator
+//!
+//! `parallel_sort()` implements a sample sort: every process sorts its values locally and picks
+//! regular samples from them, the samples of all processes are gathered to choose one splitter
+//! per process boundary, the values are exchanged with a single all to all communication so that
+//! every process receives the values between two splitters, and the received runs are sorted
+//! locally again.
+//!
+//! # Examples
+//!
+//! See `examples/parallel_sort.rs`
+//!
+//! # Standard section(s)
+//!
+//! 5.7, 5.8
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::collective::{CollectivePlan, SystemOperation};
+use crate::datatype::traits::*;
+use crate::topology::traits::*;
+use crate::Count;
+
+/// Sort the values distributed over the processes of `comm`.
+///
+/// Afterwards, `values` holds a sorted, contiguous range of the values of all processes and the
+/// ranges are ordered by rank, so concatenating `values` of all processes in rank order yields
+/// all values in sorted order. The number of values per process generally changes. Equal values
+/// end up on the same process. This is a collective operation.
+pub fn parallel_sort<C, T>(comm: &C, values: &mut Vec<T>)
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    values.sort_unstable();
+    let mut received = exchange(comm, values);
+    received.sort_unstable();
+    *values = received;
+}
+
+/// Whether the values distributed over the processes of `comm` are sorted
+///
+/// The values are sorted if `values` is sorted on every process and the last value of every
+/// process is not greater than the first value of the next process holding values. This is a
+/// collective operation.
+pub fn is_sorted<C, T>(comm: &C, values: &[T]) -> bool
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    let bounds: Vec<T> = match (values.first(), values.last()) {
+        (Some(&first), Some(&last)) => vec![first, last],
+        _ => Vec::new(),
+    };
+    let plan = CollectivePlan::from_all_gather(comm, bounds[..].count());
+    let mut all_bounds = vec![T::default(); to_usize(plan.extent())];
+    plan.all_gather_varcount_into(comm, &bounds[..], &mut all_bounds[..]);
+
+    let locally_sorted = values.windows(2).all(|pair| pair[0] <= pair[1]);
+    let mut all_sorted = false;
+    comm.all_reduce_into(
+        &locally_sorted,
+        &mut all_sorted,
+        SystemOperation::logical_and(),
+    );
+    all_sorted && all_bounds.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+/// Send every value of the sorted `values` to the process that owns its range.
+///
+/// Equal values are sent to the same process. Returns the values received from all processes,
+/// which consist of one sorted run per process.
+pub(crate) fn exchange<C, T>(comm: &C, values: &[T]) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    let size: usize = comm
+        .size()
+        .value_as()
+        .expect("Communicator size cannot be expressed as a usize.");
+    let splitters = splitters(comm, values, size);
+
+    let mut send_counts: Vec<Count> = vec![0; size];
+    for value in values {
+        let owner = match splitters.binary_search(value) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        };
+        send_counts[owner] += 1;
+    }
+    let mut recv_counts: Vec<Count> = vec![0; size];
+    comm.all_to_all_into(&send_counts[..], &mut recv_counts[..]);
+
+    let send_plan = CollectivePlan::new(send_counts);
+    let recv_plan = CollectivePlan::new(recv_counts);
+    let mut received = vec![T::default(); to_usize(recv_plan.extent())];
+    send_plan.all_to_all_varcount_into(comm, values, &recv_plan, &mut received[..]);
+    received
+}
+
+/// `size - 1` splitters chosen from regular samples of the `values` of all processes
+fn splitters<C, T>(comm: &C, values: &[T], size: usize) -> Vec<T>
+where
+    C: Communicator,
+    T: Equivalence + Ord + Copy + Default,
+{
+    let samples: Vec<T> = if values.is_empty() {
+        Vec::new()
+    } else {
+        (1..size).map(|i| values[i * values.len() / size]).collect()
+    };
+    let plan = CollectivePlan::from_all_gather(comm, samples[..].count());
+    let mut all_samples = vec![T::default(); to_usize(plan.extent())];
+    plan.all_gather_varcount_into(comm, &samples[..], &mut all_samples[..]);
+    if all_samples.is_empty() {
+        return Vec::new();
+    }
+    all_samples.sort_unstable();
+    (1..size)
+        .map(|i| all_samples[i * all_samples.len() / size])
+        .collect()
+}
+
+fn to_usize(n: Count) -> usize {
+    n.value_as().expect("Count cannot be expressed as a usize.")
+}
@@ -0,0 +1,172 @@
+//! Staging input files to all processes
+//!
+//! Large jobs often start with every process reading the same input file, e.g. a mesh or a
+//! parameter table, which overloads a shared file system with many concurrent reads of the same
+//! bytes. Instead, `broadcast_file()` reads the file once on a root process and broadcasts its
+//! contents in chunks to all processes. On clusters with fast node-local storage,
+//! `stage_file()` writes the contents once per node to a local path, from where the processes of
+//! the node read it like the original file.
+//!
+//! # Examples
+//!
+//! See `examples/broadcast_file.rs`
+//!
+//! # Standard section(s)
+//!
+//! 5.4, 6.4.2
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use conv::ConvUtil;
+
+use crate::collective::Root;
+use crate::topology::traits::*;
+
+/// Size of the chunks a file is broadcast in, which keeps every broadcast well below the largest
+/// count of MPI and bounds the memory used for reading on the root process
+const CHUNK: u64 = 16 * 1024 * 1024;
+
+/// Read the file at `path` on the root process and broadcast its contents to all processes.
+///
+/// `path` is only significant on the root process. Returns the contents of the file on every
+/// process. If the root process fails to read the file, all processes return an error. This is a
+/// collective operation.
+///
+/// # Examples
+///
+/// See `examples/broadcast_file.rs`
+///
+/// # Standard section(s)
+///
+/// 5.4
+pub fn broadcast_file<R, P>(root: &R, path: P) -> io::Result<Vec<u8>>
+where
+    R: Root,
+    P: AsRef<Path>,
+{
+    let mut contents = Vec::new();
+    broadcast_chunks(root, path.as_ref(), |chunk| {
+        contents.extend_from_slice(chunk);
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+/// Read the file at `path` on rank 0 of `comm` and write its contents to `local_path` once on
+/// every shared memory node.
+///
+/// The contents are broadcast among the leaders of the nodes only, see
+/// `Communicator::split_leaders()`, which write them to `local_path`, e.g. a directory on a
+/// node-local disk. `path` is only significant on rank 0 and `local_path` on the leaders. When
+/// this returns, the file at `local_path` is complete and can be read by all processes of the
+/// node. If reading or writing fails, the processes of the affected nodes return an error. This is
+/// a collective operation.
+///
+/// # Examples
+///
+/// See `examples/broadcast_file.rs`
+///
+/// # Standard section(s)
+///
+/// 5.4, 6.4.2
+pub fn stage_file<C, P, Q>(comm: &C, path: P, local_path: Q) -> io::Result<()>
+where
+    C: Communicator,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let (node, leaders) = comm.split_leaders();
+    let mut result = Ok(());
+    if let Some(leaders) = leaders {
+        let mut file = File::create(local_path.as_ref());
+        let broadcast = broadcast_chunks(&leaders.process_at_rank(0), path.as_ref(), |chunk| {
+            match file {
+                Ok(ref mut file) => file.write_all(chunk),
+                // The error is reported once the broadcast is complete.
+                Err(_) => Ok(()),
+            }
+        });
+        result = broadcast.and(file.map(drop));
+    }
+
+    let mut staged = result.is_ok();
+    node.process_at_rank(0).broadcast_into(&mut staged);
+    if node.rank() == 0 {
+        result
+    } else if staged {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "The leader of the node failed to stage the file.",
+        ))
+    }
+}
+
+/// Read the file at `path` on the root process and pass its contents to `sink` on all processes,
+/// one chunk after the other.
+///
+/// All chunks are broadcast even if `sink` fails, its first error is returned afterwards.
+fn broadcast_chunks<R, F>(root: &R, path: &Path, mut sink: F) -> io::Result<()>
+where
+    R: Root,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    let is_root = root.as_communicator().rank() == root.root_rank();
+    let mut file = if is_root {
+        File::open(path).and_then(|file| {
+            let len = file.metadata()?.len();
+            Ok((file, len))
+        })
+    } else {
+        Err(root_failed())
+    };
+
+    let mut header = match file {
+        Ok((_, len)) => (true, len),
+        Err(_) => (false, 0),
+    };
+    root.broadcast_into(&mut header);
+    let (opened, len) = header;
+    if !opened {
+        return Err(file.err().unwrap_or_else(root_failed));
+    }
+
+    let mut sink_result = Ok(());
+    let mut chunk = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let size: usize = CHUNK
+            .min(len - offset)
+            .value_as()
+            .expect("Size of chunk cannot be expressed as a usize.");
+        chunk.resize(size, 0);
+
+        // The file may have shrunk since its length was taken.
+        let mut read_error = None;
+        if let Ok((ref mut file, _)) = file {
+            read_error = file.read_exact(&mut chunk).err();
+        }
+        let mut read = read_error.is_none();
+        root.broadcast_into(&mut read);
+        if !read {
+            return Err(read_error.unwrap_or_else(root_failed));
+        }
+
+        root.broadcast_into(&mut chunk[..]);
+        if sink_result.is_ok() {
+            sink_result = sink(&chunk);
+        }
+        offset += CHUNK.min(len - offset);
+    }
+    sink_result
+}
+
+fn root_failed() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "The root process failed to read the file.",
+    )
+}
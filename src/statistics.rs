@@ -0,0 +1,302 @@
+//! Message matching statistics
+//!
+//! When enabled, rsmpi counts the messages and bytes sent to and received from every peer by tag,
+//! as well as the invocations of every collective operation, separately for every communicator.
+//! The statistics can be queried at runtime and summarized on rank 0 to find load imbalance and
+//! chatty communication patterns without an external profiler.
+//!
+//! Counting is disabled by default. While disabled and no hooks are registered, it costs two atomic
+//! loads per operation, see the `hooks` module.
+//! While enabled, the statistics are gathered by a built-in hook that observes the same calls as
+//! the hooks of the `hooks` module, so communication issued from within a hook is not counted.
+//! Sends are counted when they are initiated. Receives are counted by the blocking receive and
+//! send-receive operations on a `Source` that return a `Status`, since only those know the
+//! communicator as well as the source, tag and size of the message. Messages to and from the null
+//! process are not counted.
+//!
+//! Statistics are associated with the handle of a communicator. MPI libraries can reuse the handle
+//! of a freed communicator for a new communicator, use `reset_communicator()` before freeing a
+//! communicator whose statistics are no longer needed.
+//!
+//! # Examples
+//!
+//! See `examples/statistics.rs`
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use conv::ConvUtil;
+use once_cell::sync::Lazy;
+
+use crate::collective::traits::*;
+use crate::datatype::DatatypeRef;
+use crate::ffi::{self, MPI_Comm, MPI_Datatype};
+use crate::hooks::{self, Call, CommHook};
+use crate::point_to_point::Status;
+use crate::raw::traits::*;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+use crate::{with_uninitialized, Count, Tag};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static STATISTICS: Lazy<Mutex<HashMap<ffi::RSMPI_Fint, CommunicatorStatistics>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start counting messages and collective operations.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stop counting messages and collective operations.
+///
+/// Statistics gathered so far are kept.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether messages and collective operations are currently counted
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Discard the statistics of all communicators.
+pub fn reset() {
+    lock().clear();
+}
+
+/// Discard the statistics of communicator `comm`.
+pub fn reset_communicator<C: Communicator>(comm: &C) {
+    lock().remove(&key(comm.as_raw()));
+}
+
+/// A snapshot of the statistics of communicator `comm` on the calling process
+pub fn of<C: Communicator>(comm: &C) -> CommunicatorStatistics {
+    lock().get(&key(comm.as_raw())).cloned().unwrap_or_default()
+}
+
+/// Summarize the statistics of communicator `comm` of all processes on rank 0.
+///
+/// Returns the summaries of all processes in rank order on rank 0 and `None` on all other
+/// processes. This is a collective operation, it is not counted in the statistics.
+pub fn summarize<C: Communicator>(comm: &C) -> Option<Vec<Summary>> {
+    let local = of(comm).summary().to_array();
+    let root = comm.process_at_rank(0);
+    hooks::unobserved(|| {
+        if comm.rank() == 0 {
+            let size: usize = comm
+                .size()
+                .value_as()
+                .expect("Communicator size cannot be expressed as a usize.");
+            let mut all = vec![0u64; size * Summary::LEN];
+            root.gather_into_root(&local[..], &mut all[..]);
+            Some(all.chunks(Summary::LEN).map(Summary::from_slice).collect())
+        } else {
+            root.gather_into(&local[..]);
+            None
+        }
+    })
+}
+
+/// Number of messages and their total size in bytes
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Traffic {
+    /// Number of messages
+    pub messages: u64,
+    /// Total size of the messages in bytes
+    pub bytes: u64,
+}
+
+impl AddAssign for Traffic {
+    fn add_assign(&mut self, other: Traffic) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Statistics of a single communicator on the calling process
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct CommunicatorStatistics {
+    sent: BTreeMap<(Rank, Tag), Traffic>,
+    received: BTreeMap<(Rank, Tag), Traffic>,
+    collectives: BTreeMap<&'static str, u64>,
+}
+
+impl CommunicatorStatistics {
+    /// Messages sent by the calling process, keyed by destination rank and tag
+    pub fn sent(&self) -> &BTreeMap<(Rank, Tag), Traffic> {
+        &self.sent
+    }
+
+    /// Messages received by the calling process, keyed by source rank and tag
+    pub fn received(&self) -> &BTreeMap<(Rank, Tag), Traffic> {
+        &self.received
+    }
+
+    /// Invocations of collective operations, keyed by the name of the operation
+    pub fn collectives(&self) -> &BTreeMap<&'static str, u64> {
+        &self.collectives
+    }
+
+    /// All messages sent by the calling process
+    pub fn total_sent(&self) -> Traffic {
+        total(&self.sent)
+    }
+
+    /// All messages received by the calling process
+    pub fn total_received(&self) -> Traffic {
+        total(&self.received)
+    }
+
+    /// Number of invocations of all collective operations
+    pub fn total_collectives(&self) -> u64 {
+        self.collectives.values().sum()
+    }
+
+    /// The totals of these statistics
+    pub fn summary(&self) -> Summary {
+        Summary {
+            sent: self.total_sent(),
+            received: self.total_received(),
+            collectives: self.total_collectives(),
+        }
+    }
+}
+
+/// Totals of the statistics of a single communicator on a single process
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Summary {
+    /// All messages sent
+    pub sent: Traffic,
+    /// All messages received
+    pub received: Traffic,
+    /// Number of invocations of all collective operations
+    pub collectives: u64,
+}
+
+impl Summary {
+    const LEN: usize = 5;
+
+    fn to_array(self) -> [u64; Summary::LEN] {
+        [
+            self.sent.messages,
+            self.sent.bytes,
+            self.received.messages,
+            self.received.bytes,
+            self.collectives,
+        ]
+    }
+
+    fn from_slice(values: &[u64]) -> Summary {
+        Summary {
+            sent: Traffic {
+                messages: values[0],
+                bytes: values[1],
+            },
+            received: Traffic {
+                messages: values[2],
+                bytes: values[3],
+            },
+            collectives: values[4],
+        }
+    }
+}
+
+fn total(traffic: &BTreeMap<(Rank, Tag), Traffic>) -> Traffic {
+    let mut total = Traffic::default();
+    for &t in traffic.values() {
+        total += t;
+    }
+    total
+}
+
+fn lock() -> std::sync::MutexGuard<'static, HashMap<ffi::RSMPI_Fint, CommunicatorStatistics>> {
+    STATISTICS
+        .lock()
+        .expect("rsmpi internal error: statistics lock poisoned")
+}
+
+fn key(comm: MPI_Comm) -> ffi::RSMPI_Fint {
+    unsafe { ffi::RSMPI_Comm_c2f(comm) }
+}
+
+fn bytes(count: Count, datatype: MPI_Datatype) -> u64 {
+    let size: Count = unsafe { with_uninitialized(|size| ffi::MPI_Type_size(datatype, size)).1 };
+    let count: u64 = count
+        .value_as()
+        .expect("Message length cannot be expressed as a u64.");
+    let size: u64 = size
+        .value_as()
+        .expect("Datatype size cannot be expressed as a u64.");
+    count * size
+}
+
+/// The hook that gathers the statistics while they are enabled
+pub(crate) struct Recorder;
+
+impl CommHook for Recorder {
+    fn before(&self, call: &Call) {
+        match (call.peer, call.tag, call.count, call.sent) {
+            (Some(destination), Some(tag), Some(count), Some(datatype)) => {
+                record_send(call.comm, destination, tag, count, datatype)
+            }
+            (None, ..) => record_collective(call.comm, call.name),
+            _ => {}
+        }
+    }
+
+    fn after(&self, call: &Call, _duration: Duration) {
+        if let (Some(status), Some(datatype)) = (call.received, call.receives) {
+            record_receive(call.comm, &status, datatype);
+        }
+    }
+}
+
+/// Count a message of `count` elements of type `datatype` sent to `destination` with `tag`.
+fn record_send(comm: MPI_Comm, destination: Rank, tag: Tag, count: Count, datatype: MPI_Datatype) {
+    if is_enabled() && destination != unsafe { ffi::RSMPI_PROC_NULL } {
+        let traffic = Traffic {
+            messages: 1,
+            bytes: bytes(count, datatype),
+        };
+        *lock()
+            .entry(key(comm))
+            .or_default()
+            .sent
+            .entry((destination, tag))
+            .or_default() += traffic;
+    }
+}
+
+/// Count the message described by `status`, consisting of elements of type `datatype`.
+fn record_receive(comm: MPI_Comm, status: &Status, datatype: MPI_Datatype) {
+    if is_enabled() && status.source_rank() != unsafe { ffi::RSMPI_PROC_NULL } {
+        let count = status.count(unsafe { DatatypeRef::from_raw(datatype) });
+        let traffic = Traffic {
+            messages: 1,
+            bytes: bytes(count.max(0), datatype),
+        };
+        *lock()
+            .entry(key(comm))
+            .or_default()
+            .received
+            .entry((status.source_rank(), status.tag()))
+            .or_default() += traffic;
+    }
+}
+
+/// Count an invocation of the collective operation `name`.
+fn record_collective(comm: MPI_Comm, name: &'static str) {
+    if is_enabled() {
+        *lock()
+            .entry(key(comm))
+            .or_default()
+            .collectives
+            .entry(name)
+            .or_default() += 1;
+    }
+}
@@ -0,0 +1,251 @@
+//! Capture and forwarding of the standard output and error streams
+//!
+//! The output of the processes of an MPI program usually ends up interleaved on the terminal of
+//! the process manager, often in the middle of a line. `capture()` redirects the standard output
+//! and error streams of the calling process into temporary files. `Capture::forward()` collects
+//! the complete lines written since its last call from all processes on a root process, which
+//! writes them to its original streams in rank order, each line prefixed with the rank of the
+//! process that wrote it. Alternatively, `redirect_to_files()` sends the streams of every process
+//! into files of its own.
+//!
+//! Output written through Rust's `print!()` and C's `stdio` is flushed before it is collected.
+//! This module is only available on Unix.
+//!
+//! # Examples
+//!
+//! See `examples/stdio.rs`
+
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::mem::ManuallyDrop;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use conv::ConvUtil;
+
+use crate::collective::traits::*;
+use crate::collective::CollectivePlan;
+use crate::datatype::traits::*;
+use crate::topology::traits::*;
+use crate::topology::Rank;
+
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Redirect the standard output and error streams of the calling process.
+///
+/// The streams are restored when the returned `Capture` is dropped. Output that has not been
+/// forwarded by then is written to the restored streams of the calling process. This is a local
+/// operation, `comm` is only used to determine the rank that prefixes the captured lines.
+pub fn capture<C: Communicator>(comm: &C) -> io::Result<Capture> {
+    flush();
+    let stdout = Redirect::new(libc::STDOUT_FILENO, "stdout")?;
+    let stderr = Redirect::new(libc::STDERR_FILENO, "stderr")?;
+    Ok(Capture {
+        rank: comm.rank(),
+        stdout,
+        stderr,
+    })
+}
+
+/// Redirect the standard output and error streams of the calling process into files.
+///
+/// The streams are written to `<prefix>.<rank>.out` and `<prefix>.<rank>.err`, where `rank` is the
+/// rank of the calling process in `comm`. Existing files are truncated. The redirection lasts
+/// until the process exits. This is a local operation.
+pub fn redirect_to_files<C, P>(comm: &C, prefix: P) -> io::Result<()>
+where
+    C: Communicator,
+    P: AsRef<Path>,
+{
+    let rank = comm.rank();
+    flush();
+    for &(fd, suffix) in &[(libc::STDOUT_FILENO, "out"), (libc::STDERR_FILENO, "err")] {
+        let file = File::create(rank_file(prefix.as_ref(), rank, suffix))?;
+        if unsafe { libc::dup2(file.as_raw_fd(), fd) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// The file that `redirect_to_files()` writes stream `suffix` of process `rank` to
+pub fn rank_file<P: AsRef<Path>>(prefix: P, rank: Rank, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(prefix.as_ref().as_os_str());
+    name.push(format!(".{}.{}", rank, suffix));
+    PathBuf::from(name)
+}
+
+/// The redirected standard output and error streams of the calling process
+///
+/// Created by `capture()`.
+pub struct Capture {
+    rank: Rank,
+    stdout: Redirect,
+    stderr: Redirect,
+}
+
+impl Capture {
+    /// Forward the lines captured on all processes to `root`.
+    ///
+    /// Every process hands over the complete lines written to its streams since the last call,
+    /// an incomplete last line is kept until it is completed. The root process writes the lines
+    /// to its original streams in rank order, standard output lines to standard output and
+    /// standard error lines to standard error. Errors writing the lines on the root process are
+    /// returned after the collective operation has completed.
+    ///
+    /// This is a collective operation on the communicator of `root`.
+    pub fn forward<R: Root>(&mut self, root: &R) -> io::Result<()> {
+        flush();
+        let stdout = self.stdout.lines(self.rank);
+        let stderr = self.stderr.lines(self.rank);
+        let stdout = gather_bytes(root, &stdout);
+        let stderr = gather_bytes(root, &stderr);
+        if let (Some(stdout), Some(stderr)) = (stdout, stderr) {
+            self.stdout.write_original(&stdout)?;
+            self.stderr.write_original(&stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        flush();
+        let stdout = self.stdout.rest(self.rank);
+        let stderr = self.stderr.rest(self.rank);
+        let _ = self.stdout.write_original(&stdout);
+        let _ = self.stderr.write_original(&stderr);
+    }
+}
+
+/// A stream redirected into a temporary file
+struct Redirect {
+    fd: RawFd,
+    saved: RawFd,
+    reader: File,
+    pending: Vec<u8>,
+}
+
+impl Redirect {
+    fn new(fd: RawFd, name: &str) -> io::Result<Redirect> {
+        let path = env::temp_dir().join(format!(
+            "rsmpi-{}-{}-{}",
+            process::id(),
+            name,
+            FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&path)?;
+        let reader = File::open(&path);
+        let _ = fs::remove_file(&path);
+        let reader = reader?;
+
+        let saved = unsafe { libc::dup(fd) };
+        if saved < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if unsafe { libc::dup2(writer.as_raw_fd(), fd) } < 0 {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(saved);
+            }
+            return Err(error);
+        }
+        Ok(Redirect {
+            fd,
+            saved,
+            reader,
+            pending: Vec::new(),
+        })
+    }
+
+    /// The complete lines written since the last call, prefixed with `rank`
+    fn lines(&mut self, rank: Rank) -> Vec<u8> {
+        self.reader
+            .read_to_end(&mut self.pending)
+            .expect("Failed to read captured output.");
+        let end = self
+            .pending
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let complete: Vec<u8> = self.pending.drain(..end).collect();
+        prefix_lines(rank, &complete)
+    }
+
+    /// Everything written since the last call, prefixed with `rank`
+    fn rest(&mut self, rank: Rank) -> Vec<u8> {
+        let _ = self.reader.read_to_end(&mut self.pending);
+        let rest = prefix_lines(rank, &self.pending);
+        self.pending.clear();
+        rest
+    }
+
+    /// Write `bytes` to the stream that was redirected.
+    fn write_original(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut original = ManuallyDrop::new(unsafe { File::from_raw_fd(self.saved) });
+        original.write_all(bytes)
+    }
+}
+
+impl Drop for Redirect {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dup2(self.saved, self.fd);
+            libc::close(self.saved);
+        }
+    }
+}
+
+/// Flush the buffers of Rust's and C's standard streams.
+fn flush() {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    unsafe {
+        libc::fflush(ptr::null_mut());
+    }
+}
+
+/// Prefix every line of `bytes` with `[rank] `, terminating an incomplete last line.
+fn prefix_lines(rank: Rank, bytes: &[u8]) -> Vec<u8> {
+    let mut prefixed = Vec::new();
+    if bytes.is_empty() {
+        return prefixed;
+    }
+    let prefix = format!("[{}] ", rank);
+    let bytes = match bytes.split_last() {
+        Some((&b'\n', init)) => init,
+        _ => bytes,
+    };
+    for line in bytes.split(|&b| b == b'\n') {
+        prefixed.extend_from_slice(prefix.as_bytes());
+        prefixed.extend_from_slice(line);
+        prefixed.push(b'\n');
+    }
+    prefixed
+}
+
+/// Gather `bytes` from all processes on `root` in rank order.
+fn gather_bytes<R: Root>(root: &R, bytes: &[u8]) -> Option<Vec<u8>> {
+    let comm = root.as_communicator();
+    let plan = CollectivePlan::from_all_gather(comm, bytes.count());
+    if comm.rank() == root.root_rank() {
+        let len: usize = plan
+            .extent()
+            .value_as()
+            .expect("Length of the forwarded output cannot be expressed as a usize.");
+        let mut buf = vec![0u8; len];
+        plan.gather_varcount_into_root(root, bytes, &mut buf[..]);
+        Some(buf)
+    } else {
+        root.gather_varcount_into(bytes);
+        None
+    }
+}
@@ -0,0 +1,159 @@
+//! Byte streams between two processes
+//!
+//! `ByteSender` implements `std::io::Write` and `ByteReceiver` implements `std::io::Read` on top
+//! of point to point messages of bytes, so any encoder that writes into an `io::Write`, e.g. an
+//! archiver or a streaming serializer, can be piped from one process to another without holding
+//! its whole output in memory.
+//!
+//! The sender collects written bytes into chunks and sends every full chunk as one message. An
+//! empty message marks the end of the stream, it is sent by `ByteSender::finish()` or when the
+//! sender is dropped. The receiver has to read from the sending process (not from
+//! `any_process()`) with the same tag, and no other messages with that tag may be exchanged
+//! between the two processes while the stream is open.
+//!
+//! # Examples
+//!
+//! See `examples/byte_stream.rs`
+
+use std::cmp;
+use std::io::{self, BufRead, Read, Write};
+
+use crate::point_to_point::{Destination, Source};
+use crate::Tag;
+
+/// The size of the chunks a `ByteSender` sends, unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The sending end of a byte stream
+pub struct ByteSender<D: Destination> {
+    destination: D,
+    tag: Tag,
+    chunk_size: usize,
+    buf: Vec<u8>,
+    finished: bool,
+}
+
+impl<D: Destination> ByteSender<D> {
+    /// A stream of bytes to `destination` in messages with tag `tag`
+    pub fn new(destination: D, tag: Tag) -> ByteSender<D> {
+        ByteSender::with_chunk_size(destination, tag, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// A stream of bytes to `destination` sent in messages of `chunk_size` bytes
+    pub fn with_chunk_size(destination: D, tag: Tag, chunk_size: usize) -> ByteSender<D> {
+        assert!(
+            chunk_size > 0,
+            "The chunk size of a byte stream must not be zero."
+        );
+        ByteSender {
+            destination,
+            tag,
+            chunk_size,
+            buf: Vec::with_capacity(chunk_size),
+            finished: false,
+        }
+    }
+
+    /// The size of the chunks the stream is sent in
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Send all buffered bytes and mark the end of the stream.
+    pub fn finish(mut self) {
+        self.end();
+    }
+
+    fn send_buffered(&mut self) {
+        if !self.buf.is_empty() {
+            self.destination.send_with_tag(&self.buf[..], self.tag);
+            self.buf.clear();
+        }
+    }
+
+    fn end(&mut self) {
+        if !self.finished {
+            self.send_buffered();
+            self.destination.send_with_tag(&[0u8; 0][..], self.tag);
+            self.finished = true;
+        }
+    }
+}
+
+impl<D: Destination> Write for ByteSender<D> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let len = cmp::min(bytes.len(), self.chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&bytes[..len]);
+        if self.buf.len() == self.chunk_size {
+            self.send_buffered();
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffered();
+        Ok(())
+    }
+}
+
+impl<D: Destination> Drop for ByteSender<D> {
+    fn drop(&mut self) {
+        self.end();
+    }
+}
+
+/// The receiving end of a byte stream
+pub struct ByteReceiver<S: Source> {
+    source: S,
+    tag: Tag,
+    buf: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<S: Source> ByteReceiver<S> {
+    /// A stream of bytes from `source` in messages with tag `tag`
+    pub fn new(source: S, tag: Tag) -> ByteReceiver<S> {
+        ByteReceiver {
+            source,
+            tag,
+            buf: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Whether the end of the stream has been received
+    pub fn is_finished(&self) -> bool {
+        self.finished && self.position == self.buf.len()
+    }
+}
+
+impl<S: Source> BufRead for ByteReceiver<S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position == self.buf.len() && !self.finished {
+            let (chunk, _) = self.source.receive_vec_with_tag::<u8>(self.tag);
+            self.finished = chunk.is_empty();
+            self.buf = chunk;
+            self.position = 0;
+        }
+        Ok(&self.buf[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = cmp::min(self.position + amount, self.buf.len());
+    }
+}
+
+impl<S: Source> Read for ByteReceiver<S> {
+    fn read(&mut self, bytes: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = cmp::min(available.len(), bytes.len());
+            bytes[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
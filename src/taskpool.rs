@@ -0,0 +1,156 @@
+//! A manager/worker task pool
+//!
+//! Many parallel programs consist of a large number of independent tasks of varying cost, e.g.
+//! parameter sweeps or the analysis of many input files. A `TaskPool` hands out such tasks
+//! dynamically: the root process acts as the manager that holds the descriptions of all tasks,
+//! all other processes are workers that ask the manager for the next task as soon as they have
+//! finished their previous one, so fast workers automatically take on more tasks than slow ones.
+//! The results are collected on the manager in the order of the tasks.
+//!
+//! Tasks are described by values of a type with an MPI equivalent, e.g. an index into an input
+//! list or a struct of parameters, rather than by closures. The work itself is done by a closure
+//! that maps a task description to its result on the workers.
+//!
+//! # Examples
+//!
+//! See `examples/taskpool.rs`
+
+use conv::ConvUtil;
+
+use crate::datatype::traits::*;
+use crate::point_to_point::traits::*;
+use crate::topology::traits::*;
+use crate::topology::{Rank, UserCommunicator};
+use crate::{Count, Tag};
+
+/// Tag of the messages in which workers ask for a task
+const READY: Tag = 1;
+/// Tag of the messages carrying task descriptions
+const TASK: Tag = 2;
+/// Tag of the messages carrying results
+const RESULT: Tag = 3;
+
+/// Index sent to a worker instead of a task when no tasks are left
+const DONE: Count = -1;
+
+/// A pool of processes that execute tasks handed out by a manager process
+///
+/// Creating the pool duplicates the communicator, so the messages of the pool do not interfere
+/// with other communication.
+pub struct TaskPool {
+    comm: UserCommunicator,
+    manager: Rank,
+}
+
+impl TaskPool {
+    /// A pool of the processes of `comm` with process `manager` as the manager.
+    ///
+    /// This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, manager: Rank) -> TaskPool {
+        assert!(
+            0 <= manager && manager < comm.size(),
+            "Manager rank {} is out of range for a communicator of size {}.",
+            manager,
+            comm.size()
+        );
+        TaskPool {
+            comm: comm.duplicate(),
+            manager,
+        }
+    }
+
+    /// The rank of the manager process
+    pub fn manager(&self) -> Rank {
+        self.manager
+    }
+
+    /// Whether the calling process is the manager
+    pub fn is_manager(&self) -> bool {
+        self.comm.rank() == self.manager
+    }
+
+    /// Execute `tasks` on the workers of the pool.
+    ///
+    /// `tasks` is only significant on the manager, which returns the results of all tasks in the
+    /// order of `tasks`. The workers execute the tasks handed to them by calling `work` and return
+    /// `None`. If the manager is the only process of the pool, it executes all tasks itself.
+    ///
+    /// This is a collective operation.
+    pub fn run<T, R, F>(&self, tasks: &[T], mut work: F) -> Option<Vec<R>>
+    where
+        T: Equivalence + Clone,
+        R: Equivalence,
+        F: FnMut(T) -> R,
+    {
+        if self.comm.size() == 1 {
+            return Some(tasks.iter().cloned().map(work).collect());
+        }
+        if self.is_manager() {
+            Some(self.manage(tasks))
+        } else {
+            self.work(&mut work);
+            None
+        }
+    }
+
+    fn manage<T, R>(&self, tasks: &[T]) -> Vec<R>
+    where
+        T: Equivalence,
+        R: Equivalence,
+    {
+        let mut results: Vec<Option<R>> = tasks.iter().map(|_| None).collect();
+        let mut next = 0;
+        let mut workers = self.comm.size() - 1;
+        while workers > 0 {
+            let (finished, status) = self.comm.any_process().receive_with_tag::<Count>(READY);
+            let worker = self.comm.process_at_rank(status.source_rank());
+            if finished != DONE {
+                let (result, _) = worker.receive_with_tag::<R>(RESULT);
+                results[to_usize(finished)] = Some(result);
+            }
+            if next < tasks.len() {
+                let index: Count = next
+                    .value_as()
+                    .expect("Task index cannot be expressed as an MPI Count.");
+                worker.send_with_tag(&index, TASK);
+                worker.send_with_tag(&tasks[next], TASK);
+                next += 1;
+            } else {
+                worker.send_with_tag(&DONE, TASK);
+                workers -= 1;
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("rsmpi internal error: task without result"))
+            .collect()
+    }
+
+    fn work<T, R, F>(&self, work: &mut F)
+    where
+        T: Equivalence,
+        R: Equivalence,
+        F: FnMut(T) -> R,
+    {
+        let manager = self.comm.process_at_rank(self.manager);
+        let mut finished = DONE;
+        let mut result: Option<R> = None;
+        loop {
+            manager.send_with_tag(&finished, READY);
+            if let Some(result) = result.take() {
+                manager.send_with_tag(&result, RESULT);
+            }
+            let (index, _) = manager.receive_with_tag::<Count>(TASK);
+            if index == DONE {
+                break;
+            }
+            let (task, _) = manager.receive_with_tag::<T>(TASK);
+            result = Some(work(task));
+            finished = index;
+        }
+    }
+}
+
+fn to_usize(n: Count) -> usize {
+    n.value_as().expect("Count cannot be expressed as a usize.")
+}
@@ -0,0 +1,222 @@
+//! Live progress counters of all processes on rank 0
+//!
+//! Long running simulations are monitored by watching counters like the current iteration, the
+//! residual of a solver or the amount of data communicated. A `Telemetry` feed gathers the
+//! `Counters` of all processes on rank 0 every few iterations with a non-blocking gather on a
+//! duplicate of the communicator, so the main communication is neither matched nor delayed by
+//! it. Rank 0 hands the counters of all processes to a callback, e.g. to print a progress line or
+//! to serve a dashboard.
+//!
+//! # Examples
+//!
+//! See `examples/telemetry.rs`
+//!
+//! # Standard section(s)
+//!
+//! 5.12.3
+
+use std::fmt;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+
+use conv::ConvUtil;
+use memoffset::offset_of;
+
+use crate::datatype::traits::*;
+use crate::datatype::{UncommittedDatatypeRef, UserDatatype};
+use crate::environment;
+use crate::ffi;
+use crate::raw::traits::*;
+use crate::request::{Request, StaticScope};
+use crate::topology::traits::*;
+use crate::topology::{Rank, UserCommunicator};
+use crate::{with_uninitialized, Address};
+
+/// The rank that receives the counters of all processes
+const ROOT: Rank = 0;
+
+/// The progress of one process
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Counters {
+    /// The current iteration
+    pub iteration: u64,
+    /// The current residual, e.g. of a linear solver
+    pub residual: f64,
+    /// The number of bytes communicated so far
+    pub bytes_communicated: u64,
+}
+
+unsafe impl Equivalence for Counters {
+    type Out = UserDatatype;
+    fn equivalent_datatype() -> Self::Out {
+        UserDatatype::structured(
+            &[1, 1, 1],
+            &[
+                offset(offset_of!(Counters, iteration)),
+                offset(offset_of!(Counters, residual)),
+                offset(offset_of!(Counters, bytes_communicated)),
+            ],
+            &[
+                UncommittedDatatypeRef::from(u64::equivalent_datatype()),
+                UncommittedDatatypeRef::from(f64::equivalent_datatype()),
+                UncommittedDatatypeRef::from(u64::equivalent_datatype()),
+            ],
+        )
+    }
+}
+
+fn offset(offset: usize) -> Address {
+    offset
+        .value_as()
+        .expect("Field offset cannot be expressed as an Address.")
+}
+
+/// A feed of the `Counters` of all processes of a communicator to rank 0
+///
+/// All processes call `push()` once per iteration. Every `period`-th push starts a gather of the
+/// pushed counters, which completes in the background. Once it is complete, which is checked by
+/// the following pushes and by `poll()`, rank 0 passes the counters of all processes in rank order
+/// to the callback set with `on_update()`.
+///
+/// # Examples
+///
+/// See `examples/telemetry.rs`
+pub struct Telemetry {
+    comm: UserCommunicator,
+    period: u64,
+    pushes: u64,
+    sent: Box<Counters>,
+    gathered: Box<[Counters]>,
+    request: Option<Request<'static>>,
+    callback: Option<Box<dyn FnMut(&[Counters])>>,
+}
+
+impl Telemetry {
+    /// A feed of the counters of the processes of `comm` that gathers every `period`-th push.
+    ///
+    /// `period` has to be the same on all processes. This is a collective operation.
+    pub fn new<C: Communicator>(comm: &C, period: u64) -> Telemetry {
+        assert!(period > 0, "The telemetry period has to be positive.");
+        let comm = comm.duplicate();
+        let len = if comm.rank() == ROOT {
+            comm.size()
+                .value_as()
+                .expect("Communicator size cannot be expressed as a usize.")
+        } else {
+            0
+        };
+        Telemetry {
+            comm,
+            period,
+            pushes: 0,
+            sent: Box::new(Counters::default()),
+            gathered: vec![Counters::default(); len].into_boxed_slice(),
+            request: None,
+            callback: None,
+        }
+    }
+
+    /// Call `callback` on rank 0 with the counters of all processes whenever a gather completes.
+    ///
+    /// The callback is never called on the other processes.
+    pub fn on_update<F>(&mut self, callback: F)
+    where
+        F: FnMut(&[Counters]) + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+    }
+
+    /// The number of pushes between two gathers
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// Push the counters of the calling process.
+    ///
+    /// Every `period`-th push gathers `counters` on rank 0. If the previous gather has not
+    /// completed by then, the push waits for it. This is a collective operation every `period`-th
+    /// time it is called.
+    pub fn push(&mut self, counters: Counters) {
+        self.pushes += 1;
+        if self.pushes % self.period != 0 {
+            self.poll();
+            return;
+        }
+        self.flush();
+        *self.sent = counters;
+
+        let datatype = Counters::equivalent_datatype();
+        let (recvbuf, recvcount) = if self.comm.rank() == ROOT {
+            (self.gathered.as_mut_ptr() as *mut c_void, 1)
+        } else {
+            (ptr::null_mut(), 0)
+        };
+        unsafe {
+            let request = with_uninitialized(|request| {
+                ffi::MPI_Igather(
+                    &*self.sent as *const Counters as *const c_void,
+                    1,
+                    datatype.as_raw(),
+                    recvbuf,
+                    recvcount,
+                    datatype.as_raw(),
+                    ROOT,
+                    self.comm.as_raw(),
+                    request,
+                )
+            })
+            .1;
+            self.request = Some(Request::from_raw(request, StaticScope));
+        }
+    }
+
+    /// Check whether the pending gather has completed, and if so pass its counters to the
+    /// callback.
+    pub fn poll(&mut self) {
+        if let Some(request) = self.request.take() {
+            match request.test() {
+                Ok(_) => self.deliver(),
+                Err(request) => self.request = Some(request),
+            }
+        }
+    }
+
+    /// Wait for the pending gather, if any, and pass its counters to the callback.
+    pub fn flush(&mut self) {
+        if let Some(request) = self.request.take() {
+            request.wait();
+            self.deliver();
+        }
+    }
+
+    fn deliver(&mut self) {
+        if let Some(ref mut callback) = self.callback {
+            if self.comm.rank() == ROOT {
+                callback(&self.gathered);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Telemetry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("period", &self.period)
+            .field("pushes", &self.pushes)
+            .field("pending", &self.request.is_some())
+            .finish()
+    }
+}
+
+impl Drop for Telemetry {
+    /// Completes the pending gather.
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("Telemetry") {
+            mem::forget(self.request.take());
+            return;
+        }
+        self.flush();
+    }
+}
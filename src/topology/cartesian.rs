@@ -1,8 +1,10 @@
-use std::mem;
+use std::{fmt, mem};
 
 use conv::ConvUtil;
 
-use super::{AsCommunicator, Communicator, IntoTopology, Rank, UserCommunicator};
+use super::{
+    debug_communicator, AsCommunicator, Communicator, IntoTopology, Rank, UserCommunicator,
+};
 use crate::ffi::MPI_Comm;
 use crate::{
     datatype::traits::*, ffi, raw::traits::*, with_uninitialized, with_uninitialized2, Count,
@@ -449,10 +451,83 @@ impl CartesianCommunicator {
 
         unsafe { self.subgroup_unchecked(retain) }
     }
+
+    /// The communicator of the processes in the same row of a 2-D process grid.
+    ///
+    /// Axis 0 of the grid indexes its rows and axis 1 its columns, so the rank of a process in its
+    /// row communicator is its column index. Panics if the communicator is not 2-dimensional.
+    ///
+    /// # Examples
+    /// See `examples/cartesian_grid.rs`
+    ///
+    /// # Standard section(s)
+    /// 7.5.7 (MPI_Cart_sub)
+    pub fn row_comm(&self) -> CartesianCommunicator {
+        self.assert_grid();
+        self.subgroup(&[false, true])
+    }
+
+    /// The communicator of the processes in the same column of a 2-D process grid.
+    ///
+    /// The rank of a process in its column communicator is its row index. Panics if the
+    /// communicator is not 2-dimensional. See [`row_comm`](#method.row_comm).
+    ///
+    /// # Standard section(s)
+    /// 7.5.7 (MPI_Cart_sub)
+    pub fn col_comm(&self) -> CartesianCommunicator {
+        self.assert_grid();
+        self.subgroup(&[true, false])
+    }
+
+    /// The number of rows and columns of a 2-D process grid
+    ///
+    /// Panics if the communicator is not 2-dimensional.
+    pub fn grid_shape(&self) -> (Count, Count) {
+        self.assert_grid();
+        let layout = self.get_layout();
+        (layout.dims[0], layout.dims[1])
+    }
+
+    /// The row and column of process `rank` in a 2-D process grid
+    ///
+    /// Panics if the communicator is not 2-dimensional.
+    pub fn grid_position(&self, rank: Rank) -> (Count, Count) {
+        self.assert_grid();
+        let coords = self.rank_to_coordinates(rank);
+        (coords[0], coords[1])
+    }
+
+    /// The rank of the process in row `row` and column `col` of a 2-D process grid
+    ///
+    /// Rows and columns in periodic axes that are out of range are shifted back into the grid.
+    /// Panics if the communicator is not 2-dimensional.
+    pub fn grid_rank(&self, row: Count, col: Count) -> Rank {
+        self.assert_grid();
+        self.coordinates_to_rank(&[row, col])
+    }
+
+    fn assert_grid(&self) {
+        assert_eq!(
+            self.num_dimensions(),
+            2,
+            "Row and column operations need a 2-dimensional CartesianCommunicator"
+        );
+    }
 }
 
 impl Communicator for CartesianCommunicator {}
 
+impl fmt::Debug for CartesianCommunicator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let layout = self.get_layout();
+        debug_communicator(f, "CartesianCommunicator", self)
+            .field("dims", &layout.dims)
+            .field("periods", &layout.periods)
+            .field("coords", &layout.coords)
+            .finish()
+    }
+}
+
 impl AsCommunicator for CartesianCommunicator {
     type Out = CartesianCommunicator;
     fn as_communicator(&self) -> &Self::Out {
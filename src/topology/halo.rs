@@ -0,0 +1,302 @@
+use std::marker::PhantomData;
+
+use conv::ConvUtil;
+
+use super::{CartesianCommunicator, Rank};
+use crate::datatype::{Order, UserDatatype};
+use crate::{datatype::traits::*, ffi, raw::traits::*, Count, Tag};
+
+/// Which neighbors of a process take part in a halo exchange
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HaloStencil {
+    /// Only neighbors that share a face with the local array, i.e. neighbors that differ in
+    /// exactly one cartesian coordinate
+    Faces,
+    /// All neighbors that share a face, an edge or a corner with the local array
+    Full,
+}
+
+/// A neighbor of the calling process in a halo exchange
+pub struct HaloNeighbor {
+    offset: Vec<Count>,
+    rank: Option<Rank>,
+    boundary: UserDatatype,
+    ghost: UserDatatype,
+}
+
+impl HaloNeighbor {
+    /// The direction of the neighbor, `offset[i]` is one of `-1`, `0` or `1`.
+    pub fn offset(&self) -> &[Count] {
+        &self.offset
+    }
+
+    /// The rank of the neighbor or `None` if the calling process lies on the boundary of a
+    /// non-periodic dimension in the direction of `offset()`.
+    pub fn rank(&self) -> Option<Rank> {
+        self.rank
+    }
+
+    /// The part of the local array that is sent to this neighbor
+    pub fn boundary_datatype(&self) -> &UserDatatype {
+        &self.boundary
+    }
+
+    /// The ghost cells of the local array that lie in the direction of this neighbor
+    pub fn ghost_datatype(&self) -> &UserDatatype {
+        &self.ghost
+    }
+
+    /// The number of non-zero components of `offset()`, `1` for faces, `2` for edges and `3` for
+    /// corners of a three-dimensional array.
+    pub fn codimension(&self) -> usize {
+        self.offset.iter().filter(|&&o| o != 0).count()
+    }
+}
+
+/// A halo exchange plan for arrays of `T` distributed across a `CartesianCommunicator`
+///
+/// Every process owns a local block of shape `local_shape` stored in row-major order and
+/// surrounded by `ghost_width` layers of ghost cells in every dimension, i.e. the local array has
+/// shape `local_shape[i] + 2 * ghost_width`. The plan contains the subarray datatypes that
+/// describe the boundary and ghost regions of every face, edge and corner, as well as the ranks
+/// of the corresponding neighbors.
+///
+/// # Examples
+/// See `examples/halo.rs`
+///
+/// # Standard section(s)
+/// 4.1.3, 7.5
+pub struct Halo<'c, T> {
+    comm: &'c CartesianCommunicator,
+    padded_shape: Vec<Count>,
+    neighbors: Vec<HaloNeighbor>,
+    phantom: PhantomData<T>,
+}
+
+impl<'c, T> Halo<'c, T>
+where
+    T: Equivalence,
+{
+    /// Build the halo exchange plan for the calling process in `comm`.
+    ///
+    /// Panics if `local_shape` is not of length `comm.num_dimensions()` or if the local array is
+    /// thinner than `ghost_width` in any dimension.
+    pub fn new(
+        comm: &'c CartesianCommunicator,
+        local_shape: &[Count],
+        ghost_width: Count,
+        stencil: HaloStencil,
+    ) -> Halo<'c, T> {
+        let layout = comm.get_layout();
+        assert_eq!(
+            layout.dims.len(),
+            local_shape.len(),
+            "The local shape must have as many dimensions as the CartesianCommunicator"
+        );
+        assert!(ghost_width > 0, "The ghost width must be positive");
+        assert!(
+            local_shape.iter().all(|&n| n >= ghost_width),
+            "The local array {:?} is thinner than the ghost width {}",
+            local_shape,
+            ghost_width
+        );
+
+        let padded_shape: Vec<Count> = local_shape.iter().map(|&n| n + 2 * ghost_width).collect();
+        let oldtype = T::equivalent_datatype();
+
+        let neighbors = offsets(local_shape.len())
+            .into_iter()
+            .filter(|offset| {
+                stencil == HaloStencil::Full || offset.iter().filter(|&&o| o != 0).count() == 1
+            })
+            .map(|offset| {
+                let rank =
+                    neighbor_rank(comm, &layout.dims, &layout.periods, &layout.coords, &offset);
+                let (boundary_starts, ghost_starts, subsizes) =
+                    regions(local_shape, ghost_width, &offset);
+                let boundary = UserDatatype::subarray(
+                    &padded_shape,
+                    &subsizes,
+                    &boundary_starts,
+                    Order::RowMajor,
+                    &oldtype,
+                );
+                let ghost = UserDatatype::subarray(
+                    &padded_shape,
+                    &subsizes,
+                    &ghost_starts,
+                    Order::RowMajor,
+                    &oldtype,
+                );
+                HaloNeighbor {
+                    offset,
+                    rank,
+                    boundary,
+                    ghost,
+                }
+            })
+            .collect();
+
+        Halo {
+            comm,
+            padded_shape,
+            neighbors,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The shape of the local array including the ghost cells
+    pub fn padded_shape(&self) -> &[Count] {
+        &self.padded_shape
+    }
+
+    /// The number of elements of the local array including the ghost cells
+    pub fn padded_len(&self) -> usize {
+        self.padded_shape
+            .iter()
+            .map(|&n| {
+                n.value_as::<usize>()
+                    .expect("Array dimension cannot be expressed as a usize.")
+            })
+            .product()
+    }
+
+    /// The neighbors taking part in the halo exchange
+    ///
+    /// Neighbors are ordered such that the neighbor in the opposite direction of neighbor `i` is
+    /// neighbor `len - 1 - i`.
+    pub fn neighbors(&self) -> &[HaloNeighbor] {
+        &self.neighbors
+    }
+
+    /// Fill the ghost cells of `array` with the boundary cells of the neighboring processes.
+    ///
+    /// This is a collective operation over the communicator of the plan. Messages are tagged with
+    /// tags starting at `Tag::default()`.
+    pub fn exchange(&self, array: &mut [T]) {
+        self.exchange_with_tag(array, Tag::default())
+    }
+
+    /// Fill the ghost cells of `array` with the boundary cells of the neighboring processes.
+    ///
+    /// Messages are tagged with tags from `base_tag` to `base_tag + neighbors().len() - 1`.
+    pub fn exchange_with_tag(&self, array: &mut [T], base_tag: Tag) {
+        assert_eq!(
+            array.len(),
+            self.padded_len(),
+            "The local array does not match the padded shape {:?} of the halo",
+            self.padded_shape
+        );
+
+        let n = self.neighbors.len();
+        let pointer = array.as_mut_ptr();
+        for (i, towards) in self.neighbors.iter().enumerate() {
+            // The ghost cells facing away from `towards` are filled by the neighbor on the other
+            // side, which sends its boundary in the same direction.
+            let from = &self.neighbors[n - 1 - i];
+            let tag = base_tag
+                + i.value_as::<Tag>()
+                    .expect("Number of halo neighbors cannot be expressed as a Tag.");
+            unsafe {
+                ffi::MPI_Sendrecv(
+                    pointer as _,
+                    1,
+                    towards.boundary.as_raw(),
+                    towards.rank.unwrap_or(ffi::RSMPI_PROC_NULL),
+                    tag,
+                    pointer as _,
+                    1,
+                    from.ghost.as_raw(),
+                    from.rank.unwrap_or(ffi::RSMPI_PROC_NULL),
+                    tag,
+                    self.comm.as_raw(),
+                    ffi::RSMPI_STATUS_IGNORE,
+                );
+            }
+        }
+    }
+}
+
+impl CartesianCommunicator {
+    /// Build a halo exchange plan for arrays of `T` with local shape `local_shape` surrounded by
+    /// `ghost_width` layers of ghost cells.
+    ///
+    /// See [`Halo`](struct.Halo.html).
+    pub fn halo<T>(
+        &self,
+        local_shape: &[Count],
+        ghost_width: Count,
+        stencil: HaloStencil,
+    ) -> Halo<'_, T>
+    where
+        T: Equivalence,
+    {
+        Halo::new(self, local_shape, ghost_width, stencil)
+    }
+}
+
+/// All offsets in `{-1, 0, 1}^ndims` except the origin in lexicographic order, so that the
+/// opposite of offset `i` is offset `len - 1 - i`.
+fn offsets(ndims: usize) -> Vec<Vec<Count>> {
+    let mut offsets = vec![vec![]];
+    for _ in 0..ndims {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|prefix: Vec<Count>| {
+                (-1..=1).map(move |o| {
+                    let mut offset = prefix.clone();
+                    offset.push(o);
+                    offset
+                })
+            })
+            .collect();
+    }
+    offsets.retain(|offset| offset.iter().any(|&o| o != 0));
+    offsets
+}
+
+/// The rank of the process at `coords + offset` or `None` if that lies outside of a non-periodic
+/// dimension.
+fn neighbor_rank(
+    comm: &CartesianCommunicator,
+    dims: &[Count],
+    periods: &[bool],
+    coords: &[Count],
+    offset: &[Count],
+) -> Option<Rank> {
+    let mut neighbor = Vec::with_capacity(coords.len());
+    for (((&coord, &o), &dim), &periodic) in coords.iter().zip(offset).zip(dims).zip(periods) {
+        let coord = coord + o;
+        if coord >= 0 && coord < dim {
+            neighbor.push(coord);
+        } else if periodic {
+            neighbor.push(coord.rem_euclid(dim));
+        } else {
+            return None;
+        }
+    }
+    Some(unsafe { comm.coordinates_to_rank_unchecked(&neighbor) })
+}
+
+/// Starts of the boundary and ghost regions in direction `offset` and their common extent
+fn regions(
+    local_shape: &[Count],
+    ghost_width: Count,
+    offset: &[Count],
+) -> (Vec<Count>, Vec<Count>, Vec<Count>) {
+    let mut boundary_starts = Vec::with_capacity(offset.len());
+    let mut ghost_starts = Vec::with_capacity(offset.len());
+    let mut subsizes = Vec::with_capacity(offset.len());
+    for (&n, &o) in local_shape.iter().zip(offset) {
+        let g = ghost_width;
+        let (boundary_start, ghost_start, subsize) = match o {
+            -1 => (g, 0, g),
+            0 => (g, g, n),
+            _ => (n, n + g, g),
+        };
+        boundary_starts.push(boundary_start);
+        ghost_starts.push(ghost_start);
+        subsizes.push(subsize);
+    }
+    (boundary_starts, ghost_starts, subsizes)
+}
@@ -0,0 +1,433 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem::{self, MaybeUninit};
+use std::os::raw::c_char;
+
+use super::{
+    private, tags, AsPeerCommunicator, PeerCommunicator, Rank, UserCommunicator, UserGroup,
+};
+use crate::datatype::traits::*;
+use crate::ffi::MPI_Comm;
+use crate::hooks::{self, Call};
+use crate::point_to_point::{Destination, Source};
+use crate::{environment, ffi, raw::traits::*, with_uninitialized, Tag};
+
+/// An inter-communicator connecting two disjoint groups of processes
+///
+/// An inter-communicator is not a `Communicator`: ranks used in point to point communication refer
+/// to processes in the remote group and collective operations move data between the two groups.
+/// Processes of the remote group are addressed via `remote_process()` and `any_remote_process()`,
+/// collective operations are provided as methods.
+///
+/// # Standard section(s)
+///
+/// 6.6
+pub struct InterCommunicator(RemoteGroup);
+
+impl InterCommunicator {
+    /// If the raw value is the null handle returns `None`
+    ///
+    /// # Safety
+    /// - `raw` must be a live MPI_Comm object that is an inter-communicator.
+    /// - `raw` must not be used after calling `from_raw`.
+    pub unsafe fn from_raw(raw: MPI_Comm) -> Option<InterCommunicator> {
+        if raw == ffi::RSMPI_COMM_NULL {
+            None
+        } else {
+            Some(InterCommunicator::from_raw_unchecked(raw))
+        }
+    }
+
+    /// Wraps the raw value without checking for null handle
+    ///
+    /// # Safety
+    /// - `raw` must be a live MPI_Comm object that is an inter-communicator.
+    /// - `raw` must not be used after calling `from_raw_unchecked`.
+    /// - `raw` must not be `MPI_COMM_NULL`.
+    unsafe fn from_raw_unchecked(raw: MPI_Comm) -> InterCommunicator {
+        debug_assert_ne!(raw, ffi::RSMPI_COMM_NULL);
+        debug_assert!(with_uninitialized(|flag| ffi::MPI_Comm_test_inter(raw, flag)).1 != 0);
+        InterCommunicator(RemoteGroup(raw))
+    }
+
+    /// Number of processes in the remote group
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.6.1
+    pub fn remote_size(&self) -> Rank {
+        self.0.size()
+    }
+
+    /// The remote group
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.6.1
+    pub fn remote_group(&self) -> UserGroup {
+        unsafe {
+            UserGroup(
+                with_uninitialized(|group| ffi::MPI_Comm_remote_group(self.as_raw(), group)).1,
+            )
+        }
+    }
+
+    /// Bundles a reference to the remote group with the `Rank` of a process in the remote group
+    /// into a `RemoteProcess`.
+    ///
+    /// Panics if `r` is not a valid rank in the remote group.
+    ///
+    /// # Examples
+    /// See `examples/coupling.rs`
+    pub fn remote_process(&self, r: Rank) -> RemoteProcess {
+        let size = self.remote_size();
+        assert!(
+            0 <= r && r < size,
+            "Rank {} is out of range for a remote group of size {}.",
+            r,
+            size
+        );
+        RemoteProcess {
+            group: &self.0,
+            rank: r,
+        }
+    }
+
+    /// An arbitrary process of the remote group, e.g. for use as a `Source`
+    pub fn any_remote_process(&self) -> AnyRemoteProcess {
+        AnyRemoteProcess(&self.0)
+    }
+
+    /// Barrier synchronization between the two groups
+    ///
+    /// Returns once all processes of the remote group have entered the barrier. This is a
+    /// collective operation on both groups.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.3
+    pub fn barrier(&self) {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "inter_barrier"));
+        unsafe {
+            ffi::MPI_Barrier(self.as_raw());
+        }
+    }
+
+    /// Broadcast the contents of `buffer` to all processes of the remote group.
+    ///
+    /// All processes of the sending group call this method, with `is_root` set on exactly one of
+    /// them. `buffer` is only significant on that process. The processes of the remote group call
+    /// `broadcast_from_remote()`. This is a collective operation on both groups.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    pub fn broadcast_to_remote<Buf: ?Sized>(&self, is_root: bool, buffer: &Buf)
+    where
+        Buf: Buffer,
+    {
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "broadcast_to_remote"));
+        let root = if is_root {
+            unsafe { ffi::RSMPI_ROOT }
+        } else {
+            unsafe { ffi::RSMPI_PROC_NULL }
+        };
+        unsafe {
+            ffi::MPI_Bcast(
+                buffer.pointer() as *mut _,
+                buffer.count(),
+                buffer.as_datatype().as_raw(),
+                root,
+                self.as_raw(),
+            );
+        }
+    }
+
+    /// Receive the broadcast of process `root` of the remote group into `buffer`.
+    ///
+    /// Matches a call to `broadcast_to_remote()` on the remote group. This is a collective
+    /// operation on both groups.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 5.4
+    pub fn broadcast_from_remote<Buf: ?Sized>(&self, root: Rank, buffer: &mut Buf)
+    where
+        Buf: BufferMut,
+    {
+        let size = self.remote_size();
+        assert!(
+            0 <= root && root < size,
+            "Rank {} is out of range for a remote group of size {}.",
+            root,
+            size
+        );
+        let _call = hooks::enter(|| Call::collective(self.as_raw(), "broadcast_from_remote"));
+        unsafe {
+            ffi::MPI_Bcast(
+                buffer.pointer_mut(),
+                buffer.count(),
+                buffer.as_datatype().as_raw(),
+                root,
+                self.as_raw(),
+            );
+        }
+    }
+
+    /// Merge the local and remote group into an intra-communicator.
+    ///
+    /// Processes of the group that passes `high = false` are ordered before the processes of the
+    /// group that passes `high = true`.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.6.2
+    pub fn merge(&self, high: bool) -> UserCommunicator {
+        unsafe {
+            UserCommunicator::from_raw_unchecked(
+                with_uninitialized(|newcomm| {
+                    ffi::MPI_Intercomm_merge(self.as_raw(), high as _, newcomm)
+                })
+                .1,
+            )
+        }
+    }
+
+    /// Disconnect from the remote group.
+    ///
+    /// Waits for all pending communication on the inter-communicator to complete before
+    /// deallocating it. Afterwards the two groups are independent of each other, e.g. the failure
+    /// of one group does not affect the other group. This is a collective operation.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 10.5.4
+    pub fn disconnect(mut self) {
+        tags::release((self.0).0);
+        private::release((self.0).0);
+        unsafe {
+            ffi::MPI_Comm_disconnect(&mut (self.0).0);
+        }
+        assert_eq!((self.0).0, unsafe { ffi::RSMPI_COMM_NULL });
+        mem::forget(self);
+    }
+}
+
+unsafe impl AsRaw for InterCommunicator {
+    type Raw = MPI_Comm;
+    fn as_raw(&self) -> Self::Raw {
+        (self.0).0
+    }
+}
+
+impl fmt::Debug for InterCommunicator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InterCommunicator")
+            .field("remote_size", &self.remote_size())
+            .finish()
+    }
+}
+
+impl Drop for InterCommunicator {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("InterCommunicator") {
+            return;
+        }
+        tags::release((self.0).0);
+        private::release((self.0).0);
+        unsafe {
+            ffi::MPI_Comm_free(&mut (self.0).0);
+        }
+        assert_eq!((self.0).0, unsafe { ffi::RSMPI_COMM_NULL });
+    }
+}
+
+/// The remote group of an `InterCommunicator`, as addressed by point to point operations
+///
+/// This is the communication context of the processes returned by `remote_process()` and
+/// `any_remote_process()`. The calling process is not a member of the remote group, so it is only
+/// a `PeerCommunicator` and not a `Communicator`. Use the methods of `InterCommunicator` for
+/// collective operations.
+pub struct RemoteGroup(MPI_Comm);
+
+impl RemoteGroup {
+    /// Number of processes in the remote group
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.6.1
+    pub fn size(&self) -> Rank {
+        unsafe { with_uninitialized(|size| ffi::MPI_Comm_remote_size(self.0, size)).1 }
+    }
+}
+
+unsafe impl AsRaw for RemoteGroup {
+    type Raw = MPI_Comm;
+    fn as_raw(&self) -> Self::Raw {
+        self.0
+    }
+}
+
+impl PeerCommunicator for RemoteGroup {
+    fn send_tag(&self) -> Tag {
+        Tag::default()
+    }
+
+    fn receive_tag(&self) -> Tag {
+        unsafe { ffi::RSMPI_ANY_TAG }
+    }
+}
+
+impl fmt::Debug for RemoteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteGroup")
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// Identifies a process of the remote group of an `InterCommunicator` by its `Rank`, for use as
+/// a `Source` or `Destination` in point to point communication
+#[derive(Copy, Clone)]
+pub struct RemoteProcess<'a> {
+    group: &'a RemoteGroup,
+    rank: Rank,
+}
+
+impl<'a> RemoteProcess<'a> {
+    /// The rank of the process in the remote group
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+}
+
+impl<'a> AsPeerCommunicator for RemoteProcess<'a> {
+    type Out = RemoteGroup;
+    fn as_peer_communicator(&self) -> &Self::Out {
+        self.group
+    }
+}
+
+unsafe impl<'a> Source for RemoteProcess<'a> {
+    fn source_rank(&self) -> Rank {
+        self.rank
+    }
+}
+
+impl<'a> Destination for RemoteProcess<'a> {
+    fn destination_rank(&self) -> Rank {
+        self.rank
+    }
+}
+
+/// Identifies an arbitrary process of the remote group of an `InterCommunicator`, for use as a
+/// `Source` in point to point communication
+#[derive(Copy, Clone)]
+pub struct AnyRemoteProcess<'a>(&'a RemoteGroup);
+
+impl<'a> AsPeerCommunicator for AnyRemoteProcess<'a> {
+    type Out = RemoteGroup;
+    fn as_peer_communicator(&self) -> &Self::Out {
+        self.0
+    }
+}
+
+unsafe impl<'a> Source for AnyRemoteProcess<'a> {
+    fn source_rank(&self) -> Rank {
+        unsafe { ffi::RSMPI_ANY_SOURCE }
+    }
+}
+
+/// A port on which a group of processes accepts connections from other groups of processes
+///
+/// The port is closed when it is dropped.
+///
+/// # Standard section(s)
+///
+/// 10.4.2
+pub struct Port {
+    name: CString,
+}
+
+impl Port {
+    /// Open a new port.
+    ///
+    /// This is a local operation. The name of the port can be passed to the connecting processes
+    /// out of band or via `publish_name()`.
+    pub fn open() -> Port {
+        type BufType = [c_char; ffi::MPI_MAX_PORT_NAME as usize];
+
+        unsafe {
+            let mut buf = MaybeUninit::<BufType>::uninit();
+            ffi::MPI_Open_port(ffi::RSMPI_INFO_NULL, &mut (*buf.as_mut_ptr())[0]);
+            let name = CStr::from_ptr(buf.assume_init().as_ptr()).to_owned();
+            Port { name }
+        }
+    }
+
+    /// The name of the port
+    pub fn name(&self) -> &str {
+        self.name
+            .to_str()
+            .expect("Port name returned by the MPI library is not valid UTF-8")
+    }
+}
+
+impl Drop for Port {
+    fn drop(&mut self) {
+        if environment::leak_if_finalized("Port") {
+            return;
+        }
+        unsafe {
+            ffi::MPI_Close_port(self.name.as_ptr());
+        }
+    }
+}
+
+/// Publish `port_name` under the name `service_name` with the MPI name service.
+///
+/// # Standard section(s)
+///
+/// 10.4.4
+pub fn publish_name(service_name: &str, port_name: &str) {
+    let c_service = CString::new(service_name).expect("Failed to convert the service name");
+    let c_port = CString::new(port_name).expect("Failed to convert the port name");
+    unsafe {
+        ffi::MPI_Publish_name(c_service.as_ptr(), ffi::RSMPI_INFO_NULL, c_port.as_ptr());
+    }
+}
+
+/// Remove the publication of `port_name` under the name `service_name`.
+///
+/// # Standard section(s)
+///
+/// 10.4.4
+pub fn unpublish_name(service_name: &str, port_name: &str) {
+    let c_service = CString::new(service_name).expect("Failed to convert the service name");
+    let c_port = CString::new(port_name).expect("Failed to convert the port name");
+    unsafe {
+        ffi::MPI_Unpublish_name(c_service.as_ptr(), ffi::RSMPI_INFO_NULL, c_port.as_ptr());
+    }
+}
+
+/// Look up the name of the port published under the name `service_name`.
+///
+/// # Standard section(s)
+///
+/// 10.4.4
+pub fn lookup_name(service_name: &str) -> String {
+    type BufType = [c_char; ffi::MPI_MAX_PORT_NAME as usize];
+
+    let c_service = CString::new(service_name).expect("Failed to convert the service name");
+    unsafe {
+        let mut buf = MaybeUninit::<BufType>::uninit();
+        ffi::MPI_Lookup_name(
+            c_service.as_ptr(),
+            ffi::RSMPI_INFO_NULL,
+            &mut (*buf.as_mut_ptr())[0],
+        );
+        CStr::from_ptr(buf.assume_init().as_ptr())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
@@ -14,37 +14,43 @@
 //!   - **6.4.2**: Constructors, `MPI_Comm_dup_with_info()`, `MPI_Comm_idup()`,
 //!     `MPI_Comm_split_type()`
 //!   - **6.4.4**: Info, `MPI_Comm_set_info()`, `MPI_Comm_get_info()`
-//! - **6.6**: Inter-communication
 //! - **6.7**: Caching
 //! - **6.8**: Naming objects
 //! - **7**: Process topologies
 //! - **Parts of sections**: 8, 10, 12
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::mem::MaybeUninit;
 use std::os::raw::{c_char, c_int};
 use std::process;
 
 use conv::ConvUtil;
 
-#[cfg(not(msmpi))]
-use crate::Tag;
-use crate::{Count, IntArray};
+use crate::{Count, IntArray, Tag};
 
 use crate::datatype::traits::*;
+use crate::environment;
 use crate::ffi;
 use crate::ffi::{MPI_Comm, MPI_Group};
 use crate::raw::traits::*;
 use crate::with_uninitialized;
 
 mod cartesian;
+mod halo;
+mod intercommunicator;
+pub(crate) mod private;
+mod tags;
 
 /// Topology traits
 pub mod traits {
-    pub use super::{AsCommunicator, Communicator, Group};
+    pub use super::{AsCommunicator, AsPeerCommunicator, Communicator, Group, PeerCommunicator};
 }
 
 // Re-export cartesian functions and types from topology modules.
 pub use self::cartesian::*;
+pub use self::halo::*;
+pub use self::intercommunicator::*;
+pub use self::tags::{TagRange, TaggedCommunicator};
 
 /// Something that has a communicator associated with it
 pub trait AsCommunicator {
@@ -54,6 +60,38 @@ pub trait AsCommunicator {
     fn as_communicator(&self) -> &Self::Out;
 }
 
+/// The context of point to point communication
+///
+/// Every `Communicator` is a `PeerCommunicator`. The `RemoteGroup` of an `InterCommunicator` is
+/// only a `PeerCommunicator`, since the calling process is not a member of the remote group.
+pub trait PeerCommunicator: AsRaw<Raw = MPI_Comm> {
+    /// The tag of messages sent by operations that take no tag, see `Communicator::default_tag()`
+    fn send_tag(&self) -> Tag;
+
+    /// The tag matched by receive and probe operations that take no tag, see
+    /// `Communicator::default_receive_tag()`
+    fn receive_tag(&self) -> Tag;
+}
+
+impl<C: ?Sized + Communicator> PeerCommunicator for C {
+    fn send_tag(&self) -> Tag {
+        self.default_tag()
+    }
+
+    fn receive_tag(&self) -> Tag {
+        self.default_receive_tag()
+    }
+}
+
+/// Something that has a point to point communication context, e.g. a `Process` as the source or
+/// destination of a message
+pub trait AsPeerCommunicator {
+    /// The type of the associated communication context
+    type Out: PeerCommunicator;
+    /// Returns the associated communication context.
+    fn as_peer_communicator(&self) -> &Self::Out;
+}
+
 /// Identifies a certain process within a communicator.
 pub type Rank = c_int;
 
@@ -109,6 +147,27 @@ impl AsCommunicator for SystemCommunicator {
     }
 }
 
+impl fmt::Debug for SystemCommunicator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        debug_communicator(f, "SystemCommunicator", self).finish()
+    }
+}
+
+/// Start formatting `comm` for `Debug` as a struct named `type_name` with the name, rank and size
+/// of the communicator
+pub(crate) fn debug_communicator<'a, 'b, C: Communicator>(
+    f: &'a mut fmt::Formatter<'b>,
+    type_name: &str,
+    comm: &C,
+) -> fmt::DebugStruct<'a, 'b> {
+    let mut debug = f.debug_struct(type_name);
+    debug
+        .field("name", &comm.get_name())
+        .field("rank", &comm.rank())
+        .field("size", &comm.size());
+    debug
+}
+
 /// An enum describing the topology of a communicator
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Topology {
@@ -219,8 +278,19 @@ unsafe impl AsRaw for UserCommunicator {
 
 impl Communicator for UserCommunicator {}
 
+impl fmt::Debug for UserCommunicator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        debug_communicator(f, "UserCommunicator", self).finish()
+    }
+}
+
 impl Drop for UserCommunicator {
     fn drop(&mut self) {
+        if environment::leak_if_finalized("UserCommunicator") {
+            return;
+        }
+        tags::release(self.0);
+        private::release(self.0);
         unsafe {
             ffi::MPI_Comm_free(&mut self.0);
         }
@@ -234,6 +304,73 @@ impl From<CartesianCommunicator> for UserCommunicator {
     }
 }
 
+/// Any kind of communicator
+///
+/// Libraries that keep a communicator in a struct field can store an `AnyCommunicator` instead of
+/// becoming generic over the communicator type in their entire API. It implements `Communicator`,
+/// so all communication operations are available on it directly.
+/// `InterCommunicator` is not a `Communicator` and therefore not covered.
+///
+/// # Examples
+///
+/// See `examples/any_communicator.rs`
+pub enum AnyCommunicator {
+    /// A built-in communicator, e.g. the world communicator
+    System(SystemCommunicator),
+    /// A user-defined communicator
+    User(UserCommunicator),
+    /// A communicator with a Cartesian topology
+    Cartesian(CartesianCommunicator),
+}
+
+impl AnyCommunicator {
+    /// The communicator, if it has a Cartesian topology
+    pub fn as_cartesian(&self) -> Option<&CartesianCommunicator> {
+        match *self {
+            AnyCommunicator::Cartesian(ref comm) => Some(comm),
+            _ => None,
+        }
+    }
+}
+
+impl From<SystemCommunicator> for AnyCommunicator {
+    fn from(comm: SystemCommunicator) -> Self {
+        AnyCommunicator::System(comm)
+    }
+}
+
+impl From<UserCommunicator> for AnyCommunicator {
+    fn from(comm: UserCommunicator) -> Self {
+        AnyCommunicator::User(comm)
+    }
+}
+
+impl From<CartesianCommunicator> for AnyCommunicator {
+    fn from(comm: CartesianCommunicator) -> Self {
+        AnyCommunicator::Cartesian(comm)
+    }
+}
+
+unsafe impl AsRaw for AnyCommunicator {
+    type Raw = MPI_Comm;
+    fn as_raw(&self) -> Self::Raw {
+        match *self {
+            AnyCommunicator::System(ref comm) => comm.as_raw(),
+            AnyCommunicator::User(ref comm) => comm.as_raw(),
+            AnyCommunicator::Cartesian(ref comm) => comm.as_raw(),
+        }
+    }
+}
+
+impl Communicator for AnyCommunicator {}
+
+impl AsCommunicator for AnyCommunicator {
+    type Out = AnyCommunicator;
+    fn as_communicator(&self) -> &Self::Out {
+        self
+    }
+}
+
 /// Unimplemented
 #[allow(missing_copy_implementations)]
 pub struct GraphCommunicator;
@@ -301,14 +438,75 @@ pub trait Communicator: AsRaw<Raw = MPI_Comm> {
     ///
     /// # Examples
     /// See `examples/broadcast.rs` `examples/gather.rs` `examples/send_receive.rs`
+    ///
+    /// Panics if `r` is not a valid rank in this communicator.
     fn process_at_rank(&self, r: Rank) -> Process<Self>
     where
         Self: Sized,
     {
-        assert!(0 <= r && r < self.size());
+        let size = self.size();
+        assert!(
+            0 <= r && r < size,
+            "Rank {} is out of range for a communicator of size {}.",
+            r,
+            size
+        );
         Process::by_rank_unchecked(self, r)
     }
 
+    /// Bundles a reference to this communicator with a specific `Rank` into a `Process` or
+    /// returns `None` if `r` is not a valid rank in this communicator.
+    fn checked_process_at_rank(&self, r: Rank) -> Option<Process<Self>>
+    where
+        Self: Sized,
+    {
+        if 0 <= r && r < self.size() {
+            Some(Process::by_rank_unchecked(self, r))
+        } else {
+            None
+        }
+    }
+
+    /// A `Process` for `MPI_PROC_NULL`, the null process
+    ///
+    /// Sending to the null process succeeds immediately without transferring any data and
+    /// receiving from the null process succeeds immediately with an empty message, leaving the
+    /// receive buffer untouched. This allows processes on the boundary of a non-periodic grid to
+    /// run the same communication code as processes in the interior.
+    ///
+    /// # Examples
+    /// See `examples/null_process.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.11
+    fn null_process(&self) -> Process<Self>
+    where
+        Self: Sized,
+    {
+        Process::by_rank_unchecked(self, unsafe { ffi::RSMPI_PROC_NULL })
+    }
+
+    /// The `Process` at rank `r` or the null process if `r` is `None`
+    ///
+    /// Fits the neighbor ranks returned by e.g. `CartesianCommunicator::shift()`, which are `None`
+    /// on the boundary of non-periodic dimensions.
+    ///
+    /// Panics if `r` is not a valid rank in this communicator.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 3.11
+    fn process_at_rank_or_null(&self, r: Option<Rank>) -> Process<Self>
+    where
+        Self: Sized,
+    {
+        match r {
+            Some(r) => self.process_at_rank(r),
+            None => self.null_process(),
+        }
+    }
+
     /// Returns an `AnyProcess` identifier that can be used, e.g. as a `Source` in point to point
     /// communication.
     fn any_process(&self) -> AnyProcess<Self>
@@ -424,6 +622,31 @@ pub trait Communicator: AsRaw<Raw = MPI_Comm> {
         }
     }
 
+    /// Split the communicator into one communicator per shared memory node and one communicator
+    /// connecting the leaders of all nodes.
+    ///
+    /// Returns the node communicator and, on the process of rank 0 of each node communicator, the
+    /// leader communicator. The processes keep their relative order in both communicators. This is
+    /// the two-level hierarchy used for node-aggregated I/O and hierarchical reductions.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/split_leaders.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.4.2
+    fn split_leaders(&self) -> (UserCommunicator, Option<UserCommunicator>) {
+        let node = self.split_shared(self.rank());
+        let color = if node.rank() == 0 {
+            Color::with_value(0)
+        } else {
+            Color::undefined()
+        };
+        let leaders = self.split_by_color_with_key(color, self.rank());
+        (node, leaders)
+    }
+
     /// Split a communicator collectively by subgroup.
     ///
     /// Proceses pass in a group that is a subgroup of the group associated with the old
@@ -509,6 +732,112 @@ pub trait Communicator: AsRaw<Raw = MPI_Comm> {
         }
     }
 
+    /// Accept a connection from another group of processes on the port named `port_name`.
+    ///
+    /// `port_name` is only significant on process `root`. Returns an inter-communicator whose
+    /// remote group consists of the processes that called `connect()`. This is a collective
+    /// operation.
+    ///
+    /// # Examples
+    /// See `examples/coupling.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 10.4.2
+    fn accept(&self, port_name: &str, root: Rank) -> InterCommunicator {
+        let c_port = CString::new(port_name).expect("Failed to convert the port name");
+        unsafe {
+            InterCommunicator::from_raw(
+                with_uninitialized(|newcomm| {
+                    ffi::MPI_Comm_accept(
+                        c_port.as_ptr(),
+                        ffi::RSMPI_INFO_NULL,
+                        root,
+                        self.as_raw(),
+                        newcomm,
+                    )
+                })
+                .1,
+            )
+            .expect("rsmpi internal error: MPI_Comm_accept returned MPI_COMM_NULL")
+        }
+    }
+
+    /// Connect to a group of processes accepting connections on the port named `port_name`.
+    ///
+    /// `port_name` is only significant on process `root`. Returns an inter-communicator whose
+    /// remote group consists of the processes that called `accept()`. This is a collective
+    /// operation.
+    ///
+    /// # Examples
+    /// See `examples/coupling.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 10.4.2
+    fn connect(&self, port_name: &str, root: Rank) -> InterCommunicator {
+        let c_port = CString::new(port_name).expect("Failed to convert the port name");
+        unsafe {
+            InterCommunicator::from_raw(
+                with_uninitialized(|newcomm| {
+                    ffi::MPI_Comm_connect(
+                        c_port.as_ptr(),
+                        ffi::RSMPI_INFO_NULL,
+                        root,
+                        self.as_raw(),
+                        newcomm,
+                    )
+                })
+                .1,
+            )
+            .expect("rsmpi internal error: MPI_Comm_connect returned MPI_COMM_NULL")
+        }
+    }
+
+    /// Create an inter-communicator between this communicator and a disjoint group of processes.
+    ///
+    /// The two groups are connected through their leaders: process `local_leader` of this
+    /// communicator and process `remote_leader` of the other group, whose rank is given in
+    /// `peer`. `peer` is a communicator containing both leaders and is only significant on the
+    /// local leader. Messages between the leaders are sent on `peer` with tag `tag`, which has to
+    /// be the same on both sides and must not collide with other communication on `peer`. This
+    /// is a collective operation on both groups.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/pipeline.rs`
+    ///
+    /// # Standard section(s)
+    ///
+    /// 6.6.2
+    fn create_intercommunicator<P>(
+        &self,
+        local_leader: Rank,
+        peer: &P,
+        remote_leader: Rank,
+        tag: Tag,
+    ) -> InterCommunicator
+    where
+        P: Communicator,
+    {
+        unsafe {
+            InterCommunicator::from_raw(
+                with_uninitialized(|newcomm| {
+                    ffi::MPI_Intercomm_create(
+                        self.as_raw(),
+                        local_leader,
+                        peer.as_raw(),
+                        remote_leader,
+                        tag,
+                        newcomm,
+                    )
+                })
+                .1,
+            )
+            .expect("rsmpi internal error: MPI_Intercomm_create returned MPI_COMM_NULL")
+        }
+    }
+
     /// Abort program execution
     ///
     /// # Standard section(s)
@@ -553,6 +882,64 @@ pub trait Communicator: AsRaw<Raw = MPI_Comm> {
         }
     }
 
+    /// Make errors in operations on this communicator return an error code instead of aborting the
+    /// program.
+    ///
+    /// By default, MPI aborts the program when an operation fails. Most operations of rsmpi ignore
+    /// the returned error codes, functions like `request::join_or_cancel()` report them.
+    ///
+    /// # Safety
+    /// - Until the default error handler is restored, only operations that report the error code
+    ///   of MPI, like `request::join_or_cancel()`, may fail on this communicator. Other operations
+    ///   use the outputs of a failed MPI call, which may be uninitialized.
+    ///
+    /// # Standard section(s)
+    ///
+    /// 8.3.1
+    unsafe fn set_errors_return(&self) {
+        ffi::MPI_Comm_set_errhandler(self.as_raw(), ffi::RSMPI_ERRORS_RETURN);
+    }
+
+    /// Allocate a tag that is not handed out by any other call to `allocate_tag()` or
+    /// `allocate_tags()` on this communicator.
+    ///
+    /// Layered libraries that communicate on the same communicator can allocate their tags
+    /// instead of hard-coding them to avoid collisions. Tags are allocated downwards from
+    /// `environment::tag_upper_bound()`, so they do not collide with small hard-coded tags either.
+    /// Allocation is a local operation, but it has to be performed in the same order on all
+    /// processes so that they agree on the tags. The tags of a `UserCommunicator` are released when
+    /// it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// See `examples/allocate_tags.rs`
+    fn allocate_tag(&self) -> Tag {
+        tags::allocate(self.as_raw(), 1).start()
+    }
+
+    /// Allocate a range of `len` consecutive tags, see `allocate_tag()`.
+    ///
+    /// Panics if fewer than `len` tags are left on this communicator.
+    fn allocate_tags(&self, len: Tag) -> TagRange {
+        tags::allocate(self.as_raw(), len)
+    }
+
+    /// The tag of messages sent on this communicator by operations that take no tag, like
+    /// `send()`
+    ///
+    /// `Tag::default()` unless the communicator is a `TaggedCommunicator`.
+    fn default_tag(&self) -> Tag {
+        Tag::default()
+    }
+
+    /// The tag matched by receive and probe operations on this communicator that take no tag,
+    /// like `receive()`
+    ///
+    /// Matches any tag unless the communicator is a `TaggedCommunicator`.
+    fn default_receive_tag(&self) -> Tag {
+        unsafe { ffi::RSMPI_ANY_TAG }
+    }
+
     /// Creates a communicator with ranks laid out in a multi-dimensional space, allowing for easy
     /// neighbor-to-neighbor communication, while providing MPI with information to allow it to
     /// better optimize the physical locality of ranks that are logically close.
@@ -797,6 +1184,11 @@ where
     pub fn rank(&self) -> Rank {
         self.rank
     }
+
+    /// Whether this is the null process `MPI_PROC_NULL`
+    pub fn is_null(&self) -> bool {
+        self.rank == unsafe { ffi::RSMPI_PROC_NULL }
+    }
 }
 
 impl<'a, C> AsCommunicator for Process<'a, C>
@@ -809,6 +1201,16 @@ where
     }
 }
 
+impl<'a, C> AsPeerCommunicator for Process<'a, C>
+where
+    C: 'a + Communicator,
+{
+    type Out = C;
+    fn as_peer_communicator(&self) -> &Self::Out {
+        self.comm
+    }
+}
+
 /// Identifies an arbitrary process that is a member of a certain communicator, e.g. for use as a
 /// `Source` in point to point communication.
 pub struct AnyProcess<'a, C>(&'a C)
@@ -825,6 +1227,16 @@ where
     }
 }
 
+impl<'a, C> AsPeerCommunicator for AnyProcess<'a, C>
+where
+    C: 'a + Communicator,
+{
+    type Out = C;
+    fn as_peer_communicator(&self) -> &Self::Out {
+        self.0
+    }
+}
+
 /// A built-in group, e.g. `MPI_GROUP_EMPTY`
 ///
 /// # Standard section(s)
@@ -858,6 +1270,9 @@ pub struct UserGroup(MPI_Group);
 
 impl Drop for UserGroup {
     fn drop(&mut self) {
+        if environment::leak_if_finalized("UserGroup") {
+            return;
+        }
         unsafe {
             ffi::MPI_Group_free(&mut self.0);
         }
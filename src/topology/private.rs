@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard};
+
+use once_cell::sync::Lazy;
+
+use super::{AsCommunicator, Communicator};
+use crate::ffi::{self, MPI_Comm};
+use crate::raw::traits::*;
+use crate::with_uninitialized;
+
+/// The private duplicates of communicators, keyed by the Fortran handles of the communicator and
+/// its duplicate
+static DUPLICATES: Lazy<Mutex<HashMap<ffi::RSMPI_Fint, ffi::RSMPI_Fint>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn duplicates() -> MutexGuard<'static, HashMap<ffi::RSMPI_Fint, ffi::RSMPI_Fint>> {
+    DUPLICATES
+        .lock()
+        .expect("rsmpi internal error: DUPLICATES lock poisoned")
+}
+
+/// A duplicate of a communicator that only rsmpi communicates on, see `duplicate()`
+#[derive(Copy, Clone)]
+pub(crate) struct PrivateCommunicator<'a>(MPI_Comm, PhantomData<&'a ()>);
+
+unsafe impl<'a> AsRaw for PrivateCommunicator<'a> {
+    type Raw = MPI_Comm;
+    fn as_raw(&self) -> Self::Raw {
+        self.0
+    }
+}
+
+impl<'a> Communicator for PrivateCommunicator<'a> {}
+
+impl<'a> AsCommunicator for PrivateCommunicator<'a> {
+    type Out = PrivateCommunicator<'a>;
+    fn as_communicator(&self) -> &Self::Out {
+        self
+    }
+}
+
+impl<'a> fmt::Debug for PrivateCommunicator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        super::debug_communicator(f, "PrivateCommunicator", self).finish()
+    }
+}
+
+/// The private duplicate of `comm`
+///
+/// Operations that rsmpi implements on top of point to point messages, e.g.
+/// `all_reduce_deterministic_into()`, communicate on the private duplicate so that their
+/// messages cannot match messages of the caller on `comm`. The duplicate is created by the first
+/// such operation on `comm`, which makes that call collective, and freed together with `comm`.
+pub(crate) fn duplicate<C: ?Sized + Communicator>(comm: &C) -> PrivateCommunicator<'_> {
+    let key = unsafe { ffi::RSMPI_Comm_c2f(comm.as_raw()) };
+    // Duplicating is collective, so the lock is not held while waiting for the other processes.
+    let cached = duplicates().get(&key).cloned();
+    let duplicate = match cached {
+        Some(duplicate) => unsafe { ffi::RSMPI_Comm_f2c(duplicate) },
+        None => {
+            let duplicate = unsafe {
+                with_uninitialized(|newcomm| ffi::MPI_Comm_dup(comm.as_raw(), newcomm)).1
+            };
+            duplicates().insert(key, unsafe { ffi::RSMPI_Comm_c2f(duplicate) });
+            duplicate
+        }
+    };
+    PrivateCommunicator(duplicate, PhantomData)
+}
+
+/// Free the private duplicate of `comm`, if any, e.g. before `comm` is freed.
+pub(crate) fn release(comm: MPI_Comm) {
+    let key = unsafe { ffi::RSMPI_Comm_c2f(comm) };
+    if let Some(duplicate) = duplicates().remove(&key) {
+        unsafe {
+            let mut duplicate = ffi::RSMPI_Comm_f2c(duplicate);
+            ffi::MPI_Comm_free(&mut duplicate);
+        }
+    }
+}
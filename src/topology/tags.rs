@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use conv::ConvUtil;
+use once_cell::sync::Lazy;
+
+use super::{AsCommunicator, Communicator};
+use crate::ffi::{self, MPI_Comm};
+use crate::raw::traits::*;
+use crate::Tag;
+
+/// The lowest tag allocated so far on every communicator, keyed by the Fortran handle of the
+/// communicator
+static ALLOCATED: Lazy<Mutex<HashMap<ffi::RSMPI_Fint, Tag>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A range of consecutive tags allocated on a communicator
+///
+/// See `Communicator::allocate_tags()`.
+///
+/// # Examples
+///
+/// See `examples/allocate_tags.rs`
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TagRange {
+    start: Tag,
+    len: Tag,
+}
+
+impl TagRange {
+    /// The first tag of the range
+    pub fn start(&self) -> Tag {
+        self.start
+    }
+
+    /// The number of tags in the range
+    pub fn len(&self) -> Tag {
+        self.len
+    }
+
+    /// Whether the range contains no tags
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tag number `i` of the range
+    ///
+    /// Panics if `i` is not less than `len()`.
+    pub fn tag(&self, i: Tag) -> Tag {
+        assert!(
+            0 <= i && i < self.len,
+            "Tag index {} is out of range for a tag range of length {}.",
+            i,
+            self.len
+        );
+        self.start + i
+    }
+
+    /// Whether `tag` lies in the range
+    pub fn contains(&self, tag: Tag) -> bool {
+        self.start <= tag && tag < self.start + self.len
+    }
+
+    /// The tags of the range
+    pub fn tags(&self) -> Range<Tag> {
+        self.start..self.start + self.len
+    }
+}
+
+/// A communicator that tags messages sent and received without an explicit tag with a tag of
+/// its own
+///
+/// The point to point operations that take no tag, like `send()` and `receive()`, use the
+/// current tag of the communicator, see `Communicator::default_tag()`. The tag is either fixed or
+/// cycles through a range of tags, advancing with every call to `next_iteration()`. Cycling tags
+/// keep the messages of consecutive iterations of a loop apart when the non-blocking operations
+/// of one iteration are still in flight while the next iteration starts: a receive of iteration
+/// `k + 1` cannot match a message of iteration `k` as long as fewer than `period()` iterations
+/// overlap.
+///
+/// # Examples
+///
+/// See `examples/tag_cycle.rs`
+pub struct TaggedCommunicator<C> {
+    comm: C,
+    tags: TagRange,
+    iteration: usize,
+}
+
+impl<C: Communicator> TaggedCommunicator<C> {
+    /// Wrap `comm`, tagging all messages with `tag`.
+    pub fn new(comm: C, tag: Tag) -> TaggedCommunicator<C> {
+        assert!(
+            0 <= tag && tag <= crate::environment::tag_upper_bound(),
+            "Tag {} is not a valid tag.",
+            tag
+        );
+        TaggedCommunicator {
+            comm,
+            tags: TagRange { start: tag, len: 1 },
+            iteration: 0,
+        }
+    }
+
+    /// Wrap `comm`, cycling through `period` tags allocated on `comm` with
+    /// `Communicator::allocate_tags()`.
+    ///
+    /// Like the allocation, this has to happen in the same order on all processes.
+    pub fn cycling(comm: C, period: Tag) -> TaggedCommunicator<C> {
+        assert!(period > 0, "A tag cycle needs at least one tag.");
+        let tags = comm.allocate_tags(period);
+        TaggedCommunicator::with_tags(comm, tags)
+    }
+
+    /// Wrap `comm`, cycling through the tags of `tags`.
+    pub fn with_tags(comm: C, tags: TagRange) -> TaggedCommunicator<C> {
+        assert!(!tags.is_empty(), "A tag cycle needs at least one tag.");
+        TaggedCommunicator {
+            comm,
+            tags,
+            iteration: 0,
+        }
+    }
+
+    /// The tag of the current iteration
+    pub fn tag(&self) -> Tag {
+        let period: usize = self
+            .tags
+            .len()
+            .value_as()
+            .expect("Number of tags cannot be expressed as a usize.");
+        let index: Tag = (self.iteration % period)
+            .value_as()
+            .expect("Tag index cannot be expressed as a Tag.");
+        self.tags.tag(index)
+    }
+
+    /// The number of tags the communicator cycles through
+    pub fn period(&self) -> Tag {
+        self.tags.len()
+    }
+
+    /// The number of calls to `next_iteration()` so far
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Advance to the next iteration and return its tag.
+    ///
+    /// All processes have to advance in step.
+    pub fn next_iteration(&mut self) -> Tag {
+        self.iteration += 1;
+        self.tag()
+    }
+
+    /// The wrapped communicator
+    pub fn inner(&self) -> &C {
+        &self.comm
+    }
+
+    /// Unwrap the communicator.
+    pub fn into_inner(self) -> C {
+        self.comm
+    }
+}
+
+unsafe impl<C: Communicator> AsRaw for TaggedCommunicator<C> {
+    type Raw = MPI_Comm;
+    fn as_raw(&self) -> Self::Raw {
+        self.comm.as_raw()
+    }
+}
+
+impl<C: Communicator> AsCommunicator for TaggedCommunicator<C> {
+    type Out = TaggedCommunicator<C>;
+    fn as_communicator(&self) -> &Self::Out {
+        self
+    }
+}
+
+impl<C: Communicator> Communicator for TaggedCommunicator<C> {
+    fn default_tag(&self) -> Tag {
+        self.tag()
+    }
+
+    fn default_receive_tag(&self) -> Tag {
+        self.tag()
+    }
+}
+
+impl<C: Communicator> fmt::Debug for TaggedCommunicator<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        super::debug_communicator(f, "TaggedCommunicator", self)
+            .field("tag", &self.tag())
+            .field("iteration", &self.iteration)
+            .finish()
+    }
+}
+
+fn key(comm: MPI_Comm) -> ffi::RSMPI_Fint {
+    unsafe { ffi::RSMPI_Comm_c2f(comm) }
+}
+
+/// Allocate `len` tags on `comm`, counting down from the tag upper bound.
+pub(crate) fn allocate(comm: MPI_Comm, len: Tag) -> TagRange {
+    assert!(len >= 0, "Cannot allocate a negative number of tags.");
+    let mut allocated = ALLOCATED
+        .lock()
+        .expect("rsmpi internal error: tag allocator lock poisoned");
+    let lowest = allocated
+        .entry(key(comm))
+        .or_insert_with(|| crate::environment::tag_upper_bound() + 1);
+    let start = *lowest - len;
+    assert!(
+        start >= 0,
+        "Cannot allocate {} more tags, only {} tags are left on the communicator.",
+        len,
+        *lowest
+    );
+    *lowest = start;
+    TagRange { start, len }
+}
+
+/// Release all tags allocated on `comm`, e.g. before it is freed.
+pub(crate) fn release(comm: MPI_Comm) {
+    ALLOCATED
+        .lock()
+        .expect("rsmpi internal error: tag allocator lock poisoned")
+        .remove(&key(comm));
+}
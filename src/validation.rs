@@ -0,0 +1,251 @@
+//! Validation of point to point messages through checksums
+//!
+//! Data corruption caused by mismatched datatypes on the sending and receiving side or by faulty
+//! network stacks is hard to trace back to its origin. With the `validate` feature, every message
+//! sent by one of the send operations of `Destination`, blocking or immediate, or by one of the
+//! send-receive operations of `point_to_point` is followed by a companion message holding a
+//! CRC-32 checksum of the packed contents of the message. The blocking receive operations of
+//! `Source`, the send-receive operations and the matched receive operations of `Message` receive
+//! the companion message and panic if the checksum of the received contents does not match.
+//!
+//! The companion message is sent to the same process on the same communicator with the same tag
+//! directly after the message, so the ordering guarantees of MPI deliver it right after the
+//! message it belongs to. The immediate receive operations post the receive of the companion
+//! message right after the receive of the message, but cannot check it, since the contents of the
+//! message are only known once the request has been completed. Immediate receives from any
+//! process cannot be paired with their companion message and panic while validation is enabled.
+//! Messages sent or received by calling the MPI library directly carry no companion message. All
+//! processes must be built with the same features.
+//!
+//! This module is only available with the `validate` feature. It is meant for debugging, since it
+//! doubles the number of messages and packs the contents of every message. The `validate` feature
+//! is therefore not part of the features the examples are run with.
+//!
+//! # Examples
+//!
+//! See `examples/validate.rs`
+
+use std::os::raw::c_void;
+use std::sync::{Mutex, MutexGuard};
+
+use conv::ConvUtil;
+use once_cell::sync::Lazy;
+
+use crate::datatype::traits::*;
+use crate::datatype::DatatypeRef;
+use crate::ffi::{self, MPI_Comm, MPI_Datatype, MPI_Request};
+use crate::point_to_point::Status;
+use crate::topology::Rank;
+use crate::{with_uninitialized, Count, Tag};
+
+/// A companion message that is still being sent or received
+struct Companion {
+    request: MPI_Request,
+    /// The checksum, which has to stay in place until the request is complete
+    checksum: Box<u32>,
+}
+
+unsafe impl Send for Companion {}
+
+/// The companion messages of immediate operations, which are completed by later operations
+static PENDING: Lazy<Mutex<Vec<Companion>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn pending() -> MutexGuard<'static, Vec<Companion>> {
+    PENDING
+        .lock()
+        .expect("rsmpi internal error: validation PENDING lock poisoned")
+}
+
+/// Start sending or receiving a companion message with `post` and keep it pending until it is
+/// complete.
+///
+/// Completed companion messages are removed from the pending ones on the way.
+fn start<F>(checksum: u32, post: F)
+where
+    F: FnOnce(*mut c_void, *mut MPI_Request),
+{
+    let mut pending = pending();
+    let mut i = 0;
+    while i < pending.len() {
+        let (_, flag) = unsafe {
+            with_uninitialized(|flag| {
+                ffi::MPI_Test(&mut pending[i].request, flag, ffi::RSMPI_STATUS_IGNORE)
+            })
+        };
+        if flag != 0 {
+            pending.swap_remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut checksum = Box::new(checksum);
+    let (_, request) = unsafe {
+        with_uninitialized(|request| post(&mut *checksum as *mut u32 as *mut c_void, request))
+    };
+    pending.push(Companion { request, checksum });
+}
+
+/// Wait for all pending companion messages.
+///
+/// Called before finalizing, which requires all communication to be complete.
+pub(crate) fn complete_pending() {
+    for mut companion in pending().drain(..) {
+        unsafe {
+            ffi::MPI_Wait(&mut companion.request, ffi::RSMPI_STATUS_IGNORE);
+        }
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// The checksum of the packed contents of `count` elements of `datatype` at `buf`
+pub(crate) fn checksum(buf: *const c_void, count: Count, datatype: MPI_Datatype) -> u32 {
+    unsafe {
+        let comm = ffi::RSMPI_COMM_SELF;
+        let size = with_uninitialized(|size| ffi::MPI_Pack_size(count, datatype, comm, size)).1;
+        let len: usize = size
+            .value_as()
+            .expect("Packed message size cannot be expressed as a usize.");
+        let mut packed = vec![0u8; len];
+        let mut position = 0;
+        ffi::MPI_Pack(
+            buf,
+            count,
+            datatype,
+            packed.as_mut_ptr() as *mut c_void,
+            size,
+            &mut position,
+            comm,
+        );
+        let position: usize = position
+            .value_as()
+            .expect("Packed message size cannot be expressed as a usize.");
+        crc32(&packed[..position])
+    }
+}
+
+/// Send the checksum of a message of `count` elements of `datatype` at `buf` that has just been
+/// sent or started to be sent to `destination` with `tag` on `comm`.
+pub(crate) fn send_checksum(
+    comm: MPI_Comm,
+    destination: Rank,
+    tag: Tag,
+    buf: *const c_void,
+    count: Count,
+    datatype: MPI_Datatype,
+) {
+    send_companion(comm, destination, tag, checksum(buf, count, datatype));
+}
+
+/// Send `checksum` as the companion of a message that has just been sent or started to be sent
+/// to `destination` with `tag` on `comm`.
+///
+/// The companion message is sent without blocking, so that two processes that send each other a
+/// message before receiving do not wait for each other's companion messages.
+pub(crate) fn send_companion(comm: MPI_Comm, destination: Rank, tag: Tag, checksum: u32) {
+    start(checksum, |checksum, request| unsafe {
+        ffi::MPI_Isend(
+            checksum,
+            1,
+            u32::equivalent_datatype().as_raw(),
+            destination,
+            tag,
+            comm,
+            request,
+        );
+    });
+}
+
+/// Start receiving the companion of a message from `source` with `tag` on `comm`, whose receive
+/// has just been started.
+///
+/// The checksum is not checked, since the message is only complete once its request is.
+pub(crate) fn skip_checksum(comm: MPI_Comm, source: Rank, tag: Tag) {
+    assert_ne!(
+        source,
+        unsafe { ffi::RSMPI_ANY_SOURCE },
+        "Immediate receives from any process cannot be paired with the checksums of the \
+         `validate` feature."
+    );
+    start(0, |checksum, request| unsafe {
+        ffi::MPI_Irecv(
+            checksum,
+            1,
+            u32::equivalent_datatype().as_raw(),
+            source,
+            tag,
+            comm,
+            request,
+        );
+    });
+}
+
+/// Receive the checksum of the message described by `status` that has been received or matched
+/// on `comm`.
+///
+/// Returns `None` for messages from the null process, which have no checksum.
+pub(crate) fn receive_checksum(comm: MPI_Comm, status: &Status) -> Option<u32> {
+    if status.source_rank() == unsafe { ffi::RSMPI_PROC_NULL } {
+        return None;
+    }
+    let mut checksum = 0u32;
+    unsafe {
+        ffi::MPI_Recv(
+            &mut checksum as *mut u32 as *mut c_void,
+            1,
+            u32::equivalent_datatype().as_raw(),
+            status.source_rank(),
+            status.tag(),
+            comm,
+            ffi::RSMPI_STATUS_IGNORE,
+        );
+    }
+    Some(checksum)
+}
+
+/// Check the contents of the message described by `status`, which has been received into `buf`
+/// as elements of `datatype`, against the checksum `expected`.
+///
+/// Panics if the checksums differ.
+pub(crate) fn verify(
+    expected: Option<u32>,
+    status: &Status,
+    buf: *const c_void,
+    datatype: MPI_Datatype,
+) {
+    if let Some(expected) = expected {
+        let count = status.count(unsafe { DatatypeRef::from_raw(datatype) });
+        let actual = checksum(buf, count.max(0), datatype);
+        assert_eq!(
+            expected,
+            actual,
+            "Checksum mismatch in message from rank {} with tag {}, the datatypes of sender and \
+             receiver may not match.",
+            status.source_rank(),
+            status.tag()
+        );
+    }
+}
+
+/// Receive the checksum of the message described by `status` on `comm` and check the contents of
+/// the message in `buf` against it.
+pub(crate) fn check_received(
+    comm: MPI_Comm,
+    status: &Status,
+    buf: *const c_void,
+    datatype: MPI_Datatype,
+) {
+    verify(receive_checksum(comm, status), status, buf, datatype);
+}